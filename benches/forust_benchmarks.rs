@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use forust_ml::binning::bin_matrix;
-use forust_ml::constraints::ConstraintMap;
+use forust_ml::constraints::{ConstraintMap, MonotoneConstraintMethod};
 use forust_ml::data::Matrix;
 use forust_ml::gradientbooster::GradientBooster;
 use forust_ml::objective::{LogLoss, ObjectiveFunction};
@@ -44,6 +44,8 @@ pub fn tree_benchmarks(c: &mut Criterion) {
         learning_rate: 0.3,
         allow_missing_splits: true,
         constraints_map: ConstraintMap::new(),
+        monotone_constraint_method: MonotoneConstraintMethod::Basic,
+        interaction_constraints: None,
     };
     let mut tree = Tree::new();
 
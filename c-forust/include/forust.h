@@ -0,0 +1,80 @@
+#ifndef FORUST_H
+#define FORUST_H
+
+/* Generated with cbindgen:0.26.0 */
+
+/* Generated with cbindgen from src/lib.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * An opaque handle to a loaded booster. It's created with `forust_booster_from_json`,
+ * or `forust_booster_from_binary`, and has to be freed with `forust_booster_free`.
+ */
+typedef struct ForustBooster ForustBooster;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The message of the last error on this thread, or null if there hasn't been an
+ * error. The message is owned by forust, and is valid until the next error.
+ */
+const char *forust_last_error(void);
+
+/**
+ * Load a booster from the null terminated json written by `save_booster`, or
+ * `json_dump`. Returns null if the json isn't a valid booster.
+ *
+ * # Safety
+ * `json` must be a valid null terminated string.
+ */
+struct ForustBooster *forust_booster_from_json(const char *json);
+
+/**
+ * Load a booster from the bytes written by `save_binary`. Returns null if the bytes
+ * aren't a valid booster.
+ *
+ * # Safety
+ * `bytes` must point to `len` readable bytes.
+ */
+struct ForustBooster *forust_booster_from_binary(const uint8_t *bytes, size_t len);
+
+/**
+ * Predict on a buffer of `rows` by `cols` values, writing a prediction for each row
+ * to `out`. Returns 0 on success, and -1 on an error, such as if `rows * cols`
+ * overflows, or there are fewer columns than the model has features.
+ *
+ * # Safety
+ * `booster` must be a handle from this library, `data` must point to `rows * cols`
+ * readable values, and `out` to `rows` writable values.
+ *
+ * * `row_major` - If the values of each row are contiguous, otherwise the values of
+ *   each column are.
+ */
+int32_t forust_booster_predict(const struct ForustBooster *booster,
+                               const double *data,
+                               size_t rows,
+                               size_t cols,
+                               bool row_major,
+                               bool parallel,
+                               double *out);
+
+/**
+ * Free a booster. Passing null does nothing.
+ *
+ * # Safety
+ * `booster` must be a handle from this library, that hasn't been freed.
+ */
+void forust_booster_free(struct ForustBooster *booster);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* FORUST_H */
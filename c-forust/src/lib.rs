@@ -0,0 +1,158 @@
+//! A stable C API, for serving forust models from other languages. A booster trained
+//! in Rust, or Python, is saved with `save_booster`, or `save_binary`, and loaded here
+//! from its serialized bytes. Functions that can fail return a null pointer, or a
+//! non zero status, and the reason can be read with `forust_last_error`.
+use forust_ml::data::{Matrix, RowMajorView};
+use forust_ml::gradientbooster::GradientBooster;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Run a function, recording its error, or panic, as the last error, rather than
+/// unwinding across the C boundary.
+fn ffi_try<T, F: FnOnce() -> Result<T, String>>(f: F) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(v)) => Some(v),
+        Ok(Err(e)) => {
+            set_last_error(e);
+            None
+        }
+        Err(_) => {
+            set_last_error("forust panicked".to_string());
+            None
+        }
+    }
+}
+
+/// An opaque handle to a loaded booster. It's created with `forust_booster_from_json`,
+/// or `forust_booster_from_binary`, and has to be freed with `forust_booster_free`.
+pub struct ForustBooster {
+    booster: GradientBooster,
+}
+
+fn into_handle(booster: Result<GradientBooster, String>) -> *mut ForustBooster {
+    match ffi_try(|| booster) {
+        Some(booster) => Box::into_raw(Box::new(ForustBooster { booster })),
+        None => ptr::null_mut(),
+    }
+}
+
+/// The message of the last error on this thread, or null if there hasn't been an
+/// error. The message is owned by forust, and is valid until the next error.
+#[no_mangle]
+pub extern "C" fn forust_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
+
+/// Load a booster from the null terminated json written by `save_booster`, or
+/// `json_dump`. Returns null if the json isn't a valid booster.
+///
+/// # Safety
+/// `json` must be a valid null terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn forust_booster_from_json(json: *const c_char) -> *mut ForustBooster {
+    if json.is_null() {
+        return into_handle(Err("json is null".to_string()));
+    }
+    let json = CStr::from_ptr(json).to_str().map_err(|e| e.to_string());
+    into_handle(json.and_then(|j| GradientBooster::from_json(j).map_err(|e| e.to_string())))
+}
+
+/// Load a booster from the bytes written by `save_binary`. Returns null if the bytes
+/// aren't a valid booster.
+///
+/// # Safety
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn forust_booster_from_binary(
+    bytes: *const u8,
+    len: usize,
+) -> *mut ForustBooster {
+    if bytes.is_null() {
+        return into_handle(Err("bytes is null".to_string()));
+    }
+    let bytes = slice::from_raw_parts(bytes, len);
+    into_handle(GradientBooster::from_binary(bytes).map_err(|e| e.to_string()))
+}
+
+/// Predict on a buffer of `rows` by `cols` values, writing a prediction for each row
+/// to `out`. Returns 0 on success, and -1 on an error, such as if `rows * cols`
+/// overflows, or there are fewer columns than the model has features.
+///
+/// # Safety
+/// `booster` must be a handle from this library, `data` must point to `rows * cols`
+/// readable values, and `out` to `rows` writable values.
+///
+/// * `row_major` - If the values of each row are contiguous, otherwise the values of
+///   each column are.
+#[no_mangle]
+pub unsafe extern "C" fn forust_booster_predict(
+    booster: *const ForustBooster,
+    data: *const f64,
+    rows: usize,
+    cols: usize,
+    row_major: bool,
+    parallel: bool,
+    out: *mut f64,
+) -> i32 {
+    if booster.is_null() || data.is_null() || out.is_null() {
+        set_last_error("booster, data, and out can't be null".to_string());
+        return -1;
+    }
+    // A slice can't hold more than isize::MAX bytes.
+    let len = match rows.checked_mul(cols) {
+        Some(len) if len <= isize::MAX as usize / std::mem::size_of::<f64>() => len,
+        _ => {
+            set_last_error(format!("{} rows and {} columns is too large", rows, cols));
+            return -1;
+        }
+    };
+    let booster = &(*booster).booster;
+    // A short row would be read past its end, which can't be caught if panics abort.
+    let n_features = booster.n_features();
+    if cols < n_features {
+        set_last_error(format!(
+            "The data has {} columns, but the model has {} features.",
+            cols, n_features
+        ));
+        return -1;
+    }
+    let data = slice::from_raw_parts(data, len);
+    let preds = ffi_try(|| {
+        if row_major {
+            let data = RowMajorView::new(data, rows, cols).map_err(|e| e.to_string())?;
+            Ok(booster.predict(&data, parallel))
+        } else {
+            Ok(booster.predict(&Matrix::new(data, rows, cols), parallel))
+        }
+    });
+    match preds {
+        Some(preds) => {
+            slice::from_raw_parts_mut(out, rows).copy_from_slice(&preds);
+            0
+        }
+        None => -1,
+    }
+}
+
+/// Free a booster. Passing null does nothing.
+///
+/// # Safety
+/// `booster` must be a handle from this library, that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn forust_booster_free(booster: *mut ForustBooster) {
+    if !booster.is_null() {
+        drop(Box::from_raw(booster));
+    }
+}
@@ -0,0 +1,262 @@
+//! An Arrow Flight service, that scores record batches with a persisted booster, to
+//! integrate forust with data platforms that exchange Arrow data.
+use arrow_array::{ArrayRef, Float64Array, RecordBatch};
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, Result as ActionResult, SchemaResult,
+    Ticket,
+};
+use arrow_schema::{DataType, Field, Schema};
+use forust_ml::arrow::matrix_data_from_record_batch;
+use forust_ml::errors::ForustError;
+use forust_ml::gradientbooster::GradientBooster;
+use futures::stream::{BoxStream, StreamExt, TryStreamExt};
+use std::sync::Arc;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status, Streaming};
+
+/// Name of the column of predictions, in the record batches streamed back.
+pub const PREDICTION_COLUMN: &str = "prediction";
+
+/// An Arrow Flight service, that scores record batches with a persisted booster.
+///
+/// Record batches are streamed to the service with `DoExchange`, and a record batch
+/// with a single `prediction` column is streamed back for each one, in the same order.
+/// If the booster has feature names, the features are read from the columns with those
+/// names, so the batches can have other columns, in any order. Otherwise the features
+/// are read from the columns of the batch, in order. Null values are missing.
+///
+/// The other Flight methods are not implemented.
+pub struct FlightScoringService {
+    booster: Arc<GradientBooster>,
+}
+
+impl FlightScoringService {
+    /// Create a service scoring record batches with a booster.
+    ///
+    /// * `booster` - The booster used to score record batches.
+    pub fn new(booster: GradientBooster) -> Self {
+        FlightScoringService {
+            booster: Arc::new(booster),
+        }
+    }
+
+    /// The gRPC service, to add to a `tonic` server along with other services.
+    pub fn into_server(self) -> FlightServiceServer<Self> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Serve the service on an address, such as `0.0.0.0:50051`, until the process is stopped.
+    ///
+    /// * `addr` - The address to listen on.
+    pub async fn serve(self, addr: &str) -> Result<(), ForustError> {
+        let addr = addr
+            .parse()
+            .map_err(|e: std::net::AddrParseError| ForustError::UnableToRead(e.to_string()))?;
+        Server::builder()
+            .add_service(self.into_server())
+            .serve(addr)
+            .await
+            .map_err(|e| ForustError::UnableToRead(e.to_string()))
+    }
+}
+
+/// Score a record batch, returning a record batch of the predictions.
+fn predict_record_batch(
+    booster: &GradientBooster,
+    batch: &RecordBatch,
+) -> Result<RecordBatch, ForustError> {
+    let data = matrix_data_from_record_batch(batch, booster.feature_names.as_deref())?;
+    let preds = booster.predict(&data.as_matrix(), true);
+    let schema = Schema::new(vec![Field::new(
+        PREDICTION_COLUMN,
+        DataType::Float64,
+        false,
+    )]);
+    let column = Arc::new(Float64Array::from(preds)) as ArrayRef;
+    RecordBatch::try_new(Arc::new(schema), vec![column])
+        .map_err(|e| ForustError::UnableToWrite(e.to_string()))
+}
+
+#[tonic::async_trait]
+impl FlightService for FlightScoringService {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+    type DoActionStream = BoxStream<'static, Result<ActionResult, Status>>;
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+    type DoExchangeStream = BoxStream<'static, Result<FlightData, Status>>;
+
+    async fn do_exchange(
+        &self,
+        request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        let booster = Arc::clone(&self.booster);
+        let batches = FlightRecordBatchStream::new_from_flight_data(
+            request.into_inner().map_err(FlightError::from),
+        );
+        // Scoring is CPU bound, so each batch is scored off the async runtime.
+        let predictions = batches.then(move |batch| {
+            let booster = Arc::clone(&booster);
+            async move {
+                let batch = batch?;
+                tokio::task::spawn_blocking(move || predict_record_batch(&booster, &batch))
+                    .await
+                    .map_err(|e| FlightError::ExternalError(Box::new(e)))?
+                    .map_err(|e| FlightError::ExternalError(Box::new(e)))
+            }
+        });
+        let stream = FlightDataEncoderBuilder::new()
+            .build(predictions)
+            .map_err(Status::from);
+        Ok(Response::new(stream.boxed()))
+    }
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn get_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn do_get(
+        &self,
+        _request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("Only DoExchange is implemented."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Float64Type;
+    use arrow_flight::FlightClient;
+    use forust_ml::arrow::matrix_to_record_batch;
+    use forust_ml::data::Matrix;
+    use std::fs;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Channel;
+
+    #[test]
+    fn test_flight_scoring_service() {
+        let file = fs::read_to_string("../resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("../resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let names: Vec<String> = (0..5).map(|i| format!("f{}", i)).collect();
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_feature_names(Some(names.clone()));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let expected = booster.predict(&data, true);
+        // The columns are sent in reverse, to check they are selected by name.
+        let reversed: Vec<String> = names.iter().rev().cloned().collect();
+        let reversed_data: Vec<f64> = (0..5)
+            .rev()
+            .flat_map(|j| data.get_col(j).to_vec())
+            .collect();
+        let batch =
+            matrix_to_record_batch(&Matrix::new(&reversed_data, 891, 5), &reversed).unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let preds: Vec<f64> = runtime.block_on(async move {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+            let service = FlightScoringService::new(booster);
+            tokio::spawn(
+                Server::builder()
+                    .add_service(service.into_server())
+                    .serve_with_incoming(incoming),
+            );
+            let channel = Channel::from_shared(format!("http://{}", addr))
+                .unwrap()
+                .connect()
+                .await
+                .unwrap();
+            let mut client = FlightClient::new(channel);
+            let batches = vec![Ok(batch.slice(0, 500)), Ok(batch.slice(500, 391))];
+            let request = FlightDataEncoderBuilder::new().build(futures::stream::iter(batches));
+            let response: Vec<RecordBatch> = client
+                .do_exchange(request)
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            response
+                .iter()
+                .flat_map(|b| {
+                    b.column_by_name(PREDICTION_COLUMN)
+                        .unwrap()
+                        .as_primitive::<Float64Type>()
+                        .values()
+                        .to_vec()
+                })
+                .collect()
+        });
+        assert_eq!(preds, expected);
+    }
+}
@@ -0,0 +1,61 @@
+package forust;
+
+/**
+ * A forust booster, loaded from a model saved in Rust, Python, or R, to score batches
+ * of records in process. The booster is held in native memory, and is freed by
+ * {@link #close()}. Predicting is thread safe, but the booster can't be used once it
+ * has been closed.
+ */
+public final class Booster implements AutoCloseable {
+    static {
+        System.loadLibrary("forust_jni");
+    }
+
+    private long handle;
+
+    private Booster(long handle) {
+        this.handle = handle;
+    }
+
+    /** Load a booster from the json written by {@code save_booster}, or {@code json_dump}. */
+    public static Booster fromJson(String json) {
+        return new Booster(loadJson(json));
+    }
+
+    /** Load a booster from the bytes written by {@code save_binary}. */
+    public static Booster fromBinary(byte[] bytes) {
+        return new Booster(loadBinary(bytes));
+    }
+
+    /**
+     * Predict on a batch of {@code rows} records, of {@code cols} features each.
+     *
+     * @param data The values of the records, the values of each row are contiguous if
+     *     {@code rowMajor} is true, and the values of each column otherwise.
+     * @param parallel Predict on multiple threads.
+     * @return The prediction of each record.
+     */
+    public double[] predict(double[] data, int rows, int cols, boolean rowMajor, boolean parallel) {
+        return predictHandle(handle, data, rows, cols, rowMajor, parallel);
+    }
+
+    /** Predict on a batch of row major records, on a single thread. */
+    public double[] predict(double[] data, int rows, int cols) {
+        return predict(data, rows, cols, true, false);
+    }
+
+    @Override
+    public synchronized void close() {
+        free(handle);
+        handle = 0;
+    }
+
+    private static native long loadJson(String json);
+
+    private static native long loadBinary(byte[] bytes);
+
+    private static native double[] predictHandle(
+            long handle, double[] data, int rows, int cols, boolean rowMajor, boolean parallel);
+
+    private static native void free(long handle);
+}
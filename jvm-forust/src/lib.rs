@@ -0,0 +1,183 @@
+//! JNI bindings of the prediction path, so Java, and Scala jobs can load a forust
+//! model, and score batches in process. The native methods of `forust.Booster`, in
+//! `java/forust/Booster.java`, are implemented here. A booster is passed to Java as
+//! a handle, the address of the boxed booster, which is freed by `Booster.close`.
+use forust_ml::data::{Matrix, RowMajorView};
+use forust_ml::gradientbooster::GradientBooster;
+use jni::objects::{JByteArray, JClass, JDoubleArray, JString, ReleaseMode};
+use jni::sys::{jboolean, jdoubleArray, jint, jlong, JNI_TRUE};
+use jni::JNIEnv;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const ILLEGAL_ARGUMENT: &str = "java/lang/IllegalArgumentException";
+
+/// Throw an exception with the message of an error, returning the value returned
+/// to Java, which is ignored as an exception is pending.
+fn throw<T>(env: &mut JNIEnv, message: String, ignored: T) -> T {
+    // If an exception can't be thrown, one is already pending.
+    let _ = env.throw_new(ILLEGAL_ARGUMENT, message);
+    ignored
+}
+
+fn into_handle(booster: GradientBooster) -> jlong {
+    Box::into_raw(Box::new(booster)) as jlong
+}
+
+/// Borrow the booster of a handle.
+///
+/// # Safety
+/// The handle must be from `into_handle`, and not have been freed.
+unsafe fn from_handle<'a>(handle: jlong) -> &'a GradientBooster {
+    &*(handle as *const GradientBooster)
+}
+
+#[no_mangle]
+pub extern "system" fn Java_forust_Booster_loadJson(
+    mut env: JNIEnv,
+    _class: JClass,
+    json: JString,
+) -> jlong {
+    let json: String = match env.get_string(&json) {
+        Ok(s) => s.into(),
+        Err(e) => return throw(&mut env, e.to_string(), 0),
+    };
+    match GradientBooster::from_json(&json) {
+        Ok(booster) => into_handle(booster),
+        Err(e) => throw(&mut env, e.to_string(), 0),
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_forust_Booster_loadBinary(
+    mut env: JNIEnv,
+    _class: JClass,
+    bytes: JByteArray,
+) -> jlong {
+    let bytes = match env.convert_byte_array(&bytes) {
+        Ok(b) => b,
+        Err(e) => return throw(&mut env, e.to_string(), 0),
+    };
+    match GradientBooster::from_binary(&bytes) {
+        Ok(booster) => into_handle(booster),
+        Err(e) => throw(&mut env, e.to_string(), 0),
+    }
+}
+
+fn predict(
+    booster: &GradientBooster,
+    values: &[f64],
+    rows: usize,
+    cols: usize,
+    row_major: bool,
+    parallel: bool,
+) -> Result<Vec<f64>, String> {
+    let n_features = booster.n_features();
+    if rows.checked_mul(cols) != Some(values.len()) {
+        Err(format!(
+            "The data has {} values, but {} rows, and {} columns.",
+            values.len(),
+            rows,
+            cols
+        ))
+    } else if cols < n_features {
+        Err(format!(
+            "The data has {} columns, but the model has {} features.",
+            cols, n_features
+        ))
+    } else if row_major {
+        RowMajorView::new(values, rows, cols)
+            .map(|data| booster.predict(&data, parallel))
+            .map_err(|e| e.to_string())
+    } else {
+        Ok(booster.predict(&Matrix::new(values, rows, cols), parallel))
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_forust_Booster_predictHandle(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    data: JDoubleArray,
+    rows: jint,
+    cols: jint,
+    row_major: jboolean,
+    parallel: jboolean,
+) -> jdoubleArray {
+    // A panic can't unwind into the JVM, it's thrown as an exception instead.
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let null = std::ptr::null_mut();
+        if handle == 0 {
+            return throw(&mut env, "The booster has been closed.".to_string(), null);
+        }
+        let booster = unsafe { from_handle(handle) };
+        if rows < 0 || cols < 0 {
+            let message = format!("The shape ({}, {}) can't be negative.", rows, cols);
+            return throw(&mut env, message, null);
+        }
+        let (rows, cols) = (rows as usize, cols as usize);
+        let parallel = parallel == JNI_TRUE;
+        // The array is read in place, no JNI functions are called until it's released.
+        let preds = match unsafe { env.get_array_elements_critical(&data, ReleaseMode::NoCopyBack) }
+        {
+            Ok(elements) => predict(
+                booster,
+                &elements,
+                rows,
+                cols,
+                row_major == JNI_TRUE,
+                parallel,
+            ),
+            Err(e) => Err(e.to_string()),
+        };
+        let preds = match preds {
+            Ok(p) => p,
+            Err(e) => return throw(&mut env, e, null),
+        };
+        let out = match env.new_double_array(preds.len() as jint) {
+            Ok(a) => a,
+            Err(e) => return throw(&mut env, e.to_string(), null),
+        };
+        if let Err(e) = env.set_double_array_region(&out, 0, &preds) {
+            return throw(&mut env, e.to_string(), null);
+        }
+        out.into_raw()
+    }));
+    match result {
+        Ok(out) => out,
+        Err(_) => throw(
+            &mut env,
+            "forust panicked".to_string(),
+            std::ptr::null_mut(),
+        ),
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_forust_Booster_free(_env: JNIEnv, _class: JClass, handle: jlong) {
+    if handle != 0 {
+        drop(unsafe { Box::from_raw(handle as *mut GradientBooster) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_predict_short_matrix() {
+        let data_vec = vec![1., 2., 3., 4., 5., 6., 0., 1., 0., 1., 0., 1.];
+        let y = vec![0., 0., 0., 1., 1., 1.];
+        let data = Matrix::new(&data_vec, 6, 2);
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_min_leaf_weight(0.);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+
+        let preds = predict(&booster, &data_vec, 6, 2, false, false).unwrap();
+        assert_eq!(preds, booster.predict(&data, false));
+        let err = predict(&booster, &data_vec[..6], 6, 1, true, false).unwrap_err();
+        assert!(err.contains("has 1 columns"));
+        assert!(predict(&booster, &data_vec, usize::MAX, 2, true, false).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use forust_ml::data::{Matrix, RowMajorView};
+use forust_ml::gradientbooster::GradientBooster as CrateGradientBooster;
+use napi::bindgen_prelude::{Buffer, Float64Array};
+use napi::{Error, Result};
+use napi_derive::napi;
+
+fn to_js_error<T, E: std::fmt::Display>(value: std::result::Result<T, E>) -> Result<T> {
+    value.map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Check a row, or each row of a batch, has a value for every feature of the booster.
+fn check_features(booster: &CrateGradientBooster, cols: usize) -> Result<()> {
+    let n_features = booster.n_features();
+    if cols < n_features {
+        Err(Error::from_reason(format!(
+            "The data has {} columns, but the model has {} features.",
+            cols, n_features
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// A booster trained, and saved in Rust, Python, or R, loaded to score rows natively
+/// from Node.js. Only the prediction path is exposed.
+#[napi]
+pub struct GradientBooster {
+    booster: CrateGradientBooster,
+}
+
+#[napi]
+impl GradientBooster {
+    /// Load a booster from the json written by `save_booster`, or `json_dump`.
+    #[napi(factory)]
+    pub fn from_json(json_str: String) -> Result<Self> {
+        let booster = to_js_error(CrateGradientBooster::from_json(&json_str))?;
+        Ok(GradientBooster { booster })
+    }
+
+    /// Load a booster from the bytes written by `save_binary`.
+    #[napi(factory)]
+    pub fn from_binary(bytes: Buffer) -> Result<Self> {
+        let booster = to_js_error(CrateGradientBooster::from_binary(&bytes))?;
+        Ok(GradientBooster { booster })
+    }
+
+    /// Load a booster from a file, in the binary format if the path ends with
+    /// `.bin`, and from json otherwise.
+    #[napi(factory)]
+    pub fn load(path: String) -> Result<Self> {
+        let booster = if path.ends_with(".bin") {
+            CrateGradientBooster::load_binary(&path)
+        } else {
+            CrateGradientBooster::load_booster(&path)
+        };
+        Ok(GradientBooster {
+            booster: to_js_error(booster)?,
+        })
+    }
+
+    /// Predict on a batch of rows.
+    ///
+    /// * `data` - The values of the rows, the values of each row are contiguous if
+    ///   `rowMajor` is true, which is the default, and the values of each column otherwise.
+    /// * `rows` - The number of rows.
+    /// * `cols` - The number of columns.
+    /// * `row_major` - Whether the data is in row major order.
+    /// * `parallel` - Predict on multiple threads, false by default.
+    #[napi(catch_unwind)]
+    pub fn predict(
+        &self,
+        data: Float64Array,
+        rows: u32,
+        cols: u32,
+        row_major: Option<bool>,
+        parallel: Option<bool>,
+    ) -> Result<Float64Array> {
+        let (rows, cols) = (rows as usize, cols as usize);
+        let parallel = parallel.unwrap_or(false);
+        check_features(&self.booster, cols)?;
+        let preds = if row_major.unwrap_or(true) {
+            let data = to_js_error(RowMajorView::new(&data, rows, cols))?;
+            self.booster.predict(&data, parallel)
+        } else {
+            if rows.checked_mul(cols) != Some(data.len()) {
+                return Err(Error::from_reason(format!(
+                    "The data has {} values, but {} rows, and {} columns.",
+                    data.len(),
+                    rows,
+                    cols
+                )));
+            }
+            self.booster
+                .predict(&Matrix::new(&data, rows, cols), parallel)
+        };
+        Ok(Float64Array::new(preds))
+    }
+
+    /// Predict on a single row.
+    #[napi(catch_unwind)]
+    pub fn predict_row(&self, row: Float64Array) -> Result<f64> {
+        check_features(&self.booster, row.len())?;
+        Ok(self.booster.predict_row(&row))
+    }
+
+    /// Set the iteration to predict up to, including the tree of that iteration, the
+    /// same as `set_prediction_iteration` of the crate. All trees are used by default.
+    #[napi]
+    pub fn set_prediction_iteration(&mut self, iteration: Option<u32>) -> Result<()> {
+        let n_trees = self.booster.trees.len();
+        let iteration = iteration.map(|i| i as usize);
+        if let Some(i) = iteration.filter(|i| *i >= n_trees) {
+            return Err(Error::from_reason(format!(
+                "The iteration {} is past the last of the {} trees.",
+                i, n_trees
+            )));
+        }
+        self.booster.prediction_iteration = iteration.map(|i| i + 1);
+        Ok(())
+    }
+
+    /// Get the value associated with a key in the booster's metadata.
+    #[napi]
+    pub fn get_metadata(&self, key: String) -> Option<String> {
+        self.booster.get_metadata(&key)
+    }
+
+    /// The names of the features the booster was trained with, if it has them.
+    #[napi(getter)]
+    pub fn feature_names(&self) -> Option<Vec<String>> {
+        self.booster.feature_names.clone()
+    }
+}
@@ -42,6 +42,7 @@ class BoosterType(Protocol):
         evaluation_data: None
         | list[tuple[FrameLike, ArrayLike, None | ArrayLike]] = None,
         parallel: bool = True,
+        row_major: bool = False,
     ):
         ...
 
@@ -51,6 +52,7 @@ class BoosterType(Protocol):
         rows: int,
         cols: int,
         parallel: bool = True,
+        row_major: bool = False,
     ) -> np.ndarray:
         ...
 
@@ -61,6 +63,7 @@ class BoosterType(Protocol):
         cols: int,
         method: str,
         parallel: bool = True,
+        row_major: bool = False,
     ) -> np.ndarray:
         ...
 
@@ -71,6 +74,18 @@ class BoosterType(Protocol):
     ) -> float:
         ...
 
+    def data_partial_dependence(
+        self,
+        feature: int,
+        grid: list[float],
+        flat_data: np.ndarray,
+        rows: int,
+        cols: int,
+        sample_weight: np.ndarray | None = None,
+        row_major: bool = False,
+    ) -> list[float]:
+        ...
+
     def text_dump(self) -> list[str]:
         ...
 
@@ -81,6 +96,9 @@ class BoosterType(Protocol):
     def save_booster(self, path: str):
         ...
 
+    def save_upstream_json(self, path: str):
+        ...
+
     @classmethod
     def from_json(cls, json_str: str) -> BoosterType:
         ...
@@ -101,11 +119,21 @@ class BoosterType(Protocol):
         ...
 
 
-def _convert_input_frame(X: FrameLike) -> tuple[list[str], np.ndarray, int, int]:
-    """Convert data to format needed by booster.
+def _convert_input_frame(
+    X: FrameLike, order: Union[str, None] = None
+) -> tuple[list[str], np.ndarray, int, int, bool]:
+    """Convert data to format needed by booster. Contiguous float64 data is
+    flattened without a copy, C contiguous data is passed as row major, and all
+    other data as column major.
+
+    Args:
+        X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+        order (Union[str, None], optional): Force the data to be flattened in "C"
+            (row major), or "F" (column major) order. If `None` is passed, the order
+            that doesn't copy the data is used. Defaults to `None`.
 
     Returns:
-        tuple[list[str], np.ndarray, int, int, ]: Return column names, the flat data, number of rows, and the number of columns
+        tuple[list[str], np.ndarray, int, int, bool]: Return column names, the flat data, number of rows, the number of columns, and if the flat data is row major
     """
     if isinstance(X, pd.DataFrame):
         X_ = X.to_numpy()
@@ -116,9 +144,12 @@ def _convert_input_frame(X: FrameLike) -> tuple[list[str], np.ndarray, int, int]
         features_ = []
     if not np.issubdtype(X_.dtype, "float64"):
         X_ = X_.astype(dtype="float64", copy=False)
-    flat_data = X_.ravel(order="F")
+    if order is None:
+        row_major = X_.flags.c_contiguous and not X_.flags.f_contiguous
+        order = "C" if row_major else "F"
+    flat_data = X_.ravel(order=order)
     rows, cols = X_.shape
-    return features_, flat_data, rows, cols
+    return features_, flat_data, rows, cols, order == "C"
 
 
 def _convert_input_array(x: ArrayLike) -> np.ndarray:
@@ -332,7 +363,7 @@ class GradientBooster:
                 in `early_stopping_rounds` training will be cut short.
         """
 
-        features_, flat_data, rows, cols = _convert_input_frame(X)
+        features_, flat_data, rows, cols, row_major = _convert_input_frame(X)
         if len(features_) > 0:
             self.feature_names_in_ = features_
             self.insert_metadata("feature_names_in_", self.feature_names_in_)
@@ -366,9 +397,14 @@ class GradientBooster:
                     eval_X, eval_y = eval_
                     eval_w_ = np.ones(eval_X.shape[0], dtype="float64")
 
-                features_, eval_flat_data, eval_rows, eval_cols = _convert_input_frame(
-                    eval_X
-                )
+                # The evaluation data has to be in the same layout as the training data.
+                (
+                    features_,
+                    eval_flat_data,
+                    eval_rows,
+                    eval_cols,
+                    _,
+                ) = _convert_input_frame(eval_X, order="C" if row_major else "F")
                 self._validate_features(features_)
                 evaluation_data_.append(
                     (
@@ -389,6 +425,7 @@ class GradientBooster:
             y=y_,
             sample_weight=sample_weight_,
             evaluation_data=evaluation_data_,
+            row_major=row_major,
         )
 
     def _validate_features(self, features: list[str]):
@@ -422,7 +459,7 @@ class GradientBooster:
         Returns:
             np.ndarray: Returns a numpy array of the predictions.
         """
-        features_, flat_data, rows, cols = _convert_input_frame(X)
+        features_, flat_data, rows, cols, row_major = _convert_input_frame(X)
         self._validate_features(features_)
         parallel_ = self.parallel if parallel is None else parallel
         return self.booster.predict(
@@ -430,6 +467,7 @@ class GradientBooster:
             rows=rows,
             cols=cols,
             parallel=parallel_,
+            row_major=row_major,
         )
 
     def predict_contributions(
@@ -453,7 +491,7 @@ class GradientBooster:
         Returns:
             np.ndarray: Returns a numpy array of the predictions.
         """
-        features_, flat_data, rows, cols = _convert_input_frame(X)
+        features_, flat_data, rows, cols, row_major = _convert_input_frame(X)
         self._validate_features(features_)
         parallel_ = self.parallel if parallel is None else parallel
 
@@ -463,6 +501,7 @@ class GradientBooster:
             cols=cols,
             method=CONTRIBUTION_METHODS[method],
             parallel=parallel_,
+            row_major=row_major,
         )
         return np.reshape(contributions, (rows, cols + 1))
 
@@ -472,7 +511,9 @@ class GradientBooster:
         feature: Union[str, int],
         samples: int | None = 100,
         exclude_missing: bool = True,
-        percentile_bounds: tuple[float, float] = (0.2, 0.98),
+        percentile_bounds: tuple[float, float] = (0.05, 0.95),
+        sample_weight: Union[ArrayLike, None] = None,
+        grid: Union[ArrayLike, None] = None,
     ) -> np.ndarray:
         """Calculate the partial dependence values of a feature. For each unique
         value of the feature, this gives the estimate of the predicted value for that
@@ -489,9 +530,19 @@ class GradientBooster:
             samples (int | None, optional): Number of evenly spaced samples to select. If None
                 is passed all unique values will be used. Defaults to 100.
             exclude_missing (bool, optional): Should missing excluded from the features? Defaults to True.
-            percentile_bounds (tuple[float, float], optional): Upper and lower percentiles to start at
-                when calculating the samples. Defaults to (0.2, 0.98) to cap the samples selected
-                at the 5th and 95th percentiles respectively.
+            percentile_bounds (tuple[float, float], optional): Lower and upper percentiles of the
+                feature, the samples are evenly spaced between. Defaults to (0.05, 0.95) to cap
+                the samples selected at the 5th and 95th percentiles respectively, leaving out
+                the sparse tails of the feature.
+            sample_weight (Union[ArrayLike, None], optional): Instance weights of the records
+                of X. If provided, the partial dependence is the weighted average of the
+                predictions over X, with the feature set to each value, so it reflects the
+                weighted population, such as exposure weighted data. Otherwise, the
+                partial dependence is calculated from the cover of the training data in
+                each tree. Defaults to None.
+            grid (Union[ArrayLike, None], optional): The values of the feature to calculate
+                the partial dependence for. If provided, `samples`, `exclude_missing`, and
+                `percentile_bounds` are ignored. Defaults to None.
 
         Raises:
             ValueError: An error will be raised if the provided X parameter is not a
@@ -533,16 +584,36 @@ class GradientBooster:
             )
         min_p, max_p = percentile_bounds
         values = values[~(np.isnan(values) | (values == self.missing))]
-        if samples is None:
+        if grid is not None:
+            search_values = np.asarray(grid, dtype="float64")
+        elif samples is None:
             search_values = np.sort(np.unique(values))
         else:
             # Exclude missing from this calculation.
             search_values = np.quantile(values, np.linspace(min_p, max_p, num=samples))
 
         # Add missing back, if they wanted it...
-        if not exclude_missing:
+        if not exclude_missing and grid is None:
             search_values = np.append([self.missing], search_values)
 
+        if sample_weight is not None:
+            _, flat_data, rows, cols, row_major = _convert_input_frame(X)
+            sample_weight_ = (
+                sample_weight.to_numpy()
+                if isinstance(sample_weight, pd.Series)
+                else np.asarray(sample_weight)
+            ).astype("float64", copy=False)
+            pd_values = self.booster.data_partial_dependence(
+                feature=feature_idx,
+                grid=[float(v) for v in search_values],
+                flat_data=flat_data,
+                rows=rows,
+                cols=cols,
+                sample_weight=sample_weight_,
+                row_major=row_major,
+            )
+            return np.column_stack([search_values, pd_values])
+
         res = []
         for v in search_values:
             res.append(
@@ -598,6 +669,16 @@ class GradientBooster:
         """
         self.booster.save_booster(str(path))
 
+    def save_upstream_json(self, path: str):
+        """Save a booster object as a json file, that can be loaded by the upstream
+        forust package. Models saved by the upstream package can be loaded with
+        `load_booster`.
+
+        Args:
+            path (str): Path to save the booster object.
+        """
+        self.booster.save_upstream_json(str(path))
+
     def _standardize_monotonicity_map(
         self,
         X: Union[pd.DataFrame, np.ndarray],
@@ -0,0 +1,218 @@
+from __future__ import annotations
+
+import sys
+from typing import Any, Union
+
+import numpy as np
+from sklearn.base import BaseEstimator, ClassifierMixin, RegressorMixin
+from sklearn.utils.validation import check_is_fitted
+
+from forust import ArrayLike, FrameLike, GradientBooster, _convert_input_array
+
+
+class _ForustEstimator(BaseEstimator):
+    """Shared parameters of the scikit-learn estimators. The parameters are stored as
+    they are passed, and the booster is only created when the estimator is fit, so the
+    estimator can be cloned, and have its parameters set by `GridSearchCV`. See
+    `GradientBooster` for a description of each parameter.
+    """
+
+    _objective_type = "SquaredLoss"
+
+    def __init__(
+        self,
+        *,
+        iterations: int = 100,
+        learning_rate: float = 0.3,
+        max_depth: int = 5,
+        max_leaves: int = sys.maxsize,
+        l2: float = 1.0,
+        gamma: float = 0.0,
+        min_leaf_weight: float = 1.0,
+        base_score: float | None = None,
+        nbins: int = 256,
+        parallel: bool = True,
+        allow_missing_splits: bool = True,
+        monotone_constraints: Union[dict[Any, int], None] = None,
+        subsample: float = 1.0,
+        top_rate: float = 0.1,
+        other_rate: float = 0.2,
+        seed: int = 0,
+        missing: float = np.nan,
+        create_missing_branch: bool = False,
+        sample_method: str | None = None,
+        grow_policy: str = "DepthWise",
+        evaluation_metric: str | None = None,
+        early_stopping_rounds: int | None = None,
+        initialize_base_score: bool = False,
+    ):
+        self.iterations = iterations
+        self.learning_rate = learning_rate
+        self.max_depth = max_depth
+        self.max_leaves = max_leaves
+        self.l2 = l2
+        self.gamma = gamma
+        self.min_leaf_weight = min_leaf_weight
+        self.base_score = base_score
+        self.nbins = nbins
+        self.parallel = parallel
+        self.allow_missing_splits = allow_missing_splits
+        self.monotone_constraints = monotone_constraints
+        self.subsample = subsample
+        self.top_rate = top_rate
+        self.other_rate = other_rate
+        self.seed = seed
+        self.missing = missing
+        self.create_missing_branch = create_missing_branch
+        self.sample_method = sample_method
+        self.grow_policy = grow_policy
+        self.evaluation_metric = evaluation_metric
+        self.early_stopping_rounds = early_stopping_rounds
+        self.initialize_base_score = initialize_base_score
+
+    def _fit_booster(
+        self,
+        X: FrameLike,
+        y: np.ndarray,
+        sample_weight: Union[ArrayLike, None] = None,
+        evaluation_data: Union[list[tuple], None] = None,
+    ):
+        self.booster_ = GradientBooster(
+            objective_type=self._objective_type, **self.get_params()
+        )
+        self.booster_.fit(
+            X, y, sample_weight=sample_weight, evaluation_data=evaluation_data
+        )
+        self.n_features_in_ = X.shape[1]
+        if hasattr(self.booster_, "feature_names_in_"):
+            self.feature_names_in_ = np.asarray(self.booster_.feature_names_in_)
+        return self
+
+    def _predict_booster(self, X: FrameLike) -> np.ndarray:
+        check_is_fitted(self, "booster_")
+        if X.shape[1] != self.n_features_in_:
+            raise ValueError(
+                f"X has {X.shape[1]} features, but the estimator was fit on {self.n_features_in_} features."
+            )
+        return self.booster_.predict(X)
+
+
+class ForustRegressor(RegressorMixin, _ForustEstimator):
+    """A gradient booster, fit with the "SquaredLoss" objective, that can be used
+    in scikit-learn pipelines, and model selection tools such as `GridSearchCV`.
+    """
+
+    _objective_type = "SquaredLoss"
+
+    def fit(
+        self,
+        X: FrameLike,
+        y: ArrayLike,
+        sample_weight: Union[ArrayLike, None] = None,
+        evaluation_data: Union[list[tuple], None] = None,
+    ) -> ForustRegressor:
+        """Fit the regressor on a provided dataset.
+
+        Args:
+            X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+            y (ArrayLike): Either a pandas Series, or a 1 dimensional numpy array.
+            sample_weight (Union[ArrayLike, None], optional): Instance weights to use when
+                training the model. Defaults to None.
+            evaluation_data (Union[list[tuple], None], optional): Evaluation sets, passed
+                to `GradientBooster.fit`. Defaults to None.
+
+        Returns:
+            ForustRegressor: The fitted regressor.
+        """
+        return self._fit_booster(
+            X, _convert_input_array(y), sample_weight, evaluation_data
+        )
+
+    def predict(self, X: FrameLike) -> np.ndarray:
+        """Predict the target of new data.
+
+        Args:
+            X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+
+        Returns:
+            np.ndarray: The prediction of each record.
+        """
+        return self._predict_booster(X)
+
+
+class ForustClassifier(ClassifierMixin, _ForustEstimator):
+    """A gradient booster, fit with the "LogLoss" objective, that can be used in
+    scikit-learn pipelines, and model selection tools such as `GridSearchCV`. The
+    target can have any two classes, the second of the sorted classes is the
+    positive class.
+    """
+
+    _objective_type = "LogLoss"
+
+    def fit(
+        self,
+        X: FrameLike,
+        y: ArrayLike,
+        sample_weight: Union[ArrayLike, None] = None,
+        evaluation_data: Union[list[tuple], None] = None,
+    ) -> ForustClassifier:
+        """Fit the classifier on a provided dataset.
+
+        Args:
+            X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+            y (ArrayLike): The class of each record, there must be exactly two classes.
+            sample_weight (Union[ArrayLike, None], optional): Instance weights to use when
+                training the model. Defaults to None.
+            evaluation_data (Union[list[tuple], None], optional): Evaluation sets, passed
+                to `GradientBooster.fit`, their target has to be encoded as 0 and 1.
+                Defaults to None.
+
+        Raises:
+            ValueError: Raised if `y` doesn't have exactly two classes.
+
+        Returns:
+            ForustClassifier: The fitted classifier.
+        """
+        y_ = np.asarray(y)
+        self.classes_ = np.unique(y_)
+        if len(self.classes_) != 2:
+            raise ValueError(
+                f"ForustClassifier only supports binary classification, but y has {len(self.classes_)} classes."
+            )
+        y_ = (y_ == self.classes_[1]).astype("float64")
+        return self._fit_booster(X, y_, sample_weight, evaluation_data)
+
+    def decision_function(self, X: FrameLike) -> np.ndarray:
+        """Predict the log odds of the positive class.
+
+        Args:
+            X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+
+        Returns:
+            np.ndarray: The log odds of each record being the positive class.
+        """
+        return self._predict_booster(X)
+
+    def predict_proba(self, X: FrameLike) -> np.ndarray:
+        """Predict the probability of each class.
+
+        Args:
+            X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+
+        Returns:
+            np.ndarray: A two column array, with the probability of each class, in the
+                order of `classes_`.
+        """
+        p = 1 / (1 + np.exp(-self.decision_function(X)))
+        return np.column_stack([1 - p, p])
+
+    def predict(self, X: FrameLike) -> np.ndarray:
+        """Predict the class of new data.
+
+        Args:
+            X (FrameLike): Either a pandas DataFrame, or a 2 dimensional numpy array.
+
+        Returns:
+            np.ndarray: The most likely class of each record.
+        """
+        return self.classes_[(self.decision_function(X) > 0).astype(int)]
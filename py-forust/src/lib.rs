@@ -1,5 +1,5 @@
 use forust_ml::constraints::{Constraint, ConstraintMap};
-use forust_ml::data::Matrix;
+use forust_ml::data::{row_major_to_column_major, Matrix, RowMajorView};
 use forust_ml::gradientbooster::EvaluationData;
 use forust_ml::gradientbooster::{
     ContributionsMethod, GradientBooster as CrateGradientBooster, GrowPolicy,
@@ -148,12 +148,37 @@ impl GradientBooster {
         y: PyReadonlyArray1<f64>,
         sample_weight: PyReadonlyArray1<f64>,
         evaluation_data: Option<Vec<PyEvaluationData>>,
+        row_major: Option<bool>,
     ) -> PyResult<()> {
         let flat_data = flat_data.as_slice()?;
-        let data = Matrix::new(flat_data, rows, cols);
         let y = y.as_slice()?;
         let sample_weight = sample_weight.as_slice()?;
 
+        // The evaluation data is passed in the same layout as the training data.
+        if row_major.unwrap_or(false) {
+            let data = to_value_error(RowMajorView::new(flat_data, rows, cols))?;
+            let evaluation_data_ = match evaluation_data.as_ref() {
+                None => None,
+                Some(values) => {
+                    let mut eval_data = Vec::new();
+                    for (a, r, c, y_, w_) in values.iter() {
+                        eval_data.push((
+                            to_value_error(RowMajorView::new(a.as_slice()?, *r, *c))?,
+                            y_.as_slice()?,
+                            w_.as_slice()?,
+                        ));
+                    }
+                    Some(eval_data)
+                }
+            };
+            return to_value_error(self.booster.fit_row_major(
+                &data,
+                y,
+                sample_weight,
+                evaluation_data_,
+            ));
+        }
+        let data = Matrix::new(flat_data, rows, cols);
         let evaluation_data_: Option<Vec<EvaluationData>> = match evaluation_data.as_ref() {
             None => None,
             Some(values) => {
@@ -181,11 +206,19 @@ impl GradientBooster {
         rows: usize,
         cols: usize,
         parallel: Option<bool>,
+        row_major: Option<bool>,
     ) -> PyResult<&'py PyArray1<f64>> {
         let flat_data = flat_data.as_slice()?;
-        let data = Matrix::new(flat_data, rows, cols);
         let parallel = parallel.unwrap_or(true);
-        Ok(self.booster.predict(&data, parallel).into_pyarray(py))
+        // C contiguous arrays are row major, and are predicted on without a copy.
+        let preds = if row_major.unwrap_or(false) {
+            let data = to_value_error(RowMajorView::new(flat_data, rows, cols))?;
+            self.booster.predict(&data, parallel)
+        } else {
+            let data = Matrix::new(flat_data, rows, cols);
+            self.booster.predict(&data, parallel)
+        };
+        Ok(preds.into_pyarray(py))
     }
     pub fn predict_contributions<'py>(
         &self,
@@ -195,9 +228,17 @@ impl GradientBooster {
         cols: usize,
         method: &str,
         parallel: Option<bool>,
+        row_major: Option<bool>,
     ) -> PyResult<&'py PyArray1<f64>> {
         let flat_data = flat_data.as_slice()?;
-        let data = Matrix::new(flat_data, rows, cols);
+        // The contributions are calculated on column major data.
+        let converted;
+        let data = if row_major.unwrap_or(false) {
+            converted = to_value_error(row_major_to_column_major(flat_data, rows, cols))?;
+            Matrix::new(&converted, rows, cols)
+        } else {
+            Matrix::new(flat_data, rows, cols)
+        };
         let parallel = parallel.unwrap_or(true);
         let method_ = to_value_error(ContributionsMethod::from_str(method))?;
         Ok(self
@@ -210,6 +251,33 @@ impl GradientBooster {
         Ok(self.booster.value_partial_dependence(feature, value))
     }
 
+    pub fn data_partial_dependence(
+        &self,
+        feature: usize,
+        grid: Vec<f64>,
+        flat_data: PyReadonlyArray1<f64>,
+        rows: usize,
+        cols: usize,
+        sample_weight: Option<PyReadonlyArray1<f64>>,
+        row_major: Option<bool>,
+    ) -> PyResult<Vec<f64>> {
+        let flat_data = flat_data.as_slice()?;
+        let sample_weight = match &sample_weight {
+            Some(w) => Some(w.as_slice()?),
+            None => None,
+        };
+        let pd = if row_major.unwrap_or(false) {
+            let data = to_value_error(RowMajorView::new(flat_data, rows, cols))?;
+            self.booster
+                .data_partial_dependence(feature, &grid, &data, sample_weight)
+        } else {
+            let data = Matrix::new(flat_data, rows, cols);
+            self.booster
+                .data_partial_dependence(feature, &grid, &data, sample_weight)
+        };
+        to_value_error(pd)
+    }
+
     pub fn text_dump(&self) -> PyResult<Vec<String>> {
         let mut trees = Vec::new();
         for t in &self.booster.trees {
@@ -225,6 +293,13 @@ impl GradientBooster {
         }
     }
 
+    pub fn save_upstream_json(&self, path: &str) -> PyResult<()> {
+        match self.booster.save_upstream_json(path) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(PyValueError::new_err(e.to_string())),
+        }
+    }
+
     pub fn json_dump(&self) -> PyResult<String> {
         match self.booster.json_dump() {
             Ok(m) => Ok(m),
@@ -269,6 +344,7 @@ impl GradientBooster {
         let objective_ = match self.booster.objective_type {
             ObjectiveType::LogLoss => "LogLoss",
             ObjectiveType::SquaredLoss => "SquaredLoss",
+            ObjectiveType::QuantileLoss => "QuantileLoss",
         };
         let sample_method_: Option<&str> = match self.booster.sample_method {
             SampleMethod::Random => Some("random"),
@@ -692,3 +692,37 @@ def test_booster_to_xgboosts_with_base_score_squared_loss(X_y):
     fmod.fit(X, y=y)
     fmod_preds = fmod.predict(X)
     assert np.allclose(fmod_preds, xmod_preds, atol=0.001)
+
+
+def test_booster_row_major(X_y):
+    X, y = X_y
+    X_f = np.asfortranarray(X.to_numpy(dtype="float64"))
+    X_c = np.ascontiguousarray(X_f)
+    fmod = GradientBooster(iterations=10, objective_type="LogLoss")
+    fmod.fit(X_f, y=y, evaluation_data=[(X_c, y)])
+    fmod_c = GradientBooster(iterations=10, objective_type="LogLoss")
+    fmod_c.fit(X_c, y=y, evaluation_data=[(X_f, y)])
+    assert np.allclose(fmod.predict(X_f), fmod_c.predict(X_c))
+    assert np.allclose(fmod.predict(X_c), fmod_c.predict(X_f))
+    assert np.allclose(
+        fmod.predict_contributions(X_c), fmod_c.predict_contributions(X_f)
+    )
+
+
+def test_weighted_partial_dependence(X_y):
+    X, y = X_y
+    fmod = GradientBooster(iterations=10, objective_type="LogLoss")
+    fmod.fit(X, y=y)
+    w = np.ones(X.shape[0])
+    p_d = fmod.partial_dependence(X, feature="age", samples=5, sample_weight=w)
+    assert p_d.shape == (5, 2)
+    # With unit weights, the average of the predictions over the data is returned.
+    X_ = X.copy()
+    X_["age"] = p_d[2, 0]
+    assert np.isclose(p_d[2, 1], fmod.predict(X_).mean())
+    # All the weight on a single record, gives the predictions of that record.
+    w = np.zeros(X.shape[0])
+    w[3] = 1.0
+    p_d_w = fmod.partial_dependence(X, feature="age", samples=5, sample_weight=w)
+    assert np.allclose(p_d_w[:, 0], p_d[:, 0])
+    assert np.isclose(p_d_w[2, 1], fmod.predict(X_.iloc[[3]])[0])
@@ -0,0 +1,63 @@
+from typing import Tuple
+
+import numpy as np
+import pandas as pd
+import pytest
+from sklearn.base import clone
+from sklearn.impute import SimpleImputer
+from sklearn.model_selection import GridSearchCV
+from sklearn.pipeline import make_pipeline
+
+from forust import GradientBooster
+from forust.sklearn import ForustClassifier, ForustRegressor
+
+
+@pytest.fixture
+def X_y() -> Tuple[pd.DataFrame, pd.Series]:
+    df = pd.read_csv("../resources/titanic.csv")
+    X = df.select_dtypes("number").drop(columns="survived").reset_index(drop=True)
+    y = df["survived"]
+    return X, y
+
+
+def test_classifier(X_y):
+    X, y = X_y
+    clf = ForustClassifier(iterations=10).fit(X, y.map({0: "died", 1: "survived"}))
+    fmod = GradientBooster(iterations=10, objective_type="LogLoss")
+    fmod.fit(X, y)
+    assert np.allclose(clf.decision_function(X), fmod.predict(X))
+    proba = clf.predict_proba(X)
+    assert proba.shape == (X.shape[0], 2)
+    assert np.allclose(proba.sum(axis=1), 1)
+    assert set(clf.predict(X)) <= {"died", "survived"}
+    assert list(clf.feature_names_in_) == list(X.columns)
+    with pytest.raises(ValueError):
+        ForustClassifier().fit(X, np.arange(X.shape[0]) % 3)
+
+
+def test_regressor(X_y):
+    X, y = X_y
+    y = X["fare"]
+    X = X.drop(columns="fare")
+    reg = ForustRegressor(iterations=10).fit(X, y)
+    fmod = GradientBooster(iterations=10, objective_type="SquaredLoss")
+    fmod.fit(X, y)
+    assert np.allclose(reg.predict(X), fmod.predict(X))
+
+
+def test_sklearn_tools(X_y):
+    X, y = X_y
+    clf = ForustClassifier(iterations=10, max_depth=3)
+    cloned = clone(clf)
+    assert cloned.get_params() == clf.get_params()
+    assert clf.set_params(max_depth=4).max_depth == 4
+
+    pipeline = make_pipeline(SimpleImputer(), ForustClassifier(iterations=10))
+    pipeline.fit(X, y)
+    assert pipeline.score(X, y) > 0.7
+
+    search = GridSearchCV(
+        ForustClassifier(iterations=10), {"max_depth": [2, 4]}, cv=2, scoring="roc_auc"
+    )
+    search.fit(X, y)
+    assert search.best_params_["max_depth"] in [2, 4]
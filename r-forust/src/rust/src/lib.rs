@@ -0,0 +1,138 @@
+use extendr_api::prelude::*;
+use forust_ml::data::Matrix;
+use forust_ml::gradientbooster::GradientBooster;
+use forust_ml::metric::Metric;
+use forust_ml::params::BoosterParams;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+fn to_r_error<T, E: std::fmt::Display>(value: std::result::Result<T, E>) -> Result<T> {
+    value.map_err(|e| Error::Other(e.to_string()))
+}
+
+/// Convert the named parameters passed from R, to the json values of `BoosterParams`.
+/// R numbers are doubles, so whole numbers are passed as integers, for the
+/// parameters that are counts.
+fn params_map(params: List) -> Result<Map<String, Value>> {
+    let mut values = Map::new();
+    for (name, value) in params.iter() {
+        let v = if value.is_null() {
+            Value::Null
+        } else if let Some(b) = value.as_bool() {
+            Value::Bool(b)
+        } else if let Some(i) = value.as_integer() {
+            Value::from(i)
+        } else if let Some(f) = value.as_real() {
+            if f.fract() == 0. && f.abs() < 2f64.powi(53) {
+                Value::from(f as i64)
+            } else {
+                Value::from(f)
+            }
+        } else if let Some(s) = value.as_str() {
+            Value::from(s)
+        } else {
+            return Err(Error::Other(format!(
+                "The parameter {} must be a single number, string, or logical.",
+                name
+            )));
+        };
+        values.insert(name.to_string(), v);
+    }
+    Ok(values)
+}
+
+/// The data of a double matrix, which R stores in column major order.
+fn matrix_data(x: &RMatrix<f64>) -> Result<Matrix<'_, f64>> {
+    let data = x
+        .as_real_slice()
+        .ok_or_else(|| Error::Other("x must be a double matrix.".to_string()))?;
+    Ok(Matrix::new(data, x.nrows(), x.ncols()))
+}
+
+/// A gradient booster, fit, and predicted on from R.
+struct ForustBooster {
+    booster: GradientBooster,
+}
+
+#[extendr]
+impl ForustBooster {
+    /// Create a booster from a named list of parameters, parameters that aren't
+    /// in the list keep their default value.
+    fn new(params: List) -> Result<Self> {
+        let params = to_r_error(BoosterParams::from_map(&params_map(params)?))?;
+        let booster = to_r_error(GradientBooster::from_params(params))?;
+        Ok(ForustBooster { booster })
+    }
+
+    fn fit(
+        &mut self,
+        x: RMatrix<f64>,
+        y: &[f64],
+        sample_weight: &[f64],
+        feature_names: Nullable<Vec<String>>,
+    ) -> Result<()> {
+        let data = matrix_data(&x)?;
+        self.booster.feature_names = feature_names.into_option();
+        to_r_error(self.booster.fit(&data, y, sample_weight, None))
+    }
+
+    fn predict(&self, x: RMatrix<f64>, parallel: bool) -> Result<Vec<f64>> {
+        let data = matrix_data(&x)?;
+        Ok(self.booster.predict(&data, parallel))
+    }
+
+    /// The mean permutation importance of each feature, with the evaluation metric
+    /// of the booster, if no metric is passed.
+    fn permutation_importance(
+        &self,
+        x: RMatrix<f64>,
+        y: &[f64],
+        sample_weight: &[f64],
+        metric: Nullable<String>,
+        n_repeats: i32,
+        seed: i32,
+    ) -> Result<Vec<f64>> {
+        let data = matrix_data(&x)?;
+        let metric = match metric.into_option() {
+            Some(m) => Some(to_r_error(Metric::from_str(&m))?),
+            None => None,
+        };
+        let importance = to_r_error(self.booster.permutation_importance(
+            &data,
+            y,
+            sample_weight,
+            metric,
+            n_repeats as usize,
+            seed as u64,
+        ))?;
+        Ok(importance.importances_mean)
+    }
+
+    fn feature_names(&self) -> Nullable<Vec<String>> {
+        match &self.booster.feature_names {
+            Some(names) => Nullable::NotNull(names.clone()),
+            None => Nullable::Null,
+        }
+    }
+
+    fn save_booster(&self, path: &str) -> Result<()> {
+        to_r_error(self.booster.save_booster(path))
+    }
+
+    fn load_booster(path: &str) -> Result<Self> {
+        let booster = to_r_error(GradientBooster::load_booster(path))?;
+        Ok(ForustBooster { booster })
+    }
+
+    fn json_dump(&self) -> Result<String> {
+        to_r_error(self.booster.json_dump())
+    }
+}
+
+// Macro to generate exports.
+// This ensures exported functions are registered with R.
+// See corresponding C code in `entrypoint.c`.
+extendr_module! {
+    mod forust;
+    impl ForustBooster;
+}
@@ -0,0 +1,280 @@
+// Generated by forust 0.2.12, from a booster with 5 trees.
+
+#[allow(clippy::all)]
+pub fn fixture(row: &[f64]) -> f64 {
+    let mut p: f64 = 0.24116205681688804_f64;
+    p += fixture_tree_0(row);
+    p += fixture_tree_1(row);
+    p += fixture_tree_2(row);
+    p += fixture_tree_3(row);
+    p += fixture_tree_4(row);
+    p
+}
+
+#[inline(always)]
+fn fixture_value(row: &[f64], i: usize) -> f64 {
+    row[i]
+}
+
+#[inline(always)]
+fn fixture_tree_0(row: &[f64]) -> f64 {
+    let v = fixture_value(row, 1);
+    if v.is_nan() {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            -9.48363521047213e-9_f64
+        } else if v < 2.4_f64 {
+            -0.37634411454200745_f64
+        } else {
+            -0.5795536637306213_f64
+        }
+    } else if v < 4.1_f64 {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.23942384123802185_f64
+            } else if v < 1.4_f64 {
+                0.29569897055625916_f64
+            } else {
+                0.35541194677352905_f64
+            }
+        } else if v < 6.8_f64 {
+            -0.624239981174469_f64
+        } else {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                0.28047463297843933_f64
+            } else if v < 1.6_f64 {
+                0.02688172087073326_f64
+            } else {
+                0.36921924352645874_f64
+            }
+        }
+    } else {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            0.35277214646339417_f64
+        } else if v < 2.3_f64 {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.22519873082637787_f64
+            } else if v < 7.9_f64 {
+                -0.5095027089118958_f64
+            } else {
+                0.31956106424331665_f64
+            }
+        } else {
+            0.47084900736808777_f64
+        }
+    }
+}
+
+#[inline(always)]
+fn fixture_tree_1(row: &[f64]) -> f64 {
+    let v = fixture_value(row, 1);
+    if v.is_nan() {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            0.02115427702665329_f64
+        } else if v < 8.7_f64 {
+            -0.4452868103981018_f64
+        } else {
+            -0.2818653881549835_f64
+        }
+    } else if v < 4.5_f64 {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.14933188259601593_f64
+            } else if v < 2.7_f64 {
+                0.3180153965950012_f64
+            } else {
+                0.24839860200881958_f64
+            }
+        } else if v < 6.8_f64 {
+            -0.45419755578041077_f64
+        } else {
+            let v = fixture_value(row, 2);
+            if v.is_nan() {
+                0.25367242097854614_f64
+            } else if v < 4.5_f64 {
+                0.008538685739040375_f64
+            } else {
+                0.3112972378730774_f64
+            }
+        }
+    } else {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            0.3006485402584076_f64
+        } else if v < 2.3_f64 {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.1524541676044464_f64
+            } else if v < 7.9_f64 {
+                -0.38635656237602234_f64
+            } else {
+                0.273903489112854_f64
+            }
+        } else {
+            0.3970869481563568_f64
+        }
+    }
+}
+
+#[inline(always)]
+fn fixture_tree_2(row: &[f64]) -> f64 {
+    let v = fixture_value(row, 1);
+    if v.is_nan() {
+        let v = fixture_value(row, 2);
+        if v.is_nan() {
+            0.03564396873116493_f64
+        } else if v < 8.1_f64 {
+            -0.373224675655365_f64
+        } else {
+            -0.23523494601249695_f64
+        }
+    } else if v < 4.9_f64 {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            let v = fixture_value(row, 2);
+            if v.is_nan() {
+                -0.09860868006944656_f64
+            } else if v < 2.6_f64 {
+                0.21658848226070404_f64
+            } else {
+                0.2878614366054535_f64
+            }
+        } else if v < 6.8_f64 {
+            -0.36554741859436035_f64
+        } else {
+            let v = fixture_value(row, 2);
+            if v.is_nan() {
+                0.23293808102607727_f64
+            } else if v < 3.5_f64 {
+                -0.0452163964509964_f64
+            } else {
+                0.30912548303604126_f64
+            }
+        }
+    } else {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            0.2773951292037964_f64
+        } else if v < 2.3_f64 {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.1064969003200531_f64
+            } else if v < 7.9_f64 {
+                -0.31561535596847534_f64
+            } else {
+                0.24038629233837128_f64
+            }
+        } else {
+            0.3651982843875885_f64
+        }
+    }
+}
+
+#[inline(always)]
+fn fixture_tree_3(row: &[f64]) -> f64 {
+    let v = fixture_value(row, 1);
+    if v.is_nan() {
+        let v = fixture_value(row, 2);
+        if v.is_nan() {
+            0.0478074848651886_f64
+        } else if v < 1.9_f64 {
+            -0.21241353452205658_f64
+        } else {
+            -0.3273775577545166_f64
+        }
+    } else if v < 4.1_f64 {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            let v = fixture_value(row, 2);
+            if v.is_nan() {
+                -0.10821255296468735_f64
+            } else if v < 5.1_f64 {
+                0.22645045816898346_f64
+            } else {
+                0.20546814799308777_f64
+            }
+        } else if v < 6.8_f64 {
+            -0.34585148096084595_f64
+        } else {
+            let v = fixture_value(row, 0);
+            if v.is_nan() {
+                0.156553715467453_f64
+            } else if v < 8.2_f64 {
+                -0.028863484039902687_f64
+            } else {
+                0.25456398725509644_f64
+            }
+        }
+    } else {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            0.2309114784002304_f64
+        } else if v < 3.7_f64 {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.09020746499300003_f64
+            } else if v < 7.9_f64 {
+                -0.26918908953666687_f64
+            } else {
+                0.24129381775856018_f64
+            }
+        } else {
+            0.34605178236961365_f64
+        }
+    }
+}
+
+#[inline(always)]
+fn fixture_tree_4(row: &[f64]) -> f64 {
+    let v = fixture_value(row, 1);
+    if v.is_nan() {
+        let v = fixture_value(row, 2);
+        if v.is_nan() {
+            0.0582582950592041_f64
+        } else if v < 1.9_f64 {
+            -0.19135993719100952_f64
+        } else {
+            -0.29282206296920776_f64
+        }
+    } else if v < 4.1_f64 {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            -0.0848638266324997_f64
+        } else if v < 6.8_f64 {
+            -0.3143380880355835_f64
+        } else {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                0.13566090166568756_f64
+            } else if v < 1.6_f64 {
+                -0.032788943499326706_f64
+            } else {
+                0.22499753534793854_f64
+            }
+        }
+    } else {
+        let v = fixture_value(row, 0);
+        if v.is_nan() {
+            0.21095362305641174_f64
+        } else if v < 3.7_f64 {
+            let v = fixture_value(row, 1);
+            if v.is_nan() {
+                -0.06972070783376694_f64
+            } else if v < 6.4_f64 {
+                -0.29870370030403137_f64
+            } else {
+                0.15198580920696259_f64
+            }
+        } else {
+            0.31794214248657227_f64
+        }
+    }
+}
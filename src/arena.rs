@@ -0,0 +1,203 @@
+use crate::data::{DataMatrix, FloatData};
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::preprocessing::Imputer;
+use crate::utils::is_missing;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+
+/// The split feature of leaf nodes.
+const LEAF: u32 = u32::MAX;
+
+/// A node of a `NodeArena`, holding only what is needed for prediction.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+struct ArenaNode<W> {
+    split_value: f64,
+    /// The feature split on, or `LEAF` for leaf nodes.
+    split_feature: u32,
+    left_child: u32,
+    right_child: u32,
+    missing_node: u32,
+    leaf_value: W,
+}
+
+/// The trees of a booster, stored for prediction in a single compact arena, with
+/// `u32` node indexes, and leaf values of type `W`, either `f32` or `f64`. The nodes of
+/// each tree are stored one after the other, so predicting a record reads a small,
+/// contiguous block of memory, and the arena takes a fraction of the memory of the
+/// trees of the booster, which also hold the statistics used in training.
+/// The predictions are the same as the predictions of the booster.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NodeArena<W = f32> {
+    nodes: Vec<ArenaNode<W>>,
+    /// The index of the root node of each tree.
+    roots: Vec<u32>,
+    base_score: f64,
+    missing: f64,
+    imputer: Option<Imputer>,
+}
+
+impl<W: FloatData<W> + From<f32>> NodeArena<W> {
+    /// Build an arena from the trees a booster uses for prediction.
+    ///
+    /// * `booster` - A fitted booster.
+    pub fn from_booster(booster: &GradientBooster) -> Result<Self, ForustError> {
+        let trees = booster.get_prediction_trees();
+        let n_nodes: usize = trees.iter().map(|t| t.nodes.len()).sum();
+        if n_nodes >= LEAF as usize {
+            return Err(ForustError::InvalidParameter {
+                name: "booster".to_string(),
+                constraint: format!("fewer than {} nodes", LEAF),
+                value: format!("{} nodes", n_nodes),
+            });
+        }
+        let mut nodes = Vec::with_capacity(n_nodes);
+        let mut roots = Vec::with_capacity(trees.len());
+        for tree in trees {
+            let offset = nodes.len() as u32;
+            roots.push(offset);
+            nodes.extend(tree.nodes.iter().map(|n| ArenaNode {
+                split_value: n.split_value,
+                split_feature: if n.is_leaf {
+                    LEAF
+                } else {
+                    n.split_feature as u32
+                },
+                left_child: offset + n.left_child as u32,
+                right_child: offset + n.right_child as u32,
+                missing_node: offset + n.missing_node as u32,
+                leaf_value: W::from(n.weight_value),
+            }));
+        }
+        Ok(NodeArena {
+            nodes,
+            roots,
+            base_score: booster.base_score,
+            missing: booster.missing,
+            imputer: booster.imputer.clone(),
+        })
+    }
+
+    /// Generate predictions on data, the same as the booster the arena was built from.
+    ///
+    /// * `data` - The records to predict.
+    /// * `parallel` - Predict the records in parallel.
+    pub fn predict<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        match &self.imputer {
+            Some(imputer) => self.predict_rows(&imputer.view(data, self.missing), parallel),
+            None => self.predict_rows(data, parallel),
+        }
+    }
+
+    /// Predict a single record, including the imputation of missing values, if the
+    /// booster the arena was built from has an imputer.
+    ///
+    /// * `row` - The value of each feature of the record.
+    pub fn predict_row<T: FloatData<T>>(&self, row: &[T]) -> f64 {
+        self.predict_row_with(|feature| {
+            let v = row[feature].to_f64();
+            match &self.imputer {
+                Some(imputer) if is_missing(&v, &self.missing) => imputer.values[feature],
+                _ => v,
+            }
+        })
+    }
+
+    /// The number of trees in the arena.
+    pub fn n_trees(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// The number of nodes of all of the trees in the arena.
+    pub fn n_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of bytes used to store the nodes of the trees.
+    pub fn nodes_size(&self) -> usize {
+        self.nodes.len() * size_of::<ArenaNode<W>>() + self.roots.len() * size_of::<u32>()
+    }
+
+    fn predict_rows<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        if parallel {
+            (0..data.rows())
+                .into_par_iter()
+                .map(|i| self.predict_row_with(|feature| data.value(i, feature)))
+                .collect()
+        } else {
+            (0..data.rows())
+                .map(|i| self.predict_row_with(|feature| data.value(i, feature)))
+                .collect()
+        }
+    }
+
+    fn predict_row_with<F: Fn(usize) -> f64>(&self, value: F) -> f64 {
+        self.roots.iter().fold(self.base_score, |p, root| {
+            let mut node = &self.nodes[*root as usize];
+            while node.split_feature != LEAF {
+                let v = value(node.split_feature as usize);
+                let child = if is_missing(&v, &self.missing) {
+                    node.missing_node
+                } else if v < node.split_value {
+                    node.left_child
+                } else {
+                    node.right_child
+                };
+                node = &self.nodes[child as usize];
+            }
+            p + node.leaf_value.to_f64()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use crate::node::Node;
+    use crate::preprocessing::ImputeStrategy;
+    use std::fs;
+
+    #[test]
+    fn test_node_arena() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default().set_iterations(20);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, false);
+
+        let arena = NodeArena::<f32>::from_booster(&booster).unwrap();
+        assert_eq!(arena.n_trees(), 20);
+        assert_eq!(arena.predict(&data, false), preds);
+        assert_eq!(arena.predict(&data, true), preds);
+        let row: Vec<f64> = (0..data.cols).map(|j| *data.get(3, j)).collect();
+        assert_eq!(arena.predict_row(&row), preds[3]);
+        let n_nodes: usize = booster.trees.iter().map(|t| t.nodes.len()).sum();
+        assert_eq!(arena.n_nodes(), n_nodes);
+        assert!(arena.nodes_size() < n_nodes * size_of::<Node>() / 2);
+
+        let arena = NodeArena::<f64>::from_booster(&booster).unwrap();
+        assert_eq!(arena.predict(&data, true), preds);
+
+        // Missing values are imputed, and only the prediction trees are stored.
+        let imputer = Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).unwrap();
+        let mut booster = GradientBooster::default()
+            .set_iterations(20)
+            .set_imputer(Some(imputer));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let booster = booster.set_prediction_iteration(Some(9));
+        let arena = NodeArena::<f32>::from_booster(&booster).unwrap();
+        assert_eq!(arena.n_trees(), 10);
+        assert_eq!(arena.predict(&data, true), booster.predict(&data, true));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::data::{Matrix, MatrixData};
+use crate::errors::ForustError;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch};
+use arrow_schema::{DataType, Field, Schema};
+use std::sync::Arc;
+
+/// Copy the numeric columns of a record batch into column major data. Each column is
+/// copied directly into the data, without an intermediate vector. Null values are
+/// read as NaN, and boolean values as 0 or 1.
+///
+/// * `batch` - The record batch to read.
+/// * `columns` - Names of the columns to read, if None, all columns are read.
+pub fn matrix_data_from_record_batch(
+    batch: &RecordBatch,
+    columns: Option<&[String]>,
+) -> Result<MatrixData<f64>, ForustError> {
+    let names: Vec<String> = match columns {
+        Some(columns) => columns.to_vec(),
+        None => batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().to_string())
+            .collect(),
+    };
+    let mut data = Vec::with_capacity(batch.num_rows() * names.len());
+    for name in names.iter() {
+        append_column(&mut data, column_by_name(batch, name)?.as_ref(), name)?;
+    }
+    Ok(MatrixData {
+        data,
+        rows: batch.num_rows(),
+        cols: names.len(),
+    })
+}
+
+/// Read a single numeric column of a record batch, such as the target, or sample weight.
+///
+/// * `batch` - The record batch to read.
+/// * `name` - Name of the column to read.
+pub fn column_from_record_batch(batch: &RecordBatch, name: &str) -> Result<Vec<f64>, ForustError> {
+    let mut values = Vec::with_capacity(batch.num_rows());
+    append_column(&mut values, column_by_name(batch, name)?.as_ref(), name)?;
+    Ok(values)
+}
+
+/// Convert a matrix into a record batch, with a nullable f64 column for each column of
+/// the matrix. Missing values are kept as they are, and not converted to nulls.
+///
+/// * `data` - The matrix to convert.
+/// * `names` - Names of the columns of the matrix.
+pub fn matrix_to_record_batch(
+    data: &Matrix<f64>,
+    names: &[String],
+) -> Result<RecordBatch, ForustError> {
+    if names.len() != data.cols {
+        return Err(ForustError::ShapeMismatch {
+            name: "names".to_string(),
+            expected: format!("a name for each of the {} columns", data.cols),
+            got: format!("{} names", names.len()),
+        });
+    }
+    let fields: Vec<Field> = names
+        .iter()
+        .map(|n| Field::new(n, DataType::Float64, true))
+        .collect();
+    let arrays: Vec<ArrayRef> = (0..data.cols)
+        .map(|j| Arc::new(Float64Array::from(data.get_col(j).to_vec())) as ArrayRef)
+        .collect();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+        .map_err(|e| ForustError::UnableToWrite(e.to_string()))
+}
+
+fn column_by_name<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef, ForustError> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| ForustError::InvalidParameter {
+            name: "columns".to_string(),
+            constraint: "the name of a column in the record batch".to_string(),
+            value: name.to_string(),
+        })
+}
+
+/// Append the values of a numeric array to the data, as f64.
+fn append_column(data: &mut Vec<f64>, array: &dyn Array, name: &str) -> Result<(), ForustError> {
+    macro_rules! append_primitive {
+        ($t:ty) => {{
+            let a = array.as_primitive::<$t>();
+            data.extend(a.iter().map(|v| v.map_or(f64::NAN, |v| v as f64)));
+        }};
+    }
+    match array.data_type() {
+        DataType::Float64 => {
+            let a = array.as_primitive::<Float64Type>();
+            if a.null_count() == 0 {
+                data.extend_from_slice(a.values());
+            } else {
+                data.extend(a.iter().map(|v| v.unwrap_or(f64::NAN)));
+            }
+        }
+        DataType::Float32 => append_primitive!(Float32Type),
+        DataType::Int8 => append_primitive!(Int8Type),
+        DataType::Int16 => append_primitive!(Int16Type),
+        DataType::Int32 => append_primitive!(Int32Type),
+        DataType::Int64 => append_primitive!(Int64Type),
+        DataType::UInt8 => append_primitive!(UInt8Type),
+        DataType::UInt16 => append_primitive!(UInt16Type),
+        DataType::UInt32 => append_primitive!(UInt32Type),
+        DataType::UInt64 => append_primitive!(UInt64Type),
+        DataType::Boolean => data.extend(
+            array
+                .as_boolean()
+                .iter()
+                .map(|v| v.map_or(f64::NAN, |v| f64::from(u8::from(v)))),
+        ),
+        data_type => {
+            return Err(ForustError::InvalidParameter {
+                name: name.to_string(),
+                constraint: "a numeric, or boolean column".to_string(),
+                value: format!("a column of type {}", data_type),
+            })
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{BooleanArray, Int32Array, StringArray};
+
+    #[test]
+    fn test_record_batch_round_trip() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "a",
+                Arc::new(Float64Array::from(vec![0.5, 1.5, 2.5])) as ArrayRef,
+            ),
+            (
+                "b",
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])) as ArrayRef,
+            ),
+            (
+                "c",
+                Arc::new(BooleanArray::from(vec![true, false, true])) as ArrayRef,
+            ),
+            (
+                "d",
+                Arc::new(StringArray::from(vec!["x", "y", "z"])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let names: Vec<String> = ["a", "b", "c"].iter().map(|n| n.to_string()).collect();
+        let data = matrix_data_from_record_batch(&batch, Some(&names)).unwrap();
+        assert_eq!((data.rows, data.cols), (3, 3));
+        assert_eq!(&data.data[..4], &[0.5, 1.5, 2.5, 1.]);
+        assert!(data.data[4].is_nan());
+        assert_eq!(&data.data[5..], &[3., 1., 0., 1.]);
+        assert_eq!(
+            column_from_record_batch(&batch, "c").unwrap(),
+            vec![1., 0., 1.]
+        );
+        // Text columns, and columns not in the batch, can't be read.
+        assert!(matrix_data_from_record_batch(&batch, None).is_err());
+        assert!(column_from_record_batch(&batch, "e").is_err());
+
+        let converted = matrix_to_record_batch(&data.as_matrix(), &names).unwrap();
+        assert_eq!(converted.num_columns(), 3);
+        let round_trip = matrix_data_from_record_batch(&converted, None).unwrap();
+        assert_eq!(&round_trip.data[..4], &data.data[..4]);
+        assert!(round_trip.data[4].is_nan());
+        assert!(matrix_to_record_batch(&data.as_matrix(), &names[..2]).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Train a booster from a CSV, or Parquet file, and score files with it, without
+//! writing any Rust.
+//!
+//! ```text
+//! forust train <config.toml>
+//! forust predict <model> <data> <output.csv>
+//! forust serve <model> <address>
+//! ```
+//!
+//! `serve` is only available with the `serve` feature, and scores records posted to
+//! `/predict`, see `PredictionServer`.
+//!
+//! The config of `train` names the data, the target, where the model is written, and
+//! the parameters of the booster, in a `[params]` table, with the same names as the
+//! fields of `BoosterParams`.
+//!
+//! ```toml
+//! data = "train.csv"
+//! target = "survived"
+//! model = "model.json"
+//!
+//! [params]
+//! objective_type = "LogLoss"
+//! iterations = 100
+//! ```
+
+use forust_ml::data::LoadedData;
+use forust_ml::data::{read_csv, CsvOptions};
+#[cfg(feature = "parquet")]
+use forust_ml::data::{read_parquet, ParquetOptions};
+use forust_ml::errors::ForustError;
+use forust_ml::params::BoosterParams;
+#[cfg(feature = "serve")]
+use forust_ml::serve::PredictionServer;
+use forust_ml::{Dataset, GradientBooster};
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+
+const USAGE: &str = "Usage:
+    forust train <config.toml>
+    forust predict <model> <data> <output.csv>
+    forust serve <model> <address>";
+
+/// The config of the `train` command.
+#[derive(Deserialize)]
+struct TrainConfig {
+    /// The file to train on, read as Parquet if it ends with `.parquet`, and as
+    /// CSV otherwise.
+    data: String,
+    /// Name of the target column.
+    target: String,
+    /// Name of the sample weight column.
+    weight: Option<String>,
+    /// Names of the feature columns, all other numeric columns are used by default.
+    columns: Option<Vec<String>>,
+    /// Where the model is written, in the binary format if it ends with `.bin`, and
+    /// as json otherwise.
+    model: String,
+    #[serde(default)]
+    params: BoosterParams,
+}
+
+/// Read the columns of a data file.
+fn read_data(
+    path: &str,
+    target: Option<String>,
+    weight: Option<String>,
+    columns: Option<Vec<String>>,
+) -> Result<LoadedData, ForustError> {
+    if path.ends_with(".parquet") {
+        #[cfg(feature = "parquet")]
+        return read_parquet(
+            path,
+            &ParquetOptions::default()
+                .set_target(target)
+                .set_weight(weight)
+                .set_columns(columns),
+        );
+        #[cfg(not(feature = "parquet"))]
+        return Err(ForustError::UnableToRead(
+            "Parquet files can only be read with the `parquet` feature".to_string(),
+        ));
+    }
+    read_csv(
+        path,
+        &CsvOptions::default()
+            .set_target(target)
+            .set_weight(weight)
+            .set_columns(columns),
+    )
+}
+
+fn train(config_path: &str) -> Result<(), Box<dyn Error>> {
+    let config: TrainConfig = toml::from_str(&fs::read_to_string(config_path)?)?;
+    let loaded = read_data(
+        &config.data,
+        Some(config.target),
+        config.weight,
+        config.columns,
+    )?;
+    let dataset = Dataset::try_from(loaded)?;
+    let mut booster = GradientBooster::from_params(config.params)?;
+    booster.fit_dataset(&dataset, None)?;
+    if config.model.ends_with(".bin") {
+        booster.save_binary(&config.model)?;
+    } else {
+        booster.save_booster(&config.model)?;
+    }
+    println!(
+        "Trained {} trees on {} rows, and saved the model to {}",
+        booster.trees.len(),
+        dataset.data.rows,
+        config.model
+    );
+    Ok(())
+}
+
+/// Load a model, from the binary format if the path ends with `.bin`, and from
+/// json otherwise.
+fn load_model(path: &str) -> Result<GradientBooster, ForustError> {
+    if path.ends_with(".bin") {
+        GradientBooster::load_binary(path)
+    } else {
+        GradientBooster::load_booster(path)
+    }
+}
+
+fn predict(model_path: &str, data_path: &str, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let booster = load_model(model_path)?;
+    // The features are selected by the names they were trained with, so the columns
+    // of the file can be in any order.
+    let loaded = read_data(data_path, None, None, booster.feature_names.clone())?;
+    let preds = booster.predict(&loaded.data.as_matrix(), true);
+    let mut writer = csv::Writer::from_path(output_path)?;
+    writer.write_record(["prediction"])?;
+    for p in preds.iter() {
+        writer.write_record([p.to_string()])?;
+    }
+    writer.flush()?;
+    println!("Wrote {} predictions to {}", preds.len(), output_path);
+    Ok(())
+}
+
+#[cfg(feature = "serve")]
+fn serve(model_path: &str, addr: &str) -> Result<(), Box<dyn Error>> {
+    let server = PredictionServer::new(load_model(model_path)?, addr)?;
+    println!("Serving {} on {}", model_path, server.addr());
+    Ok(server.serve()?)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    match args.as_slice() {
+        ["train", config] => train(config),
+        ["predict", model, data, output] => predict(model, data, output),
+        #[cfg(feature = "serve")]
+        ["serve", model, addr] => serve(model, addr),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -1,10 +1,15 @@
-use crate::data::{FloatData, JaggedMatrix, Matrix};
+use crate::data::{DataMatrix, FloatData, JaggedMatrix, Matrix, MatrixData};
 use crate::errors::ForustError;
+use crate::histogram::{create_feature_histogram, Bin};
 use crate::utils::{is_missing, map_bin, percentiles};
+use rayon::prelude::*;
+use std::borrow::Cow;
+use std::fs;
+use std::ops::Index;
 
 /// If there are fewer unique values than their are
 /// percentiles, just return the unique values of the
-/// vectors.
+/// vectors. NaN values are left out.
 ///
 /// * `v` - A numeric slice to calculate percentiles for.
 /// * `sample_weight` - Instance weights for each row in the data.
@@ -12,8 +17,8 @@ fn percentiles_or_value<T>(v: &[T], sample_weight: &[T], pcts: &[T]) -> Vec<T>
 where
     T: FloatData<T>,
 {
-    let mut v_u = v.to_owned();
-    v_u.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut v_u: Vec<T> = v.iter().copied().filter(|x| !x.is_nan()).collect();
+    v_u.sort_unstable_by(|a, b| a.total_cmp(b));
     v_u.dedup();
     if v_u.len() <= pcts.len() + 1 {
         v_u
@@ -40,81 +45,366 @@ pub struct BinnedData<T> {
     pub nunique: Vec<usize>,
 }
 
+impl BinnedData<f64> {
+    /// A value for each binned record, that every split on the bins routes the same
+    /// way as the original value. Bin `b` holds the values from cut `b - 1` up to cut
+    /// `b`, so cut `b - 1` is used, and the missing value is used for bin 0.
+    ///
+    /// * `missing` - Float value to use for missing records.
+    pub fn bin_values(&self, missing: f64) -> Vec<f64> {
+        let rows = self.binned_data.len() / self.cuts.cols.max(1);
+        self.binned_data
+            .iter()
+            .enumerate()
+            .map(|(i, b)| match b {
+                0 => missing,
+                b => self.cuts.get_col(i / rows)[usize::from(*b) - 1],
+            })
+            .collect()
+    }
+
+    /// Borrow the binned data, and cuts.
+    pub fn view(&self) -> BinnedDataRef<'_> {
+        BinnedDataRef {
+            binned_data: &self.binned_data,
+            cuts: &self.cuts,
+        }
+    }
+
+    /// Save the binned data to a file, so it can be memory mapped with `MmapBinnedData`,
+    /// or loaded with `load`. The bins are written as little endian u16 values, after
+    /// a header with the cuts of each column.
+    ///
+    /// * `path` - Path to save the binned data to.
+    pub fn save(&self, path: &str) -> Result<(), ForustError> {
+        let cols = self.cuts.cols;
+        let rows = self.binned_data.len() / cols.max(1);
+        let mut bytes = Vec::with_capacity(BINNED_HEADER_LEN + self.binned_data.len() * 2);
+        bytes.extend_from_slice(BINNED_MAGIC);
+        bytes.extend_from_slice(&BINNED_FORMAT_VERSION.to_le_bytes());
+        for v in [rows, cols, self.cuts.data.len()] {
+            bytes.extend_from_slice(&(v as u64).to_le_bytes());
+        }
+        for e in self.cuts.ends.iter().chain(self.nunique.iter()) {
+            bytes.extend_from_slice(&(*e as u64).to_le_bytes());
+        }
+        for c in self.cuts.data.iter() {
+            bytes.extend_from_slice(&c.to_le_bytes());
+        }
+        for b in self.binned_data.iter() {
+            bytes.extend_from_slice(&b.to_le_bytes());
+        }
+        fs::write(path, bytes).map_err(ForustError::Io)
+    }
+
+    /// Load binned data saved with `save`, into memory.
+    ///
+    /// * `path` - Path to the saved binned data.
+    pub fn load(path: &str) -> Result<Self, ForustError> {
+        let bytes = fs::read(path).map_err(ForustError::Io)?;
+        let header = BinnedHeader::parse(&bytes)?;
+        let binned_data = bytes[header.offset..]
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        Ok(BinnedData {
+            binned_data,
+            cuts: header.cuts,
+            nunique: header.nunique,
+        })
+    }
+}
+
+/// Leading bytes of binned data saved with `BinnedData::save`.
+const BINNED_MAGIC: &[u8; 4] = b"FRBN";
+const BINNED_FORMAT_VERSION: u32 = 1;
+/// Length of the fixed part of the header, the magic bytes, version,
+/// rows, columns, and number of cuts.
+const BINNED_HEADER_LEN: usize = 32;
+
+/// The header of saved binned data.
+struct BinnedHeader {
+    cuts: JaggedMatrix<f64>,
+    nunique: Vec<usize>,
+    /// Where the bins start in the file. This is a multiple of 8,
+    /// so the bins are aligned when the file is memory mapped.
+    offset: usize,
+}
+
+impl BinnedHeader {
+    fn parse(bytes: &[u8]) -> Result<Self, ForustError> {
+        let invalid = || ForustError::UnableToRead("invalid binned data file".to_string());
+        if bytes.len() < BINNED_HEADER_LEN || &bytes[..4] != BINNED_MAGIC {
+            return Err(invalid());
+        }
+        if bytes[4..8] != BINNED_FORMAT_VERSION.to_le_bytes() {
+            return Err(ForustError::UnableToRead(
+                "unsupported binned data version".to_string(),
+            ));
+        }
+        let mut words = bytes[8..]
+            .chunks_exact(8)
+            .map(|w| u64::from_le_bytes(w.try_into().unwrap()));
+        let mut next = || words.next().map(|w| w as usize).ok_or_else(invalid);
+        let (rows, cols, n_cuts) = (next()?, next()?, next()?);
+        // Check the sizes fit in the file, before allocating anything.
+        let offset = cols
+            .checked_mul(2)
+            .and_then(|c| c.checked_add(n_cuts))
+            .and_then(|w| w.checked_mul(8))
+            .and_then(|w| w.checked_add(BINNED_HEADER_LEN))
+            .ok_or_else(invalid)?;
+        let n_bins = rows.checked_mul(cols).ok_or_else(invalid)?;
+        if offset > bytes.len() || (bytes.len() - offset) / 2 != n_bins {
+            return Err(invalid());
+        }
+        let ends = (0..cols).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+        let nunique = (0..cols).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+        let data: Vec<f64> = bytes[(offset - n_cuts * 8)..offset]
+            .chunks_exact(8)
+            .map(|w| f64::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        if ends.windows(2).any(|e| e[0] > e[1]) || ends.last().map_or(0, |e| *e) != n_cuts {
+            return Err(invalid());
+        }
+        let cuts = JaggedMatrix {
+            data,
+            n_records: ends.iter().sum(),
+            cols,
+            ends,
+        };
+        Ok(BinnedHeader {
+            cuts,
+            nunique,
+            offset,
+        })
+    }
+}
+
+/// Binned data, and the cuts of each column, borrowed from a `BinnedData`, or
+/// from a memory mapped file of binned data.
+#[derive(Clone, Copy)]
+pub struct BinnedDataRef<'a> {
+    pub binned_data: &'a [u16],
+    pub cuts: &'a JaggedMatrix<f64>,
+}
+
+/// Binned data read as the value of each bin, see `BinnedData::bin_values`.
+/// The values are looked up from the bins as they are read, so the data is
+/// never copied into f64.
+pub struct BinValues<'a> {
+    bins: BinnedDataRef<'a>,
+    rows: usize,
+    missing: f64,
+}
+
+impl<'a> BinValues<'a> {
+    /// * `bins` - The binned data.
+    /// * `missing` - Float value to use for missing records.
+    pub fn new(bins: BinnedDataRef<'a>, missing: f64) -> Self {
+        let rows = bins.binned_data.len() / bins.cuts.cols.max(1);
+        BinValues {
+            bins,
+            rows,
+            missing,
+        }
+    }
+
+    fn bin_value(&self, col: usize, bin: u16) -> f64 {
+        match bin {
+            0 => self.missing,
+            b => self.bins.cuts.get_col(col)[usize::from(b) - 1],
+        }
+    }
+}
+
+impl<'a> DataMatrix for BinValues<'a> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+    fn cols(&self) -> usize {
+        self.bins.cuts.cols
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        self.bin_value(col, self.bins.binned_data[col * self.rows + row])
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        let bins = &self.bins.binned_data[(col * self.rows)..((col + 1) * self.rows)];
+        Cow::Owned(bins.iter().map(|b| self.bin_value(col, *b)).collect())
+    }
+}
+
+/// Binned data the trees are grown on. The histograms of a node are built from the
+/// bins of its records, and the records are split on the bins of the split feature.
+pub trait BinnedMatrix: Sync {
+    /// The number of columns of the data.
+    fn cols(&self) -> usize;
+    /// Get the bin of a row and column of the data.
+    fn bin(&self, row: usize, col: usize) -> &u16;
+    /// The histograms of every column, one after the other, of the records in `index`.
+    /// The gradient, and hessian, of each record are in the same order as the index.
+    fn histograms(
+        &self,
+        cuts: &JaggedMatrix<f64>,
+        grad: &[f32],
+        hess: &[f32],
+        index: &[usize],
+        parallel: bool,
+    ) -> Vec<Bin<f32>>;
+}
+
+impl<'a> BinnedMatrix for Matrix<'a, u16> {
+    fn cols(&self) -> usize {
+        self.cols
+    }
+    fn bin(&self, row: usize, col: usize) -> &u16 {
+        self.get(row, col)
+    }
+    fn histograms(
+        &self,
+        cuts: &JaggedMatrix<f64>,
+        grad: &[f32],
+        hess: &[f32],
+        index: &[usize],
+        parallel: bool,
+    ) -> Vec<Bin<f32>> {
+        let histogram = |col: usize| {
+            create_feature_histogram(self.get_col(col), cuts.get_col(col), grad, hess, index)
+        };
+        if parallel {
+            (0..self.cols).into_par_iter().flat_map(histogram).collect()
+        } else {
+            (0..self.cols).flat_map(histogram).collect()
+        }
+    }
+}
+
+/// A column of binned data, that can be indexed by row.
+pub struct BinColumn<'a, B: ?Sized> {
+    data: &'a B,
+    col: usize,
+}
+
+impl<'a, B: BinnedMatrix + ?Sized> BinColumn<'a, B> {
+    /// * `data` - The binned data.
+    /// * `col` - The index of the column.
+    pub fn new(data: &'a B, col: usize) -> Self {
+        BinColumn { data, col }
+    }
+}
+
+impl<'a, B: BinnedMatrix + ?Sized> Index<usize> for BinColumn<'a, B> {
+    type Output = u16;
+    fn index(&self, row: usize) -> &u16 {
+        self.data.bin(row, self.col)
+    }
+}
+
+/// Binned data saved with `BinnedData::save`, memory mapped rather than read into
+/// memory. The operating system pages the bins in, as the histograms are built, and
+/// out again when memory is needed, so data larger than memory can be trained on.
+#[cfg(feature = "mmap")]
+pub struct MmapBinnedData {
+    mmap: memmap2::Mmap,
+    offset: usize,
+    pub rows: usize,
+    pub cuts: JaggedMatrix<f64>,
+    pub nunique: Vec<usize>,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapBinnedData {
+    /// Memory map binned data saved with `BinnedData::save`. The file must
+    /// not be modified while it's mapped.
+    ///
+    /// * `path` - Path to the saved binned data.
+    pub fn open(path: &str) -> Result<Self, ForustError> {
+        if cfg!(target_endian = "big") {
+            return Err(ForustError::UnableToRead(
+                "binned data can only be memory mapped on little endian targets".to_string(),
+            ));
+        }
+        let file = fs::File::open(path).map_err(ForustError::Io)?;
+        // Safety: the file is only read, and the caller mustn't modify it while it's mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(ForustError::Io)?;
+        let header = BinnedHeader::parse(&mmap)?;
+        let rows = (mmap.len() - header.offset) / 2 / header.cuts.cols.max(1);
+        Ok(MmapBinnedData {
+            mmap,
+            offset: header.offset,
+            rows,
+            cuts: header.cuts,
+            nunique: header.nunique,
+        })
+    }
+
+    /// Borrow the memory mapped bins, and the cuts.
+    pub fn view(&self) -> BinnedDataRef<'_> {
+        // Safety: any bit pattern is a valid u16, and the map is page aligned, with
+        // the bins starting at a multiple of 8 bytes, so there is no unaligned prefix.
+        let (prefix, bins, _) = unsafe { self.mmap[self.offset..].align_to::<u16>() };
+        assert!(prefix.is_empty());
+        BinnedDataRef {
+            binned_data: bins,
+            cuts: &self.cuts,
+        }
+    }
+}
+
 /// Convert a matrix of data, into a binned matrix.
 ///
 /// * `data` - Numeric data to be binned.
 /// * `cuts` - A slice of Vectors, where the vectors are the corresponding
 ///     cut values for each of the columns.
-fn bin_matrix_from_cuts<T: std::cmp::PartialOrd>(
-    data: &Matrix<T>,
-    cuts: &JaggedMatrix<T>,
-) -> Vec<u16> {
-    // loop through the matrix, binning the data.
-    // We will determine the column we are in, by
-    // using the modulo operator, on the record value.
-    data.data
-        .iter()
-        .enumerate()
-        .map(|(i, v)| {
-            let col = i / data.rows;
-            // This will always be smaller than u16::MAX so we
-            // are good to just unwrap here.
-            map_bin(cuts.get_col(col), v).unwrap()
-        })
-        .collect()
+fn bin_matrix_from_cuts<D: DataMatrix>(data: &D, cuts: &JaggedMatrix<f64>) -> Vec<u16> {
+    // loop through the columns of the matrix, binning the data,
+    // so the binned data is column major.
+    let mut binned = Vec::with_capacity(data.rows() * data.cols());
+    for col in 0..data.cols() {
+        let col_cuts = cuts.get_col(col);
+        // This will always be smaller than u16::MAX so we
+        // are good to just unwrap here.
+        binned.extend(
+            data.column(col)
+                .iter()
+                .map(|v| map_bin(col_cuts, v).unwrap()),
+        );
+    }
+    binned
 }
 
-/// Bin a numeric matrix.
+/// Bin a numeric matrix. The cuts are calculated in f64, so data of
+/// any float type is binned the same way.
 ///
 /// * `data` - A numeric matrix, of data to be binned.
 /// * `sample_weight` - Instance weights for each row of the data.
 /// * `nbins` - The number of bins each column should be binned into.
 /// * `missing` - Float value to consider as missing.
-pub fn bin_matrix(
-    data: &Matrix<f64>,
+pub fn bin_matrix<D: DataMatrix>(
+    data: &D,
     sample_weight: &[f64],
     nbins: u16,
     missing: f64,
 ) -> Result<BinnedData<f64>, ForustError> {
-    let mut pcts = Vec::new();
-    let nbins_ = f64::from_u16(nbins);
-    for i in 0..nbins {
-        let v = f64::from_u16(i) / nbins_;
-        pcts.push(v);
-    }
+    let pcts = bin_percentiles(nbins);
 
     // First we need to generate the bins for each of the columns.
     // We will loop through all of the columns, and generate the cuts.
     let mut cuts = JaggedMatrix::new();
     let mut nunique = Vec::new();
-    for i in 0..data.cols {
+    for i in 0..data.cols() {
         let (no_miss, w): (Vec<f64>, Vec<f64>) = data
-            .get_col(i)
+            .column(i)
             .iter()
-            .zip(sample_weight.iter())
+            .copied()
+            .zip(sample_weight.iter().copied())
             // It is unrecoverable if they have provided missing values in
             // the data other than the specificized missing.
             .filter(|(v, _)| !is_missing(v, &missing))
             .unzip();
         assert_eq!(no_miss.len(), w.len());
-        let mut col_cuts = percentiles_or_value(&no_miss, &w, &pcts);
-        col_cuts.push(f64::MAX);
-        col_cuts.dedup();
-        if col_cuts.len() < 2 {
-            return Err(ForustError::NoVariance(i));
-        }
-        // There will be one less bins, then there are cuts.
-        // The first value will be for missing.
-        nunique.push(col_cuts.len());
-        let l = col_cuts.len();
-        cuts.data.extend(col_cuts);
-        let e = match cuts.ends.last() {
-            Some(v) => v + l,
-            None => l,
-        };
-        cuts.ends.push(e);
-        cuts.cols = cuts.ends.len();
-        cuts.n_records = cuts.ends.iter().sum();
+        let col_cuts = percentiles_or_value(&no_miss, &w, &pcts);
+        push_col_cuts(&mut cuts, &mut nunique, col_cuts, i)?;
     }
 
     let binned_data = bin_matrix_from_cuts(data, &cuts);
@@ -126,9 +416,633 @@ pub fn bin_matrix(
     })
 }
 
+/// The percentiles to cut each column at, for `nbins` bins.
+fn bin_percentiles(nbins: u16) -> Vec<f64> {
+    let nbins_ = f64::from_u16(nbins);
+    (0..nbins).map(|i| f64::from_u16(i) / nbins_).collect()
+}
+
+/// Add the cuts of a column, to the cuts of the matrix.
+///
+/// * `cuts` - The cuts of the columns so far.
+/// * `nunique` - The number of cuts of each column so far.
+/// * `col_cuts` - The percentiles of the column.
+/// * `col` - Index of the column, for the error if it has no variance.
+fn push_col_cuts(
+    cuts: &mut JaggedMatrix<f64>,
+    nunique: &mut Vec<usize>,
+    mut col_cuts: Vec<f64>,
+    col: usize,
+) -> Result<(), ForustError> {
+    col_cuts.push(f64::MAX);
+    col_cuts.dedup();
+    if col_cuts.len() < 2 {
+        return Err(ForustError::NoVariance(col));
+    }
+    // There will be one less bins, then there are cuts.
+    // The first value will be for missing.
+    nunique.push(col_cuts.len());
+    let l = col_cuts.len();
+    cuts.data.extend(col_cuts);
+    let e = match cuts.ends.last() {
+        Some(v) => v + l,
+        None => l,
+    };
+    cuts.ends.push(e);
+    cuts.cols = cuts.ends.len();
+    cuts.n_records = cuts.ends.iter().sum();
+    Ok(())
+}
+
+/// Sparse binned data, with a bin for each stored value of the data, held by row, in
+/// the compressed sparse row (CSR) format. The values that aren't stored are zero, so
+/// they share the bin of zero in each column. The histograms of a node are built from
+/// the stored bins of its records, and the bin of zero gets the rest of the gradient,
+/// and hessian, of the node, so training takes memory, and time, in proportion to the
+/// number of stored values, rather than the dense shape of the data.
+pub struct SparseBinnedData {
+    /// Where the bins of each row start, the bins of row `i` are at `indptr[i]..indptr[i + 1]`.
+    pub indptr: Vec<usize>,
+    /// The column of each bin, in increasing order within each row.
+    pub indices: Vec<usize>,
+    /// The bins of the stored values.
+    pub bins: Vec<u16>,
+    /// The bin of zero in each column, for the values that aren't stored.
+    pub zero_bins: Vec<u16>,
+    pub cuts: JaggedMatrix<f64>,
+    pub nunique: Vec<usize>,
+}
+
+impl BinnedMatrix for SparseBinnedData {
+    fn cols(&self) -> usize {
+        self.zero_bins.len()
+    }
+    fn bin(&self, row: usize, col: usize) -> &u16 {
+        let (start, end) = (self.indptr[row], self.indptr[row + 1]);
+        match self.indices[start..end].binary_search(&col) {
+            Ok(i) => &self.bins[start + i],
+            Err(_) => &self.zero_bins[col],
+        }
+    }
+    fn histograms(
+        &self,
+        cuts: &JaggedMatrix<f64>,
+        grad: &[f32],
+        hess: &[f32],
+        index: &[usize],
+        parallel: bool,
+    ) -> Vec<Bin<f32>> {
+        let starts: Vec<usize> = (0..cuts.cols)
+            .map(|col| if col == 0 { 0 } else { cuts.ends[col - 1] })
+            .collect();
+        // The histograms have the layout of the dense histograms, a missing bin, and
+        // then a bin for every cut, but the last, of each column.
+        let empty = || {
+            let mut histograms = Vec::with_capacity(cuts.data.len());
+            for col in 0..cuts.cols {
+                let col_cuts = cuts.get_col(col);
+                histograms.push(Bin::new_f64(f64::NAN));
+                histograms.extend(
+                    col_cuts[..(col_cuts.len() - 1)]
+                        .iter()
+                        .map(|c| Bin::new_f64(*c)),
+                );
+            }
+            histograms
+        };
+        // Add the stored bins of some of the records, along with their totals.
+        let add = |index: &[usize], grad: &[f32], hess: &[f32]| {
+            let mut histograms = empty();
+            let (mut gradient_sum, mut hessian_sum) = (0., 0.);
+            for ((row, g), h) in index.iter().zip(grad).zip(hess) {
+                let (g, h) = (f64::from(*g), f64::from(*h));
+                gradient_sum += g;
+                hessian_sum += h;
+                for i in self.indptr[*row]..self.indptr[*row + 1] {
+                    let bin = &mut histograms[starts[self.indices[i]] + usize::from(self.bins[i])];
+                    bin.gradient_sum += g;
+                    bin.hessian_sum += h;
+                }
+            }
+            (histograms, gradient_sum, hessian_sum)
+        };
+        let (grad, hess) = (&grad[..index.len()], &hess[..index.len()]);
+        let (mut histograms, gradient_sum, hessian_sum) = if parallel && !index.is_empty() {
+            // Each thread adds a chunk of the records, to its own histograms.
+            let chunk = index.len().div_ceil(rayon::current_num_threads());
+            index
+                .par_chunks(chunk)
+                .zip(grad.par_chunks(chunk))
+                .zip(hess.par_chunks(chunk))
+                .map(|((index, grad), hess)| add(index, grad, hess))
+                .reduce_with(|(mut a, ag, ah), (b, bg, bh)| {
+                    a.iter_mut().zip(b.iter()).for_each(|(a, b)| {
+                        a.gradient_sum += b.gradient_sum;
+                        a.hessian_sum += b.hessian_sum;
+                    });
+                    (a, ag + bg, ah + bh)
+                })
+                .unwrap_or_else(|| (empty(), 0., 0.))
+        } else {
+            add(index, grad, hess)
+        };
+        // The records without a stored value in a column, are in the bin of zero.
+        for (col, zero_bin) in self.zero_bins.iter().enumerate() {
+            let histogram = &mut histograms[starts[col]..cuts.ends[col]];
+            let (stored_gradient, stored_hessian) = histogram.iter().fold((0., 0.), |(g, h), b| {
+                (g + b.gradient_sum, h + b.hessian_sum)
+            });
+            let bin = &mut histogram[usize::from(*zero_bin)];
+            bin.gradient_sum += gradient_sum - stored_gradient;
+            bin.hessian_sum += hessian_sum - stored_hessian;
+        }
+        histograms.iter().map(|b| b.as_f32_bin()).collect()
+    }
+}
+
+/// Bin sparse data from its stored values, see `SparseBinnedData`. The values that
+/// aren't stored are zero, and are added to the cuts of each column as a single value,
+/// with their total weight.
+///
+/// * `data` - Sparse data, to be binned.
+/// * `sample_weight` - Instance weights for each row of the data.
+/// * `nbins` - The number of bins each column should be binned into.
+/// * `missing` - Float value to consider as missing.
+pub fn bin_sparse_matrix<D: DataMatrix>(
+    data: &D,
+    sample_weight: &[f64],
+    nbins: u16,
+    missing: f64,
+) -> Result<SparseBinnedData, ForustError> {
+    let pcts = bin_percentiles(nbins);
+    let total_weight: f64 = sample_weight.iter().sum();
+    let mut cuts = JaggedMatrix::new();
+    let mut nunique = Vec::new();
+    let mut zero_bins = Vec::with_capacity(data.cols());
+    // The number of stored values of each row.
+    let mut counts = vec![0; data.rows()];
+    for col in 0..data.cols() {
+        let (rows, values) = data.stored_column(col);
+        let mut stored_weight = 0.;
+        let (mut no_miss, mut w) = (Vec::new(), Vec::new());
+        for (row, v) in rows.iter().zip(values.iter()) {
+            counts[*row] += 1;
+            stored_weight += sample_weight[*row];
+            if !is_missing(v, &missing) {
+                no_miss.push(*v);
+                w.push(sample_weight[*row]);
+            }
+        }
+        if rows.len() < data.rows() && !is_missing(&0., &missing) {
+            no_miss.push(0.);
+            w.push((total_weight - stored_weight).max(0.));
+        }
+        let col_cuts = percentiles_or_value(&no_miss, &w, &pcts);
+        push_col_cuts(&mut cuts, &mut nunique, col_cuts, col)?;
+        zero_bins.push(map_bin(cuts.get_col(col), &0.).unwrap());
+    }
+
+    let mut indptr = Vec::with_capacity(data.rows() + 1);
+    indptr.push(0);
+    for c in counts {
+        indptr.push(indptr[indptr.len() - 1] + c);
+    }
+    // Fill the rows a column at a time, so the columns of each row are increasing.
+    let mut next = indptr[..data.rows()].to_vec();
+    let n_stored = indptr[data.rows()];
+    let (mut indices, mut bins) = (vec![0; n_stored], vec![0; n_stored]);
+    for col in 0..data.cols() {
+        let col_cuts = cuts.get_col(col);
+        let (rows, values) = data.stored_column(col);
+        for (row, v) in rows.iter().zip(values.iter()) {
+            indices[next[*row]] = col;
+            bins[next[*row]] = map_bin(col_cuts, v).unwrap();
+            next[*row] += 1;
+        }
+    }
+    Ok(SparseBinnedData {
+        indptr,
+        indices,
+        bins,
+        zero_bins,
+        cuts,
+        nunique,
+    })
+}
+
+/// The binned data a booster is trained on, either dense, or sparse.
+pub(crate) enum TrainingBins<'a> {
+    Dense(Matrix<'a, u16>),
+    Sparse(&'a SparseBinnedData),
+}
+
+impl<'a> BinnedMatrix for TrainingBins<'a> {
+    fn cols(&self) -> usize {
+        match self {
+            TrainingBins::Dense(data) => data.cols,
+            TrainingBins::Sparse(data) => data.cols(),
+        }
+    }
+    fn bin(&self, row: usize, col: usize) -> &u16 {
+        match self {
+            TrainingBins::Dense(data) => data.bin(row, col),
+            TrainingBins::Sparse(data) => data.bin(row, col),
+        }
+    }
+    fn histograms(
+        &self,
+        cuts: &JaggedMatrix<f64>,
+        grad: &[f32],
+        hess: &[f32],
+        index: &[usize],
+        parallel: bool,
+    ) -> Vec<Bin<f32>> {
+        match self {
+            TrainingBins::Dense(data) => data.histograms(cuts, grad, hess, index, parallel),
+            TrainingBins::Sparse(data) => data.histograms(cuts, grad, hess, index, parallel),
+        }
+    }
+}
+
+/// Binned data, with the target and weights, from `bin_chunks`.
+pub struct ChunkedData {
+    pub binned_data: BinnedData<f64>,
+    pub y: Vec<f64>,
+    pub sample_weight: Vec<f64>,
+}
+
+/// A chunk of rows, for binning data that is read one chunk at a time, with
+/// `bin_chunks`.
+pub struct DataChunk {
+    /// The features of the rows in the chunk.
+    pub data: MatrixData<f64>,
+    /// The target of each row.
+    pub y: Vec<f64>,
+    /// The instance weight of each row.
+    pub sample_weight: Vec<f64>,
+}
+
+/// The sketch of a column holds this many values for each bin.
+const SKETCH_VALUES_PER_BIN: usize = 32;
+
+/// Weighted values of a column, summarised into a bounded number of values, so the
+/// percentiles of a column can be found without holding all of it in memory. Once
+/// there are too many values, they are merged down to one value at each of `size`
+/// evenly spaced weighted quantiles. Each kept value carries the weight of the smaller
+/// values merged into it, so the total weight up to each kept value stays exact. The
+/// percentiles are exact until the column has more than `size` unique values, and are
+/// then within `2 / size` of the true percentile, as a kept value can carry the weight
+/// of a previously merged value, as well as its own step.
+struct QuantileSketch {
+    values: Vec<(f64, f64)>,
+    size: usize,
+}
+
+impl QuantileSketch {
+    fn new(size: usize) -> Self {
+        QuantileSketch {
+            values: Vec::new(),
+            size,
+        }
+    }
+
+    fn push(&mut self, v: f64, w: f64) {
+        self.values.push((v, w));
+        if self.values.len() >= 2 * self.size {
+            self.compress();
+        }
+    }
+
+    fn compress(&mut self) {
+        self.values.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        self.values.dedup_by(|b, a| {
+            let same = a.0 == b.0;
+            if same {
+                a.1 += b.1;
+            }
+            same
+        });
+        if self.values.len() <= self.size {
+            return;
+        }
+        let step = self.values.iter().map(|(_, w)| w).sum::<f64>() / self.size as f64;
+        let last = self.values.len() - 1;
+        let mut merged = Vec::with_capacity(self.size + 1);
+        let (mut cuml, mut carried, mut next) = (0., 0., step);
+        for (i, (v, w)) in self.values.iter().enumerate() {
+            cuml += w;
+            carried += w;
+            // The smallest and largest values are always kept.
+            if i == 0 || cuml >= next || i == last {
+                merged.push((*v, carried));
+                carried = 0.;
+                while next <= cuml {
+                    next += step;
+                }
+            }
+        }
+        self.values = merged;
+    }
+
+    fn percentiles(mut self, pcts: &[f64]) -> Vec<f64> {
+        self.compress();
+        let (v, w): (Vec<f64>, Vec<f64>) = self.values.into_iter().unzip();
+        if v.is_empty() {
+            return v;
+        }
+        percentiles_or_value(&v, &w, pcts)
+    }
+}
+
+/// Bin data that is read in chunks of rows, without holding all of the data in
+/// memory as f64. The chunks are read twice, first the percentiles of each column
+/// are sketched, to find the cuts, and then each chunk is binned with the cuts. Only
+/// the binned data, the target, and the weights are kept.
+///
+/// * `chunks` - Called once for each pass, and must produce the same chunks each time.
+/// * `nbins` - The number of bins each column should be binned into.
+/// * `missing` - Float value to consider as missing.
+pub fn bin_chunks<F, I>(mut chunks: F, nbins: u16, missing: f64) -> Result<ChunkedData, ForustError>
+where
+    F: FnMut() -> I,
+    I: IntoIterator<Item = DataChunk>,
+{
+    let size = usize::from(nbins).max(1) * SKETCH_VALUES_PER_BIN;
+    let mut sketches: Vec<QuantileSketch> = Vec::new();
+    let mut rows = 0;
+    for (n, chunk) in chunks().into_iter().enumerate() {
+        if n == 0 {
+            sketches = (0..chunk.data.cols)
+                .map(|_| QuantileSketch::new(size))
+                .collect();
+        }
+        validate_chunk(&chunk, sketches.len())?;
+        let data = chunk.data.as_matrix();
+        for (i, sketch) in sketches.iter_mut().enumerate() {
+            data.get_col(i)
+                .iter()
+                .zip(chunk.sample_weight.iter())
+                .filter(|(v, _)| !is_missing(v, &missing))
+                .for_each(|(v, w)| sketch.push(*v, *w));
+        }
+        rows += data.rows;
+    }
+
+    let pcts = bin_percentiles(nbins);
+    let mut cuts = JaggedMatrix::new();
+    let mut nunique = Vec::new();
+    let cols = sketches.len();
+    for (i, sketch) in sketches.into_iter().enumerate() {
+        push_col_cuts(&mut cuts, &mut nunique, sketch.percentiles(&pcts), i)?;
+    }
+
+    let mut binned_data = vec![0; rows * cols];
+    let mut y = Vec::with_capacity(rows);
+    let mut sample_weight = Vec::with_capacity(rows);
+    for chunk in chunks() {
+        validate_chunk(&chunk, cols)?;
+        let start = y.len();
+        if start + chunk.y.len() > rows {
+            return Err(ForustError::InvalidParameter {
+                name: "chunks".to_string(),
+                constraint: format!("the same {} rows on each pass", rows),
+                value: "more rows on the second pass".to_string(),
+            });
+        }
+        let data = chunk.data.as_matrix();
+        for i in 0..cols {
+            let col_cuts = cuts.get_col(i);
+            let binned = &mut binned_data[(i * rows + start)..(i * rows + start + data.rows)];
+            for (b, v) in binned.iter_mut().zip(data.get_col(i)) {
+                // This will always be smaller than u16::MAX.
+                *b = map_bin(col_cuts, v).unwrap();
+            }
+        }
+        y.extend(chunk.y);
+        sample_weight.extend(chunk.sample_weight);
+    }
+    if y.len() != rows {
+        return Err(ForustError::InvalidParameter {
+            name: "chunks".to_string(),
+            constraint: format!("the same {} rows on each pass", rows),
+            value: format!("{} rows on the second pass", y.len()),
+        });
+    }
+    Ok(ChunkedData {
+        binned_data: BinnedData {
+            binned_data,
+            cuts,
+            nunique,
+        },
+        y,
+        sample_weight,
+    })
+}
+
+fn validate_chunk(chunk: &DataChunk, cols: usize) -> Result<(), ForustError> {
+    if chunk.data.cols != cols {
+        return Err(ForustError::ShapeMismatch {
+            name: "chunks".to_string(),
+            expected: format!("chunks with {} columns", cols),
+            got: format!("a chunk with {} columns", chunk.data.cols),
+        });
+    }
+    let rows = chunk.data.rows;
+    if chunk.y.len() != rows || chunk.sample_weight.len() != rows {
+        return Err(ForustError::InvalidParameter {
+            name: "chunks".to_string(),
+            constraint: format!(
+                "a target and weight for each of the {} rows in the chunk",
+                rows
+            ),
+            value: format!(
+                "{} targets, and {} weights",
+                chunk.y.len(),
+                chunk.sample_weight.len()
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Bin a numeric matrix, with the cuts of a previously binned matrix, so
+/// the bins of the new data match the bins of the original data.
+///
+/// * `data` - A numeric matrix, of data to be binned.
+/// * `cuts` - The cuts of each column, from a previous call to `bin_matrix`.
+pub fn bin_matrix_with_cuts<D: DataMatrix>(
+    data: &D,
+    cuts: JaggedMatrix<f64>,
+) -> Result<BinnedData<f64>, ForustError> {
+    if cuts.cols != data.cols() {
+        return Err(ForustError::ShapeMismatch {
+            name: "data".to_string(),
+            expected: format!("a matrix with {} columns", cuts.cols),
+            got: format!("a matrix with {} columns", data.cols()),
+        });
+    }
+    let nunique = (0..cuts.cols).map(|i| cuts.get_col(i).len()).collect();
+    let binned_data = bin_matrix_from_cuts(data, &cuts);
+    Ok(BinnedData {
+        binned_data,
+        cuts,
+        nunique,
+    })
+}
+
+/// Bins should hold at least this many records on average, when choosing `nbins`.
+const MIN_RECORDS_PER_BIN: usize = 20;
+/// Bounds on the number of bins chosen by `auto_nbins`.
+const MIN_AUTO_NBINS: usize = 32;
+const MAX_AUTO_NBINS: usize = 4096;
+
+/// Choose the number of bins to use for a dataset. A column needs at most one fewer
+/// bins than it has unique values, to keep every unique value, while using more than
+/// one bin per `MIN_RECORDS_PER_BIN` rows leaves too few records in each bin. The
+/// largest number of bins any column can use, within this limit, is chosen.
+///
+/// * `data` - Numeric data to be binned.
+/// * `missing` - Float value to consider as missing.
+pub fn auto_nbins<D: DataMatrix>(data: &D, missing: f64) -> u16 {
+    let max_bins = (data.rows() / MIN_RECORDS_PER_BIN).clamp(MIN_AUTO_NBINS, MAX_AUTO_NBINS);
+    let nbins = (0..data.cols())
+        .map(|i| {
+            let mut v: Vec<f64> = data
+                .column(i)
+                .iter()
+                .copied()
+                .filter(|v| !is_missing(v, &missing))
+                .collect();
+            v.sort_unstable_by(|a, b| a.total_cmp(b));
+            v.dedup();
+            v.len().saturating_sub(1).min(max_bins)
+        })
+        .max()
+        .unwrap_or(0);
+    // There is no need to check for overflow, as the max is well below u16::MAX.
+    nbins.max(2) as u16
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data::CscMatrix;
+
+    #[test]
+    fn test_binned_data_save_load() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let b = bin_matrix(&data, &vec![1.; data.rows], 50, f64::NAN).unwrap();
+        let path = "resources/binned_data.bin";
+        b.save(path).unwrap();
+        let loaded = BinnedData::load(path).unwrap();
+        assert_eq!(loaded.binned_data, b.binned_data);
+        assert_eq!(loaded.cuts.data, b.cuts.data);
+        assert_eq!(loaded.cuts.ends, b.cuts.ends);
+        assert_eq!(loaded.nunique, b.nunique);
+
+        // The bin values route every split the same way.
+        let values = BinValues::new(b.view(), -1.);
+        let expected = b.bin_values(-1.);
+        let expected = Matrix::new(&expected, data.rows, data.cols);
+        assert_eq!(values.value(3, 2), *expected.get(3, 2));
+        assert_eq!(values.column(1).as_ref()[..10], expected.get_col(1)[..10]);
+
+        let mut bytes = fs::read(path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        fs::write(path, &bytes).unwrap();
+        assert!(BinnedData::load(path).is_err());
+    }
+
+    #[test]
+    fn test_bin_chunks() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let y: Vec<f64> = (0..data.rows).map(|i| i as f64).collect();
+        let w = vec![1.; data.rows];
+        let b = bin_matrix(&data, &w, 50, f64::NAN).unwrap();
+
+        // Read the data in chunks of 100 rows.
+        let chunks = || {
+            (0..data.rows).step_by(100).map(|start| {
+                let end = (start + 100).min(data.rows);
+                let rows = (start..end)
+                    .map(|i| (0..data.cols).map(|j| *data.get(i, j)).collect())
+                    .collect::<Vec<Vec<f64>>>();
+                DataChunk {
+                    data: MatrixData::from_row_iter(rows).unwrap(),
+                    y: y[start..end].to_vec(),
+                    sample_weight: w[start..end].to_vec(),
+                }
+            })
+        };
+        // The columns have fewer values than the sketch holds, so the cuts are exact.
+        let chunked = bin_chunks(chunks, 50, f64::NAN).unwrap();
+        assert_eq!(chunked.binned_data.cuts.data, b.cuts.data);
+        assert_eq!(chunked.binned_data.binned_data, b.binned_data);
+        assert_eq!(chunked.binned_data.nunique, b.nunique);
+        assert_eq!((chunked.y, chunked.sample_weight), (y.clone(), w.clone()));
+
+        // The chunks have to be the same on both passes.
+        let mut pass = 0;
+        let changing = || {
+            pass += 1;
+            chunks().take(if pass == 1 { 9 } else { 8 })
+        };
+        assert!(bin_chunks(changing, 50, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_quantile_sketch() {
+        // With more unique values than the sketch holds, the percentiles are approximate.
+        let mut sketch = QuantileSketch::new(64);
+        (0..10_000).for_each(|i| sketch.push(f64::from(i), 1.));
+        let p = sketch.percentiles(&[0., 0.25, 0.5, 0.75]);
+        assert_eq!(p.len(), 4);
+        for (v, e) in p.iter().zip([0., 2500., 5000., 7500.]) {
+            assert!((v - e).abs() <= 2. * 10_000. / 64., "{} {}", v, e);
+        }
+    }
+
+    #[test]
+    fn test_percentiles_or_value_nan() {
+        let v = vec![3., f64::NAN, 1., 2., 1., f64::NAN];
+        let w = vec![1., 2., 1., 1., 1., 2.];
+        assert_eq!(percentiles_or_value(&v, &w, &[0.5, 1.]), vec![1., 2., 3.]);
+        let v: Vec<f64> = (0..20)
+            .map(|i| if i % 4 == 0 { f64::NAN } else { f64::from(i) })
+            .collect();
+        let w = vec![1.; v.len()];
+        let p = percentiles_or_value(&v, &w, &[0.5, 1.]);
+        assert!(p.iter().all(|x| !x.is_nan()));
+        assert_eq!(p.last(), Some(&19.));
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_mmap_binned_data() {
+        let file = fs::read_to_string("resources/contiguous_no_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let b = bin_matrix(&data, &vec![1.; data.rows], 50, f64::NAN).unwrap();
+        let path = "resources/mmap_binned_data.bin";
+        b.save(path).unwrap();
+        let mapped = MmapBinnedData::open(path).unwrap();
+        assert_eq!(mapped.rows, data.rows);
+        assert_eq!(mapped.view().binned_data, b.binned_data.as_slice());
+        assert_eq!(mapped.view().cuts.data, b.cuts.data);
+    }
     use std::fs;
     #[test]
     fn test_bin_data() {
@@ -159,4 +1073,86 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_bin_sparse_matrix() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        // Store the non zero values, and the missing values.
+        let (mut indptr, mut indices, mut values) = (vec![0], Vec::new(), Vec::new());
+        for j in 0..data.cols {
+            for (i, v) in data.get_col(j).iter().enumerate() {
+                if *v != 0. {
+                    indices.push(i);
+                    values.push(*v);
+                }
+            }
+            indptr.push(indices.len());
+        }
+        let sparse = CscMatrix::new(&indptr, &indices, &values, data.rows).unwrap();
+        let w: Vec<f64> = (0..data.rows).map(|i| 1. + (i % 3) as f64).collect();
+
+        // The sparse data is binned the same as the dense data, storing only its values.
+        let dense = bin_matrix(&data, &w, 50, f64::NAN).unwrap();
+        let bdata = Matrix::new(&dense.binned_data, data.rows, data.cols);
+        let b = bin_sparse_matrix(&sparse, &w, 50, f64::NAN).unwrap();
+        assert_eq!(b.cuts.data, dense.cuts.data);
+        assert_eq!(b.cuts.ends, dense.cuts.ends);
+        assert_eq!(b.bins.len(), values.len());
+        for i in 0..data.rows {
+            for j in 0..data.cols {
+                assert_eq!(b.bin(i, j), bdata.bin(i, j));
+            }
+        }
+
+        // The histograms of some of the records match, with the bin of zero derived
+        // from the totals of the records.
+        let index: Vec<usize> = (0..data.rows).filter(|i| i % 4 != 0).collect();
+        let grad: Vec<f32> = index.iter().map(|i| (*i as f32).sin()).collect();
+        let hess: Vec<f32> = index.iter().map(|i| 1. + (*i % 5) as f32).collect();
+        for parallel in [true, false] {
+            let expected = bdata.histograms(&dense.cuts, &grad, &hess, &index, parallel);
+            let histograms = b.histograms(&b.cuts, &grad, &hess, &index, parallel);
+            assert_eq!(histograms.len(), expected.len());
+            for (h, e) in histograms.iter().zip(expected.iter()) {
+                assert!((h.gradient_sum - e.gradient_sum).abs() < 1e-3);
+                assert!((h.hessian_sum - e.hessian_sum).abs() < 1e-3);
+                assert!(h.cut_value == e.cut_value || h.cut_value.is_nan());
+            }
+        }
+    }
+
+    #[test]
+    fn test_auto_nbins() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        // The fare column has far more unique values than 891 / 20 records.
+        assert_eq!(auto_nbins(&data, f64::NAN), 44);
+
+        // Near binary columns only need a few bins.
+        let data_vec = vec![0., 1., 1., 0., 1., 2., 0., 1.];
+        let data = Matrix::new(&data_vec, 4, 2);
+        assert_eq!(auto_nbins(&data, f64::NAN), 2);
+
+        // Every unique value is kept, with the chosen number of bins.
+        let data_vec: Vec<f64> = (0..1000).map(|i| (i % 100) as f64).collect();
+        let data = Matrix::new(&data_vec, 1000, 1);
+        let nbins = auto_nbins(&data, f64::NAN);
+        assert_eq!(nbins, 50);
+        let data_vec: Vec<f64> = (0..1000).map(|i| (i % 40) as f64).collect();
+        let data = Matrix::new(&data_vec, 1000, 1);
+        let nbins = auto_nbins(&data, f64::NAN);
+        let b = bin_matrix(&data, &vec![1.; 1000], nbins, f64::NAN).unwrap();
+        assert_eq!(b.nunique[0], 41);
+    }
 }
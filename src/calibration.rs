@@ -0,0 +1,304 @@
+use crate::errors::ForustError;
+use serde::{Deserialize, Serialize};
+
+/// A mapping from the raw scores of a booster, to calibrated probabilities.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum Calibration {
+    /// Platt scaling, the probability is `1 / (1 + exp(-(a * score + b)))`.
+    Sigmoid { a: f64, b: f64 },
+    /// Isotonic regression, the probability is interpolated between the fitted values
+    /// at each threshold, and is constant beyond the first and last threshold.
+    Isotonic {
+        thresholds: Vec<f64>,
+        values: Vec<f64>,
+    },
+}
+
+// log(1 + exp(x)), without overflowing for large x.
+fn log1p_exp(x: f64) -> f64 {
+    if x > 0. {
+        x + (-x).exp().ln_1p()
+    } else {
+        x.exp().ln_1p()
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1. / (1. + (-x).exp())
+}
+
+/// Check there is a target, and a weight, for each score.
+fn validate_lengths(scores: &[f64], y: &[f64], sample_weight: &[f64]) -> Result<(), ForustError> {
+    for (name, len) in [("y", y.len()), ("sample_weight", sample_weight.len())] {
+        if len != scores.len() {
+            return Err(ForustError::LengthMismatch(
+                name.to_string(),
+                len,
+                scores.len(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Calibration {
+    /// Fit a sigmoid calibration with Platt's method, using Newton's method on the
+    /// log loss of the calibrated probabilities. The targets are smoothed as
+    /// described by Platt, to avoid overfitting small calibration sets.
+    ///
+    /// * `scores` - Raw scores of the booster, on the log odds scale.
+    /// * `y` - The binary target variable.
+    /// * `sample_weight` - Instance weights for each record.
+    pub fn fit_sigmoid(
+        scores: &[f64],
+        y: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<Self, ForustError> {
+        validate_lengths(scores, y, sample_weight)?;
+        let (mut n_pos, mut n_neg) = (0., 0.);
+        for (y_, w) in y.iter().zip(sample_weight) {
+            if *y_ == 1. {
+                n_pos += w;
+            } else if *y_ == 0. {
+                n_neg += w;
+            } else {
+                return Err(ForustError::InvalidParameter {
+                    name: "y".to_string(),
+                    constraint: "a binary target of 0 and 1".to_string(),
+                    value: y_.to_string(),
+                });
+            }
+        }
+        if n_pos <= 0. || n_neg <= 0. {
+            return Err(ForustError::InvalidParameter {
+                name: "y".to_string(),
+                constraint: "records of both classes".to_string(),
+                value: "a single class".to_string(),
+            });
+        }
+        let t_pos = (n_pos + 1.) / (n_pos + 2.);
+        let t_neg = 1. / (n_neg + 2.);
+        let targets: Vec<f64> = y
+            .iter()
+            .map(|y_| if *y_ == 1. { t_pos } else { t_neg })
+            .collect();
+        let loss = |a: f64, b: f64| -> f64 {
+            scores
+                .iter()
+                .zip(targets.iter())
+                .zip(sample_weight)
+                .map(|((s, t), w)| {
+                    let z = a * s + b;
+                    // -t * log(p) - (1 - t) * log(1 - p)
+                    w * (t * log1p_exp(-z) + (1. - t) * log1p_exp(z))
+                })
+                .sum()
+        };
+
+        // Starting from the identity, as the raw scores are already log odds.
+        let (mut a, mut b) = (1., 0.);
+        let mut current = loss(a, b);
+        for _ in 0..100 {
+            let (mut g_a, mut g_b, mut h_aa, mut h_ab, mut h_bb) = (0., 0., 0., 0., 0.);
+            for ((s, t), w) in scores.iter().zip(targets.iter()).zip(sample_weight) {
+                let p = sigmoid(a * s + b);
+                let d = w * (p - t);
+                let h = w * p * (1. - p);
+                g_a += d * s;
+                g_b += d;
+                h_aa += h * s * s;
+                h_ab += h * s;
+                h_bb += h;
+            }
+            if g_a.abs() < 1e-10 && g_b.abs() < 1e-10 {
+                break;
+            }
+            // A small ridge keeps the hessian invertible.
+            h_aa += 1e-12;
+            h_bb += 1e-12;
+            let det = h_aa * h_bb - h_ab * h_ab;
+            let step_a = (h_bb * g_a - h_ab * g_b) / det;
+            let step_b = (h_aa * g_b - h_ab * g_a) / det;
+            // Backtrack until the loss decreases.
+            let mut step = 1.;
+            let mut improved = false;
+            while step > 1e-10 {
+                let (new_a, new_b) = (a - step * step_a, b - step * step_b);
+                let new = loss(new_a, new_b);
+                if new < current {
+                    (a, b, current) = (new_a, new_b, new);
+                    improved = true;
+                    break;
+                }
+                step /= 2.;
+            }
+            if !improved {
+                break;
+            }
+        }
+        Ok(Calibration::Sigmoid { a, b })
+    }
+
+    /// Fit an isotonic calibration, a non decreasing step function of the scores,
+    /// using the pool adjacent violators algorithm.
+    ///
+    /// * `scores` - Raw scores of the booster.
+    /// * `y` - The binary target variable.
+    /// * `sample_weight` - Instance weights for each record.
+    pub fn fit_isotonic(
+        scores: &[f64],
+        y: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<Self, ForustError> {
+        validate_lengths(scores, y, sample_weight)?;
+        if let Some(v) = y.iter().find(|v| **v != 0. && **v != 1.) {
+            return Err(ForustError::InvalidParameter {
+                name: "y".to_string(),
+                constraint: "a binary target of 0 and 1".to_string(),
+                value: v.to_string(),
+            });
+        }
+        let mut index: Vec<usize> = (0..scores.len())
+            .filter(|i| sample_weight[*i] > 0.)
+            .collect();
+        if index.is_empty() {
+            return Err(ForustError::InvalidParameter {
+                name: "sample_weight".to_string(),
+                constraint: "at least one record with a positive weight".to_string(),
+                value: "no records".to_string(),
+            });
+        }
+        index.sort_by(|a, b| scores[*a].total_cmp(&scores[*b]));
+
+        // Blocks of (lowest score, highest score, weighted mean, weight), with
+        // tied scores always pooled in the same block.
+        let mut blocks: Vec<(f64, f64, f64, f64)> = Vec::new();
+        for i in index {
+            let (s, y_, w) = (scores[i], y[i], sample_weight[i]);
+            match blocks.last_mut() {
+                Some(last) if last.1 == s => {
+                    last.2 = (last.2 * last.3 + y_ * w) / (last.3 + w);
+                    last.3 += w;
+                }
+                _ => blocks.push((s, s, y_, w)),
+            }
+            // Pool the last blocks, while they violate the ordering.
+            while blocks.len() > 1 {
+                let n = blocks.len();
+                let (prev, last) = (blocks[n - 2], blocks[n - 1]);
+                if prev.2 <= last.2 {
+                    break;
+                }
+                let w = prev.3 + last.3;
+                blocks[n - 2] = (prev.0, last.1, (prev.2 * prev.3 + last.2 * last.3) / w, w);
+                blocks.pop();
+            }
+        }
+
+        // Each block is represented by its lowest and highest score,
+        // so the values are interpolated between the blocks.
+        let mut thresholds = Vec::new();
+        let mut values = Vec::new();
+        for (low, high, v, _) in blocks {
+            thresholds.push(low);
+            values.push(v);
+            if high > low {
+                thresholds.push(high);
+                values.push(v);
+            }
+        }
+        Ok(Calibration::Isotonic { thresholds, values })
+    }
+
+    /// Convert raw scores to calibrated probabilities.
+    ///
+    /// * `scores` - Raw scores of the booster, on the log odds scale.
+    pub fn transform(&self, scores: &[f64]) -> Vec<f64> {
+        match self {
+            Calibration::Sigmoid { a, b } => scores.iter().map(|s| sigmoid(a * s + b)).collect(),
+            Calibration::Isotonic { thresholds, values } => scores
+                .iter()
+                .map(|s| {
+                    let i = thresholds.partition_point(|t| t <= s);
+                    if i == 0 {
+                        values[0]
+                    } else if i == thresholds.len() {
+                        values[i - 1]
+                    } else {
+                        let (t0, t1) = (thresholds[i - 1], thresholds[i]);
+                        let (v0, v1) = (values[i - 1], values[i]);
+                        v0 + (v1 - v0) * (s - t0) / (t1 - t0)
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_sigmoid() {
+        // Scores that are twice as confident as they should be,
+        // are scaled back down.
+        let scores: Vec<f64> = (0..2000).map(|i| (i as f64 / 1000.) - 1.).collect();
+        let y: Vec<f64> = scores
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                // Deterministic labels, where the fraction of positives
+                // follows sigmoid(s / 2) in each window.
+                let p = sigmoid(s / 2.);
+                if ((i * 7919) % 100) as f64 / 100. < p {
+                    1.
+                } else {
+                    0.
+                }
+            })
+            .collect();
+        let w = vec![1.; y.len()];
+        let calibration = Calibration::fit_sigmoid(&scores, &y, &w).unwrap();
+        let Calibration::Sigmoid { a, b } = calibration else {
+            panic!("expected a sigmoid calibration");
+        };
+        assert!((a - 0.5).abs() < 0.1, "{}", a);
+        assert!(b.abs() < 0.1, "{}", b);
+        let p = calibration.transform(&[0.]);
+        assert!((p[0] - 0.5).abs() < 0.05);
+
+        assert!(Calibration::fit_sigmoid(&[0., 1.], &[1., 1.], &[1., 1.]).is_err());
+        assert!(Calibration::fit_sigmoid(&[0., 1.], &[0., 2.], &[1., 1.]).is_err());
+        assert!(Calibration::fit_sigmoid(&[0., 1., 2.], &[0., 1.], &[1., 1., 1.]).is_err());
+        assert!(Calibration::fit_sigmoid(&[0., 1., 2.], &[0., 1., 1.], &[1., 1.]).is_err());
+    }
+
+    #[test]
+    fn test_fit_isotonic() {
+        let scores = vec![1., 2., 3., 4., 5., 6., 6.];
+        let y = vec![0., 1., 0., 0., 1., 1., 0.];
+        let w = vec![1., 1., 1., 1., 1., 1., 1.];
+        let calibration = Calibration::fit_isotonic(&scores, &y, &w).unwrap();
+        let Calibration::Isotonic { thresholds, values } = &calibration else {
+            panic!("expected an isotonic calibration");
+        };
+        // The violators 2 to 4, and 5 to 6 are pooled.
+        assert_eq!(thresholds, &vec![1., 2., 4., 5., 6.]);
+        let expected = [0., 1. / 3., 1. / 3., 2. / 3., 2. / 3.];
+        assert!(values
+            .iter()
+            .zip(expected)
+            .all(|(v, e)| (v - e).abs() < 1e-12));
+        // Values are interpolated between the blocks, and constant beyond them.
+        let p = calibration.transform(&[0., 3., 4.5, 10.]);
+        let expected = [0., 1. / 3., 0.5, 2. / 3.];
+        assert!(p.iter().zip(expected).all(|(v, e)| (v - e).abs() < 1e-12));
+        let p = calibration.transform(&scores);
+        assert!(p.windows(2).all(|w| w[0] <= w[1]));
+
+        assert!(Calibration::fit_isotonic(&[0., 1.], &[0., 2.], &[1., 1.]).is_err());
+        assert!(Calibration::fit_isotonic(&[0., 1., 2.], &[0., 1.], &[1., 1., 1.]).is_err());
+        assert!(Calibration::fit_isotonic(&[0., 1., 2.], &[0., 1., 1.], &[1., 1.]).is_err());
+    }
+}
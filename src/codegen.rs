@@ -0,0 +1,281 @@
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::tree::Tree;
+use std::fmt::Write;
+use std::fs;
+
+// Keywords, and reserved words, of Rust, that can't be used as a function name.
+const KEYWORDS: [&str; 52] = [
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Save a gradient booster as Rust source code, see `to_rust_source`.
+///
+/// * `booster` - The booster to export.
+/// * `fn_name` - Name of the generated prediction function.
+/// * `path` - Path to save the source code.
+pub fn save_rust_source(
+    booster: &GradientBooster,
+    fn_name: &str,
+    path: &str,
+) -> Result<(), ForustError> {
+    let source = to_rust_source(booster, fn_name)?;
+    match fs::write(path, source) {
+        Err(e) => Err(ForustError::Io(e)),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Compile a gradient booster into Rust source code, with each tree written as nested
+/// if else statements, so the splits are compiled into branches, instead of being read
+/// from the nodes of the tree, for faster batch prediction. The source has no
+/// dependencies, and can be included in a crate with `include!`. It defines a public
+/// function, that predicts a single record from the value of each feature, the same
+/// as `predict` does, on the same scale, such as the log odds for "LogLoss".
+/// Missing values are imputed, if the booster has an imputer.
+///
+/// ```ignore
+/// pub fn fn_name(row: &[f64]) -> f64
+/// ```
+///
+/// * `booster` - The booster to export.
+/// * `fn_name` - Name of the generated prediction function, the functions of the trees are prefixed with it.
+pub fn to_rust_source(booster: &GradientBooster, fn_name: &str) -> Result<String, ForustError> {
+    let valid_name = fn_name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && fn_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name || fn_name == "_" || KEYWORDS.contains(&fn_name) {
+        return Err(ForustError::InvalidParameter {
+            name: "fn_name".to_string(),
+            constraint: "a valid Rust identifier, that isn't a keyword".to_string(),
+            value: fn_name.to_string(),
+        });
+    }
+    let trees = booster.get_prediction_trees();
+    let mut src = String::new();
+    // Writing to a string can't fail, so the results are ignored throughout.
+    let _ = writeln!(
+        src,
+        "// Generated by forust {}, from a booster with {} trees.",
+        env!("CARGO_PKG_VERSION"),
+        trees.len()
+    );
+    let _ = writeln!(src);
+    let _ = writeln!(src, "#[allow(clippy::all)]");
+    let _ = writeln!(src, "pub fn {}(row: &[f64]) -> f64 {{", fn_name);
+    let _ = writeln!(src, "    let mut p: f64 = {};", literal(booster.base_score));
+    for i in 0..trees.len() {
+        let _ = writeln!(src, "    p += {}_tree_{}(row);", fn_name, i);
+    }
+    let _ = writeln!(src, "    p");
+    let _ = writeln!(src, "}}");
+
+    // The value of a feature of the record, after imputation.
+    let _ = writeln!(src);
+    let _ = writeln!(src, "#[inline(always)]");
+    let _ = writeln!(src, "fn {}_value(row: &[f64], i: usize) -> f64 {{", fn_name);
+    match &booster.imputer {
+        Some(imputer) => {
+            let values: Vec<String> = imputer.values.iter().map(|v| literal(*v)).collect();
+            let _ = writeln!(
+                src,
+                "    const IMPUTED: [f64; {}] = [{}];",
+                values.len(),
+                values.join(", ")
+            );
+            let _ = writeln!(
+                src,
+                "    if {} {{ IMPUTED[i] }} else {{ row[i] }}",
+                missing_check(booster.missing, "row[i]")
+            );
+        }
+        None => {
+            let _ = writeln!(src, "    row[i]");
+        }
+    }
+    let _ = writeln!(src, "}}");
+
+    for (i, tree) in trees.iter().enumerate() {
+        let _ = writeln!(src);
+        let _ = writeln!(src, "#[inline(always)]");
+        let _ = writeln!(src, "fn {}_tree_{}(row: &[f64]) -> f64 {{", fn_name, i);
+        write_node(&mut src, tree, 0, 1, fn_name, booster.missing);
+        let _ = writeln!(src, "}}");
+    }
+    Ok(src)
+}
+
+/// Write the expression of a node, and the nodes under it.
+fn write_node(
+    src: &mut String,
+    tree: &Tree,
+    node_idx: usize,
+    indent: usize,
+    fn_name: &str,
+    missing: f64,
+) {
+    let pad = "    ".repeat(indent);
+    let n = &tree.nodes[node_idx];
+    if n.is_leaf {
+        let _ = writeln!(src, "{}{}", pad, literal(f64::from(n.weight_value)));
+        return;
+    }
+    let _ = writeln!(
+        src,
+        "{}let v = {}_value(row, {});",
+        pad, fn_name, n.split_feature
+    );
+    let less = format!("v < {}", literal(n.split_value));
+    if n.has_missing_branch() {
+        let _ = writeln!(src, "{}if {} {{", pad, missing_check(missing, "v"));
+        write_node(src, tree, n.missing_node, indent + 1, fn_name, missing);
+        let _ = writeln!(src, "{}}} else if {} {{", pad, less);
+    } else if n.missing_node == n.left_child {
+        let _ = writeln!(
+            src,
+            "{}if {} || {} {{",
+            pad,
+            missing_check(missing, "v"),
+            less
+        );
+    } else if missing.is_nan() {
+        // NaN is never less than the split value, so goes right.
+        let _ = writeln!(src, "{}if {} {{", pad, less);
+    } else {
+        let _ = writeln!(src, "{}if v != {} && {} {{", pad, literal(missing), less);
+    }
+    write_node(src, tree, n.left_child, indent + 1, fn_name, missing);
+    let _ = writeln!(src, "{}}} else {{", pad);
+    write_node(src, tree, n.right_child, indent + 1, fn_name, missing);
+    let _ = writeln!(src, "{}}}", pad);
+}
+
+/// The condition a value is missing.
+fn missing_check(missing: f64, value: &str) -> String {
+    if missing.is_nan() {
+        format!("{}.is_nan()", value)
+    } else {
+        format!("{} == {}", value, literal(missing))
+    }
+}
+
+/// A Rust literal of a float, the shortest representation that reads back exactly.
+fn literal(v: f64) -> String {
+    if v.is_nan() {
+        "f64::NAN".to_string()
+    } else if v == f64::INFINITY {
+        "f64::INFINITY".to_string()
+    } else if v == f64::NEG_INFINITY {
+        "f64::NEG_INFINITY".to_string()
+    } else {
+        format!("{:?}_f64", v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use std::fs;
+
+    // Generated from "resources/codegen_booster.json", with `to_rust_source`.
+    mod fixture {
+        include!("../resources/codegen_booster.rs");
+    }
+
+    #[test]
+    fn test_to_rust_source() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let src = to_rust_source(&booster, "titanic").unwrap();
+        assert!(src.contains("pub fn titanic(row: &[f64]) -> f64 {"));
+        assert_eq!(src.matches("p += titanic_tree_").count(), 10);
+        // Every split and leaf is written once.
+        for tree in booster.trees.iter() {
+            for n in tree.nodes.iter() {
+                if n.is_leaf {
+                    assert!(src.contains(&literal(f64::from(n.weight_value))));
+                } else {
+                    assert!(src.contains(&format!("v < {} {{", literal(n.split_value))));
+                }
+            }
+        }
+        let n_splits: usize = booster
+            .trees
+            .iter()
+            .map(|t| t.nodes.iter().filter(|n| !n.is_leaf).count())
+            .sum();
+        assert_eq!(src.matches("let v = ").count(), n_splits);
+        assert_eq!(literal(0.1), "0.1_f64");
+        assert_eq!(literal(3.), "3.0_f64");
+        // With a missing branch, missing values are checked separately.
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_max_depth(3)
+            .set_create_missing_branch(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let src = to_rust_source(&booster, "titanic").unwrap();
+        let n_missing_branches: usize = booster
+            .trees
+            .iter()
+            .map(|t| {
+                t.nodes
+                    .iter()
+                    .filter(|n| !n.is_leaf && n.has_missing_branch())
+                    .count()
+            })
+            .sum();
+        assert!(n_missing_branches > 0);
+        assert_eq!(src.matches("if v.is_nan() {").count(), n_missing_branches);
+        assert!(to_rust_source(&booster, "1predict").is_err());
+        assert!(to_rust_source(&booster, "pre-dict").is_err());
+        assert!(to_rust_source(&booster, "fn").is_err());
+        assert!(to_rust_source(&booster, "_").is_err());
+    }
+
+    #[test]
+    fn test_compiled_source() {
+        let booster = GradientBooster::load_booster("resources/codegen_booster.json").unwrap();
+        // The fixture is current, apart from the version in the header.
+        let src = to_rust_source(&booster, "fixture").unwrap();
+        let fixture = fs::read_to_string("resources/codegen_booster.rs").unwrap();
+        assert_eq!(
+            src.lines().skip(1).collect::<Vec<_>>(),
+            fixture.lines().skip(1).collect::<Vec<_>>()
+        );
+
+        let rows = 200;
+        let mut data_vec = Vec::with_capacity(rows * 3);
+        for j in 0..3 {
+            for i in 0..rows {
+                let v = ((i * (j + 5) * 6007) % 103) as f64 / 10.;
+                data_vec.push(if (i + j) % 5 == 0 { f64::NAN } else { v });
+            }
+        }
+        let data = Matrix::new(&data_vec, rows, 3);
+        let preds = booster.predict(&data, false);
+        for (i, p) in preds.iter().enumerate() {
+            let row: Vec<f64> = (0..3).map(|j| *data.get(i, j)).collect();
+            assert!((fixture::fixture(&row) - p).abs() < 1e-12);
+        }
+    }
+}
@@ -1,7 +1,8 @@
+use crate::errors::ForustError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 pub enum Constraint {
     Positive,
     Negative,
@@ -9,3 +10,102 @@ pub enum Constraint {
 }
 
 pub type ConstraintMap = HashMap<usize, Constraint>;
+
+/// How the monotone constraints are enforced, when evaluating a split.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum MonotoneConstraintMethod {
+    /// The weights of the children of a split on a constrained feature are clipped to
+    /// the bounds of their parent, and splits where the clipped weights break the
+    /// constraint are discarded. Splits on other features are not bounded.
+    Basic,
+    /// If the weights of the children of a split on a constrained feature break the
+    /// constraint, both are recalculated at their midpoint, weighted by the hessian of
+    /// each child, and the split is scored with these weights, rather than being
+    /// discarded, so a split that separates the missing values can still be made.
+    /// The bounds of a node are not set at the midpoint of its parent's children, but
+    /// recalculated when it is split, from the leaves on the other side of each split
+    /// on a constrained feature above it, as the tree is at that point. The bounds are
+    /// looser, so less accuracy is lost. The weights of every split, and missing
+    /// branch, below a split on a constrained feature are clipped to the bounds, so
+    /// the predictions of the trees hold the constraints. Nodes are split one at a
+    /// time, as the bounds of a node depend on the nodes split before it.
+    Exact,
+}
+
+/// Groups of features that are allowed to interact. The features split on along
+/// any branch of a tree must all be in one of the groups, and a feature in none of
+/// the groups can only be split on in branches that split on no other feature.
+/// The groups can be given by the index of each feature, or by the name of each feature,
+/// which is resolved against the feature names of the booster when it's fit, so the
+/// groups are kept with the model, even if the columns of the data are reordered.
+/// In json the groups are tagged with their kind, such as `{"Names": [["age", "fare"]]}`,
+/// or `{"Indices": [[0, 1]]}`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub enum InteractionConstraints {
+    /// Each group, as the index of each of its features.
+    Indices(Vec<Vec<usize>>),
+    /// Each group, as the name of each of its features.
+    Names(Vec<Vec<String>>),
+}
+
+impl InteractionConstraints {
+    /// The groups, as the index of each feature.
+    ///
+    /// * `feature_names` - Names of the features, required if the groups are given by name.
+    /// * `n_features` - The number of features of the data.
+    pub fn resolve(
+        &self,
+        feature_names: Option<&[String]>,
+        n_features: usize,
+    ) -> Result<Vec<Vec<usize>>, ForustError> {
+        let groups = match self {
+            InteractionConstraints::Indices(groups) => groups.clone(),
+            InteractionConstraints::Names(groups) => {
+                let names = feature_names.ok_or_else(|| ForustError::InvalidParameter {
+                    name: "interaction_constraints".to_string(),
+                    constraint: "feature indices, as the booster has no feature names".to_string(),
+                    value: format!("{:?}", groups),
+                })?;
+                groups
+                    .iter()
+                    .map(|group| {
+                        group
+                            .iter()
+                            .map(|name| {
+                                names.iter().position(|n| n == name).ok_or_else(|| {
+                                    ForustError::InvalidParameter {
+                                        name: "interaction_constraints".to_string(),
+                                        constraint: "the names of features of the booster"
+                                            .to_string(),
+                                        value: name.to_string(),
+                                    }
+                                })
+                            })
+                            .collect()
+                    })
+                    .collect::<Result<Vec<Vec<usize>>, ForustError>>()?
+            }
+        };
+        if let Some(f) = groups.iter().flatten().find(|f| **f >= n_features) {
+            return Err(ForustError::InvalidParameter {
+                name: "interaction_constraints".to_string(),
+                constraint: format!("feature indices less than {}", n_features),
+                value: f.to_string(),
+            });
+        }
+        Ok(groups)
+    }
+}
+
+/// Can a feature be split on, in a branch that has split on `path_features`, given
+/// the groups of features that are allowed to interact.
+pub fn interaction_allowed(groups: &[Vec<usize>], path_features: &[usize], feature: usize) -> bool {
+    let in_group = |group: &Vec<usize>| {
+        group.contains(&feature) && path_features.iter().all(|f| group.contains(f))
+    };
+    if groups.iter().any(|g| g.contains(&feature)) {
+        groups.iter().any(in_group)
+    } else {
+        path_features.iter().all(|f| *f == feature)
+    }
+}
@@ -1,4 +1,7 @@
+use crate::errors::ForustError;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt::{self, Debug, Display};
 use std::iter::Sum;
 use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
@@ -32,8 +35,13 @@ pub trait FloatData<T>:
     fn from_usize(v: usize) -> T;
     fn from_u16(v: u16) -> T;
     fn is_nan(self) -> bool;
+    /// The total ordering of the values, which, unlike `partial_cmp`, also orders NaN.
+    fn total_cmp(&self, other: &T) -> Ordering;
     fn ln(self) -> T;
     fn exp(self) -> T;
+    fn to_f64(self) -> f64;
+    /// Convert a slice of values to f64, borrowing the slice if it's already f64.
+    fn to_f64_slice(v: &[T]) -> Cow<'_, [f64]>;
 }
 impl FloatData<f64> for f64 {
     const ZERO: f64 = 0.0;
@@ -52,12 +60,21 @@ impl FloatData<f64> for f64 {
     fn is_nan(self) -> bool {
         self.is_nan()
     }
+    fn total_cmp(&self, other: &f64) -> Ordering {
+        f64::total_cmp(self, other)
+    }
     fn ln(self) -> f64 {
         self.ln()
     }
     fn exp(self) -> f64 {
         self.exp()
     }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn to_f64_slice(v: &[f64]) -> Cow<'_, [f64]> {
+        Cow::Borrowed(v)
+    }
 }
 
 impl FloatData<f32> for f32 {
@@ -77,12 +94,21 @@ impl FloatData<f32> for f32 {
     fn is_nan(self) -> bool {
         self.is_nan()
     }
+    fn total_cmp(&self, other: &f32) -> Ordering {
+        f32::total_cmp(self, other)
+    }
     fn ln(self) -> f32 {
         self.ln()
     }
     fn exp(self) -> f32 {
         self.exp()
     }
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+    fn to_f64_slice(v: &[f32]) -> Cow<'_, [f64]> {
+        Cow::Owned(v.iter().map(|x| f64::from(*x)).collect())
+    }
 }
 
 /// Contigious Column major matrix data container. This is
@@ -98,7 +124,8 @@ pub struct Matrix<'a, T> {
 }
 
 impl<'a, T> Matrix<'a, T> {
-    // Defaults to column major
+    // Defaults to column major. Row major data, where each row is
+    // contiguous, can be converted with `row_major_to_column_major`.
     pub fn new(data: &'a [T], rows: usize, cols: usize) -> Self {
         Matrix {
             data,
@@ -159,6 +186,679 @@ where
     pub fn get_row(&self, row: usize) -> Vec<T> {
         self.get_row_iter(row).copied().collect()
     }
+
+    /// Copy the selected rows into a new column major vector, with
+    /// the rows in the order they are provided.
+    ///
+    /// * `rows` - Indices of the rows to select.
+    pub fn select_rows(&self, rows: &[usize]) -> Vec<T> {
+        (0..self.cols)
+            .flat_map(|j| rows.iter().map(move |i| *self.get(*i, j)))
+            .collect()
+    }
+}
+
+/// Data the booster can be trained on, and predict with. Values are read as f64,
+/// whatever type, or layout, the data is stored in.
+pub trait DataMatrix: Sync {
+    /// The number of rows of the data.
+    fn rows(&self) -> usize;
+    /// The number of columns of the data.
+    fn cols(&self) -> usize;
+    /// Get the value of a row and column of the data.
+    fn value(&self, row: usize, col: usize) -> f64;
+    /// Get an entire column of the data, borrowing it if possible.
+    fn column(&self, col: usize) -> Cow<'_, [f64]>;
+    /// Whether only some of the values are stored, and the rest are zero, so the
+    /// data is binned from its stored values, see `stored_column`.
+    fn is_sparse(&self) -> bool {
+        false
+    }
+    /// Get the rows, and values, of the stored values of a column. Every value of
+    /// dense data is stored.
+    fn stored_column(&self, col: usize) -> (Cow<'_, [usize]>, Cow<'_, [f64]>) {
+        (Cow::Owned((0..self.rows()).collect()), self.column(col))
+    }
+}
+
+impl<'a, T: FloatData<T>> DataMatrix for Matrix<'a, T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+    fn cols(&self) -> usize {
+        self.cols
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        self.get(row, col).to_f64()
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        T::to_f64_slice(self.get_col(col))
+    }
+}
+
+/// Integer data, such as counts, or IDs, that can be trained on as it is, rather than
+/// being cast to a float by the caller. The values are converted to f64 as they are
+/// binned, so i64 values larger than 2^53 can share a bin with their neighbours.
+pub trait IntegerData: Copy + Send + Sync {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_integer_data {
+    ($t:ty) => {
+        impl IntegerData for $t {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+
+        impl<'a> DataMatrix for Matrix<'a, $t> {
+            fn rows(&self) -> usize {
+                self.rows
+            }
+            fn cols(&self) -> usize {
+                self.cols
+            }
+            fn value(&self, row: usize, col: usize) -> f64 {
+                self.get(row, col).to_f64()
+            }
+            fn column(&self, col: usize) -> Cow<'_, [f64]> {
+                Cow::Owned(self.get_col(col).iter().map(|v| v.to_f64()).collect())
+            }
+        }
+    };
+}
+
+impl_integer_data!(i32);
+impl_integer_data!(i64);
+
+/// A subset of the columns of a matrix, borrowed without copying the data, so
+/// feature selection over a wide matrix doesn't duplicate it for each subset.
+/// Column `i` of the view is column `columns[i]` of the matrix.
+pub struct MatrixView<'a, T> {
+    matrix: &'a Matrix<'a, T>,
+    columns: Vec<usize>,
+}
+
+impl<'a, T> MatrixView<'a, T> {
+    /// Create a view of some of the columns of a matrix.
+    ///
+    /// * `matrix` - The matrix to view.
+    /// * `columns` - Indices of the columns to select, in the order they're viewed.
+    pub fn new(matrix: &'a Matrix<'a, T>, columns: Vec<usize>) -> Result<Self, ForustError> {
+        if let Some(col) = columns.iter().find(|c| **c >= matrix.cols) {
+            return Err(ForustError::InvalidParameter {
+                name: "columns".to_string(),
+                constraint: format!("column indices less than {}", matrix.cols),
+                value: col.to_string(),
+            });
+        }
+        Ok(MatrixView { matrix, columns })
+    }
+
+    /// The columns of the matrix, selected by the view.
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Get a column of the view.
+    ///
+    /// * `col` - The index of the column in the view.
+    pub fn get_col(&self, col: usize) -> &[T] {
+        self.matrix.get_col(self.columns[col])
+    }
+}
+
+impl<'a, T> DataMatrix for MatrixView<'a, T>
+where
+    Matrix<'a, T>: DataMatrix,
+{
+    fn rows(&self) -> usize {
+        self.matrix.rows()
+    }
+    fn cols(&self) -> usize {
+        self.columns.len()
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        self.matrix.value(row, self.columns[col])
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        self.matrix.column(self.columns[col])
+    }
+}
+
+/// A subset of the rows of some data, borrowed without copying it, so the folds of a
+/// cross validation, or a train and test split, can share the same data. Row `i` of
+/// the view is row `index[i]` of the data.
+pub struct RowView<'a, D> {
+    data: &'a D,
+    index: Vec<usize>,
+}
+
+impl<'a, D: DataMatrix> RowView<'a, D> {
+    /// Create a view of some of the rows of the data.
+    ///
+    /// * `data` - The data to view.
+    /// * `index` - Indices of the rows to select, in the order they're viewed.
+    pub fn new(data: &'a D, index: Vec<usize>) -> Result<Self, ForustError> {
+        if let Some(row) = index.iter().find(|r| **r >= data.rows()) {
+            return Err(ForustError::InvalidParameter {
+                name: "index".to_string(),
+                constraint: format!("row indices less than {}", data.rows()),
+                value: row.to_string(),
+            });
+        }
+        Ok(RowView { data, index })
+    }
+
+    /// The rows of the data, selected by the view.
+    pub fn index(&self) -> &[usize] {
+        &self.index
+    }
+
+    /// Select some of the rows of the view, as a view of the same data.
+    ///
+    /// * `rows` - Indices of the rows of this view to select.
+    pub fn select(&self, rows: &[usize]) -> Self {
+        RowView {
+            data: self.data,
+            index: rows.iter().map(|r| self.index[*r]).collect(),
+        }
+    }
+}
+
+impl<'a, D: DataMatrix> DataMatrix for RowView<'a, D> {
+    fn rows(&self) -> usize {
+        self.index.len()
+    }
+    fn cols(&self) -> usize {
+        self.data.cols()
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        self.data.value(self.index[row], col)
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        let column = self.data.column(col);
+        Cow::Owned(self.index.iter().map(|r| column[*r]).collect())
+    }
+}
+
+/// Data with the values of some columns replaced by a fixed value, such as the value a
+/// feature is set to when calculating partial dependence, without copying the data.
+pub(crate) struct FixedColumns<'a, D> {
+    data: &'a D,
+    values: &'a [(usize, f64)],
+}
+
+impl<'a, D: DataMatrix> FixedColumns<'a, D> {
+    /// * `data` - The data.
+    /// * `values` - Pairs of column index, and the value the column is fixed at.
+    pub(crate) fn new(data: &'a D, values: &'a [(usize, f64)]) -> Self {
+        FixedColumns { data, values }
+    }
+
+    fn fixed_value(&self, col: usize) -> Option<f64> {
+        self.values.iter().find(|(c, _)| *c == col).map(|(_, v)| *v)
+    }
+}
+
+impl<'a, D: DataMatrix> DataMatrix for FixedColumns<'a, D> {
+    fn rows(&self) -> usize {
+        self.data.rows()
+    }
+    fn cols(&self) -> usize {
+        self.data.cols()
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        match self.fixed_value(col) {
+            Some(v) => v,
+            None => self.data.value(row, col),
+        }
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        match self.fixed_value(col) {
+            Some(v) => Cow::Owned(vec![v; self.data.rows()]),
+            None => self.data.column(col),
+        }
+    }
+}
+
+/// Row major data, where each row is contiguous, borrowed without converting it to
+/// column major, such as a C contiguous numpy array. Each column is copied out of the
+/// data as it's binned, rather than the whole matrix being copied up front.
+pub struct RowMajorView<'a, T> {
+    data: &'a [T],
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl<'a, T> RowMajorView<'a, T> {
+    /// Create a view of row major data.
+    ///
+    /// * `data` - The values of each row, one row after the other.
+    /// * `rows` - The number of rows in the data.
+    /// * `cols` - The number of columns in the data.
+    pub fn new(data: &'a [T], rows: usize, cols: usize) -> Result<Self, ForustError> {
+        if rows.checked_mul(cols) != Some(data.len()) {
+            return Err(ForustError::ShapeMismatch {
+                name: "data".to_string(),
+                expected: format!(
+                    "{} values, for {} rows and {} columns",
+                    rows.saturating_mul(cols),
+                    rows,
+                    cols
+                ),
+                got: format!("{} values", data.len()),
+            });
+        }
+        Ok(RowMajorView { data, rows, cols })
+    }
+
+    /// Get a row of the data.
+    ///
+    /// * `row` - The index of the row to get.
+    pub fn get_row(&self, row: usize) -> &[T] {
+        &self.data[(row * self.cols)..((row + 1) * self.cols)]
+    }
+}
+
+impl<'a, T: FloatData<T>> DataMatrix for RowMajorView<'a, T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+    fn cols(&self) -> usize {
+        self.cols
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols + col].to_f64()
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        Cow::Owned(
+            self.data
+                .iter()
+                .skip(col)
+                .step_by(self.cols)
+                .map(|v| v.to_f64())
+                .collect(),
+        )
+    }
+}
+
+/// Sparse matrix, in the compressed sparse column (CSC) format. Values that aren't
+/// stored are zero, and not missing, so only the stored values can be missing.
+/// The matrix is accepted by `fit_sparse`, and `predict`, so sparse data, such as one
+/// hot encoded features, doesn't have to be converted by the caller. Training bins
+/// only the stored values, see `SparseBinnedData`.
+pub struct CscMatrix<'a, T> {
+    /// Where the stored values of each column start, the values of column `j` are
+    /// at `indptr[j]..indptr[j + 1]`.
+    pub indptr: &'a [usize],
+    /// The row of each stored value, in increasing order within each column.
+    pub indices: &'a [usize],
+    /// The stored values.
+    pub values: &'a [T],
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl<'a, T> CscMatrix<'a, T> {
+    /// Create a sparse matrix, checking the arrays are a valid CSC matrix.
+    ///
+    /// * `indptr` - Where the stored values of each column start, with a final
+    ///   value of the number of stored values.
+    /// * `indices` - The row of each stored value, in increasing order within each column.
+    /// * `values` - The stored values.
+    /// * `rows` - The number of rows of the matrix.
+    pub fn new(
+        indptr: &'a [usize],
+        indices: &'a [usize],
+        values: &'a [T],
+        rows: usize,
+    ) -> Result<Self, ForustError> {
+        let invalid = |expected: &str, found: String| {
+            Err(ForustError::InvalidParameter {
+                name: "data".to_string(),
+                constraint: expected.to_string(),
+                value: found,
+            })
+        };
+        if indices.len() != values.len() {
+            return invalid(
+                "a row index for each value",
+                format!("{} indices, and {} values", indices.len(), values.len()),
+            );
+        }
+        if indptr.first() != Some(&0) || indptr.last() != Some(&values.len()) {
+            return invalid(
+                "column pointers starting at 0, and ending at the number of values",
+                format!("{:?}", (indptr.first(), indptr.last())),
+            );
+        }
+        for (j, w) in indptr.windows(2).enumerate() {
+            if w[0] > w[1] {
+                return invalid("increasing column pointers", format!("column {}", j));
+            }
+            let col = &indices[w[0]..w[1]];
+            if col.windows(2).any(|r| r[0] >= r[1]) || col.last().is_some_and(|r| *r >= rows) {
+                return invalid(
+                    "increasing row indices, less than the number of rows",
+                    format!("column {}", j),
+                );
+            }
+        }
+        Ok(CscMatrix {
+            indptr,
+            indices,
+            values,
+            rows,
+            cols: indptr.len() - 1,
+        })
+    }
+}
+
+/// Sparse data in the CSC format, owned by the caller, that a `CscMatrix` can borrow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CscMatrixData<T> {
+    pub indptr: Vec<usize>,
+    pub indices: Vec<usize>,
+    pub values: Vec<T>,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl<T> CscMatrixData<T> {
+    /// Create the data from the stored values of each row, as `(column, value)` pairs.
+    /// Pairs in a row can be in any order, but a column can only be stored once per row.
+    ///
+    /// * `rows` - The stored values of each row.
+    /// * `cols` - The number of columns, this must be larger than every stored column.
+    pub fn from_sparse_rows(rows: Vec<Vec<(usize, T)>>, cols: usize) -> Result<Self, ForustError> {
+        let n_rows = rows.len();
+        let mut counts = vec![0; cols];
+        for (i, row) in rows.iter().enumerate() {
+            for (col, _) in row.iter() {
+                if *col >= cols {
+                    return Err(ForustError::InvalidParameter {
+                        name: "rows".to_string(),
+                        constraint: format!("columns less than {}", cols),
+                        value: format!("column {} in row {}", col, i),
+                    });
+                }
+                counts[*col] += 1;
+            }
+        }
+        let mut indptr = Vec::with_capacity(cols + 1);
+        indptr.push(0);
+        for c in counts {
+            indptr.push(indptr[indptr.len() - 1] + c);
+        }
+        // Fill the columns in row order, so the rows of each column are increasing.
+        let mut next = indptr[..cols].to_vec();
+        let mut entries: Vec<Option<(usize, T)>> = (0..indptr[cols]).map(|_| None).collect();
+        for (i, row) in rows.into_iter().enumerate() {
+            for (col, v) in row {
+                if next[col] > indptr[col]
+                    && entries[next[col] - 1].as_ref().map(|e| e.0) == Some(i)
+                {
+                    return Err(ForustError::InvalidParameter {
+                        name: "rows".to_string(),
+                        constraint: "each column stored once per row".to_string(),
+                        value: format!("column {} repeated in row {}", col, i),
+                    });
+                }
+                entries[next[col]] = Some((i, v));
+                next[col] += 1;
+            }
+        }
+        let (indices, values) = entries.into_iter().flatten().unzip();
+        Ok(CscMatrixData {
+            indptr,
+            indices,
+            values,
+            rows: n_rows,
+            cols,
+        })
+    }
+
+    /// A sparse matrix borrowing the data.
+    pub fn as_matrix(&self) -> CscMatrix<'_, T> {
+        CscMatrix {
+            indptr: &self.indptr,
+            indices: &self.indices,
+            values: &self.values,
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+/// Data read from a file in the LibSVM (svmlight) format by `read_libsvm`.
+#[derive(Debug, Clone)]
+pub struct LibSvmData {
+    /// The features, as a sparse matrix.
+    pub data: CscMatrixData<f64>,
+    /// The label of each row.
+    pub y: Vec<f64>,
+    /// The query id of each row, if the file has query ids, for ranking.
+    pub qid: Option<Vec<u64>>,
+}
+
+/// The largest number of features of a LibSVM file read without `n_features`.
+pub const MAX_LIBSVM_FEATURES: usize = 1 << 24;
+
+/// Read a file in the LibSVM (svmlight) format, where each line is a row of the form
+/// `label [qid:id] index:value ...`. Indices are used as the column of the values as
+/// they are, so 1 based files have an empty first column. Text after a `#` is ignored.
+///
+/// * `path` - Path to the LibSVM file.
+/// * `n_features` - The number of columns of the data, every index must be less than it.
+///   If None, the columns run up to the largest index, which must be less than
+///   `MAX_LIBSVM_FEATURES`.
+pub fn read_libsvm(path: &str, n_features: Option<usize>) -> Result<LibSvmData, ForustError> {
+    let file = std::fs::read_to_string(path).map_err(ForustError::Io)?;
+    parse_libsvm(&file, n_features)
+}
+
+fn parse_libsvm(file: &str, n_features: Option<usize>) -> Result<LibSvmData, ForustError> {
+    let error = |line: usize, msg: &str| {
+        ForustError::UnableToRead(format!("{} on line {} of the LibSVM file", msg, line + 1))
+    };
+    let (mut rows, mut y, mut qid) = (Vec::new(), Vec::new(), Vec::new());
+    let max_cols = n_features.unwrap_or(MAX_LIBSVM_FEATURES);
+    let mut cols = 0;
+    for (l, line) in file.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut tokens = line.split_whitespace();
+        let label = tokens.next().unwrap_or("");
+        y.push(
+            label
+                .parse::<f64>()
+                .map_err(|_| error(l, "invalid label"))?,
+        );
+        let mut row = Vec::new();
+        for token in tokens {
+            let (key, value) = token
+                .split_once(':')
+                .ok_or_else(|| error(l, "expected index:value"))?;
+            if key == "qid" {
+                qid.push((
+                    rows.len(),
+                    value.parse::<u64>().map_err(|_| error(l, "invalid qid"))?,
+                ));
+                continue;
+            }
+            let col = key
+                .parse::<usize>()
+                .map_err(|_| error(l, "invalid index"))?;
+            let value = value
+                .parse::<f64>()
+                .map_err(|_| error(l, "invalid value"))?;
+            match col.checked_add(1) {
+                Some(c) if c <= max_cols => cols = cols.max(c),
+                _ => {
+                    return Err(error(
+                        l,
+                        &format!("index {} is not less than {} features", col, max_cols),
+                    ))
+                }
+            }
+            row.push((col, value));
+        }
+        rows.push(row);
+    }
+    // Query ids are only kept if every row has one.
+    let qid = if qid.is_empty() {
+        None
+    } else if qid.len() == rows.len() && qid.iter().enumerate().all(|(i, (r, _))| i == *r) {
+        Some(qid.into_iter().map(|(_, q)| q).collect())
+    } else {
+        return Err(ForustError::UnableToRead(
+            "only some rows of the LibSVM file have a qid".to_string(),
+        ));
+    };
+    Ok(LibSvmData {
+        data: CscMatrixData::from_sparse_rows(rows, n_features.unwrap_or(cols))?,
+        y,
+        qid,
+    })
+}
+
+impl<'a, T: FloatData<T>> DataMatrix for CscMatrix<'a, T> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+    fn cols(&self) -> usize {
+        self.cols
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        let (start, end) = (self.indptr[col], self.indptr[col + 1]);
+        match self.indices[start..end].binary_search(&row) {
+            Ok(i) => self.values[start + i].to_f64(),
+            Err(_) => 0.,
+        }
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        let mut values = vec![0.; self.rows];
+        let (start, end) = (self.indptr[col], self.indptr[col + 1]);
+        for (i, v) in self.indices[start..end]
+            .iter()
+            .zip(self.values[start..end].iter())
+        {
+            values[*i] = v.to_f64();
+        }
+        Cow::Owned(values)
+    }
+    fn is_sparse(&self) -> bool {
+        true
+    }
+    fn stored_column(&self, col: usize) -> (Cow<'_, [usize]>, Cow<'_, [f64]>) {
+        let (start, end) = (self.indptr[col], self.indptr[col + 1]);
+        (
+            Cow::Borrowed(&self.indices[start..end]),
+            T::to_f64_slice(&self.values[start..end]),
+        )
+    }
+}
+
+/// Transpose row major data, where the values of each row are contiguous, such as data
+/// read row by row from a CSV file, into the column major layout used by `Matrix`.
+///
+/// * `data` - The values of the matrix, one row after the other.
+/// * `rows` - The number of rows of the data.
+/// * `cols` - The number of columns of the data.
+pub fn row_major_to_column_major<T: Copy>(
+    data: &[T],
+    rows: usize,
+    cols: usize,
+) -> Result<Vec<T>, ForustError> {
+    if rows.checked_mul(cols) != Some(data.len()) {
+        return Err(ForustError::ShapeMismatch {
+            name: "data".to_string(),
+            expected: format!(
+                "{} values, for {} rows and {} columns",
+                rows.saturating_mul(cols),
+                rows,
+                cols
+            ),
+            got: format!("{} values", data.len()),
+        });
+    }
+    Ok((0..cols)
+        .flat_map(|j| data.iter().skip(j).step_by(cols.max(1)).copied())
+        .collect())
+}
+
+/// Column major data, owned by the caller, that a `Matrix` can borrow. This
+/// can be built from columns, or rows, without transposing the data by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixData<T> {
+    pub data: Vec<T>,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl<T: Copy> MatrixData<T> {
+    /// Create the data from a vector for each column.
+    ///
+    /// * `columns` - The columns of the data, which must all be the same length.
+    pub fn from_columns(columns: Vec<Vec<T>>) -> Result<Self, ForustError> {
+        let rows = columns.first().map_or(0, |c| c.len());
+        if let Some((i, c)) = columns.iter().enumerate().find(|(_, c)| c.len() != rows) {
+            return Err(ForustError::ShapeMismatch {
+                name: "columns".to_string(),
+                expected: format!("{} values in each column", rows),
+                got: format!("{} values in column {}", c.len(), i),
+            });
+        }
+        let cols = columns.len();
+        Ok(MatrixData {
+            data: columns.into_iter().flatten().collect(),
+            rows,
+            cols,
+        })
+    }
+
+    /// Create the data from an iterator over the rows.
+    ///
+    /// * `rows` - The rows of the data, which must all be the same length.
+    pub fn from_row_iter<I: IntoIterator<Item = Vec<T>>>(rows: I) -> Result<Self, ForustError> {
+        let mut values = Vec::new();
+        let (mut n_rows, mut cols) = (0, 0);
+        for row in rows {
+            if n_rows == 0 {
+                cols = row.len();
+            } else if row.len() != cols {
+                return Err(ForustError::ShapeMismatch {
+                    name: "rows".to_string(),
+                    expected: format!("{} values in each row", cols),
+                    got: format!("{} values in row {}", row.len(), n_rows),
+                });
+            }
+            values.extend(row);
+            n_rows += 1;
+        }
+        Self::from_rows(&values, n_rows, cols)
+    }
+
+    /// Create the data from row major values.
+    ///
+    /// * `data` - The values of the matrix, one row after the other.
+    /// * `rows` - The number of rows of the data.
+    /// * `cols` - The number of columns of the data.
+    pub fn from_rows(data: &[T], rows: usize, cols: usize) -> Result<Self, ForustError> {
+        Ok(MatrixData {
+            data: row_major_to_column_major(data, rows, cols)?,
+            rows,
+            cols,
+        })
+    }
+
+    /// A matrix borrowing the data.
+    pub fn as_matrix(&self) -> Matrix<'_, T> {
+        Matrix::new(&self.data, self.rows, self.cols)
+    }
 }
 
 /// A lightweight row major matrix, this is primarily
@@ -202,6 +902,16 @@ impl<T> RowMajorMatrix<T> {
         idx
     }
 
+    /// Copy the data into a column major vector, that can be used to create a `Matrix`.
+    pub fn to_column_major(&self) -> Vec<T>
+    where
+        T: Copy,
+    {
+        (0..self.cols)
+            .flat_map(|j| (0..self.rows).map(move |i| *self.get(i, j)))
+            .collect()
+    }
+
     /// Add a rows to the matrix, this can be multiple
     /// rows, if they are in sequential order in the items.
     pub fn append_row(&mut self, items: Vec<T>) {
@@ -236,7 +946,7 @@ where
 }
 
 /// A jagged column aligned matrix, that owns it's data contents.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct JaggedMatrix<T> {
     /// The contents of the matrix.
     pub data: Vec<T>,
@@ -316,6 +1026,455 @@ impl<T> Default for JaggedMatrix<T> {
     }
 }
 
+/// Options for reading a CSV file with `read_csv`.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// The delimiter between the cells of a row.
+    pub delimiter: u8,
+    /// Does the first row hold the names of the columns. If not, the
+    /// columns are named `Column_0`, `Column_1`, and so on.
+    pub has_headers: bool,
+    /// Cells with any of these values are read as missing, and set to NaN.
+    pub missing_values: Vec<String>,
+    /// Name of the column holding the target, this is not included in the data.
+    pub target: Option<String>,
+    /// Name of the column holding the sample weights, this is not included in the data.
+    pub weight: Option<String>,
+    /// Names of the columns to read as features, if None, all other columns are read.
+    pub columns: Option<Vec<String>>,
+}
+
+#[cfg(feature = "csv")]
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            has_headers: true,
+            missing_values: ["", "NA", "N/A", "NaN", "nan", "null"]
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            target: None,
+            weight: None,
+            columns: None,
+        }
+    }
+}
+
+#[cfg(feature = "csv")]
+impl CsvOptions {
+    /// Set the delimiter between the cells of a row.
+    pub fn set_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set if the first row holds the names of the columns.
+    pub fn set_has_headers(mut self, has_headers: bool) -> Self {
+        self.has_headers = has_headers;
+        self
+    }
+
+    /// Set the values read as missing.
+    pub fn set_missing_values(mut self, missing_values: Vec<String>) -> Self {
+        self.missing_values = missing_values;
+        self
+    }
+
+    /// Set the name of the target column.
+    pub fn set_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set the name of the sample weight column.
+    pub fn set_weight(mut self, weight: Option<String>) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the names of the columns to read as features.
+    pub fn set_columns(mut self, columns: Option<Vec<String>>) -> Self {
+        self.columns = columns;
+        self
+    }
+}
+
+/// Data read from a file by `read_csv`, or `read_parquet`.
+#[cfg(any(feature = "csv", feature = "parquet"))]
+#[derive(Debug, Clone)]
+pub struct LoadedData {
+    /// The feature columns, in column major order.
+    pub data: MatrixData<f64>,
+    /// The names of the feature columns.
+    pub feature_names: Vec<String>,
+    /// The target column, if a target was selected.
+    pub y: Option<Vec<f64>>,
+    /// The sample weight column, if a weight was selected.
+    pub sample_weight: Option<Vec<f64>>,
+}
+
+/// The columns selected from a file, and the values read from them so far.
+#[cfg(any(feature = "csv", feature = "parquet"))]
+struct ColumnSelection {
+    target: Option<usize>,
+    weight: Option<usize>,
+    features: Vec<usize>,
+    columns: Vec<Vec<f64>>,
+    y: Vec<f64>,
+    sample_weight: Vec<f64>,
+}
+
+#[cfg(any(feature = "csv", feature = "parquet"))]
+impl ColumnSelection {
+    fn new(
+        names: &[String],
+        target: Option<&str>,
+        weight: Option<&str>,
+        columns: Option<&[String]>,
+    ) -> Result<Self, ForustError> {
+        let find = |name: &str| {
+            names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| ForustError::InvalidParameter {
+                    name: "options".to_string(),
+                    constraint: "the name of a column in the file".to_string(),
+                    value: name.to_string(),
+                })
+        };
+        let target = target.map(find).transpose()?;
+        let weight = weight.map(find).transpose()?;
+        let features: Vec<usize> = match columns {
+            Some(columns) => columns.iter().map(|c| find(c)).collect::<Result<_, _>>()?,
+            None => (0..names.len())
+                .filter(|i| Some(*i) != target && Some(*i) != weight)
+                .collect(),
+        };
+        Ok(ColumnSelection {
+            target,
+            weight,
+            columns: vec![Vec::new(); features.len()],
+            features,
+            y: Vec::new(),
+            sample_weight: Vec::new(),
+        })
+    }
+
+    /// Read the selected values of a row.
+    ///
+    /// * `value` - Read the value of a column of the row, by the index of the column.
+    fn push_row<F>(&mut self, value: F) -> Result<(), ForustError>
+    where
+        F: Fn(usize) -> Result<f64, ForustError>,
+    {
+        for (column, col) in self.columns.iter_mut().zip(self.features.iter()) {
+            column.push(value(*col)?);
+        }
+        if let Some(col) = self.target {
+            self.y.push(value(col)?);
+        }
+        if let Some(col) = self.weight {
+            self.sample_weight.push(value(col)?);
+        }
+        Ok(())
+    }
+
+    fn finish(self, names: &[String]) -> Result<LoadedData, ForustError> {
+        Ok(LoadedData {
+            data: MatrixData::from_columns(self.columns)?,
+            feature_names: self.features.iter().map(|i| names[*i].clone()).collect(),
+            y: self.target.map(|_| self.y),
+            sample_weight: self.weight.map(|_| self.sample_weight),
+        })
+    }
+}
+
+/// Read the numeric columns of a CSV file. Cells matching one of the
+/// `missing_values` are read as NaN, any other cell that isn't a number is an error.
+///
+/// * `path` - Path to the CSV file.
+/// * `options` - The options used to read the file, and select the columns.
+#[cfg(feature = "csv")]
+pub fn read_csv(path: &str, options: &CsvOptions) -> Result<LoadedData, ForustError> {
+    let read_error = |e: csv::Error| ForustError::UnableToRead(e.to_string());
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(options.has_headers)
+        .from_path(path)
+        .map_err(read_error)?;
+    let headers: Option<Vec<String>> = if options.has_headers {
+        let headers = reader.headers().map_err(read_error)?;
+        Some(headers.iter().map(|h| h.trim().to_string()).collect())
+    } else {
+        None
+    };
+    let mut records = reader.records().peekable();
+    let names = match headers {
+        Some(names) => names,
+        None => {
+            let n = match records.peek() {
+                Some(Ok(record)) => record.len(),
+                _ => 0,
+            };
+            (0..n).map(|i| format!("Column_{}", i)).collect()
+        }
+    };
+    let mut selection = ColumnSelection::new(
+        &names,
+        options.target.as_deref(),
+        options.weight.as_deref(),
+        options.columns.as_deref(),
+    )?;
+    for (row, record) in records.enumerate() {
+        let record = record.map_err(read_error)?;
+        selection.push_row(|col| {
+            let cell = record.get(col).unwrap_or("").trim();
+            if options.missing_values.iter().any(|m| m == cell) {
+                Ok(f64::NAN)
+            } else {
+                cell.parse::<f64>().map_err(|_| {
+                    ForustError::UnableToRead(format!(
+                        "unable to parse {} in row {} of column {}",
+                        cell, row, names[col]
+                    ))
+                })
+            }
+        })?;
+    }
+    selection.finish(&names)
+}
+
+/// Options for reading a Parquet file with `read_parquet`.
+#[cfg(feature = "parquet")]
+#[derive(Debug, Clone, Default)]
+pub struct ParquetOptions {
+    /// Name of the column holding the target, this is not included in the data.
+    pub target: Option<String>,
+    /// Name of the column holding the sample weights, this is not included in the data.
+    pub weight: Option<String>,
+    /// Names of the columns to read as features, if None, all other columns are read.
+    pub columns: Option<Vec<String>>,
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetOptions {
+    /// Set the name of the target column.
+    pub fn set_target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Set the name of the sample weight column.
+    pub fn set_weight(mut self, weight: Option<String>) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Set the names of the columns to read as features.
+    pub fn set_columns(mut self, columns: Option<Vec<String>>) -> Self {
+        self.columns = columns;
+        self
+    }
+}
+
+/// Read the numeric columns of a Parquet file. Null values are read as NaN,
+/// and boolean values as 0 or 1. Selecting a column of any other type is an error.
+///
+/// * `path` - Path to the Parquet file.
+/// * `options` - The options used to select the columns.
+#[cfg(feature = "parquet")]
+pub fn read_parquet(path: &str, options: &ParquetOptions) -> Result<LoadedData, ForustError> {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use parquet::record::Field;
+
+    let read_error = |e: parquet::errors::ParquetError| ForustError::UnableToRead(e.to_string());
+    let file = std::fs::File::open(path).map_err(ForustError::Io)?;
+    let reader = SerializedFileReader::new(file).map_err(read_error)?;
+    let names: Vec<String> = reader
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .root_schema()
+        .get_fields()
+        .iter()
+        .map(|f| f.name().to_string())
+        .collect();
+    let mut selection = ColumnSelection::new(
+        &names,
+        options.target.as_deref(),
+        options.weight.as_deref(),
+        options.columns.as_deref(),
+    )?;
+    for (i, row) in reader.get_row_iter(None).map_err(read_error)?.enumerate() {
+        let row = row.map_err(read_error)?;
+        let fields: Vec<&Field> = row.get_column_iter().map(|(_, f)| f).collect();
+        selection.push_row(|col| match fields[col] {
+            Field::Null => Ok(f64::NAN),
+            Field::Bool(v) => Ok(f64::from(u8::from(*v))),
+            Field::Byte(v) => Ok(f64::from(*v)),
+            Field::Short(v) => Ok(f64::from(*v)),
+            Field::Int(v) => Ok(f64::from(*v)),
+            Field::Long(v) => Ok(*v as f64),
+            Field::UByte(v) => Ok(f64::from(*v)),
+            Field::UShort(v) => Ok(f64::from(*v)),
+            Field::UInt(v) => Ok(f64::from(*v)),
+            Field::ULong(v) => Ok(*v as f64),
+            Field::Float(v) => Ok(f64::from(*v)),
+            Field::Double(v) => Ok(*v),
+            v => Err(ForustError::UnableToRead(format!(
+                "unable to read {} in row {} of column {} as a number",
+                v, i, names[col]
+            ))),
+        })?;
+    }
+    selection.finish(&names)
+}
+
+/// Copy the numeric, and boolean columns of a Polars data frame into column major
+/// data. Null values are read as NaN, and boolean values as 0 or 1.
+///
+/// * `df` - The data frame to read.
+/// * `columns` - Names of the columns to read, if None, all columns are read.
+#[cfg(feature = "polars")]
+pub fn matrix_data_from_dataframe(
+    df: &polars::prelude::DataFrame,
+    columns: Option<&[&str]>,
+) -> Result<MatrixData<f64>, ForustError> {
+    let names = match columns {
+        Some(columns) => columns.to_vec(),
+        None => df.get_column_names(),
+    };
+    let mut data = Vec::with_capacity(df.height() * names.len());
+    for name in names.iter() {
+        append_dataframe_column(&mut data, df, name)?;
+    }
+    Ok(MatrixData {
+        data,
+        rows: df.height(),
+        cols: names.len(),
+    })
+}
+
+/// Read a single numeric column of a Polars data frame, such as the target, or sample weight.
+///
+/// * `df` - The data frame to read.
+/// * `name` - Name of the column to read.
+#[cfg(feature = "polars")]
+pub fn column_from_dataframe(
+    df: &polars::prelude::DataFrame,
+    name: &str,
+) -> Result<Vec<f64>, ForustError> {
+    let mut values = Vec::with_capacity(df.height());
+    append_dataframe_column(&mut values, df, name)?;
+    Ok(values)
+}
+
+#[cfg(feature = "polars")]
+fn append_dataframe_column(
+    data: &mut Vec<f64>,
+    df: &polars::prelude::DataFrame,
+    name: &str,
+) -> Result<(), ForustError> {
+    use polars::prelude::DataType;
+    let polars_error = |e: polars::prelude::PolarsError| ForustError::InvalidParameter {
+        name: "columns".to_string(),
+        constraint: "a column of the data frame".to_string(),
+        value: e.to_string(),
+    };
+    let series = df.column(name).map_err(polars_error)?;
+    // Other types would be cast to null values, rather than failing.
+    if !(series.dtype().is_numeric() || series.dtype() == &DataType::Boolean) {
+        return Err(ForustError::InvalidParameter {
+            name: name.to_string(),
+            constraint: "a numeric, or boolean column".to_string(),
+            value: format!("a column of type {}", series.dtype()),
+        });
+    }
+    let values = series.cast(&DataType::Float64).map_err(polars_error)?;
+    data.extend(
+        values
+            .f64()
+            .map_err(polars_error)?
+            .into_iter()
+            .map(|v| v.unwrap_or(f64::NAN)),
+    );
+    Ok(())
+}
+
+/// Borrow a column major (Fortran order) array view as a matrix, without copying
+/// it. Arrays in any other layout can be converted to `MatrixData`.
+#[cfg(feature = "ndarray")]
+impl<'a, T> TryFrom<ndarray::ArrayView2<'a, T>> for Matrix<'a, T> {
+    type Error = ForustError;
+
+    fn try_from(view: ndarray::ArrayView2<'a, T>) -> Result<Self, Self::Error> {
+        let (rows, cols) = view.dim();
+        match view.reversed_axes().to_slice() {
+            Some(data) => Ok(Matrix::new(data, rows, cols)),
+            None => Err(ForustError::InvalidParameter {
+                name: "data".to_string(),
+                constraint: "a contiguous column major array".to_string(),
+                value: "an array in another layout".to_string(),
+            }),
+        }
+    }
+}
+
+/// Copy an array view, in any layout, into column major data.
+#[cfg(feature = "ndarray")]
+impl<'a, T: Copy> From<ndarray::ArrayView2<'a, T>> for MatrixData<T> {
+    fn from(view: ndarray::ArrayView2<'a, T>) -> Self {
+        let (rows, cols) = view.dim();
+        MatrixData {
+            data: view.t().iter().copied().collect(),
+            rows,
+            cols,
+        }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<'a, T: Copy> From<&Matrix<'a, T>> for ndarray::Array2<T> {
+    fn from(matrix: &Matrix<'a, T>) -> Self {
+        ndarray::Array2::from_shape_fn((matrix.rows, matrix.cols), |(i, j)| *matrix.get(i, j))
+    }
+}
+
+/// Convert the data to a column major array, without copying it.
+#[cfg(feature = "ndarray")]
+impl<T> From<MatrixData<T>> for ndarray::Array2<T> {
+    fn from(data: MatrixData<T>) -> Self {
+        use ndarray::ShapeBuilder;
+        // The length of the data always matches the shape.
+        ndarray::Array2::from_shape_vec((data.rows, data.cols).f(), data.data).unwrap()
+    }
+}
+
+/// Borrow a nalgebra matrix, which is stored column major, without copying it.
+#[cfg(feature = "nalgebra")]
+impl<'a, T: nalgebra::Scalar> From<&'a nalgebra::DMatrix<T>> for Matrix<'a, T> {
+    fn from(matrix: &'a nalgebra::DMatrix<T>) -> Self {
+        Matrix::new(matrix.as_slice(), matrix.nrows(), matrix.ncols())
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl<'a, T: nalgebra::Scalar + Copy> From<&Matrix<'a, T>> for nalgebra::DMatrix<T> {
+    fn from(matrix: &Matrix<'a, T>) -> Self {
+        nalgebra::DMatrix::from_fn(matrix.rows, matrix.cols, |i, j| *matrix.get(i, j))
+    }
+}
+
+/// Convert the data to a nalgebra matrix, without copying it.
+#[cfg(feature = "nalgebra")]
+impl<T: nalgebra::Scalar> From<MatrixData<T>> for nalgebra::DMatrix<T> {
+    fn from(data: MatrixData<T>) -> Self {
+        nalgebra::DMatrix::from_vec(data.rows, data.cols, data.data)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -339,6 +1498,264 @@ mod tests {
         assert_eq!(m.get(2, 1), &-2);
     }
 
+    #[test]
+    fn test_row_major_to_column_major() {
+        let v = vec![1, 5, 2, 6, 3, 7];
+        let col_major = row_major_to_column_major(&v, 3, 2).unwrap();
+        assert_eq!(col_major, vec![1, 2, 3, 5, 6, 7]);
+        let m = Matrix::new(&col_major, 3, 2);
+        assert_eq!(m.get_row(1), vec![2, 6]);
+        assert_eq!(
+            RowMajorMatrix::new(v.clone(), 3, 2).to_column_major(),
+            col_major
+        );
+        assert!(row_major_to_column_major(&v, 2, 2).is_err());
+        assert!(row_major_to_column_major(&v, 4, 2).is_err());
+    }
+
+    #[test]
+    fn test_row_major_view() {
+        let v = vec![1, 5, 2, 6, 3, 7];
+        let view = RowMajorView::new(&v, 3, 2).unwrap();
+        assert_eq!(view.get_row(1), &[2, 6]);
+        assert!(RowMajorView::new(&v, 2, 2).is_err());
+        // A shape whose size wraps around to the number of values is rejected.
+        assert!(RowMajorView::new(&v, (1 << (usize::BITS - 1)) + 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_matrix_data() {
+        let from_columns = MatrixData::from_columns(vec![vec![1, 2, 3], vec![5, 6, 7]]).unwrap();
+        let from_rows =
+            MatrixData::from_row_iter(vec![vec![1, 5], vec![2, 6], vec![3, 7]]).unwrap();
+        assert_eq!(from_columns, from_rows);
+        assert_eq!((from_rows.rows, from_rows.cols), (3, 2));
+        assert_eq!(from_rows.as_matrix().get_row(1), vec![2, 6]);
+        assert_eq!(
+            MatrixData::from_rows(&[1, 5, 2, 6, 3, 7], 3, 2).unwrap(),
+            from_columns
+        );
+        assert!(MatrixData::from_columns(vec![vec![1, 2, 3], vec![5, 6]]).is_err());
+        assert!(MatrixData::from_row_iter(vec![vec![1, 5], vec![2]]).is_err());
+        let empty = MatrixData::<f64>::from_row_iter(Vec::new()).unwrap();
+        assert_eq!((empty.rows, empty.cols), (0, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "csv")]
+    fn test_read_csv() {
+        let options = CsvOptions::default()
+            .set_target(Some("survived".to_string()))
+            .set_columns(Some(
+                ["pclass", "age", "sibsp", "parch", "fare"]
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect(),
+            ));
+        let csv_data = read_csv("resources/titanic.csv", &options).unwrap();
+        assert_eq!((csv_data.data.rows, csv_data.data.cols), (891, 5));
+        assert_eq!(csv_data.feature_names[1], "age");
+        assert!(csv_data.sample_weight.is_none());
+        let file = std::fs::read_to_string("resources/contiguous_with_missing.csv").unwrap();
+        let expected: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        // Missing ages are read as NaN.
+        assert!(csv_data
+            .data
+            .data
+            .iter()
+            .zip(expected.iter())
+            .all(|(a, b)| a == b || (a.is_nan() && b.is_nan())));
+        let y = csv_data.y.unwrap();
+        assert_eq!(&y[..3], &[0., 1., 1.]);
+
+        // Text columns can't be read as numbers.
+        let options = CsvOptions::default().set_target(Some("survived".to_string()));
+        assert!(read_csv("resources/titanic.csv", &options).is_err());
+        let options = CsvOptions::default().set_target(Some("not_a_column".to_string()));
+        assert!(read_csv("resources/titanic.csv", &options).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_read_parquet() {
+        use parquet::data_type::{DoubleType, Int32Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let path = "resources/read_parquet.parquet";
+        let schema = parse_message_type(
+            "message schema { REQUIRED DOUBLE a; OPTIONAL INT32 b; REQUIRED DOUBLE y; }",
+        )
+        .unwrap();
+        let file = std::fs::File::create(path).unwrap();
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema), props).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        let mut i = 0;
+        while let Some(mut column) = row_group.next_column().unwrap() {
+            match i {
+                0 => column
+                    .typed::<DoubleType>()
+                    .write_batch(&[0.5, 1.5, 2.5], None, None)
+                    .unwrap(),
+                // The second value of b is null.
+                1 => column
+                    .typed::<Int32Type>()
+                    .write_batch(&[1, 3], Some(&[1, 0, 1]), None)
+                    .unwrap(),
+                _ => column
+                    .typed::<DoubleType>()
+                    .write_batch(&[0., 1., 0.], None, None)
+                    .unwrap(),
+            };
+            column.close().unwrap();
+            i += 1;
+        }
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let options = ParquetOptions::default().set_target(Some("y".to_string()));
+        let loaded = read_parquet(path, &options).unwrap();
+        assert_eq!(loaded.feature_names, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!((loaded.data.rows, loaded.data.cols), (3, 2));
+        assert_eq!(&loaded.data.data[..4], &[0.5, 1.5, 2.5, 1.]);
+        assert!(loaded.data.data[4].is_nan());
+        assert_eq!(loaded.data.data[5], 3.);
+        assert_eq!(loaded.y, Some(vec![0., 1., 0.]));
+
+        let options = ParquetOptions::default().set_columns(Some(vec!["b".to_string()]));
+        let loaded = read_parquet(path, &options).unwrap();
+        assert_eq!(loaded.data.cols, 1);
+        assert!(loaded.y.is_none());
+        let options = ParquetOptions::default().set_target(Some("c".to_string()));
+        assert!(read_parquet(path, &options).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "polars")]
+    fn test_matrix_data_from_dataframe() {
+        use polars::prelude::*;
+        let df = df!(
+            "a" => &[0.5, 1.5, 2.5],
+            "b" => &[Some(1i32), None, Some(3)],
+            "c" => &[true, false, true],
+            "d" => &["x", "y", "z"]
+        )
+        .unwrap();
+        let data = matrix_data_from_dataframe(&df, Some(&["a", "b", "c"])).unwrap();
+        assert_eq!((data.rows, data.cols), (3, 3));
+        assert_eq!(&data.data[..4], &[0.5, 1.5, 2.5, 1.]);
+        assert!(data.data[4].is_nan());
+        assert_eq!(&data.data[5..], &[3., 1., 0., 1.]);
+        assert_eq!(column_from_dataframe(&df, "c").unwrap(), vec![1., 0., 1.]);
+        // Text columns, and columns not in the data frame, can't be read.
+        assert!(matrix_data_from_dataframe(&df, None).is_err());
+        assert!(column_from_dataframe(&df, "e").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "ndarray")]
+    fn test_ndarray_conversions() {
+        use ndarray::{array, Array2, ShapeBuilder};
+        let a = array![[1, 5], [2, 6], [3, 7]];
+        // Row major arrays can't be borrowed, but can be copied.
+        assert!(Matrix::try_from(a.view()).is_err());
+        let data = MatrixData::from(a.view());
+        assert_eq!(data.data, vec![1, 2, 3, 5, 6, 7]);
+        let f = Array2::from_shape_vec((3, 2).f(), vec![1, 2, 3, 5, 6, 7]).unwrap();
+        assert_eq!(f, a);
+        let m = Matrix::try_from(f.view()).unwrap();
+        assert_eq!((m.rows, m.cols), (3, 2));
+        assert_eq!(m.get_col(1), &[5, 6, 7]);
+        assert_eq!(Array2::from(&m), a);
+        assert_eq!(Array2::from(data), a);
+    }
+
+    #[test]
+    #[cfg(feature = "nalgebra")]
+    fn test_nalgebra_conversions() {
+        use nalgebra::DMatrix;
+        let a = DMatrix::from_row_slice(3, 2, &[1, 5, 2, 6, 3, 7]);
+        let m = Matrix::from(&a);
+        assert_eq!((m.rows, m.cols), (3, 2));
+        assert_eq!(m.get_col(1), &[5, 6, 7]);
+        assert_eq!(DMatrix::from(&m), a);
+        let data = MatrixData::from_columns(vec![vec![1, 2, 3], vec![5, 6, 7]]).unwrap();
+        assert_eq!(DMatrix::from(data), a);
+    }
+
+    #[test]
+    fn test_matrix_view() {
+        let v = vec![1., 2., 3., 4., 5., 6.];
+        let m = Matrix::new(&v, 2, 3);
+        let view = MatrixView::new(&m, vec![2, 0]).unwrap();
+        assert_eq!((view.rows(), view.cols()), (2, 2));
+        assert_eq!(view.get_col(0), &[5., 6.]);
+        assert_eq!(view.value(1, 1), 2.);
+        assert_eq!(view.column(0).as_ref(), &[5., 6.]);
+        assert!(MatrixView::new(&m, vec![3]).is_err());
+    }
+
+    #[test]
+    fn test_row_view() {
+        let v = vec![1., 2., 3., 4., 5., 6.];
+        let m = Matrix::new(&v, 3, 2);
+        let view = RowView::new(&m, vec![2, 0]).unwrap();
+        assert_eq!((view.rows(), view.cols()), (2, 2));
+        assert_eq!(view.value(0, 1), 6.);
+        assert_eq!(view.column(0).as_ref(), &[3., 1.]);
+        assert_eq!(view.select(&[1]).index(), &[0]);
+        assert!(RowView::new(&m, vec![3]).is_err());
+    }
+
+    #[test]
+    fn test_csc_matrix() {
+        // [[1, 0], [0, 2], [3, NaN]]
+        let (indptr, indices, values) =
+            (vec![0, 2, 4], vec![0, 2, 1, 2], vec![1., 3., 2., f64::NAN]);
+        let m = CscMatrix::new(&indptr, &indices, &values, 3).unwrap();
+        assert_eq!((m.rows(), m.cols()), (3, 2));
+        assert_eq!(m.value(1, 0), 0.);
+        assert_eq!(m.value(2, 0), 3.);
+        assert!(m.value(2, 1).is_nan());
+        assert_eq!(m.column(0).as_ref(), &[1., 0., 3.]);
+        assert!(CscMatrix::new(&indptr, &indices, &values, 2).is_err());
+        assert!(CscMatrix::new(&[0, 2, 3], &indices, &values, 3).is_err());
+        assert!(CscMatrix::new(&indptr, &[2, 0, 1, 2], &values, 3).is_err());
+    }
+
+    #[test]
+    fn test_parse_libsvm() {
+        let file = "1 qid:3 1:0.5 3:2 # comment\n\n0 qid:3 2:1.5\n1 qid:4 3:-1 1:4\n";
+        let parsed = parse_libsvm(file, None).unwrap();
+        assert_eq!(parsed.y, vec![1., 0., 1.]);
+        assert_eq!(parsed.qid, Some(vec![3, 3, 4]));
+        let m = parsed.data.as_matrix();
+        assert_eq!((m.rows(), m.cols()), (3, 4));
+        assert_eq!(m.column(0).as_ref(), &[0., 0., 0.]);
+        assert_eq!(m.column(1).as_ref(), &[0.5, 0., 4.]);
+        assert_eq!(m.column(3).as_ref(), &[2., 0., -1.]);
+        // The data is a valid sparse matrix.
+        let d = &parsed.data;
+        assert!(CscMatrix::new(&d.indptr, &d.indices, &d.values, d.rows).is_ok());
+
+        assert!(parse_libsvm("1 1:0.5\n0 qid:1 2:1", None).is_err());
+        assert!(parse_libsvm("1 1:0.5 1:2", None).is_err());
+        assert!(parse_libsvm("a 1:0.5", None).is_err());
+        assert_eq!(parse_libsvm("1 2:1\n0 1:1", None).unwrap().qid, None);
+        // Indices past the number of features are rejected, instead of allocated.
+        assert!(parse_libsvm("1 18446744073709551615:1", None).is_err());
+        assert!(parse_libsvm(&format!("1 {}:1", MAX_LIBSVM_FEATURES), None).is_err());
+        assert!(parse_libsvm("1 3:1", Some(3)).is_err());
+        let m = parse_libsvm("1 1:1", Some(3)).unwrap().data;
+        assert_eq!((m.rows, m.cols), (1, 3));
+    }
+
     #[test]
     fn test_matrix_get() {
         let v = vec![1, 2, 3, 5, 6, 7];
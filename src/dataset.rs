@@ -0,0 +1,228 @@
+use crate::constraints::{Constraint, ConstraintMap};
+#[cfg(any(feature = "csv", feature = "parquet"))]
+use crate::data::LoadedData;
+use crate::data::{FloatData, Matrix, MatrixData};
+use crate::errors::ForustError;
+use serde::{Deserialize, Serialize};
+
+/// Whether a feature is numeric, or categorical.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum FeatureType {
+    Numeric,
+    /// Integer encoded categories, such as the codes of a `CategoricalEncoder`.
+    Categorical,
+}
+
+/// What is known about a feature, kept with the data, so the name, type, and
+/// monotonicity constraint of the feature are only set in one place.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FeatureMeta {
+    pub name: Option<String>,
+    pub feature_type: FeatureType,
+    pub constraint: Constraint,
+}
+
+impl Default for FeatureMeta {
+    fn default() -> Self {
+        FeatureMeta {
+            name: None,
+            feature_type: FeatureType::Numeric,
+            constraint: Constraint::Unconstrained,
+        }
+    }
+}
+
+/// The features, target, and sample weights of a dataset, with the metadata of each
+/// feature. A booster can be fit on a dataset with `GradientBooster::fit_dataset`,
+/// rather than passing each part separately.
+#[derive(Debug, Clone)]
+pub struct Dataset<T = f64> {
+    /// The features, in column major order.
+    pub data: MatrixData<T>,
+    /// The target of each row.
+    pub y: Vec<T>,
+    /// The instance weight of each row, one for every row by default.
+    pub sample_weight: Vec<T>,
+    /// The metadata of each feature.
+    pub features: Vec<FeatureMeta>,
+}
+
+impl<T: FloatData<T>> Dataset<T> {
+    /// Create a dataset, with a weight of one for each row, and unnamed,
+    /// unconstrained, numeric features.
+    ///
+    /// * `data` - The features of the dataset.
+    /// * `y` - The target of each row.
+    pub fn new(data: MatrixData<T>, y: Vec<T>) -> Result<Self, ForustError> {
+        if y.len() != data.rows {
+            return Err(ForustError::LengthMismatch(
+                "y".to_string(),
+                y.len(),
+                data.rows,
+            ));
+        }
+        Ok(Dataset {
+            sample_weight: vec![T::ONE; data.rows],
+            features: vec![FeatureMeta::default(); data.cols],
+            data,
+            y,
+        })
+    }
+
+    /// Set the sample weights of the dataset.
+    /// * `sample_weight` - Instance weights for each row.
+    pub fn set_sample_weight(mut self, sample_weight: Vec<T>) -> Self {
+        self.sample_weight = sample_weight;
+        self
+    }
+
+    /// Set the metadata of the features.
+    /// * `features` - The metadata of each feature.
+    pub fn set_features(mut self, features: Vec<FeatureMeta>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Set the names of the features.
+    /// * `feature_names` - A name for each feature.
+    pub fn set_feature_names(mut self, feature_names: Vec<String>) -> Self {
+        self.features
+            .resize_with(feature_names.len(), FeatureMeta::default);
+        for (f, name) in self.features.iter_mut().zip(feature_names) {
+            f.name = Some(name);
+        }
+        self
+    }
+
+    /// Set which features are categorical.
+    /// * `categorical` - Whether each feature is categorical.
+    pub fn set_categorical(mut self, categorical: Vec<bool>) -> Self {
+        self.features
+            .resize_with(categorical.len(), FeatureMeta::default);
+        for (f, c) in self.features.iter_mut().zip(categorical) {
+            f.feature_type = if c {
+                FeatureType::Categorical
+            } else {
+                FeatureType::Numeric
+            };
+        }
+        self
+    }
+
+    /// Set the monotonicity constraints of the features.
+    /// * `constraints` - The constraint of each constrained feature.
+    pub fn set_monotone_constraints(mut self, constraints: &ConstraintMap) -> Self {
+        for (i, c) in constraints.iter() {
+            if *i >= self.features.len() {
+                self.features.resize_with(*i + 1, FeatureMeta::default);
+            }
+            self.features[*i].constraint = *c;
+        }
+        self
+    }
+
+    /// The names of the features, if every feature is named.
+    pub fn feature_names(&self) -> Option<Vec<String>> {
+        self.features.iter().map(|f| f.name.clone()).collect()
+    }
+
+    /// The indices of the categorical features.
+    pub fn categorical_features(&self) -> Vec<usize> {
+        self.features
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.feature_type == FeatureType::Categorical)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The constraints of the features that are constrained.
+    pub fn monotone_constraints(&self) -> ConstraintMap {
+        self.features
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.constraint != Constraint::Unconstrained)
+            .map(|(i, f)| (i, f.constraint))
+            .collect()
+    }
+
+    /// Borrow the features as a matrix.
+    pub fn as_matrix(&self) -> Matrix<'_, T> {
+        self.data.as_matrix()
+    }
+
+    /// Check there are sample weights for each row, and metadata for each
+    /// column of the data.
+    pub fn validate(&self) -> Result<(), ForustError> {
+        if self.sample_weight.len() != self.data.rows {
+            return Err(ForustError::LengthMismatch(
+                "sample_weight".to_string(),
+                self.sample_weight.len(),
+                self.data.rows,
+            ));
+        }
+        if self.features.len() != self.data.cols {
+            return Err(ForustError::ShapeMismatch {
+                name: "features".to_string(),
+                expected: format!("metadata for each of the {} columns", self.data.cols),
+                got: format!("metadata for {} columns", self.features.len()),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(feature = "csv", feature = "parquet"))]
+impl TryFrom<LoadedData> for Dataset<f64> {
+    type Error = ForustError;
+
+    /// Create a dataset from data read with `read_csv`, or `read_parquet`,
+    /// which must have a target.
+    fn try_from(loaded: LoadedData) -> Result<Self, Self::Error> {
+        let y = loaded
+            .y
+            .ok_or_else(|| ForustError::MissingTarget("a dataset".to_string()))?;
+        let mut dataset = Dataset::new(loaded.data, y)?;
+        if let Some(sample_weight) = loaded.sample_weight {
+            dataset = dataset.set_sample_weight(sample_weight);
+        }
+        Ok(dataset.set_feature_names(loaded.feature_names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset() {
+        let data = MatrixData::from_columns(vec![vec![1., 2., 3.], vec![0., 1., 0.]]).unwrap();
+        let dataset = Dataset::new(data.clone(), vec![0., 1., 1.])
+            .unwrap()
+            .set_feature_names(vec!["age".to_string(), "sex".to_string()])
+            .set_categorical(vec![false, true])
+            .set_monotone_constraints(&ConstraintMap::from([(0, Constraint::Positive)]));
+        assert!(dataset.validate().is_ok());
+        assert_eq!(dataset.sample_weight, vec![1., 1., 1.]);
+        assert_eq!(
+            dataset.feature_names(),
+            Some(vec!["age".to_string(), "sex".to_string()])
+        );
+        assert_eq!(dataset.categorical_features(), vec![1]);
+        assert_eq!(dataset.features[1].feature_type, FeatureType::Categorical);
+        assert_eq!(
+            dataset.monotone_constraints(),
+            ConstraintMap::from([(0, Constraint::Positive)])
+        );
+        assert_eq!(dataset.as_matrix().get_col(1), &[0., 1., 0.]);
+
+        assert!(Dataset::new(data.clone(), vec![0., 1.]).is_err());
+        let dataset = dataset.set_sample_weight(vec![1., 2.]);
+        assert!(dataset.validate().is_err());
+        let dataset = Dataset::new(data, vec![0., 1., 1.])
+            .unwrap()
+            .set_categorical(vec![true]);
+        assert!(dataset.validate().is_err());
+        assert_eq!(dataset.feature_names(), None);
+    }
+}
@@ -1,18 +1,77 @@
 use thiserror::Error;
 
+/// The errors of forust. New variants may be added, so matches on the error
+/// should include a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum ForustError {
     #[error("Feature number {0} has no variance, when missing values are excluded.")]
     NoVariance(usize),
+    #[error("Feature number {0} only has missing values.")]
+    AllMissing(usize),
+    #[error("The data has {rows} rows and {cols} columns, but at least one of each is needed.")]
+    EmptyData { rows: usize, cols: usize },
     #[error("Unable to write model to file: {0}")]
     UnableToWrite(String),
     #[error("Unable to read model from a file {0}")]
     UnableToRead(String),
     #[error("The value {0} is set to missing, but a NaN value was found in the data.")]
     NANVAlueFound(f64),
+    /// First value is the name of the input, second is its length, third is the number of rows.
+    #[error("The length of {0} is {1}, but the data has {2} rows.")]
+    LengthMismatch(String, usize, usize),
+    /// First value is the row, second is the target value, third is what was expected.
+    #[error("Invalid target value {1} found at row {0}, expected {2}.")]
+    InvalidTarget(usize, f64, String),
+    #[error("Invalid sample weight {1} found at row {0}, expected a finite value of at least 0.")]
+    InvalidWeight(usize, f64),
     #[error("Invalid value {0} passed for {1}, expected one of {2}.")]
     ParseString(String, String, String),
-    /// First value is the name of the parameter, second is expected, third is what was passed.
-    #[error("Invalid parameter value passed for {0}, expected {1} but {2} provided.")]
-    InvalidParameter(String, String, String),
+    #[error(
+        "Invalid parameter value passed for {name}, expected {constraint} but {value} provided."
+    )]
+    InvalidParameter {
+        /// The name of the parameter.
+        name: String,
+        /// The value that was passed.
+        value: String,
+        /// The values the parameter accepts.
+        constraint: String,
+    },
+    #[error("The shape of {name} is invalid, expected {expected} but got {got}.")]
+    ShapeMismatch {
+        /// The name of the input.
+        name: String,
+        /// The expected shape, such as the number of columns.
+        expected: String,
+        /// The shape of the input.
+        got: String,
+    },
+    #[error("A target is needed for {0}, but none was provided.")]
+    MissingTarget(String),
+    #[error("The {objective} objective is not supported, {reason}.")]
+    UnsupportedObjective {
+        /// The name of the objective.
+        objective: String,
+        /// What the objective is unsupported for.
+        reason: String,
+    },
+    #[error("The {objective} objective gave a gradient, or hessian, that isn't finite at row {row}, where the prediction is {yhat}, in iteration {iteration}.")]
+    NonFiniteGradient {
+        /// The name of the objective.
+        objective: String,
+        /// The boosting round the gradient was calculated for.
+        iteration: usize,
+        /// The first row with a gradient, or hessian, that isn't finite.
+        row: usize,
+        /// The prediction of the row.
+        yhat: f64,
+    },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Unable to serialize or deserialize the model: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[cfg(feature = "linfa")]
+    #[error(transparent)]
+    Linfa(#[from] linfa::Error),
 }
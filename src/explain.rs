@@ -0,0 +1,663 @@
+use crate::constraints::{Constraint, ConstraintMap};
+use crate::data::{FixedColumns, Matrix};
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::objective::ObjectiveType;
+use crate::params::BoosterParams;
+use crate::tree::Tree;
+use std::fmt;
+
+/// Distill a booster into a single tree, fit to the predictions of the booster over
+/// a reference dataset. The returned booster contains just this tree, and has the
+/// objective of the original booster, so it predicts on the same scale, such as the
+/// log odds for "LogLoss", and can be exported or inspected like any other booster.
+///
+/// * `booster` - The booster to distill.
+/// * `data` - Reference data, the tree is fit to the predictions of the booster on this data.
+/// * `sample_weight` - Instance weights of the reference data, if None a weight of 1 is used for every record.
+/// * `max_depth` - Maximum depth of the distilled tree.
+pub fn distill(
+    booster: &GradientBooster,
+    data: &Matrix<f64>,
+    sample_weight: Option<&[f64]>,
+    max_depth: usize,
+) -> Result<GradientBooster, ForustError> {
+    let y = booster.predict(data, booster.parallel);
+    // With a squared loss, no regularization, and a learning rate of 1, a single
+    // boosting round fits a regression tree to the predictions.
+    let mut surrogate = GradientBooster::from_params(BoosterParams {
+        objective_type: ObjectiveType::SquaredLoss,
+        iterations: 1,
+        learning_rate: 1.,
+        max_depth,
+        max_leaves: usize::MAX,
+        l2: 0.,
+        gamma: 0.,
+        min_leaf_weight: 1.,
+        initialize_base_score: true,
+        nbins: booster.nbins,
+        parallel: booster.parallel,
+        missing: booster.missing,
+        create_missing_branch: false,
+        n_threads: booster.n_threads,
+        ..Default::default()
+    })?;
+    match sample_weight {
+        Some(w) => surrogate.fit(data, &y, w, None)?,
+        None => surrogate.fit_unweighted(data, &y, None)?,
+    }
+    surrogate.objective_type = booster.objective_type;
+    surrogate.feature_names = booster.feature_names.clone();
+    Ok(surrogate)
+}
+
+/// The values of a feature, of the records a rule applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureCondition {
+    /// The index of the feature.
+    pub feature: usize,
+    /// The values are at least this value, which is negative infinity if unbounded.
+    pub lower: f64,
+    /// The values are less than this value, which is infinity if unbounded.
+    pub upper: f64,
+    /// Whether the rule also applies to records, where the feature is missing.
+    pub missing: bool,
+}
+
+/// A rule of a surrogate, the records matching all of its conditions are predicted
+/// the same value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurrogateRule {
+    /// The conditions of the rule, at most one for each feature.
+    pub conditions: Vec<FeatureCondition>,
+    /// The value predicted for records matching the rule.
+    pub prediction: f64,
+    /// Share of the weight of the reference data, that matches the rule.
+    pub support: f64,
+}
+
+/// A compact list of rules, approximating a booster. Every record matches exactly one
+/// of the rules.
+#[derive(Debug, Clone)]
+pub struct SurrogateRules {
+    /// The rules, in descending order of their support.
+    pub rules: Vec<SurrogateRule>,
+    /// The weighted R squared of the predictions of the rules, against the predictions
+    /// of the booster on the reference data, 1 if they predict the same.
+    pub fidelity: f64,
+    /// Names of the features, used when displaying the rules.
+    pub feature_names: Option<Vec<String>>,
+}
+
+/// Extract a list of rules approximating a booster, from a single tree distilled from
+/// it, see `distill`. Each leaf of the tree is a rule, with the range of values of each
+/// feature split on along its path. The fidelity of the rules is how much of the variance
+/// of the booster's predictions on the reference data they explain, on the scale the
+/// booster predicts on, such as the log odds for "LogLoss".
+///
+/// * `booster` - The booster to approximate.
+/// * `data` - Reference data, the rules are fit to the predictions of the booster on this data.
+/// * `sample_weight` - Instance weights of the reference data, if None a weight of 1 is used for every record.
+/// * `max_depth` - Maximum number of conditions of a rule, there are at most 2^max_depth rules.
+pub fn surrogate_rules(
+    booster: &GradientBooster,
+    data: &Matrix<f64>,
+    sample_weight: Option<&[f64]>,
+    max_depth: usize,
+) -> Result<SurrogateRules, ForustError> {
+    let surrogate = distill(booster, data, sample_weight, max_depth)?;
+    let tree = &surrogate.trees[0];
+    let weight = |i: usize| sample_weight.map_or(1., |w| w[i]);
+    let total_weight: f64 = (0..data.rows).map(weight).sum();
+
+    // The weight of the reference data reaching each leaf.
+    let mut leaf_weight = vec![0.; tree.nodes.len()];
+    for i in 0..data.rows {
+        leaf_weight[tree.predict_leaf(data, i, &surrogate.missing)] += weight(i);
+    }
+    let mut rules = Vec::new();
+    collect_rules(tree, 0, Vec::new(), &mut |node, conditions| {
+        rules.push(SurrogateRule {
+            conditions,
+            prediction: surrogate.base_score + f64::from(tree.nodes[node].weight_value),
+            support: leaf_weight[node] / total_weight,
+        })
+    });
+    rules.sort_by(|a, b| b.support.total_cmp(&a.support));
+
+    let preds = booster.predict(data, booster.parallel);
+    let surrogate_preds = surrogate.predict(data, booster.parallel);
+    let mean = (0..data.rows).map(|i| preds[i] * weight(i)).sum::<f64>() / total_weight;
+    let (residual, total) = (0..data.rows).fold((0., 0.), |(r, t), i| {
+        (
+            r + weight(i) * (preds[i] - surrogate_preds[i]).powi(2),
+            t + weight(i) * (preds[i] - mean).powi(2),
+        )
+    });
+    let fidelity = if total > 0. {
+        1. - residual / total
+    } else {
+        1.
+    };
+    Ok(SurrogateRules {
+        rules,
+        fidelity,
+        feature_names: booster.feature_names.clone(),
+    })
+}
+
+/// Call `rule` with each leaf under a node, and the conditions of the path to it.
+/// The surrogate is grown without missing branches, so missing values follow the left
+/// or right child of each split.
+fn collect_rules<F: FnMut(usize, Vec<FeatureCondition>)>(
+    tree: &Tree,
+    node_idx: usize,
+    conditions: Vec<FeatureCondition>,
+    rule: &mut F,
+) {
+    let n = &tree.nodes[node_idx];
+    if n.is_leaf {
+        rule(node_idx, conditions);
+        return;
+    }
+    for child in [n.left_child, n.right_child] {
+        let mut conditions = conditions.clone();
+        let i = match conditions.iter().position(|c| c.feature == n.split_feature) {
+            Some(i) => i,
+            None => {
+                conditions.push(FeatureCondition {
+                    feature: n.split_feature,
+                    lower: f64::NEG_INFINITY,
+                    upper: f64::INFINITY,
+                    missing: true,
+                });
+                conditions.len() - 1
+            }
+        };
+        let c = &mut conditions[i];
+        if child == n.left_child {
+            c.upper = c.upper.min(n.split_value);
+        } else {
+            c.lower = c.lower.max(n.split_value);
+        }
+        c.missing &= child == n.missing_node;
+        collect_rules(tree, child, conditions, rule);
+    }
+}
+
+impl SurrogateRules {
+    fn feature_name(&self, feature: usize) -> String {
+        match &self.feature_names {
+            Some(names) => names[feature].clone(),
+            None => feature.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SurrogateRules {
+    /// Write the rules one per line, such as
+    /// `if 2 <= pclass and (age < 30.5 or age is missing) then -1.25 (support 0.184)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "fidelity={}", self.fidelity)?;
+        for rule in self.rules.iter() {
+            let conditions: Vec<String> = rule
+                .conditions
+                .iter()
+                .map(|c| {
+                    let name = self.feature_name(c.feature);
+                    let range = match (c.lower.is_finite(), c.upper.is_finite()) {
+                        (true, true) => format!("{} <= {} < {}", c.lower, name, c.upper),
+                        (true, false) => format!("{} <= {}", c.lower, name),
+                        _ => format!("{} < {}", name, c.upper),
+                    };
+                    if c.missing {
+                        format!("({} or {} is missing)", range, name)
+                    } else {
+                        range
+                    }
+                })
+                .collect();
+            let condition = if conditions.is_empty() {
+                "true".to_string()
+            } else {
+                conditions.join(" and ")
+            };
+            writeln!(
+                f,
+                "if {} then {} (support {})",
+                condition, rule.prediction, rule.support
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The branch a record follows at a split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The value is less than the threshold.
+    Less,
+    /// The value is greater than, or equal to the threshold.
+    GreaterOrEqual,
+    /// The value is missing, and the record follows the missing branch.
+    Missing,
+}
+
+/// A split a record passes on its way to a leaf.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision {
+    /// The index of the feature split on.
+    pub feature: usize,
+    /// The split value of the feature.
+    pub threshold: f64,
+    /// The value of the feature of the record, after any imputation.
+    pub value: f64,
+    /// The branch the record follows.
+    pub direction: Direction,
+}
+
+/// The splits a record passes in a tree, from the root to the leaf it falls in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionPath {
+    /// The decisions, starting at the root of the tree.
+    pub decisions: Vec<Decision>,
+    /// The weight of the leaf, which is added to the prediction.
+    pub leaf_value: f64,
+}
+
+/// A point where the predictions of a booster move against a monotone constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonotonicityViolation {
+    /// The index of the feature.
+    pub feature: usize,
+    /// The constraint that is violated.
+    pub constraint: Constraint,
+    /// The predictions move against the constraint, as the feature is increased from
+    /// below this value, to this value.
+    pub threshold: f64,
+    /// Share of the weight of the reference data, whose prediction moves against the constraint.
+    pub support: f64,
+    /// The largest change of a prediction against the constraint.
+    pub max_violation: f64,
+}
+
+/// Check that the predictions of a booster follow monotone constraints, over a reference
+/// dataset. For each constrained feature, each record is predicted with the feature set to
+/// every value the trees split it on, and so every value the prediction can change at.
+/// The check is exact for the reference records, and catches constraints that were
+/// set on the wrong feature, or not set at all, such as for models trained elsewhere.
+/// An empty list is returned if there are no violations.
+///
+/// * `booster` - The booster to check.
+/// * `data` - Reference data, the predictions of its records are checked.
+/// * `sample_weight` - Instance weights of the reference data, if None a weight of 1 is used for every record.
+/// * `constraints` - The constraints to check, if None the monotone constraints the booster was trained with are checked.
+pub fn monotonicity_violations(
+    booster: &GradientBooster,
+    data: &Matrix<f64>,
+    sample_weight: Option<&[f64]>,
+    constraints: Option<&ConstraintMap>,
+) -> Result<Vec<MonotonicityViolation>, ForustError> {
+    let constraints = match constraints.or(booster.monotone_constraints.as_ref()) {
+        Some(c) => c,
+        None => return Ok(Vec::new()),
+    };
+    if let Some(w) = sample_weight {
+        if w.len() != data.rows {
+            return Err(ForustError::LengthMismatch(
+                "sample_weight".to_string(),
+                w.len(),
+                data.rows,
+            ));
+        }
+    }
+    let weight = |i: usize| sample_weight.map_or(1., |w| w[i]);
+    let total_weight: f64 = (0..data.rows).map(weight).sum();
+
+    let mut features: Vec<(usize, Constraint)> = constraints
+        .iter()
+        .filter(|(_, c)| !matches!(c, Constraint::Unconstrained))
+        .map(|(f, c)| (*f, *c))
+        .collect();
+    features.sort_by_key(|(f, _)| *f);
+    let mut violations = Vec::new();
+    for (feature, constraint) in features {
+        if feature >= data.cols {
+            return Err(ForustError::InvalidParameter {
+                name: "constraints".to_string(),
+                constraint: format!("column indexes less than {}", data.cols),
+                value: feature.to_string(),
+            });
+        }
+        let mut splits: Vec<f64> = booster
+            .get_prediction_trees()
+            .iter()
+            .flat_map(|t| t.nodes.iter())
+            .filter(|n| !n.is_leaf && n.split_feature == feature)
+            .map(|n| n.split_value)
+            .collect();
+        splits.sort_by(|a, b| a.total_cmp(b));
+        splits.dedup();
+        // Values less than every split value are all predicted the same.
+        let predict_at = |v: f64| {
+            let values = [(feature, v)];
+            booster.predict(&FixedColumns::new(data, &values), booster.parallel)
+        };
+        let mut last = predict_at(f64::NEG_INFINITY);
+        for threshold in splits {
+            let preds = predict_at(threshold);
+            let (mut support, mut max_violation) = (0., 0.);
+            for (i, (a, b)) in last.iter().zip(preds.iter()).enumerate() {
+                let change = match constraint {
+                    Constraint::Positive => a - b,
+                    _ => b - a,
+                };
+                if change > 0. {
+                    support += weight(i);
+                    max_violation = f64::max(max_violation, change);
+                }
+            }
+            if max_violation > 0. {
+                violations.push(MonotonicityViolation {
+                    feature,
+                    constraint,
+                    threshold,
+                    support: support / total_weight,
+                    max_violation,
+                });
+            }
+            last = preds;
+        }
+    }
+    Ok(violations)
+}
+
+/// The strength of the interaction between two features.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InteractionStrength {
+    /// The index of the first feature.
+    pub feature_a: usize,
+    /// The index of the second feature.
+    pub feature_b: usize,
+    /// Friedman's H squared, the share of the variance of the joint partial dependence
+    /// of the features, not explained by the sum of their individual partial dependence.
+    /// This is 0 if the features don't interact.
+    pub h_squared: f64,
+}
+
+/// Estimate the strength of the interaction between each pair of features, with Friedman's
+/// H statistic. The partial dependence of each feature, and each pair of features, is
+/// calculated at the values of each record of the reference data, by averaging the
+/// predictions over the reference data, so the cost grows with the square of the number
+/// of records, and a sample of a few hundred records is usually enough.
+///
+/// * `booster` - The booster to explain.
+/// * `data` - Reference data, the partial dependence is calculated at, and averaged over its records.
+/// * `sample_weight` - Instance weights of the reference data, if None a weight of 1 is used for every record.
+/// * `features` - The indexes of the features, each pair of them is returned, in descending order of strength.
+pub fn interaction_strength(
+    booster: &GradientBooster,
+    data: &Matrix<f64>,
+    sample_weight: Option<&[f64]>,
+    features: &[usize],
+) -> Result<Vec<InteractionStrength>, ForustError> {
+    if let Some(w) = sample_weight {
+        if w.len() != data.rows {
+            return Err(ForustError::LengthMismatch(
+                "sample_weight".to_string(),
+                w.len(),
+                data.rows,
+            ));
+        }
+    }
+    for (i, f) in features.iter().enumerate() {
+        if *f >= data.cols || features[..i].contains(f) {
+            return Err(ForustError::InvalidParameter {
+                name: "features".to_string(),
+                constraint: format!("unique column indexes less than {}", data.cols),
+                value: format!("{:?}", features),
+            });
+        }
+    }
+    let weight = |i: usize| sample_weight.map_or(1., |w| w[i]);
+    let total_weight: f64 = (0..data.rows).map(weight).sum();
+    if total_weight.is_nan() || total_weight <= 0. {
+        return Err(ForustError::InvalidParameter {
+            name: "sample_weight".to_string(),
+            constraint: "weights with a positive sum".to_string(),
+            value: format!("weights summing to {}", total_weight),
+        });
+    }
+    // The weighted mean prediction of the reference data, with some features fixed.
+    let mean_prediction = |values: &[(usize, f64)]| {
+        let preds = booster.predict(&FixedColumns::new(data, values), booster.parallel);
+        preds
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p * weight(i))
+            .sum::<f64>()
+            / total_weight
+    };
+    // The partial dependence at the values of each record, centered to a mean of 0.
+    let centered_partial_dependence = |features: &[usize]| {
+        let pd: Vec<f64> = (0..data.rows)
+            .map(|i| {
+                let values: Vec<(usize, f64)> =
+                    features.iter().map(|f| (*f, *data.get(i, *f))).collect();
+                mean_prediction(&values)
+            })
+            .collect();
+        let mean = pd
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p * weight(i))
+            .sum::<f64>()
+            / total_weight;
+        pd.into_iter().map(|p| p - mean).collect::<Vec<f64>>()
+    };
+
+    let individual: Vec<Vec<f64>> = features
+        .iter()
+        .map(|f| centered_partial_dependence(&[*f]))
+        .collect();
+    let mut strengths = Vec::new();
+    for a in 0..features.len() {
+        for b in (a + 1)..features.len() {
+            let joint = centered_partial_dependence(&[features[a], features[b]]);
+            let (interaction, total) = (0..data.rows).fold((0., 0.), |(n, d), i| {
+                (
+                    n + weight(i) * (joint[i] - individual[a][i] - individual[b][i]).powi(2),
+                    d + weight(i) * joint[i].powi(2),
+                )
+            });
+            // A joint partial dependence that is constant, other than rounding errors,
+            // has no interaction.
+            let h_squared = if total > total_weight * 1e-20 {
+                interaction / total
+            } else {
+                0.
+            };
+            strengths.push(InteractionStrength {
+                feature_a: features[a],
+                feature_b: features[b],
+                h_squared,
+            });
+        }
+    }
+    strengths.sort_by(|a, b| b.h_squared.total_cmp(&a.h_squared));
+    Ok(strengths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_distill() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default().set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, true);
+        let mean = preds.iter().sum::<f64>() / preds.len() as f64;
+        let total: f64 = preds.iter().map(|p| (p - mean).powi(2)).sum();
+
+        let mut last_error = total;
+        for max_depth in [1, 3, 5] {
+            let tree = distill(&booster, &data, None, max_depth).unwrap();
+            assert_eq!(tree.trees.len(), 1);
+            assert!(tree.trees[0].nodes.iter().all(|n| n.depth <= max_depth));
+            assert!(matches!(tree.objective_type, ObjectiveType::LogLoss));
+            let error: f64 = tree
+                .predict(&data, true)
+                .iter()
+                .zip(preds.iter())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum();
+            // Deeper trees are closer to the booster.
+            assert!(error < last_error);
+            last_error = error;
+        }
+        // A depth 5 surrogate explains most of the variance of a depth 3 booster.
+        assert!(last_error / total < 0.2);
+    }
+
+    #[test]
+    fn test_surrogate_rules() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default().set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+
+        let mut last_fidelity = f64::NEG_INFINITY;
+        for max_depth in [1, 3, 5] {
+            let rules = surrogate_rules(&booster, &data, None, max_depth).unwrap();
+            assert!(rules.rules.len() <= 2usize.pow(max_depth as u32));
+            assert!((rules.rules.iter().map(|r| r.support).sum::<f64>() - 1.).abs() < 1e-9);
+            assert!(rules.fidelity > last_fidelity && rules.fidelity <= 1.);
+            last_fidelity = rules.fidelity;
+
+            // Every record matches exactly one rule, which predicts the same as the tree.
+            let tree = distill(&booster, &data, None, max_depth).unwrap();
+            let preds = tree.predict(&data, false);
+            for (i, pred) in preds.iter().enumerate() {
+                let matched: Vec<&SurrogateRule> = rules
+                    .rules
+                    .iter()
+                    .filter(|r| {
+                        r.conditions.iter().all(|c| {
+                            let v = *data.get(i, c.feature);
+                            if v.is_nan() {
+                                c.missing
+                            } else {
+                                c.lower <= v && v < c.upper
+                            }
+                        })
+                    })
+                    .collect();
+                assert_eq!(matched.len(), 1);
+                assert!((matched[0].prediction - pred).abs() < 1e-6);
+            }
+        }
+        let text = surrogate_rules(&booster, &data, None, 2)
+            .unwrap()
+            .to_string();
+        assert!(text.starts_with("fidelity="));
+        assert!(text.lines().skip(1).all(|l| l.starts_with("if ")));
+    }
+
+    #[test]
+    fn test_monotonicity_violations() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        // Survival falls with the passenger class, so a positive constraint is violated
+        // by an unconstrained booster.
+        let constraints = ConstraintMap::from([(0, Constraint::Positive)]);
+        let mut booster = GradientBooster::default().set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert!(monotonicity_violations(&booster, &data, None, None)
+            .unwrap()
+            .is_empty());
+        let violations =
+            monotonicity_violations(&booster, &data, None, Some(&constraints)).unwrap();
+        assert!(!violations.is_empty());
+        for v in violations.iter() {
+            assert_eq!(v.feature, 0);
+            assert!(v.support > 0. && v.support <= 1.);
+            assert!(v.max_violation > 0.);
+        }
+
+        // A booster trained with the constraint follows it.
+        let mut booster = GradientBooster::default()
+            .set_max_depth(3)
+            .set_monotone_constraints(Some(constraints.clone()));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert!(monotonicity_violations(&booster, &data, None, None)
+            .unwrap()
+            .is_empty());
+
+        let constraints = ConstraintMap::from([(5, Constraint::Negative)]);
+        assert!(monotonicity_violations(&booster, &data, None, Some(&constraints)).is_err());
+    }
+
+    #[test]
+    fn test_interaction_strength() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        // The first 100 records, are the reference data.
+        let reference = Matrix::new(&data_vec, 100, 5);
+        let features = [0, 1, 4];
+
+        // Trees with a single split, add up the effect of each feature.
+        let mut booster = GradientBooster::default().set_max_depth(1);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let strengths = interaction_strength(&booster, &reference, None, &features).unwrap();
+        assert_eq!(strengths.len(), 3);
+        assert!(strengths.iter().all(|s| s.h_squared < 1e-12));
+
+        let mut booster = GradientBooster::default().set_max_depth(5);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let strengths = interaction_strength(&booster, &reference, None, &features).unwrap();
+        assert!(strengths[0].h_squared > 0.01);
+        assert!(strengths
+            .windows(2)
+            .all(|w| w[0].h_squared >= w[1].h_squared));
+        assert!(interaction_strength(&booster, &reference, None, &[0, 0]).is_err());
+    }
+}
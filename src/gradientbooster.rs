@@ -1,28 +1,119 @@
-use crate::binning::bin_matrix;
-use crate::constraints::ConstraintMap;
-use crate::data::{Matrix, RowMajorMatrix};
+#[cfg(feature = "mmap")]
+use crate::binning::MmapBinnedData;
+use crate::binning::{
+    bin_chunks, bin_matrix, bin_matrix_with_cuts, bin_sparse_matrix, BinValues, BinnedData,
+    BinnedDataRef, DataChunk, TrainingBins,
+};
+use crate::calibration::Calibration;
+use crate::constraints::{
+    Constraint, ConstraintMap, InteractionConstraints, MonotoneConstraintMethod,
+};
+use crate::data::{
+    CscMatrix, DataMatrix, FixedColumns, FloatData, IntegerData, JaggedMatrix, Matrix, MatrixView,
+    RowMajorMatrix, RowMajorView, RowView,
+};
+use crate::dataset::Dataset;
 use crate::errors::ForustError;
+use crate::explain::DecisionPath;
+use crate::importance::{permutation_importance, PermutationImportance};
+use crate::memory::{estimate_training_memory, MemoryEstimate};
 use crate::metric::{is_comparison_better, metric_callables, Metric, MetricFn};
 use crate::objective::{
-    calc_init_callables, gradient_hessian_callables, LogLoss, ObjectiveFunction, ObjectiveType,
-    SquaredLoss,
+    calc_init_callables, gradient_hessian_callables, parallel_grad_hess, LogLoss,
+    ObjectiveFunction, ObjectiveType, QuantileLoss, SquaredLoss,
 };
-use crate::sampler::{GossSampler, RandomSampler, SampleMethod, Sampler};
+use crate::params::BoosterParams;
+use crate::partial_dependence::{PartialDependenceGrid, PartialDependenceResult};
+use crate::preprocessing::Imputer;
+use crate::profiler::{Phase, ProfileReport, Profiler};
+use crate::sampler::{GossSampler, RandomSampler, Sample, SampleMethod, Sampler};
 use crate::splitter::{MissingBranchSplitter, MissingImputerSplitter, Splitter};
 use crate::tree::Tree;
-use crate::utils::{items_to_strings, validate_positive_float_field};
+use crate::utils::{
+    is_missing, items_to_strings, percentiles, validate_float_parameter,
+    validate_positive_float_field,
+};
 use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use rayon::prelude::*;
-use serde::{Deserialize, Deserializer, Serialize};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use std::fs;
+use std::mem::size_of;
 use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
-pub type EvaluationData<'a> = (Matrix<'a, f64>, &'a [f64], &'a [f64]);
-pub type TrainingEvaluationData<'a> = (&'a Matrix<'a, f64>, &'a [f64], &'a [f64], Vec<f64>);
+/// Version of the json model schema written by `json_dump`.
+pub const MODEL_FORMAT_VERSION: u64 = 1;
+/// Leading bytes of a booster saved in the binary format.
+pub const BINARY_MAGIC: &[u8; 4] = b"FRST";
+/// Version of the binary format written by `binary_dump`. The binary format
+/// is not self describing, so this changes whenever fields are added to the booster.
+pub const BINARY_FORMAT_VERSION: u8 = 11;
+/// Fields of the json model written by the upstream forust package, which is the
+/// schema of the booster before any fields were added here.
+const UPSTREAM_FIELDS: &[&str] = &[
+    "objective_type",
+    "iterations",
+    "learning_rate",
+    "max_depth",
+    "max_leaves",
+    "l2",
+    "gamma",
+    "min_leaf_weight",
+    "base_score",
+    "nbins",
+    "parallel",
+    "allow_missing_splits",
+    "monotone_constraints",
+    "subsample",
+    "top_rate",
+    "other_rate",
+    "seed",
+    "missing",
+    "create_missing_branch",
+    "sample_method",
+    "grow_policy",
+    "evaluation_metric",
+    "early_stopping_rounds",
+    "initialize_base_score",
+    "evaluation_history",
+    "best_iteration",
+    "prediction_iteration",
+    "trees",
+    "metadata",
+];
+/// Metadata key the upstream forust Python package stores the feature names under,
+/// as a Python list literal.
+const UPSTREAM_FEATURE_NAMES_KEY: &str = "feature_names_in_";
+/// The size in bytes of the values of a block of rows, that are predicted by all of the
+/// trees, before the next block, chosen so a block stays in the L2 cache of most CPUs.
+const PREDICTION_BLOCK_BYTES: usize = 256 * 1024;
 
-#[derive(Serialize, Deserialize)]
+pub type EvaluationData<'a, T = f64> = (Matrix<'a, T>, &'a [T], &'a [T]);
+pub type TrainingEvaluationData<'a, T = f64> = (&'a Matrix<'a, T>, &'a [f64], &'a [f64], Vec<f64>);
+/// Evaluation data, with the target and weights converted to f64.
+type EvaluationSet<'a, D> = (&'a D, &'a [f64], &'a [f64]);
+/// Evaluation data, and the predictions of the booster on it.
+type TrainingEvaluationSet<'a, D> = (&'a D, &'a [f64], &'a [f64], Vec<f64>);
+/// Sparse evaluation data, used with `fit_sparse`.
+pub type SparseEvaluationData<'a> = (CscMatrix<'a, f64>, &'a [f64], &'a [f64]);
+/// Evaluation data viewing some columns of a matrix, used with `fit_view`.
+pub type ViewEvaluationData<'a, T> = (MatrixView<'a, T>, &'a [f64], &'a [f64]);
+/// Evaluation data viewing some rows of the data, used with `fit_rows`.
+pub type RowEvaluationData<'a, D> = (RowView<'a, D>, &'a [f64], &'a [f64]);
+/// Row major evaluation data, used with `fit_row_major`.
+pub type RowMajorEvaluationData<'a, T> = (RowMajorView<'a, T>, &'a [f64], &'a [f64]);
+/// Integer evaluation data, used with `fit_integer`.
+pub type IntegerEvaluationData<'a, T> = (Matrix<'a, T>, &'a [f64], &'a [f64]);
+/// A function adjusting the sample weights of a boosting round, see `set_sample_weight_hook`.
+pub type SampleWeightHook = Arc<dyn Fn(usize, &mut [f64]) + Send + Sync>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub enum GrowPolicy {
     DepthWise,
     LossGuide,
@@ -69,11 +160,92 @@ impl FromStr for GrowPolicy {
     }
 }
 
+/// Weights applied to the records of each class, when training a classification objective.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum ClassWeight {
+    /// Weight each class inversely to its share of the sample weight, so every
+    /// class contributes equally to the loss.
+    Balanced,
+    /// The weight of each class, indexed by the class label.
+    Weights(Vec<f64>),
+}
+
+impl ClassWeight {
+    /// Multiply the sample weight of each record, by the weight of its class.
+    ///
+    /// * `y` - The class labels, integers starting at 0.
+    /// * `sample_weight` - Instance weights for each record.
+    pub fn sample_weight(&self, y: &[f64], sample_weight: &[f64]) -> Result<Vec<f64>, ForustError> {
+        let n_classes = y.iter().fold(0., |n: f64, v| n.max(*v + 1.));
+        let invalid_label = y
+            .iter()
+            .find(|v| !(v.fract() == 0. && **v >= 0. && **v < n_classes));
+        if let Some(v) = invalid_label {
+            return Err(ForustError::InvalidParameter {
+                name: "y".to_string(),
+                constraint: "integer class labels starting at 0".to_string(),
+                value: v.to_string(),
+            });
+        }
+        let weights = match self {
+            ClassWeight::Balanced => {
+                let mut totals = vec![0.; n_classes as usize];
+                y.iter()
+                    .zip(sample_weight)
+                    .for_each(|(v, w)| totals[*v as usize] += w);
+                let total: f64 = totals.iter().sum();
+                totals
+                    .iter()
+                    .map(|t| {
+                        if *t > 0. {
+                            total / (totals.len() as f64 * t)
+                        } else {
+                            0.
+                        }
+                    })
+                    .collect()
+            }
+            ClassWeight::Weights(weights) => {
+                if weights.len() < n_classes as usize {
+                    return Err(ForustError::InvalidParameter {
+                        name: "class_weight".to_string(),
+                        constraint: format!("a weight for each of the {} classes", n_classes),
+                        value: format!("{} weights", weights.len()),
+                    });
+                }
+                weights.to_vec()
+            }
+        };
+        Ok(y.iter()
+            .zip(sample_weight)
+            .map(|(v, w)| w * weights[*v as usize])
+            .collect())
+    }
+}
+
+/// When to save checkpoints of the booster during training. A checkpoint is saved once
+/// either of the intervals has passed since the last checkpoint, at the end of a boosting round.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Checkpoint {
+    /// Path the checkpoint is saved to, as a json booster, replacing any previous checkpoint.
+    pub path: String,
+    /// Save a checkpoint every this many boosting rounds.
+    pub iterations: Option<usize>,
+    /// Save a checkpoint after this many seconds of training.
+    pub seconds: Option<f64>,
+    /// The number of boosting rounds the booster will have at the end of the fit, set
+    /// when fitting, so `resume_from_checkpoint` can find the rounds that remain, even
+    /// if the fit started from a warm start. Leave as None.
+    #[serde(default)]
+    pub target_rounds: Option<usize>,
+}
+
 /// Gradient Booster object
 ///
 /// * `objective_type` - The name of objective function used to optimize.
 ///   Valid options include "LogLoss" to use logistic loss as the objective function,
-///   or "SquaredLoss" to use Squared Error as the objective function.
+///   "SquaredLoss" to use Squared Error as the objective function, or "QuantileLoss"
+///   to predict the `quantile` of the target.
 /// * `iterations` - Total number of trees to train in the ensemble.
 /// * `learning_rate` - Step size to use at each iteration. Each
 ///   leaf weight is multiplied by this number. The smaller the value, the more
@@ -93,15 +265,25 @@ impl FromStr for GrowPolicy {
 ///   a smaller number, will result in faster training time, while potentially sacrificing
 ///   accuracy. If there are more bins, than unique values in a column, all unique values
 ///   will be used.
+///   `binning::auto_nbins` can be used to choose the number of bins for a dataset.
 /// * `allow_missing_splits` - Should the algorithm allow splits that completed seperate out missing
 /// and non-missing values, in the case where `create_missing_branch` is false. When `create_missing_branch`
 /// is true, setting this to true will result in the missin branch being further split.
 /// * `monotone_constraints` - Constraints that are used to enforce a specific relationship
 ///   between the training features and the target variable.
+/// * `monotone_constraint_method` - How the monotone constraints are enforced, "Basic"
+///   discards splits on a constrained feature that break the constraint, "Exact"
+///   recalculates the weights of the children of such splits, so they hold the constraint,
+///   and bounds every split below them, so the predictions hold the constraints.
+/// * `interaction_constraints` - Groups of features that are allowed to interact, the
+///   features split on along any branch of a tree must all be in one group. The groups
+///   can be given by feature name, which are resolved against the feature names of the
+///   booster when it's fit, see `InteractionConstraints`.
 /// * `subsample` - Percent of records to randomly sample at each iteration when training a tree.
 /// * `top_rate` - Used only in goss. The retain ratio of large gradient data.
 /// * `other_rate` - Used only in goss. the retain ratio of small gradient data.
 /// * `seed` - Integer value used to seed any randomness used in the algorithm.
+///   Fitting with the same seed, data, and parameters produces an identical model.
 /// * `missing` - Value to consider missing.
 /// * `create_missing_branch` - Should missing be split out it's own separate branch?
 /// * `sample_method` - Specify the method that records should be sampled when training?
@@ -109,6 +291,44 @@ impl FromStr for GrowPolicy {
 /// * `early_stopping_rounds` - Number of rounds where the evaluation metric value must improve in
 ///    to keep training.
 /// * `initialize_base_score` - If this is specified, the base_score will be calculated using the sample_weight and y data in accordance with the requested objective_type.
+/// * `validation_fraction` - Fraction of the training data to set aside as an evaluation
+///   set, when no evaluation data is passed to `fit`. The evaluation set is sampled
+///   randomly using the `seed`, and can be used for early stopping.
+/// * `stratify_validation` - Should the `validation_fraction` be sampled separately for
+///   each unique target value, so the evaluation set has the same class balance.
+/// * `quantile` - The quantile predicted by the "QuantileLoss" objective, in (0, 1).
+/// * `colsample_bytree` - Fraction of the columns to randomly sample for each tree,
+///   the tree can only split on the sampled columns.
+/// * `num_parallel_tree` - Number of trees to grow at each iteration, each with its own
+///   sample of records and columns, against the same gradients. The trees are averaged,
+///   so each iteration adds `num_parallel_tree` trees to the booster.
+/// * `warm_start` - Should fitting continue boosting from the trees already in the
+///   booster, adding `iterations` more trees. If false, fitting replaces any existing trees.
+///   The `learning_rate`, `subsample`, and `colsample_bytree` can be changed before
+///   continuing, and only apply to the trees added. The learning rate each tree was fit
+///   with is recorded, see `get_tree_learning_rates`.
+/// * `class_weight` - Weights for the records of each class, that are multiplied with the
+///   sample weight when training. Only used with the "LogLoss" objective.
+/// * `checkpoint` - Periodically save the booster during training, so training can be
+///   continued with `resume_from_checkpoint` if it is interrupted.
+/// * `n_threads` - Number of threads to use when fitting and predicting. If None,
+///   the global rayon thread pool is used. This is a runtime setting, and is not saved
+///   with the model.
+/// * `profile` - Should the time spent in each phase of training be recorded, the
+///   report is available with `get_profile_report` after fitting. This is a runtime
+///   setting, and is not saved with the model.
+/// * `max_histogram_memory` - The number of bytes the histograms of the nodes waiting to
+///   be split can use, before the histograms of further nodes are spilled to disk, for
+///   deep trees on data with many features. If None, all histograms are kept in memory.
+///   This is a runtime setting, and is not saved with the model.
+/// * `check_gradients` - Should the gradients, and hessians, be checked for NaN, and
+///   infinite values each iteration, returning an error with the row, and prediction
+///   that caused them, rather than fitting the remaining trees to them. This is a runtime
+///   setting, for debugging objectives, and is not saved with the model.
+/// * `sample_weight_hook` - A function called before the gradients of each boosting round
+///   are calculated, that adjusts the sample weights of the round, for example to weight
+///   recent records more as the iterations progress. This is a runtime setting, and is
+///   not saved with the model.
 #[derive(Deserialize, Serialize)]
 pub struct GradientBooster {
     pub objective_type: ObjectiveType,
@@ -130,20 +350,47 @@ pub struct GradientBooster {
     #[serde(default = "default_other_rate")]
     pub other_rate: f64,
     pub seed: u64,
-    #[serde(deserialize_with = "parse_missing")]
+    #[serde(
+        serialize_with = "serialize_missing",
+        deserialize_with = "parse_missing"
+    )]
     pub missing: f64,
     pub create_missing_branch: bool,
     #[serde(default = "default_sample_method")]
     pub sample_method: SampleMethod,
     #[serde(default = "default_grow_policy")]
     pub grow_policy: GrowPolicy,
+    #[serde(default = "default_monotone_constraint_method")]
+    pub monotone_constraint_method: MonotoneConstraintMethod,
+    #[serde(default = "default_interaction_constraints")]
+    pub interaction_constraints: Option<InteractionConstraints>,
     #[serde(default = "default_evaluation_metric")]
     pub evaluation_metric: Option<Metric>,
     #[serde(default = "default_early_stopping_rounds")]
     pub early_stopping_rounds: Option<usize>,
+    #[serde(default = "default_validation_fraction")]
+    pub validation_fraction: Option<f64>,
+    #[serde(default = "default_stratify_validation")]
+    pub stratify_validation: bool,
+    #[serde(default = "default_quantile")]
+    pub quantile: f64,
+    #[serde(default = "default_colsample_bytree")]
+    pub colsample_bytree: f64,
+    #[serde(default = "default_num_parallel_tree")]
+    pub num_parallel_tree: usize,
+    #[serde(default = "default_warm_start")]
+    pub warm_start: bool,
+    #[serde(default = "default_class_weight")]
+    pub class_weight: Option<ClassWeight>,
+    #[serde(default = "default_checkpoint")]
+    pub checkpoint: Option<Checkpoint>,
     #[serde(default = "default_initialize_base_score")]
     pub initialize_base_score: bool,
-    #[serde(default = "default_evaluation_history")]
+    #[serde(
+        default = "default_evaluation_history",
+        serialize_with = "serialize_evaluation_history",
+        deserialize_with = "parse_evaluation_history"
+    )]
     pub evaluation_history: Option<RowMajorMatrix<f64>>,
     #[serde(default = "default_best_iteration")]
     pub best_iteration: Option<usize>,
@@ -151,10 +398,38 @@ pub struct GradientBooster {
     /// defaults to best_iteration if this is defined.
     #[serde(default = "default_prediction_iteration")]
     pub prediction_iteration: Option<usize>,
+    /// Calibration of the predicted probabilities, fit after training.
+    #[serde(default = "default_calibration")]
+    pub calibration: Option<Calibration>,
+    /// The cuts used to bin the training data, reused to bin the data
+    /// passed to `update`.
+    #[serde(default = "default_cuts")]
+    pub cuts: Option<JaggedMatrix<f64>>,
+    /// Names of the features, indexed by the column of the data.
+    #[serde(default = "default_feature_names")]
+    pub feature_names: Option<Vec<String>>,
+    /// Imputer applied to the data the booster is fit, and predicts on.
+    #[serde(default = "default_imputer")]
+    pub imputer: Option<Imputer>,
     // Members internal to the booster object, and not parameters set by the user.
     // Trees is public, just to interact with it directly in the python wrapper.
     pub trees: Vec<Tree>,
     metadata: HashMap<String, String>,
+    #[serde(skip)]
+    pub n_threads: Option<usize>,
+    // Thread pool used when n_threads is set, or a pool provided by the user.
+    #[serde(skip)]
+    thread_pool: OnceLock<Arc<ThreadPool>>,
+    #[serde(skip)]
+    pub profile: bool,
+    #[serde(skip)]
+    profile_report: Option<ProfileReport>,
+    #[serde(skip)]
+    pub max_histogram_memory: Option<usize>,
+    #[serde(skip)]
+    pub check_gradients: bool,
+    #[serde(skip)]
+    sample_weight_hook: Option<SampleWeightHook>,
 }
 
 fn default_initialize_base_score() -> bool {
@@ -165,6 +440,12 @@ fn default_grow_policy() -> GrowPolicy {
     GrowPolicy::DepthWise
 }
 
+fn default_monotone_constraint_method() -> MonotoneConstraintMethod {
+    MonotoneConstraintMethod::Basic
+}
+fn default_interaction_constraints() -> Option<InteractionConstraints> {
+    None
+}
 fn default_top_rate() -> f64 {
     0.1
 }
@@ -180,6 +461,42 @@ fn default_evaluation_metric() -> Option<Metric> {
 fn default_early_stopping_rounds() -> Option<usize> {
     None
 }
+fn default_validation_fraction() -> Option<f64> {
+    None
+}
+fn default_stratify_validation() -> bool {
+    false
+}
+fn default_quantile() -> f64 {
+    0.5
+}
+fn default_colsample_bytree() -> f64 {
+    1.0
+}
+fn default_num_parallel_tree() -> usize {
+    1
+}
+fn default_warm_start() -> bool {
+    false
+}
+fn default_class_weight() -> Option<ClassWeight> {
+    None
+}
+fn default_checkpoint() -> Option<Checkpoint> {
+    None
+}
+fn default_calibration() -> Option<Calibration> {
+    None
+}
+fn default_feature_names() -> Option<Vec<String>> {
+    None
+}
+fn default_imputer() -> Option<Imputer> {
+    None
+}
+fn default_cuts() -> Option<JaggedMatrix<f64>> {
+    None
+}
 fn default_evaluation_history() -> Option<RowMajorMatrix<f64>> {
     None
 }
@@ -190,13 +507,188 @@ fn default_prediction_iteration() -> Option<usize> {
     None
 }
 
-fn parse_missing<'de, D>(d: D) -> Result<f64, D::Error>
+// Missing is written as an optional value, so that formats that are not
+// self describing, such as the binary format, read back the same type.
+pub(crate) fn serialize_missing<S>(missing: &f64, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let missing = if missing.is_nan() {
+        None
+    } else {
+        Some(*missing)
+    };
+    missing.serialize(s)
+}
+
+pub(crate) fn parse_missing<'de, D>(d: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
     Deserialize::deserialize(d).map(|x: Option<_>| x.unwrap_or(f64::NAN))
 }
 
+fn serialize_evaluation_history<S>(
+    history: &Option<RowMajorMatrix<f64>>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    history
+        .as_ref()
+        .map(|m| {
+            RowMajorMatrix::new(
+                m.data
+                    .iter()
+                    .map(|v| if v.is_nan() { None } else { Some(*v) })
+                    .collect::<Vec<Option<f64>>>(),
+                m.rows,
+                m.cols,
+            )
+        })
+        .serialize(s)
+}
+
+// Metric values can be NaN, which are written out as null in json.
+fn parse_evaluation_history<'de, D>(d: D) -> Result<Option<RowMajorMatrix<f64>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Deserialize::deserialize(d).map(|x: Option<RowMajorMatrix<Option<f64>>>| {
+        x.map(|m| {
+            RowMajorMatrix::new(
+                m.data.iter().map(|v| v.unwrap_or(f64::NAN)).collect(),
+                m.rows,
+                m.cols,
+            )
+        })
+    })
+}
+
+/// Upgrade a json model, saved with the schema `version`, to the current schema.
+/// Each step upgrades the model from one version to the next, any renamed or
+/// restructured fields should be handled here. Fields that were added with a
+/// serde default need no upgrade step.
+fn upgrade_model(model: &mut Map<String, Value>, version: u64) {
+    for v in version..MODEL_FORMAT_VERSION {
+        match v {
+            // Version 0 models predate the schema version, all fields
+            // added since then are filled in with their defaults. This includes
+            // models saved by the upstream forust package, which stores the feature
+            // names in the metadata.
+            0 => {
+                let names = model
+                    .get("metadata")
+                    .and_then(|m| m.get(UPSTREAM_FEATURE_NAMES_KEY))
+                    .and_then(|v| v.as_str())
+                    .and_then(parse_python_str_list);
+                let has_names = model.get("feature_names").is_some_and(|v| !v.is_null());
+                if let (Some(names), false) = (names, has_names) {
+                    model.insert("feature_names".to_string(), Value::from(names));
+                }
+            }
+            _ => unreachable!("No upgrade defined from model format version {}", v),
+        }
+    }
+    model.insert(
+        "format_version".to_string(),
+        Value::from(MODEL_FORMAT_VERSION),
+    );
+}
+
+/// Write strings as a Python list literal, the format the upstream forust Python
+/// package stores lists in the metadata with.
+fn python_str_list(values: &[String]) -> String {
+    let items: Vec<String> = values
+        .iter()
+        .map(|v| format!("'{}'", v.replace('\\', "\\\\").replace('\'', "\\'")))
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Parse a Python list literal of strings, such as `['age', "o'clock"]`, returning
+/// None if it's anything else.
+fn parse_python_str_list(literal: &str) -> Option<Vec<String>> {
+    let mut chars = literal.trim().strip_prefix('[')?.strip_suffix(']')?.chars();
+    let mut values = Vec::new();
+    loop {
+        let quote = match chars.by_ref().find(|c| !c.is_whitespace()) {
+            None => return Some(values),
+            Some(q @ ('\'' | '"')) => q,
+            Some(_) => return None,
+        };
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '\\' => value.push(chars.next()?),
+                c if c == quote => break,
+                c => value.push(c),
+            }
+        }
+        values.push(value);
+        match chars.by_ref().find(|c| !c.is_whitespace()) {
+            None => return Some(values),
+            Some(',') => (),
+            Some(_) => return None,
+        }
+    }
+}
+
+fn validate_feature_index(feature: usize, cols: usize) -> Result<(), ForustError> {
+    if feature >= cols {
+        return Err(ForustError::InvalidParameter {
+            name: "feature".to_string(),
+            constraint: format!("a column index less than {}", cols),
+            value: feature.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_scale_factor(factor: f32) -> Result<(), ForustError> {
+    if !factor.is_finite() {
+        return Err(ForustError::InvalidParameter {
+            name: "factor".to_string(),
+            constraint: "a finite value".to_string(),
+            value: factor.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Randomly split the records into a training, and a validation index, both sorted.
+/// If `stratify` is true, each unique target value is split separately.
+pub(crate) fn validation_split(
+    y: &[f64],
+    validation_fraction: f64,
+    stratify: bool,
+    seed: u64,
+) -> (Vec<usize>, Vec<usize>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut groups: Vec<Vec<usize>> = if stratify {
+        let mut index: Vec<usize> = (0..y.len()).collect();
+        index.sort_by(|a, b| y[*a].total_cmp(&y[*b]));
+        index
+            .chunk_by(|a, b| y[*a].total_cmp(&y[*b]).is_eq())
+            .map(|g| g.to_vec())
+            .collect()
+    } else {
+        vec![(0..y.len()).collect()]
+    };
+    let mut train_index = Vec::new();
+    let mut valid_index = Vec::new();
+    for group in groups.iter_mut() {
+        group.shuffle(&mut rng);
+        let n_valid = (group.len() as f64 * validation_fraction).round() as usize;
+        valid_index.extend_from_slice(&group[..n_valid]);
+        train_index.extend_from_slice(&group[n_valid..]);
+    }
+    train_index.sort();
+    valid_index.sort();
+    (train_index, valid_index)
+}
+
 impl Default for GradientBooster {
     fn default() -> Self {
         Self::new(
@@ -264,6 +756,7 @@ impl GradientBooster {
     /// * `top_rate` - Used only in goss. The retain ratio of large gradient data.
     /// * `other_rate` - Used only in goss. the retain ratio of small gradient data.
     /// * `seed` - Integer value used to seed any randomness used in the algorithm.
+    ///   Fitting with the same seed, data, and parameters produces an identical model.
     /// * `missing` - Value to consider missing.
     /// * `create_missing_branch` - Should missing be split out it's own separate branch?
     /// * `sample_method` - Specify the method that records should be sampled when training?
@@ -326,156 +819,1376 @@ impl GradientBooster {
             evaluation_metric,
             early_stopping_rounds,
             initialize_base_score: initialize_base_score_,
+            validation_fraction: None,
+            quantile: 0.5,
+            colsample_bytree: 1.0,
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
+            num_parallel_tree: 1,
+            warm_start: false,
+            class_weight: None,
+            checkpoint: None,
+            stratify_validation: false,
             evaluation_history: None,
             best_iteration: None,
             prediction_iteration: None,
+            calibration: None,
+            cuts: None,
+            feature_names: None,
+            imputer: None,
             trees: Vec::new(),
             metadata: HashMap::new(),
+            n_threads: None,
+            thread_pool: OnceLock::new(),
+            profile: false,
+            profile_report: None,
+            max_histogram_memory: None,
+            check_gradients: false,
+            sample_weight_hook: None,
+        };
+        booster.validate_parameters()?;
+        Ok(booster)
+    }
+
+    /// Create a builder for the booster, that validates the parameters when built.
+    pub fn builder() -> GradientBoosterBuilder {
+        GradientBoosterBuilder::default()
+    }
+
+    /// Get the training parameters of the booster.
+    pub fn params(&self) -> BoosterParams {
+        BoosterParams {
+            objective_type: self.objective_type,
+            iterations: self.iterations,
+            learning_rate: self.learning_rate,
+            max_depth: self.max_depth,
+            max_leaves: self.max_leaves,
+            l2: self.l2,
+            gamma: self.gamma,
+            min_leaf_weight: self.min_leaf_weight,
+            base_score: self.base_score,
+            initialize_base_score: self.initialize_base_score,
+            nbins: self.nbins,
+            parallel: self.parallel,
+            allow_missing_splits: self.allow_missing_splits,
+            subsample: self.subsample,
+            top_rate: self.top_rate,
+            other_rate: self.other_rate,
+            seed: self.seed,
+            missing: self.missing,
+            create_missing_branch: self.create_missing_branch,
+            sample_method: self.sample_method,
+            grow_policy: self.grow_policy,
+            evaluation_metric: self.evaluation_metric,
+            early_stopping_rounds: self.early_stopping_rounds,
+            validation_fraction: self.validation_fraction,
+            stratify_validation: self.stratify_validation,
+            quantile: self.quantile,
+            colsample_bytree: self.colsample_bytree,
+            num_parallel_tree: self.num_parallel_tree,
+            warm_start: self.warm_start,
+            class_weight: self.class_weight.clone(),
+            checkpoint: self.checkpoint.clone(),
+            monotone_constraints: self.monotone_constraints.clone(),
+            monotone_constraint_method: self.monotone_constraint_method,
+            interaction_constraints: self.interaction_constraints.clone(),
+            n_threads: self.n_threads,
+        }
+    }
+
+    /// Get the training parameters of the booster, as a map of parameter
+    /// names to their json values.
+    pub fn get_params(&self) -> Result<Map<String, Value>, ForustError> {
+        self.params().to_map()
+    }
+
+    /// Set the named training parameters of the booster, from a map of parameter names
+    /// to their json values, such as `{"max_depth": 3, "learning_rate": 0.1}`. The other
+    /// parameters, and the fitted state of the booster are kept. The parameters are
+    /// validated, and the booster is unchanged if they are invalid.
+    /// * `values` - Map of parameter names, to their new values.
+    pub fn set_params(&mut self, values: &Map<String, Value>) -> Result<(), ForustError> {
+        let booster = Self::from_params(self.params().with_values(values)?)?;
+        // The thread pool is kept, unless it no longer has the number of threads asked for.
+        let thread_pool = if booster.n_threads == self.n_threads {
+            std::mem::take(&mut self.thread_pool)
+        } else {
+            OnceLock::new()
+        };
+        *self = GradientBooster {
+            evaluation_history: self.evaluation_history.take(),
+            best_iteration: self.best_iteration,
+            prediction_iteration: self.prediction_iteration,
+            calibration: self.calibration.take(),
+            cuts: self.cuts.take(),
+            feature_names: self.feature_names.take(),
+            imputer: self.imputer.take(),
+            trees: std::mem::take(&mut self.trees),
+            metadata: std::mem::take(&mut self.metadata),
+            profile: self.profile,
+            max_histogram_memory: self.max_histogram_memory,
+            check_gradients: self.check_gradients,
+            sample_weight_hook: self.sample_weight_hook.take(),
+            thread_pool,
+            ..booster
+        };
+        Ok(())
+    }
+
+    /// Create a booster from a map of parameter names to their json values, parameters
+    /// missing from the map keep their default value. The parameters are validated.
+    /// * `values` - Map of parameter names, to their values.
+    pub fn from_params_map(values: &Map<String, Value>) -> Result<Self, ForustError> {
+        Self::from_params(BoosterParams::from_map(values)?)
+    }
+
+    /// Create a booster from a set of training parameters, the parameters are validated.
+    /// * `params` - The training parameters of the booster.
+    pub fn from_params(params: BoosterParams) -> Result<Self, ForustError> {
+        let booster = GradientBooster {
+            objective_type: params.objective_type,
+            iterations: params.iterations,
+            learning_rate: params.learning_rate,
+            max_depth: params.max_depth,
+            max_leaves: params.max_leaves,
+            l2: params.l2,
+            gamma: params.gamma,
+            min_leaf_weight: params.min_leaf_weight,
+            base_score: params.base_score,
+            initialize_base_score: params.initialize_base_score,
+            nbins: params.nbins,
+            parallel: params.parallel,
+            allow_missing_splits: params.allow_missing_splits,
+            subsample: params.subsample,
+            top_rate: params.top_rate,
+            other_rate: params.other_rate,
+            seed: params.seed,
+            missing: params.missing,
+            create_missing_branch: params.create_missing_branch,
+            sample_method: params.sample_method,
+            grow_policy: params.grow_policy,
+            evaluation_metric: params.evaluation_metric,
+            early_stopping_rounds: params.early_stopping_rounds,
+            validation_fraction: params.validation_fraction,
+            stratify_validation: params.stratify_validation,
+            quantile: params.quantile,
+            colsample_bytree: params.colsample_bytree,
+            num_parallel_tree: params.num_parallel_tree,
+            warm_start: params.warm_start,
+            class_weight: params.class_weight,
+            checkpoint: params.checkpoint,
+            monotone_constraints: params.monotone_constraints,
+            monotone_constraint_method: params.monotone_constraint_method,
+            interaction_constraints: params.interaction_constraints,
+            n_threads: params.n_threads,
+            ..Default::default()
         };
         booster.validate_parameters()?;
         Ok(booster)
     }
 
+    /// Merge boosters into a single booster, whose prediction is the weighted sum of
+    /// their predictions, on the scale of the objective, such as the log odds for "LogLoss".
+    /// The trees of each booster are copied with their leaf weights scaled by the weight
+    /// of the booster, so the boosters can be trained on different data, and binning.
+    /// The parameters of the first booster are used for the merged booster. Calibrated
+    /// boosters can't be merged, as the calibration of one booster doesn't apply to the
+    /// merged predictions, instead a calibration can be fit on the merged booster.
+    ///
+    /// * `boosters` - The boosters to merge, they must share the objective, missing value,
+    ///   feature names, number of features, and imputer.
+    /// * `weights` - The weight of each booster, if None the predictions are averaged.
+    pub fn merge(
+        boosters: &[GradientBooster],
+        weights: Option<&[f64]>,
+    ) -> Result<Self, ForustError> {
+        let first = match boosters.first() {
+            Some(b) => b,
+            None => {
+                return Err(ForustError::InvalidParameter {
+                    name: "boosters".to_string(),
+                    constraint: "at least one booster".to_string(),
+                    value: "0 boosters".to_string(),
+                })
+            }
+        };
+        let average = vec![1. / boosters.len() as f64; boosters.len()];
+        let weights = weights.unwrap_or(&average);
+        if weights.len() != boosters.len() {
+            return Err(ForustError::InvalidParameter {
+                name: "weights".to_string(),
+                constraint: format!("a weight for each of the {} boosters", boosters.len()),
+                value: format!("{} weights", weights.len()),
+            });
+        }
+        for b in boosters.iter() {
+            if b.objective_type != first.objective_type {
+                return Err(ForustError::InvalidParameter {
+                    name: "boosters".to_string(),
+                    constraint: format!("boosters with the {:?} objective", first.objective_type),
+                    value: format!("{:?}", b.objective_type),
+                });
+            }
+            // Missing values are routed by the missing value of the merged booster.
+            let same_missing =
+                (b.missing.is_nan() && first.missing.is_nan()) || b.missing == first.missing;
+            if !same_missing {
+                return Err(ForustError::InvalidParameter {
+                    name: "boosters".to_string(),
+                    constraint: format!("boosters with the missing value {}", first.missing),
+                    value: b.missing.to_string(),
+                });
+            }
+            if b.calibration.is_some() {
+                return Err(ForustError::InvalidParameter {
+                    name: "boosters".to_string(),
+                    constraint: "boosters without calibration".to_string(),
+                    value: "a calibrated booster".to_string(),
+                });
+            }
+            if b.feature_names != first.feature_names {
+                return Err(ForustError::InvalidParameter {
+                    name: "boosters".to_string(),
+                    constraint: format!(
+                        "boosters with the feature names {:?}",
+                        first.feature_names
+                    ),
+                    value: format!("{:?}", b.feature_names),
+                });
+            }
+            // The trees of each booster were fit on data imputed with its own imputer.
+            if b.imputer != first.imputer {
+                return Err(ForustError::InvalidParameter {
+                    name: "boosters".to_string(),
+                    constraint: format!("boosters with the imputer {:?}", first.imputer),
+                    value: format!("{:?}", b.imputer),
+                });
+            }
+            // The number of features is only known for boosters fit with this package.
+            if let (Some(cuts), Some(first_cuts)) = (&b.cuts, &first.cuts) {
+                if cuts.cols != first_cuts.cols {
+                    return Err(ForustError::ShapeMismatch {
+                        name: "boosters".to_string(),
+                        expected: format!("boosters fit on {} features", first_cuts.cols),
+                        got: format!("a booster fit on {} features", cuts.cols),
+                    });
+                }
+            }
+        }
+
+        let mut booster = GradientBooster::from_params(BoosterParams {
+            num_parallel_tree: 1,
+            warm_start: false,
+            ..first.params()
+        })?;
+        booster.initialize_base_score = false;
+        booster.feature_names = first.feature_names.clone();
+        booster.imputer = first.imputer.clone();
+        booster.base_score = boosters
+            .iter()
+            .zip(weights)
+            .map(|(b, w)| b.base_score * w)
+            .sum();
+        for (b, w) in boosters.iter().zip(weights) {
+            booster
+                .trees
+                .extend(b.get_prediction_trees().iter().map(|t| {
+                    let mut t = t.clone();
+                    t.scale_leaves(*w as f32);
+                    t
+                }));
+        }
+        Ok(booster)
+    }
+
+    /// Estimate the peak memory used when fitting the booster on a dataset of the given
+    /// size, such as to choose an instance size before training on a large dataset.
+    /// * `rows` - Number of rows of the training data.
+    /// * `cols` - Number of columns of the training data.
+    pub fn estimate_memory(&self, rows: usize, cols: usize) -> MemoryEstimate {
+        estimate_training_memory(rows, cols, self.nbins, self.max_depth, self.max_leaves)
+    }
+
+    /// Get the thread pool to run on, the pool is created the first time
+    /// it is needed if `n_threads` is set. If None, the global pool is used.
+    fn get_thread_pool(&self) -> Result<Option<Arc<ThreadPool>>, ForustError> {
+        if let Some(pool) = self.thread_pool.get() {
+            return Ok(Some(Arc::clone(pool)));
+        }
+        match self.n_threads {
+            None => Ok(None),
+            Some(n_threads) => {
+                let pool = ThreadPoolBuilder::new()
+                    .num_threads(n_threads)
+                    .build()
+                    .map_err(|e| ForustError::InvalidParameter {
+                        name: "n_threads".to_string(),
+                        constraint: "a number of threads that can be started".to_string(),
+                        value: e.to_string(),
+                    })?;
+                Ok(Some(Arc::clone(
+                    self.thread_pool.get_or_init(|| Arc::new(pool)),
+                )))
+            }
+        }
+    }
+
+    // Prediction can't return an error, so if the pool can't be created,
+    // the global pool is used instead.
+    fn get_prediction_thread_pool(&self, parallel: bool) -> Option<Arc<ThreadPool>> {
+        if parallel {
+            self.get_thread_pool().unwrap_or(None)
+        } else {
+            None
+        }
+    }
+
+    /// Validate the data, target, and weights passed to fit, before any training,
+    /// so invalid inputs don't silently produce NaN leaf weights.
+    fn validate_inputs<D: DataMatrix>(
+        &self,
+        data: &D,
+        y: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<(), ForustError> {
+        if data.rows() == 0 || data.cols() == 0 {
+            return Err(ForustError::EmptyData {
+                rows: data.rows(),
+                cols: data.cols(),
+            });
+        }
+        if y.len() != data.rows() {
+            return Err(ForustError::LengthMismatch(
+                "y".to_string(),
+                y.len(),
+                data.rows(),
+            ));
+        }
+        if sample_weight.len() != data.rows() {
+            return Err(ForustError::LengthMismatch(
+                "sample_weight".to_string(),
+                sample_weight.len(),
+                data.rows(),
+            ));
+        }
+        let (valid_target, expected): (fn(f64) -> bool, &str) = match self.objective_type {
+            ObjectiveType::LogLoss => (|v| (0. ..=1.).contains(&v), "a value between 0 and 1"),
+            ObjectiveType::SquaredLoss | ObjectiveType::QuantileLoss => {
+                (|v| v.is_finite(), "a finite value")
+            }
+        };
+        if let Some((i, v)) = y.iter().enumerate().find(|(_, v)| !valid_target(**v)) {
+            return Err(ForustError::InvalidTarget(i, *v, expected.to_string()));
+        }
+        if let Some((i, w)) = sample_weight
+            .iter()
+            .enumerate()
+            .find(|(_, w)| !(w.is_finite() && **w >= 0.))
+        {
+            return Err(ForustError::InvalidWeight(i, *w));
+        }
+        if let Some(names) = &self.feature_names {
+            if names.len() != data.cols() {
+                return Err(ForustError::ShapeMismatch {
+                    name: "feature_names".to_string(),
+                    expected: format!("a name for each of the {} columns", data.cols()),
+                    got: format!("{} names", names.len()),
+                });
+            }
+        }
+        if !self.missing.is_nan()
+            && (0..data.cols()).any(|j| data.column(j).iter().any(|v| v.is_nan()))
+        {
+            return Err(ForustError::NANVAlueFound(self.missing));
+        }
+        Ok(())
+    }
+
     fn validate_parameters(&self) -> Result<(), ForustError> {
         validate_positive_float_field!(self.learning_rate);
         validate_positive_float_field!(self.l2);
         validate_positive_float_field!(self.gamma);
         validate_positive_float_field!(self.min_leaf_weight);
-        validate_positive_float_field!(self.subsample);
-        validate_positive_float_field!(self.top_rate);
-        validate_positive_float_field!(self.other_rate);
+        validate_float_parameter(self.subsample, 0., 1., "subsample")?;
+        validate_float_parameter(self.top_rate, 0., 1., "top_rate")?;
+        validate_float_parameter(self.other_rate, 0., 1., "other_rate")?;
+        if let SampleMethod::Goss = self.sample_method {
+            GossSampler::new(self.top_rate, self.other_rate)?;
+        }
+        validate_float_parameter(self.colsample_bytree, 0., 1., "colsample_bytree")?;
+        // A learning rate, or subsample of zero would never update the model.
+        for (value, parameter) in [
+            (self.learning_rate, "learning_rate"),
+            (self.subsample, "subsample"),
+            (self.colsample_bytree as f32, "colsample_bytree"),
+        ] {
+            if value == 0. {
+                return Err(ForustError::InvalidParameter {
+                    name: parameter.to_string(),
+                    constraint: "real value greater than 0".to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        if let Some(validation_fraction) = self.validation_fraction {
+            if !(validation_fraction > 0. && validation_fraction < 1.) {
+                return Err(ForustError::InvalidParameter {
+                    name: "validation_fraction".to_string(),
+                    constraint: "real value between 0 and 1, exclusive".to_string(),
+                    value: validation_fraction.to_string(),
+                });
+            }
+        }
+        if !(self.quantile > 0. && self.quantile < 1.) {
+            return Err(ForustError::InvalidParameter {
+                name: "quantile".to_string(),
+                constraint: "real value between 0 and 1, exclusive".to_string(),
+                value: self.quantile.to_string(),
+            });
+        }
+        if self.num_parallel_tree == 0 {
+            return Err(ForustError::InvalidParameter {
+                name: "num_parallel_tree".to_string(),
+                constraint: "integer value of at least 1".to_string(),
+                value: "0".to_string(),
+            });
+        }
+        if self.n_threads == Some(0) {
+            return Err(ForustError::InvalidParameter {
+                name: "n_threads".to_string(),
+                constraint: "integer value of at least 1".to_string(),
+                value: "0".to_string(),
+            });
+        }
+        if let Some(class_weight) = &self.class_weight {
+            if !matches!(self.objective_type, ObjectiveType::LogLoss) {
+                return Err(ForustError::UnsupportedObjective {
+                    objective: format!("{:?}", self.objective_type),
+                    reason: format!("only LogLoss can use the class weight {:?}", class_weight),
+                });
+            }
+            if let ClassWeight::Weights(weights) = class_weight {
+                if let Some(w) = weights.iter().find(|w| !(w.is_finite() && **w >= 0.)) {
+                    return Err(ForustError::InvalidParameter {
+                        name: "class_weight".to_string(),
+                        constraint: "finite weights of at least 0".to_string(),
+                        value: w.to_string(),
+                    });
+                }
+            }
+        }
+        if let Some(checkpoint) = &self.checkpoint {
+            if checkpoint.iterations.is_none() && checkpoint.seconds.is_none() {
+                return Err(ForustError::InvalidParameter {
+                    name: "checkpoint".to_string(),
+                    constraint: "a checkpoint with an interval of iterations, or seconds"
+                        .to_string(),
+                    value: format!("{:?}", checkpoint),
+                });
+            }
+            if checkpoint.iterations == Some(0) {
+                return Err(ForustError::InvalidParameter {
+                    name: "checkpoint.iterations".to_string(),
+                    constraint: "integer value of at least 1".to_string(),
+                    value: "0".to_string(),
+                });
+            }
+            if let Some(seconds) = checkpoint.seconds {
+                if !(seconds.is_finite() && seconds > 0.) {
+                    return Err(ForustError::InvalidParameter {
+                        name: "checkpoint.seconds".to_string(),
+                        constraint: "finite value greater than 0".to_string(),
+                        value: seconds.to_string(),
+                    });
+                }
+            }
+        }
+        if self.nbins < 2 {
+            return Err(ForustError::InvalidParameter {
+                name: "nbins".to_string(),
+                constraint: "integer value of at least 2".to_string(),
+                value: self.nbins.to_string(),
+            });
+        }
         Ok(())
     }
 
-    /// Fit the gradient booster on a provided dataset.
+    /// Fit the gradient booster on a provided dataset. The data can be either f32
+    /// or f64, f32 data is binned and predicted on without copying it to f64, to
+    /// halve the memory used by the data of large datasets.
     ///
     /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
     /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
     /// * `sample_weight` - Instance weights to use when
     /// training the model. If None is passed, a weight of 1 will be used for every record.
-    pub fn fit(
+    pub fn fit<T: FloatData<T>>(
         &mut self,
-        data: &Matrix<f64>,
-        y: &[f64],
-        sample_weight: &[f64],
-        evaluation_data: Option<Vec<EvaluationData>>,
+        data: &Matrix<T>,
+        y: &[T],
+        sample_weight: &[T],
+        evaluation_data: Option<Vec<EvaluationData<T>>>,
     ) -> Result<(), ForustError> {
-        let constraints_map = self
-            .monotone_constraints
-            .as_ref()
-            .unwrap_or(&ConstraintMap::new())
-            .to_owned();
-        if self.create_missing_branch {
-            let splitter = MissingBranchSplitter {
-                l2: self.l2,
-                gamma: self.gamma,
-                min_leaf_weight: self.min_leaf_weight,
-                learning_rate: self.learning_rate,
-                allow_missing_splits: self.allow_missing_splits,
-                constraints_map,
-            };
-            self.fit_trees(y, sample_weight, data, &splitter, evaluation_data)?;
-        } else {
-            let splitter = MissingImputerSplitter {
-                l2: self.l2,
-                gamma: self.gamma,
-                min_leaf_weight: self.min_leaf_weight,
-                learning_rate: self.learning_rate,
-                allow_missing_splits: self.allow_missing_splits,
-                constraints_map,
-            };
-            self.fit_trees(y, sample_weight, data, &splitter, evaluation_data)?;
-        };
-
-        Ok(())
+        // The target and weights are only one column, so they are converted
+        // to f64 up front, while the data is used as is.
+        let y = T::to_f64_slice(y);
+        let sample_weight = T::to_f64_slice(sample_weight);
+        let evaluation_data: Option<Vec<_>> = evaluation_data.map(|evals| {
+            evals
+                .into_iter()
+                .map(|(d, y, w)| (d, T::to_f64_slice(y), T::to_f64_slice(w)))
+                .collect()
+        });
+        let evaluation_sets = evaluation_data.as_ref().map(|evals| {
+            evals
+                .iter()
+                .map(|(d, y, w)| (d, y.as_ref(), w.as_ref()))
+                .collect()
+        });
+        self.fit_f64_target(data, &y, &sample_weight, evaluation_sets)
     }
 
-    fn sample_index(
-        &self,
-        rng: &mut StdRng,
-        index: &[usize],
-        grad: &mut [f32],
-        hess: &mut [f32],
-    ) -> (Vec<usize>, Vec<usize>) {
-        match self.sample_method {
-            SampleMethod::None => (index.to_owned(), Vec::new()),
-            SampleMethod::Random => {
-                RandomSampler::new(self.subsample).sample(rng, index, grad, hess)
+    /// Fit the gradient booster on a dataset, bundling the features, target, and
+    /// sample weights. The metadata of the features is used as the single source of
+    /// the feature names, and monotonicity constraints of the booster, which are set
+    /// from the dataset if it has them. Categorical features must have few enough
+    /// categories that each category is binned on its own, rather than grouped with
+    /// its neighbouring codes.
+    ///
+    /// * `dataset` - The training data.
+    /// * `evaluation_data` - Datasets to evaluate the booster on while training.
+    pub fn fit_dataset<T: FloatData<T>>(
+        &mut self,
+        dataset: &Dataset<T>,
+        evaluation_data: Option<&[&Dataset<T>]>,
+    ) -> Result<(), ForustError> {
+        dataset.validate()?;
+        if let Some(evals) = evaluation_data {
+            for d in evals.iter() {
+                d.validate()?;
             }
-            SampleMethod::Goss => {
-                GossSampler::new(self.top_rate, self.other_rate).sample(rng, index, grad, hess)
+        }
+        let data = dataset.as_matrix();
+        // Each category gets its own cut, if there are no more than `nbins + 1` of them.
+        for i in dataset.categorical_features() {
+            let mut categories: Vec<f64> = data
+                .get_col(i)
+                .iter()
+                .map(|v| v.to_f64())
+                .filter(|v| !is_missing(v, &self.missing))
+                .collect();
+            categories.sort_unstable_by(|a, b| a.total_cmp(b));
+            categories.dedup();
+            if categories.len() > usize::from(self.nbins) + 1 {
+                return Err(ForustError::InvalidParameter {
+                    name: "nbins".to_string(),
+                    constraint: format!(
+                        "at least {}, to bin each category of feature {}",
+                        categories.len() - 1,
+                        i
+                    ),
+                    value: self.nbins.to_string(),
+                });
             }
         }
+        if let Some(names) = dataset.feature_names() {
+            self.feature_names = Some(names);
+        }
+        let constraints = dataset.monotone_constraints();
+        if !constraints.is_empty() {
+            self.monotone_constraints = Some(constraints);
+        }
+        let evaluation_data = evaluation_data.map(|evals| {
+            evals
+                .iter()
+                .map(|d| (d.as_matrix(), d.y.as_slice(), d.sample_weight.as_slice()))
+                .collect()
+        });
+        self.fit(&data, &dataset.y, &dataset.sample_weight, evaluation_data)
     }
 
-    fn get_metric_fn(&self) -> (MetricFn, bool) {
-        let metric = match &self.evaluation_metric {
-            None => match self.objective_type {
-                ObjectiveType::LogLoss => LogLoss::default_metric(),
-                ObjectiveType::SquaredLoss => SquaredLoss::default_metric(),
-            },
-            Some(v) => *v,
-        };
-        metric_callables(&metric)
-    }
-
-    fn fit_trees<T: Splitter>(
+    /// Fit the gradient booster on integer data, such as counts, or IDs, without
+    /// casting it to a float first. Each column is converted to f64 as it is binned,
+    /// so the data is never copied. Integers can't be NaN, so if the data has missing
+    /// values, `missing` should be set to the integer used for them.
+    ///
+    /// * `data` -  A matrix of i32, or i64 data.
+    /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
+    /// * `sample_weight` - Instance weights to use when training the model.
+    /// * `evaluation_data` - Integer data, with a target and weights, to evaluate the
+    ///   booster on while training.
+    pub fn fit_integer<T: IntegerData>(
         &mut self,
+        data: &Matrix<T>,
         y: &[f64],
         sample_weight: &[f64],
-        data: &Matrix<f64>,
-        splitter: &T,
-        evaluation_data: Option<Vec<EvaluationData>>,
+        evaluation_data: Option<Vec<IntegerEvaluationData<T>>>,
+    ) -> Result<(), ForustError>
+    where
+        for<'b> Matrix<'b, T>: DataMatrix,
+    {
+        let evaluation_sets = evaluation_data
+            .as_ref()
+            .map(|evals| evals.iter().map(|(d, y, w)| (d, *y, *w)).collect());
+        self.fit_f64_target(data, y, sample_weight, evaluation_sets)
+    }
+
+    fn fit_f64_target<T: Copy + Sync>(
+        &mut self,
+        data: &Matrix<T>,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<EvaluationSet<Matrix<T>>>>,
+    ) -> Result<(), ForustError>
+    where
+        for<'b> Matrix<'b, T>: DataMatrix,
+    {
+        self.validate_parameters()?;
+        self.validate_inputs(data, y, sample_weight)?;
+        if let Some(evals) = &evaluation_data {
+            for (d, y, w) in evals.iter() {
+                self.validate_inputs(*d, y, w)?;
+            }
+        }
+        if let (None, Some(validation_fraction)) = (&evaluation_data, self.validation_fraction) {
+            return self.fit_validation_split(data, y, sample_weight, validation_fraction);
+        }
+        self.fit_weighted(data, y, sample_weight, evaluation_data, None)
+    }
+
+    /// Continue training a fitted booster on a new batch of data, appending `n_new_trees`
+    /// boosting rounds, fit only on the new data. The new data is binned with the cuts
+    /// of the data the booster was first fit on, so the batch can be small, and the
+    /// booster can adapt to drifting data without retraining on the full history.
+    ///
+    /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
+    /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
+    /// * `sample_weight` - Instance weights to use when training the new trees.
+    /// * `n_new_trees` - The number of boosting rounds to add to the booster.
+    pub fn update<T: FloatData<T>>(
+        &mut self,
+        data: &Matrix<T>,
+        y: &[T],
+        sample_weight: &[T],
+        n_new_trees: usize,
+    ) -> Result<(), ForustError> {
+        self.validate_parameters()?;
+        let y = T::to_f64_slice(y);
+        let sample_weight = T::to_f64_slice(sample_weight);
+        let cuts = match &self.cuts {
+            Some(cuts) => cuts.clone(),
+            None => {
+                return Err(ForustError::InvalidParameter {
+                    name: "booster".to_string(),
+                    constraint: "a booster that has been fit".to_string(),
+                    value: "a booster without binning cuts".to_string(),
+                })
+            }
+        };
+        if cuts.cols != data.cols {
+            return Err(ForustError::ShapeMismatch {
+                name: "data".to_string(),
+                expected: format!("a matrix with {} columns", cuts.cols),
+                got: format!("a matrix with {} columns", data.cols),
+            });
+        }
+        self.validate_inputs(data, &y, &sample_weight)?;
+        if let Some(imputer) = &self.imputer {
+            if imputer.values.len() != data.cols {
+                return Err(ForustError::ShapeMismatch {
+                    name: "imputer".to_string(),
+                    expected: format!("an imputer fit on data with {} columns", data.cols),
+                    got: format!("{} columns", imputer.values.len()),
+                });
+            }
+        }
+        let (iterations, warm_start) = (self.iterations, self.warm_start);
+        self.iterations = n_new_trees;
+        self.warm_start = true;
+        // The new data is imputed, before it's binned, as when the booster was first fit.
+        let result = match self.imputer.clone() {
+            Some(imputer) => {
+                let imputed = imputer.view(data, self.missing);
+                let binned_data = bin_matrix_with_cuts(&imputed, cuts)?;
+                self.fit_class_weighted(
+                    &imputed,
+                    &y,
+                    &sample_weight,
+                    None,
+                    Some(binned_data.view()),
+                )
+            }
+            None => {
+                let binned_data = bin_matrix_with_cuts(data, cuts)?;
+                self.fit_weighted(data, &y, &sample_weight, None, Some(binned_data.view()))
+            }
+        };
+        self.iterations = iterations;
+        self.warm_start = warm_start;
+        result
+    }
+
+    /// Fit the gradient booster on data that has already been binned, such as with
+    /// `bin_matrix`, skipping the binning of the data. The data can be binned once, and
+    /// used to fit several boosters, or binned by the caller in chunks. The `nbins` of
+    /// the booster is not used, and no validation split is made, as there is no
+    /// evaluation data.
+    ///
+    /// * `binned_data` - The binned data, and the cuts of each column.
+    /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
+    /// * `sample_weight` - Instance weights to use when training the model.
+    pub fn fit_from_binned(
+        &mut self,
+        binned_data: &BinnedData<f64>,
+        y: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<(), ForustError> {
+        self.fit_from_bins(binned_data.view(), y, sample_weight)
+    }
+
+    /// Fit the gradient booster on binned data saved with `BinnedData::save`, and memory
+    /// mapped with `MmapBinnedData::open`, so data larger than memory can be trained on.
+    /// The histograms are built from the mapped bins, which the operating system pages
+    /// in and out as they're read, only the gradients, hessians, and predictions for each
+    /// record are held in memory. As with `fit_from_binned`, the `nbins` of the booster
+    /// is not used, and no validation split is made.
+    ///
+    /// * `binned_data` - The memory mapped binned data.
+    /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
+    /// * `sample_weight` - Instance weights to use when training the model.
+    #[cfg(feature = "mmap")]
+    pub fn fit_from_mmap(
+        &mut self,
+        binned_data: &MmapBinnedData,
+        y: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<(), ForustError> {
+        self.fit_from_bins(binned_data.view(), y, sample_weight)
+    }
+
+    /// Fit the gradient booster on data read in chunks of rows, so the data doesn't
+    /// have to be held in memory as f64. The chunks are read twice, the first pass
+    /// sketches the percentiles of each column to find the cuts, and the second bins
+    /// each chunk, and collects the target and weights. Only the binned data is kept,
+    /// at two bytes for each value. No validation split is made, as there is no
+    /// evaluation data.
+    ///
+    /// * `chunks` - Called once for each pass, returning an iterator of chunks of rows.
+    ///   Both calls must produce the same chunks, in the same order.
+    pub fn fit_from_chunks<F, I>(&mut self, chunks: F) -> Result<(), ForustError>
+    where
+        F: FnMut() -> I,
+        I: IntoIterator<Item = DataChunk>,
+    {
+        self.validate_parameters()?;
+        let chunked = bin_chunks(chunks, self.nbins, self.missing)?;
+        self.fit_from_bins(
+            chunked.binned_data.view(),
+            &chunked.y,
+            &chunked.sample_weight,
+        )
+    }
+
+    fn fit_from_bins(
+        &mut self,
+        binned_data: BinnedDataRef,
+        y: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<(), ForustError> {
+        self.validate_parameters()?;
+        let cols = binned_data.cuts.cols;
+        if binned_data.binned_data.len() != y.len() * cols {
+            return Err(ForustError::ShapeMismatch {
+                name: "binned_data".to_string(),
+                expected: format!("{} binned values for each of the {} records", cols, y.len()),
+                got: format!("{} binned values", binned_data.binned_data.len()),
+            });
+        }
+        // The trees are grown on the bins, and predict on a value from each bin,
+        // looked up as it's read, rather than copied out of the bins.
+        let data = BinValues::new(binned_data, self.missing);
+        self.validate_inputs(&data, y, sample_weight)?;
+        self.fit_weighted(&data, y, sample_weight, None, Some(binned_data))
+    }
+
+    /// Fit the gradient booster on sparse data, in the CSC format. Values that aren't
+    /// stored are zero, so only the stored values can be missing. Only the stored values
+    /// are binned, and the histograms are built from their bins, so training takes memory
+    /// in proportion to the number of stored values, see `SparseBinnedData`. With an
+    /// imputer, the data is imputed, and binned, as dense data. The fitted booster can
+    /// predict on sparse data with `predict`. A `validation_fraction` can't be used
+    /// with sparse data, the evaluation data has to be passed instead.
+    ///
+    /// * `data` - The sparse training data.
+    /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
+    /// * `sample_weight` - Instance weights to use when training the model.
+    /// * `evaluation_data` - Sparse evaluation sets, with their target and weights.
+    pub fn fit_sparse(
+        &mut self,
+        data: &CscMatrix<f64>,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<SparseEvaluationData>>,
+    ) -> Result<(), ForustError> {
+        let evaluation_sets = evaluation_data
+            .as_ref()
+            .map(|evals| evals.iter().map(|(d, y, w)| (d, *y, *w)).collect());
+        self.fit_without_split(data, y, sample_weight, evaluation_sets, "sparse data")
+    }
+
+    /// Fit the gradient booster on some of the columns of a matrix, selected with a
+    /// `MatrixView`, without copying the columns out of the matrix. The fitted booster
+    /// predicts on a view of the same columns. A `validation_fraction` can't be used
+    /// with a view, the evaluation data has to be passed instead.
+    ///
+    /// * `data` - A view of the columns of the training data.
+    /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
+    /// * `sample_weight` - Instance weights to use when training the model.
+    /// * `evaluation_data` - Views of evaluation sets, with their target and weights.
+    pub fn fit_view<'a, T>(
+        &mut self,
+        data: &MatrixView<'a, T>,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<ViewEvaluationData<'a, T>>>,
+    ) -> Result<(), ForustError>
+    where
+        Matrix<'a, T>: DataMatrix,
+    {
+        let evaluation_sets = evaluation_data
+            .as_ref()
+            .map(|evals| evals.iter().map(|(d, y, w)| (d, *y, *w)).collect());
+        self.fit_without_split(data, y, sample_weight, evaluation_sets, "a matrix view")
+    }
+
+    /// Fit the gradient booster on row major data, such as a C contiguous numpy
+    /// array, without converting it to column major first. The fitted booster can
+    /// predict on a `RowMajorView` too. A `validation_fraction` can't be used with
+    /// row major data, the evaluation data has to be passed instead.
+    ///
+    /// * `data` - A view of the row major training data.
+    /// * `y` - The target of each row.
+    /// * `sample_weight` - Instance weights to use when training the model.
+    /// * `evaluation_data` - Row major evaluation sets, with their target and weights.
+    pub fn fit_row_major<T: FloatData<T>>(
+        &mut self,
+        data: &RowMajorView<T>,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<RowMajorEvaluationData<T>>>,
+    ) -> Result<(), ForustError> {
+        let evaluation_sets = evaluation_data
+            .as_ref()
+            .map(|evals| evals.iter().map(|(d, y, w)| (d, *y, *w)).collect());
+        self.fit_without_split(data, y, sample_weight, evaluation_sets, "row major data")
+    }
+
+    /// Fit the gradient booster on some of the rows of the data, selected with a
+    /// `RowView`, without copying the rows out of the data, so each fold of a cross
+    /// validation can be trained on the same data. If a `validation_fraction` is set,
+    /// the training and validation sets are views of the same data too.
+    ///
+    /// * `data` - A view of the rows of the training data.
+    /// * `y` - The target of each row of the view.
+    /// * `sample_weight` - Instance weights of each row of the view.
+    /// * `evaluation_data` - Views of evaluation sets, with their target and weights.
+    pub fn fit_rows<'a, D: DataMatrix>(
+        &mut self,
+        data: &RowView<'a, D>,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<RowEvaluationData<'a, D>>>,
+    ) -> Result<(), ForustError> {
+        self.validate_parameters()?;
+        self.validate_inputs(data, y, sample_weight)?;
+        if let Some(evals) = &evaluation_data {
+            for (d, y, w) in evals.iter() {
+                self.validate_inputs(d, y, w)?;
+            }
+        }
+        if let (None, Some(validation_fraction)) = (&evaluation_data, self.validation_fraction) {
+            let (train_index, valid_index) = self.validation_indices(y, validation_fraction)?;
+            let select =
+                |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+            let (train_data, valid_data) = (data.select(&train_index), data.select(&valid_index));
+            let (train_y, valid_y) = (select(y, &train_index), select(y, &valid_index));
+            let (train_w, valid_w) = (
+                select(sample_weight, &train_index),
+                select(sample_weight, &valid_index),
+            );
+            return self.fit_weighted(
+                &train_data,
+                &train_y,
+                &train_w,
+                Some(vec![(&valid_data, &valid_y, &valid_w)]),
+                None,
+            );
+        }
+        let evaluation_sets = evaluation_data
+            .as_ref()
+            .map(|evals| evals.iter().map(|(d, y, w)| (d, *y, *w)).collect());
+        self.fit_weighted(data, y, sample_weight, evaluation_sets, None)
+    }
+
+    /// Fit the booster on data that can't be split into training and validation
+    /// sets, so a `validation_fraction` isn't allowed.
+    fn fit_without_split<D: DataMatrix>(
+        &mut self,
+        data: &D,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<EvaluationSet<D>>>,
+        data_kind: &str,
+    ) -> Result<(), ForustError> {
+        self.validate_parameters()?;
+        self.validate_inputs(data, y, sample_weight)?;
+        if let Some(evals) = &evaluation_data {
+            for (d, y, w) in evals.iter() {
+                self.validate_inputs(*d, y, w)?;
+            }
+        }
+        if let (None, Some(validation_fraction)) = (&evaluation_data, self.validation_fraction) {
+            return Err(ForustError::InvalidParameter {
+                name: "validation_fraction".to_string(),
+                constraint: format!("None, when fitting on {}", data_kind),
+                value: validation_fraction.to_string(),
+            });
+        }
+        self.fit_weighted(data, y, sample_weight, evaluation_data, None)
+    }
+
+    /// Fit the booster, applying the class weights to the training
+    /// and evaluation data.
+    fn fit_weighted<D: DataMatrix>(
+        &mut self,
+        data: &D,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<EvaluationSet<D>>>,
+        binned_data: Option<BinnedDataRef>,
+    ) -> Result<(), ForustError> {
+        // The imputer is kept on the booster while fitting, so checkpoints hold it.
+        let imputer = match self.imputer.clone() {
+            Some(imputer) => imputer,
+            None => {
+                return self.fit_class_weighted(
+                    data,
+                    y,
+                    sample_weight,
+                    evaluation_data,
+                    binned_data,
+                )
+            }
+        };
+        // Binned data can't be imputed, as the missing records are already binned.
+        if binned_data.is_some() {
+            Err(ForustError::InvalidParameter {
+                name: "imputer".to_string(),
+                constraint: "None, when fitting on binned data".to_string(),
+                value: format!("{:?}", imputer.strategy),
+            })
+        } else if imputer.values.len() != data.cols() {
+            Err(ForustError::ShapeMismatch {
+                name: "imputer".to_string(),
+                expected: format!("an imputer fit on data with {} columns", data.cols()),
+                got: format!("{} columns", imputer.values.len()),
+            })
+        } else {
+            let imputed = imputer.view(data, self.missing);
+            let imputed_evals: Option<Vec<_>> = evaluation_data.as_ref().map(|evals| {
+                evals
+                    .iter()
+                    .map(|(d, y, w)| (imputer.view(*d, self.missing), *y, *w))
+                    .collect()
+            });
+            let evaluation_sets = imputed_evals
+                .as_ref()
+                .map(|evals| evals.iter().map(|(d, y, w)| (d, *y, *w)).collect());
+            self.fit_class_weighted(&imputed, y, sample_weight, evaluation_sets, None)
+        }
+    }
+
+    /// Fit the booster, applying the class weights to the training
+    /// and evaluation data.
+    fn fit_class_weighted<D: DataMatrix>(
+        &mut self,
+        data: &D,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<EvaluationSet<D>>>,
+        binned_data: Option<BinnedDataRef>,
+    ) -> Result<(), ForustError> {
+        // The evaluation data is weighted by class as well, so the evaluation
+        // metric, and early stopping, reflect the weighted training loss.
+        let class_sample_weight;
+        let class_evaluation_weight: Vec<Vec<f64>>;
+        let (sample_weight, evaluation_data) = match (&self.class_weight, evaluation_data) {
+            (Some(class_weight), evaluation_data) => {
+                class_sample_weight = class_weight.sample_weight(y, sample_weight)?;
+                let evaluation_data = match evaluation_data {
+                    Some(evals) => {
+                        class_evaluation_weight = evals
+                            .iter()
+                            .map(|(_, y, w)| class_weight.sample_weight(y, w))
+                            .collect::<Result<_, _>>()?;
+                        Some(
+                            evals
+                                .into_iter()
+                                .zip(class_evaluation_weight.iter())
+                                .map(|((d, y, _), w)| (d, y, w.as_slice()))
+                                .collect(),
+                        )
+                    }
+                    None => None,
+                };
+                (class_sample_weight.as_slice(), evaluation_data)
+            }
+            (None, evaluation_data) => (sample_weight, evaluation_data),
+        };
+        match self.get_thread_pool()? {
+            Some(pool) => pool.install(|| {
+                self.fit_splitter(data, y, sample_weight, evaluation_data, binned_data)
+            }),
+            None => self.fit_splitter(data, y, sample_weight, evaluation_data, binned_data),
+        }
+    }
+
+    /// Fit the booster on part of the data, using the rest as an evaluation set.
+    /// Split the records into training and validation sets, erroring if either is empty.
+    fn validation_indices(
+        &self,
+        y: &[f64],
+        validation_fraction: f64,
+    ) -> Result<(Vec<usize>, Vec<usize>), ForustError> {
+        let (train_index, valid_index) =
+            validation_split(y, validation_fraction, self.stratify_validation, self.seed);
+        if train_index.is_empty() || valid_index.is_empty() {
+            return Err(ForustError::InvalidParameter {
+                name: "validation_fraction".to_string(),
+                constraint: "a fraction that leaves records for both training and evaluation"
+                    .to_string(),
+                value: validation_fraction.to_string(),
+            });
+        }
+        Ok((train_index, valid_index))
+    }
+
+    fn fit_validation_split<T: Copy + Sync>(
+        &mut self,
+        data: &Matrix<T>,
+        y: &[f64],
+        sample_weight: &[f64],
+        validation_fraction: f64,
+    ) -> Result<(), ForustError>
+    where
+        for<'b> Matrix<'b, T>: DataMatrix,
+    {
+        let (train_index, valid_index) = self.validation_indices(y, validation_fraction)?;
+        let select = |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+        let train_vec = data.select_rows(&train_index);
+        let train_data = Matrix::new(&train_vec, train_index.len(), data.cols);
+        let valid_vec = data.select_rows(&valid_index);
+        let valid_data = Matrix::new(&valid_vec, valid_index.len(), data.cols);
+        let (train_y, valid_y) = (select(y, &train_index), select(y, &valid_index));
+        let (train_w, valid_w) = (
+            select(sample_weight, &train_index),
+            select(sample_weight, &valid_index),
+        );
+        self.fit_f64_target(
+            &train_data,
+            &train_y,
+            &train_w,
+            Some(vec![(&valid_data, &valid_y, &valid_w)]),
+        )
+    }
+
+    fn fit_splitter<D: DataMatrix>(
+        &mut self,
+        data: &D,
+        y: &[f64],
+        sample_weight: &[f64],
+        evaluation_data: Option<Vec<EvaluationSet<D>>>,
+        binned_data: Option<BinnedDataRef>,
+    ) -> Result<(), ForustError> {
+        let constraints_map = self
+            .monotone_constraints
+            .as_ref()
+            .unwrap_or(&ConstraintMap::new())
+            .to_owned();
+        let interaction_constraints = match &self.interaction_constraints {
+            Some(c) => Some(c.resolve(self.feature_names.as_deref(), data.cols())?),
+            None => None,
+        };
+        if self.create_missing_branch {
+            let splitter = MissingBranchSplitter {
+                l2: self.l2,
+                gamma: self.gamma,
+                min_leaf_weight: self.min_leaf_weight,
+                learning_rate: self.learning_rate,
+                allow_missing_splits: self.allow_missing_splits,
+                constraints_map,
+                monotone_constraint_method: self.monotone_constraint_method,
+                interaction_constraints,
+            };
+            self.fit_trees(
+                y,
+                sample_weight,
+                data,
+                &splitter,
+                evaluation_data,
+                binned_data,
+            )?;
+        } else {
+            let splitter = MissingImputerSplitter {
+                l2: self.l2,
+                gamma: self.gamma,
+                min_leaf_weight: self.min_leaf_weight,
+                learning_rate: self.learning_rate,
+                allow_missing_splits: self.allow_missing_splits,
+                constraints_map,
+                monotone_constraint_method: self.monotone_constraint_method,
+                interaction_constraints,
+            };
+            self.fit_trees(
+                y,
+                sample_weight,
+                data,
+                &splitter,
+                evaluation_data,
+                binned_data,
+            )?;
+        };
+
+        Ok(())
+    }
+
+    fn sample_index(
+        &self,
+        rng: &mut StdRng,
+        index: &[usize],
+        grad: &[f32],
+        hess: &[f32],
+    ) -> Result<Sample, ForustError> {
+        Ok(match self.sample_method {
+            SampleMethod::None => Sample {
+                chosen: index.to_owned(),
+                excluded: Vec::new(),
+                factors: None,
+            },
+            SampleMethod::Random => {
+                RandomSampler::new(self.subsample).sample(rng, index, grad, hess)
+            }
+            SampleMethod::Goss => {
+                GossSampler::new(self.top_rate, self.other_rate)?.sample(rng, index, grad, hess)
+            }
+        })
+    }
+
+    /// Sample the columns a tree can split on, returned in sorted order.
+    fn sample_columns(&self, rng: &mut StdRng, cols: usize) -> Vec<usize> {
+        let mut col_index: Vec<usize> = (0..cols).collect();
+        if self.colsample_bytree < 1. {
+            let n = ((cols as f64 * self.colsample_bytree).round() as usize).max(1);
+            col_index = col_index.choose_multiple(rng, n).copied().collect();
+            col_index.sort();
+        }
+        col_index
+    }
+
+    /// Get the evaluation metric of the booster, falling back
+    /// on the default metric of the objective function.
+    pub(crate) fn get_metric(&self) -> Metric {
+        match &self.evaluation_metric {
+            None => match self.objective_type {
+                ObjectiveType::LogLoss => LogLoss::default_metric(),
+                ObjectiveType::SquaredLoss => SquaredLoss::default_metric(),
+                ObjectiveType::QuantileLoss => QuantileLoss::default_metric(),
+            },
+            Some(v) => *v,
+        }
+    }
+
+    fn get_metric_fn(&self) -> (MetricFn, bool) {
+        metric_callables(&self.get_metric())
+    }
+
+    fn fit_trees<S: Splitter + Sync, D: DataMatrix>(
+        &mut self,
+        y: &[f64],
+        sample_weight: &[f64],
+        data: &D,
+        splitter: &S,
+        evaluation_data: Option<Vec<EvaluationSet<D>>>,
+        binned_data: Option<BinnedDataRef>,
     ) -> Result<(), ForustError> {
-        let mut rng = StdRng::seed_from_u64(self.seed);
+        // Cuts can't be found for a column without any values.
+        if binned_data.is_none() {
+            let all_missing = (0..data.cols()).find(|j| {
+                if data.is_sparse() {
+                    // Only the stored values can be missing, unless zero is missing.
+                    let (rows, values) = data.stored_column(*j);
+                    (rows.len() == data.rows() || is_missing(&0., &self.missing))
+                        && values.iter().all(|v| is_missing(v, &self.missing))
+                } else {
+                    (0..data.rows()).all(|i| is_missing(&data.value(i, *j), &self.missing))
+                }
+            });
+            if let Some(j) = all_missing {
+                return Err(ForustError::AllMissing(j));
+            }
+        }
+        if !self.warm_start {
+            self.trees.clear();
+            self.evaluation_history = None;
+            self.best_iteration = None;
+            self.prediction_iteration = None;
+        }
+        let start = self.trees.len() / self.num_parallel_tree;
+        if let Some(checkpoint) = &mut self.checkpoint {
+            checkpoint.target_rounds = Some(start + self.iterations);
+        }
+        // The evaluation history can only be continued, if it was recorded
+        // for every existing round, on the same number of evaluation sets.
+        let n_eval = evaluation_data.as_ref().map_or(0, |e| e.len());
+        if let Some(history) = &self.evaluation_history {
+            if history.rows != start || history.cols != n_eval {
+                self.evaluation_history = None;
+                self.best_iteration = None;
+                self.prediction_iteration = None;
+            }
+        }
+        // Offset the seed by the number of rounds, so continued training
+        // doesn't repeat the samples of the previous fit.
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(start as u64));
+        let mut profiler = Profiler::new(self.profile);
+        let mut profile_report = ProfileReport::default();
 
-        if self.initialize_base_score {
-            self.base_score = calc_init_callables(&self.objective_type)(y, sample_weight);
+        if self.initialize_base_score && start == 0 {
+            self.base_score =
+                calc_init_callables(&self.objective_type, self.quantile)(y, sample_weight);
         }
 
-        let mut yhat = vec![self.base_score; y.len()];
+        let mut yhat = self.predict_all_trees(data);
 
-        let calc_grad_hess = gradient_hessian_callables(&self.objective_type);
-        let (mut grad, mut hess) = calc_grad_hess(y, &yhat, sample_weight);
+        let objective_grad_hess = gradient_hessian_callables(&self.objective_type, self.quantile);
+        let parallel = self.parallel;
+        let calc_grad_hess = |y: &[f64], yhat: &[f64], sample_weight: &[f64]| {
+            if parallel {
+                parallel_grad_hess(&objective_grad_hess, y, yhat, sample_weight)
+            } else {
+                objective_grad_hess(y, yhat, sample_weight)
+            }
+        };
+        let mut round_weight = self.round_sample_weight(start, sample_weight)?;
+        let (mut grad, mut hess) = profiler.time(Phase::Gradient, || {
+            calc_grad_hess(y, &yhat, round_weight.as_deref().unwrap_or(sample_weight))
+        });
+        if self.check_gradients {
+            self.check_finite_gradients(&grad, &hess, &yhat, start)?;
+        }
 
         // Generate binned data
         // TODO
         // In scikit-learn, they sample 200_000 records for generating the bins.
         // we could consider that, especially if this proved to be a large bottleneck...
-        let binned_data = bin_matrix(data, sample_weight, self.nbins, self.missing)?;
-        let bdata = Matrix::new(&binned_data.binned_data, data.rows, data.cols);
+        let binning_start = Instant::now();
+        let (computed_data, sparse_data);
+        let (bdata, cuts) = match binned_data {
+            Some(binned_data) => (
+                TrainingBins::Dense(Matrix::new(
+                    binned_data.binned_data,
+                    data.rows(),
+                    data.cols(),
+                )),
+                binned_data.cuts,
+            ),
+            // Sparse data is binned from its stored values, rather than densified.
+            None if data.is_sparse() => {
+                sparse_data = bin_sparse_matrix(data, sample_weight, self.nbins, self.missing)?;
+                (TrainingBins::Sparse(&sparse_data), &sparse_data.cuts)
+            }
+            None => {
+                computed_data = bin_matrix(data, sample_weight, self.nbins, self.missing)?;
+                (
+                    TrainingBins::Dense(Matrix::new(
+                        &computed_data.binned_data,
+                        data.rows(),
+                        data.cols(),
+                    )),
+                    &computed_data.cuts,
+                )
+            }
+        };
+        profile_report.binning = binning_start.elapsed();
+        let data_index: Vec<usize> = (0..data.rows()).collect();
 
         // Create the predictions, saving them with the evaluation data.
-        let mut evaluation_sets: Option<Vec<TrainingEvaluationData>> =
+        let mut evaluation_sets: Option<Vec<TrainingEvaluationSet<D>>> =
             evaluation_data.as_ref().map(|evals| {
                 evals
                     .iter()
-                    .map(|(d, y, w)| (d, *y, *w, vec![self.base_score; y.len()]))
+                    .map(|(d, y, w)| (*d, *y, *w, self.predict_all_trees(*d)))
                     .collect()
             });
 
-        let mut best_metric: Option<f64> = None;
-
-        for i in 0..self.iterations {
-            // We will eventually use the excluded index.
-            let (chosen_index, _excluded_index) =
-                self.sample_index(&mut rng, &data.index, &mut grad, &mut hess);
-            let mut tree = Tree::new();
-
-            tree.fit(
-                &bdata,
-                chosen_index,
-                &binned_data.cuts,
-                &grad,
-                &hess,
-                splitter,
-                self.max_leaves,
-                self.max_depth,
-                self.parallel,
-                &self.sample_method,
-                &self.grow_policy,
-            );
-            self.update_predictions_inplace(&mut yhat, &tree, data);
+        let mut best_metric: Option<f64> = match (&self.evaluation_history, self.best_iteration) {
+            (Some(history), Some(best_iteration)) => Some(*history.get(best_iteration, 0)),
+            _ => None,
+        };
+
+        let (mut checkpoint_round, mut checkpoint_time) = (start, Instant::now());
+        for i in start..(start + self.iterations) {
+            // Every tree of the round is fit to the same gradients, and
+            // their average is added to the predictions.
+            let mut trees = Vec::with_capacity(self.num_parallel_tree);
+            for _ in 0..self.num_parallel_tree {
+                // Sampling can reweight the gradients, so a tree fit to
+                // a reweighted sample, is fit to a reweighted copy of them.
+                let sample = self.sample_index(&mut rng, &data_index, &grad, &hess)?;
+                let reweighted = sample.reweight(&grad, &hess);
+                let (g, h) = match &reweighted {
+                    Some((g, h)) => (g.as_slice(), h.as_slice()),
+                    None => (grad.as_slice(), hess.as_slice()),
+                };
+                // We will eventually use the excluded index.
+                let chosen_index = sample.chosen;
+                let col_index = self.sample_columns(&mut rng, data.cols());
+                let renew_index = match self.objective_type {
+                    ObjectiveType::QuantileLoss => Some(chosen_index.clone()),
+                    _ => None,
+                };
+                let mut tree = Tree::new();
+                tree.learning_rate = Some(self.learning_rate);
+
+                tree.fit_profiled(
+                    &bdata,
+                    chosen_index,
+                    &col_index,
+                    cuts,
+                    g,
+                    h,
+                    splitter,
+                    self.max_leaves,
+                    self.max_depth,
+                    self.parallel,
+                    &self.sample_method,
+                    &self.grow_policy,
+                    self.max_histogram_memory,
+                    &mut profiler,
+                )?;
+                if let Some(index) = renew_index {
+                    let w = round_weight.as_deref().unwrap_or(sample_weight);
+                    self.renew_quantile_leaves(&mut tree, data, y, &yhat, w, &index);
+                }
+                if self.num_parallel_tree > 1 {
+                    tree.scale_leaves(1. / self.num_parallel_tree as f32);
+                }
+                trees.push(tree);
+            }
+            profiler.time(Phase::Prediction, || {
+                for tree in trees.iter() {
+                    self.update_predictions_inplace(&mut yhat, tree, data)
+                }
+            });
 
             // Update Evaluation data, if it's needed.
             if let Some(eval_sets) = &mut evaluation_sets {
@@ -485,7 +2198,11 @@ impl GradientBooster {
                 }
                 let mut metrics: Vec<f64> = Vec::new();
                 for (eval_i, (data, y, w, yhat)) in eval_sets.iter_mut().enumerate() {
-                    self.update_predictions_inplace(yhat, &tree, data);
+                    profiler.time(Phase::Prediction, || {
+                        for tree in trees.iter() {
+                            self.update_predictions_inplace(yhat, tree, *data)
+                        }
+                    });
                     let (metric_fn, maximize) = self.get_metric_fn();
                     let m = metric_fn(y, yhat, w);
                     // If early stopping rounds are defined, and this is the first
@@ -526,49 +2243,379 @@ impl GradientBooster {
                     history.append_row(metrics);
                 }
             }
-            self.trees.push(tree);
-            (grad, hess) = calc_grad_hess(y, &yhat, sample_weight);
+            self.trees.extend(trees);
+            if let Some(checkpoint) = &self.checkpoint {
+                let due = checkpoint
+                    .iterations
+                    .is_some_and(|n| i + 1 - checkpoint_round >= n)
+                    || checkpoint
+                        .seconds
+                        .is_some_and(|s| checkpoint_time.elapsed().as_secs_f64() >= s);
+                if due {
+                    self.save_checkpoint(&checkpoint.path)?;
+                    (checkpoint_round, checkpoint_time) = (i + 1, Instant::now());
+                }
+            }
+            round_weight = self.round_sample_weight(i + 1, sample_weight)?;
+            (grad, hess) = profiler.time(Phase::Gradient, || {
+                calc_grad_hess(y, &yhat, round_weight.as_deref().unwrap_or(sample_weight))
+            });
+            if self.check_gradients {
+                self.check_finite_gradients(&grad, &hess, &yhat, i + 1)?;
+            }
+            profile_report.iterations.push(profiler.take());
+        }
+        self.cuts = Some(cuts.clone());
+        if self.profile {
+            self.profile_report = Some(profile_report);
         }
         Ok(())
     }
 
+    /// The sample weights of a boosting round, as adjusted by the sample weight hook,
+    /// or None without a hook, when the sample weights are used as they are.
+    fn round_sample_weight(
+        &self,
+        round: usize,
+        sample_weight: &[f64],
+    ) -> Result<Option<Vec<f64>>, ForustError> {
+        let hook = match &self.sample_weight_hook {
+            Some(hook) => hook,
+            None => return Ok(None),
+        };
+        let mut weights = sample_weight.to_vec();
+        hook(round, &mut weights);
+        if let Some((i, w)) = weights
+            .iter()
+            .enumerate()
+            .find(|(_, w)| !(w.is_finite() && **w >= 0.))
+        {
+            return Err(ForustError::InvalidWeight(i, *w));
+        }
+        Ok(Some(weights))
+    }
+
+    /// Return an error for the first row, with a gradient, or hessian, that isn't finite.
+    fn check_finite_gradients(
+        &self,
+        grad: &[f32],
+        hess: &[f32],
+        yhat: &[f64],
+        iteration: usize,
+    ) -> Result<(), ForustError> {
+        match grad
+            .iter()
+            .zip(hess)
+            .position(|(g, h)| !(g.is_finite() && h.is_finite()))
+        {
+            Some(row) => Err(ForustError::NonFiniteGradient {
+                objective: format!("{:?}", self.objective_type),
+                iteration,
+                row,
+                yhat: yhat[row],
+            }),
+            None => Ok(()),
+        }
+    }
+
     fn update_best_iteration(&mut self, i: usize) {
         self.best_iteration = Some(i);
-        self.prediction_iteration = Some(i + 1);
+        self.prediction_iteration = Some((i + 1) * self.num_parallel_tree);
     }
 
-    fn update_predictions_inplace(&self, yhat: &mut [f64], tree: &Tree, data: &Matrix<f64>) {
-        let preds = tree.predict(data, self.parallel, &self.missing);
-        yhat.iter_mut().zip(preds).for_each(|(i, j)| *i += j);
+    /// Replace the leaf weights of a tree fit to the quantile loss, with the quantile of
+    /// the residuals of the records in each leaf, scaled by the learning rate. The
+    /// gradient of the loss only has the sign of the residual, so the weights
+    /// calculated when growing the tree don't depend on the scale of the target.
+    fn renew_quantile_leaves<D: DataMatrix>(
+        &self,
+        tree: &mut Tree,
+        data: &D,
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+        index: &[usize],
+    ) {
+        let mut leaves: HashMap<usize, (Vec<f64>, Vec<f64>)> = HashMap::new();
+        for i in index.iter().copied() {
+            let leaf = leaves
+                .entry(tree.predict_leaf(data, i, &self.missing))
+                .or_default();
+            leaf.0.push(y[i] - yhat[i]);
+            leaf.1.push(sample_weight[i]);
+        }
+        for (leaf, (residuals, weights)) in leaves {
+            if weights.iter().sum::<f64>() > 0. {
+                let q = percentiles(&residuals, &weights, &[self.quantile])[0];
+                tree.nodes[leaf].weight_value = (q * self.learning_rate as f64) as f32;
+            }
+        }
     }
 
-    /// Fit the gradient booster on a provided dataset without any weights.
+    /// Predict with a single tree of the booster, imputing the missing records
+    /// first, if the booster has an imputer.
+    pub(crate) fn predict_tree<D: DataMatrix>(
+        &self,
+        tree: &Tree,
+        data: &D,
+        parallel: bool,
+    ) -> Vec<f64> {
+        match &self.imputer {
+            Some(imputer) => {
+                tree.predict(&imputer.view(data, self.missing), parallel, &self.missing)
+            }
+            None => tree.predict(data, parallel, &self.missing),
+        }
+    }
+
+    /// Predict with every tree in the booster, ignoring the `prediction_iteration`.
+    fn predict_all_trees<D: DataMatrix>(&self, data: &D) -> Vec<f64> {
+        let mut yhat = vec![self.base_score; data.rows()];
+        for tree in self.trees.iter() {
+            self.update_predictions_inplace(&mut yhat, tree, data);
+        }
+        yhat
+    }
+
+    fn update_predictions_inplace<D: DataMatrix>(&self, yhat: &mut [f64], tree: &Tree, data: &D) {
+        let preds = tree.predict(data, self.parallel, &self.missing);
+        yhat.iter_mut().zip(preds).for_each(|(i, j)| *i += j);
+    }
+
+    /// Fit the gradient booster on a provided dataset without any weights.
     ///
     /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
     /// * `y` - Either a pandas Series, or a 1 dimensional numpy array.
-    pub fn fit_unweighted(
+    pub fn fit_unweighted<T: FloatData<T>>(
         &mut self,
-        data: &Matrix<f64>,
-        y: &[f64],
-        evaluation_data: Option<Vec<EvaluationData>>,
+        data: &Matrix<T>,
+        y: &[T],
+        evaluation_data: Option<Vec<EvaluationData<T>>>,
     ) -> Result<(), ForustError> {
-        let sample_weight = vec![1.0; data.rows];
+        let sample_weight = vec![T::ONE; data.rows];
         self.fit(data, y, &sample_weight, evaluation_data)
     }
 
     /// Generate predictions on data using the gradient booster.
     ///
+    /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array,
+    ///   or a sparse `CscMatrix`.
+    pub fn predict<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        match self.get_prediction_thread_pool(parallel) {
+            Some(pool) => pool.install(|| self.predict_trees(data, parallel)),
+            None => self.predict_trees(data, parallel),
+        }
+    }
+
+    /// Predict a single record, without building a `Matrix`, or allocating, for serving
+    /// predictions one record at a time. The prediction is the same as `predict` gives
+    /// for the record, including the imputation of missing values, if the booster has
+    /// an imputer.
+    ///
+    /// * `row` - The value of each feature of the record. This panics if the row has
+    ///   fewer values than the data the booster was fit on.
+    pub fn predict_row<T: FloatData<T>>(&self, row: &[T]) -> f64 {
+        let trees = self.get_prediction_trees();
+        match &self.imputer {
+            Some(imputer) => {
+                let value = |feature: usize| {
+                    let v = row[feature].to_f64();
+                    if is_missing(&v, &self.missing) {
+                        imputer.values[feature]
+                    } else {
+                        v
+                    }
+                };
+                trees.iter().fold(self.base_score, |p, tree| {
+                    p + tree.predict_row_with(value, &self.missing)
+                })
+            }
+            None => trees.iter().fold(self.base_score, |p, tree| {
+                p + tree.predict_row_from_row_slice(row, &self.missing)
+            }),
+        }
+    }
+
+    /// Explain the prediction of a single record, with the path it follows through each
+    /// tree used for prediction. The prediction is the base score, plus the leaf value
+    /// of each path. Missing values are imputed first, if the booster has an imputer.
+    ///
+    /// * `row` - The value of each feature of the record. This panics if the row has
+    ///   fewer values than the data the booster was fit on.
+    pub fn explain_row<T: FloatData<T>>(&self, row: &[T]) -> Vec<DecisionPath> {
+        let value = |feature: usize| {
+            let v = row[feature].to_f64();
+            match &self.imputer {
+                Some(imputer) if is_missing(&v, &self.missing) => imputer.values[feature],
+                _ => v,
+            }
+        };
+        self.get_prediction_trees()
+            .iter()
+            .map(|tree| tree.decision_path(value, &self.missing))
+            .collect()
+    }
+
+    /// Predict the records of an iterator of rows, such as rows read from a database
+    /// cursor, one at a time, without collecting them into a matrix. The predictions
+    /// are made lazily, as the returned iterator is consumed, with `predict_row`.
+    ///
+    /// * `rows` - The records to predict, each row is the value of each feature.
+    pub fn predict_iter<'a, T, R, I>(&'a self, rows: I) -> impl Iterator<Item = f64> + 'a
+    where
+        T: FloatData<T>,
+        R: AsRef<[T]>,
+        I: IntoIterator<Item = R>,
+        I::IntoIter: 'a,
+    {
+        rows.into_iter()
+            .map(move |row| self.predict_row(row.as_ref()))
+    }
+
+    /// Predict the probability of the positive class, for a booster with the
+    /// "LogLoss" objective.
+    ///
     /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
-    pub fn predict(&self, data: &Matrix<f64>, parallel: bool) -> Vec<f64> {
-        let mut init_preds = vec![self.base_score; data.rows];
-        self.get_prediction_trees().iter().for_each(|tree| {
-            for (p_, val) in init_preds
-                .iter_mut()
-                .zip(tree.predict(data, parallel, &self.missing))
-            {
-                *p_ += val;
+    /// * `parallel` - Predict in parallel.
+    /// * `calibrated` - Should the calibration of the booster be applied, if one has been fit.
+    pub fn predict_proba(
+        &self,
+        data: &Matrix<f64>,
+        parallel: bool,
+        calibrated: bool,
+    ) -> Result<Vec<f64>, ForustError> {
+        if self.objective_type != ObjectiveType::LogLoss {
+            return Err(ForustError::UnsupportedObjective {
+                objective: format!("{:?}", self.objective_type),
+                reason: "only LogLoss can predict probabilities".to_string(),
+            });
+        }
+        let scores = self.predict(data, parallel);
+        match (&self.calibration, calibrated) {
+            (Some(calibration), true) => Ok(calibration.transform(&scores)),
+            _ => Ok(scores.iter().map(|s| 1. / (1. + (-s).exp())).collect()),
+        }
+    }
+
+    /// Check the target, and weights, of a calibration set have a value for each row.
+    fn validate_calibration_lengths(
+        data: &Matrix<f64>,
+        y: &[f64],
+        sample_weight: Option<&[f64]>,
+    ) -> Result<(), ForustError> {
+        for (name, len) in [
+            ("y", Some(y.len())),
+            ("sample_weight", sample_weight.map(|w| w.len())),
+        ] {
+            if let Some(len) = len.filter(|len| *len != data.rows) {
+                return Err(ForustError::LengthMismatch(
+                    name.to_string(),
+                    len,
+                    data.rows,
+                ));
             }
-        });
+        }
+        Ok(())
+    }
+
+    /// Fit a sigmoid (Platt scaling) calibration of the raw scores of the booster,
+    /// on a held out dataset. The calibration is saved with the booster, and applied
+    /// by `predict_proba`.
+    ///
+    /// * `data` - Data the booster wasn't trained on.
+    /// * `y` - The binary target variable.
+    /// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+    pub fn calibrate_sigmoid(
+        &mut self,
+        data: &Matrix<f64>,
+        y: &[f64],
+        sample_weight: Option<&[f64]>,
+    ) -> Result<(), ForustError> {
+        if self.objective_type != ObjectiveType::LogLoss {
+            return Err(ForustError::UnsupportedObjective {
+                objective: format!("{:?}", self.objective_type),
+                reason: "only LogLoss can calibrate probabilities".to_string(),
+            });
+        }
+        Self::validate_calibration_lengths(data, y, sample_weight)?;
+        let scores = self.predict(data, self.parallel);
+        let ones;
+        let sample_weight = match sample_weight {
+            Some(w) => w,
+            None => {
+                ones = vec![1.; y.len()];
+                &ones
+            }
+        };
+        self.calibration = Some(Calibration::fit_sigmoid(&scores, y, sample_weight)?);
+        Ok(())
+    }
+
+    /// Fit an isotonic calibration of the raw scores of the booster, on a held out
+    /// dataset. The calibration is saved with the booster, and applied by `predict_proba`.
+    /// Isotonic calibration is more flexible than the sigmoid calibration, but needs more
+    /// data to avoid overfitting.
+    ///
+    /// * `data` - Data the booster wasn't trained on.
+    /// * `y` - The binary target variable.
+    /// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+    pub fn calibrate_isotonic(
+        &mut self,
+        data: &Matrix<f64>,
+        y: &[f64],
+        sample_weight: Option<&[f64]>,
+    ) -> Result<(), ForustError> {
+        if self.objective_type != ObjectiveType::LogLoss {
+            return Err(ForustError::UnsupportedObjective {
+                objective: format!("{:?}", self.objective_type),
+                reason: "only LogLoss can calibrate probabilities".to_string(),
+            });
+        }
+        Self::validate_calibration_lengths(data, y, sample_weight)?;
+        let scores = self.predict(data, self.parallel);
+        let ones;
+        let sample_weight = match sample_weight {
+            Some(w) => w,
+            None => {
+                ones = vec![1.; y.len()];
+                &ones
+            }
+        };
+        self.calibration = Some(Calibration::fit_isotonic(&scores, y, sample_weight)?);
+        Ok(())
+    }
+
+    fn predict_trees<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        match &self.imputer {
+            Some(imputer) => self.predict_tree_sum(&imputer.view(data, self.missing), parallel),
+            None => self.predict_tree_sum(data, parallel),
+        }
+    }
+
+    /// Sum the predictions of the trees, a block of rows at a time, so each block is read
+    /// from the cache by all of the trees, instead of the whole data being read by each
+    /// tree, which for large data and many trees is mostly read from memory.
+    fn predict_tree_sum<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        let trees = self.get_prediction_trees();
+        let block_rows =
+            (PREDICTION_BLOCK_BYTES / (data.cols().max(1) * size_of::<f64>())).clamp(64, 8192);
+        let predict_block = |(b, preds): (usize, &mut [f64])| {
+            for tree in trees {
+                tree.add_block_predictions(data, b * block_rows, preds, &self.missing);
+            }
+        };
+        let mut init_preds = vec![self.base_score; data.rows()];
+        if parallel {
+            init_preds
+                .par_chunks_mut(block_rows)
+                .enumerate()
+                .for_each(predict_block);
+        } else {
+            init_preds
+                .chunks_mut(block_rows)
+                .enumerate()
+                .for_each(predict_block);
+        }
         init_preds
     }
 
@@ -577,6 +2624,30 @@ impl GradientBooster {
         data: &Matrix<f64>,
         method: ContributionsMethod,
         parallel: bool,
+    ) -> Vec<f64> {
+        let imputed;
+        let imputed_matrix;
+        let data = match &self.imputer {
+            Some(imputer) => {
+                imputed = imputer.transform(data, self.missing);
+                imputed_matrix = imputed.as_matrix();
+                &imputed_matrix
+            }
+            None => data,
+        };
+        match self.get_prediction_thread_pool(parallel) {
+            Some(pool) => {
+                pool.install(|| self.predict_contributions_method(data, method, parallel))
+            }
+            None => self.predict_contributions_method(data, method, parallel),
+        }
+    }
+
+    fn predict_contributions_method(
+        &self,
+        data: &Matrix<f64>,
+        method: ContributionsMethod,
+        parallel: bool,
     ) -> Vec<f64> {
         match method {
             ContributionsMethod::Average => self.predict_contributions_average(data, parallel),
@@ -718,9 +2789,18 @@ impl GradientBooster {
         contribs
     }
 
+    /// Get the learning rate each tree of the booster was fit with, the learning rate can
+    /// change between fits with `warm_start`. The leaf weights of each tree are already
+    /// scaled by its learning rate, so predictions don't depend on the current `learning_rate`.
+    /// The learning rate of trees that weren't fit by the booster, such as imported
+    /// trees, or trees of models saved before it was recorded, is None.
+    pub fn get_tree_learning_rates(&self) -> Vec<Option<f32>> {
+        self.trees.iter().map(|t| t.learning_rate).collect()
+    }
+
     /// Get the a reference to the trees for predicting, ensureing that the right number of
     /// trees are used.
-    fn get_prediction_trees(&self) -> &[Tree] {
+    pub fn get_prediction_trees(&self) -> &[Tree] {
         let n_iterations = self.prediction_iteration.unwrap_or(self.trees.len());
         &self.trees[..n_iterations]
     }
@@ -806,53 +2886,667 @@ impl GradientBooster {
         pd + self.base_score
     }
 
+    /// Given a set of features, and the value of each of those features, return the joint
+    /// partial dependence value of those values in the model.
+    ///
+    /// * `values` - Pairs of feature index, and the value to calculate the partial dependence for.
+    pub fn values_partial_dependence(&self, values: &[(usize, f64)]) -> f64 {
+        let pd: f64 = if self.parallel {
+            self.get_prediction_trees()
+                .par_iter()
+                .map(|t| t.values_partial_dependence(values, &self.missing))
+                .sum()
+        } else {
+            self.get_prediction_trees()
+                .iter()
+                .map(|t| t.values_partial_dependence(values, &self.missing))
+                .sum()
+        };
+        pd + self.base_score
+    }
+
+    /// Calculate the partial dependence of a feature, by averaging the predictions of the
+    /// booster over a reference dataset, with the feature set to each value of a grid.
+    /// Unlike `value_partial_dependence`, which weights the branches of each tree by their
+    /// cover in the training data, the average can be weighted, such as by the exposure
+    /// of each record, so the partial dependence reflects the weighted population.
+    ///
+    /// * `feature` - The index of the feature.
+    /// * `grid` - The values of the feature to calculate the partial dependence for.
+    /// * `data` - Reference data the predictions are averaged over.
+    /// * `sample_weight` - Instance weights of the reference data, if None a weight of 1 is used for every record.
+    pub fn data_partial_dependence<D: DataMatrix>(
+        &self,
+        feature: usize,
+        grid: &[f64],
+        data: &D,
+        sample_weight: Option<&[f64]>,
+    ) -> Result<Vec<f64>, ForustError> {
+        validate_feature_index(feature, data.cols())?;
+        let total_weight = match sample_weight {
+            Some(w) if w.len() != data.rows() => {
+                return Err(ForustError::LengthMismatch(
+                    "sample_weight".to_string(),
+                    w.len(),
+                    data.rows(),
+                ))
+            }
+            Some(w) => w.iter().sum(),
+            None => data.rows() as f64,
+        };
+        if total_weight.is_nan() || total_weight <= 0. {
+            return Err(ForustError::InvalidParameter {
+                name: "sample_weight".to_string(),
+                constraint: "weights with a positive sum".to_string(),
+                value: format!("weights summing to {}", total_weight),
+            });
+        }
+        let pd = grid
+            .iter()
+            .map(|v| {
+                let values = [(feature, *v)];
+                let preds = self.predict(&FixedColumns::new(data, &values), self.parallel);
+                let total: f64 = match sample_weight {
+                    Some(w) => preds.iter().zip(w).map(|(p, w)| p * w).sum(),
+                    None => preds.iter().sum(),
+                };
+                total / total_weight
+            })
+            .collect();
+        Ok(pd)
+    }
+
+    /// Calculate the partial dependence of a feature, over a grid of its values in the
+    /// data, returning pairs of each value of the grid, and its partial dependence.
+    /// If sample weights are provided, the partial dependence is the weighted average of
+    /// the predictions over the data, see `data_partial_dependence`. Otherwise it's
+    /// calculated from the cover of the training data in each tree, see
+    /// `value_partial_dependence`.
+    ///
+    /// * `feature` - The index of the feature.
+    /// * `data` - Data the grid is generated from.
+    /// * `grid` - How the values of the feature are chosen, such as the 5th to 95th percentiles.
+    /// * `sample_weight` - Instance weights of the data, the percentiles of the grid are weighted
+    ///   by them too.
+    pub fn partial_dependence<D: DataMatrix>(
+        &self,
+        feature: usize,
+        data: &D,
+        grid: &PartialDependenceGrid,
+        sample_weight: Option<&[f64]>,
+    ) -> Result<Vec<(f64, f64)>, ForustError> {
+        validate_feature_index(feature, data.cols())?;
+        let values = grid.values(&data.column(feature), sample_weight, &self.missing)?;
+        let pd = match sample_weight {
+            Some(_) => self.data_partial_dependence(feature, &values, data, sample_weight)?,
+            None => values
+                .iter()
+                .map(|v| self.value_partial_dependence(feature, *v))
+                .collect(),
+        };
+        Ok(values.into_iter().zip(pd).collect())
+    }
+
+    /// Calculate the partial dependence of a feature, as in `partial_dependence`, returned
+    /// as a serializable result, with the name of the feature, if the booster has feature names.
+    ///
+    /// * `feature` - The index of the feature.
+    /// * `data` - Data the grid is generated from.
+    /// * `grid` - How the values of the feature are chosen, such as the 5th to 95th percentiles.
+    /// * `sample_weight` - Instance weights of the data, the percentiles of the grid are weighted
+    ///   by them too.
+    pub fn partial_dependence_result<D: DataMatrix>(
+        &self,
+        feature: usize,
+        data: &D,
+        grid: &PartialDependenceGrid,
+        sample_weight: Option<&[f64]>,
+    ) -> Result<PartialDependenceResult, ForustError> {
+        let pd = self.partial_dependence(feature, data, grid, sample_weight)?;
+        let name = self
+            .feature_names
+            .as_ref()
+            .and_then(|n| n.get(feature).cloned());
+        Ok(PartialDependenceResult::new(feature, name, &pd))
+    }
+
+    /// Calculate the partial dependence of a categorical feature. Rather than evaluating
+    /// the feature over a numeric grid, every distinct category present in the provided
+    /// data is evaluated. The returned pairs contain the category value, and the average
+    /// prediction of the model for that category, in ascending category order.
+    /// If the feature contains missing values, the missing category is returned last.
+    ///
+    /// * `feature` - The index of the categorical feature.
+    /// * `data` - Data used to find the categories of the feature.
+    pub fn categorical_partial_dependence(
+        &self,
+        feature: usize,
+        data: &Matrix<f64>,
+    ) -> Vec<(f64, f64)> {
+        let mut has_missing = false;
+        let mut categories = Vec::new();
+        for v in data.get_col(feature) {
+            if is_missing(v, &self.missing) {
+                has_missing = true;
+            } else {
+                categories.push(*v);
+            }
+        }
+        categories.sort_by(|a, b| a.total_cmp(b));
+        categories.dedup();
+        if has_missing {
+            categories.push(self.missing);
+        }
+        categories
+            .into_iter()
+            .map(|c| (c, self.value_partial_dependence(feature, c)))
+            .collect()
+    }
+
+    /// Calculate the two way partial dependence surface of a pair of features.
+    /// The returned matrix has a row for every value in the grid of the first feature,
+    /// and a column for every value in the grid of the second feature.
+    ///
+    /// * `features` - The index of the two features.
+    /// * `grid` - The values to evaluate for each of the features.
+    pub fn partial_dependence_2d(
+        &self,
+        features: (usize, usize),
+        grid: (&[f64], &[f64]),
+    ) -> RowMajorMatrix<f64> {
+        let (f1, f2) = features;
+        let (grid1, grid2) = grid;
+        let mut surface = RowMajorMatrix::new(Vec::new(), 0, grid2.len());
+        for v1 in grid1 {
+            let row = grid2
+                .iter()
+                .map(|v2| self.values_partial_dependence(&[(f1, *v1), (f2, *v2)]))
+                .collect();
+            surface.append_row(row);
+        }
+        surface
+    }
+
+    /// Multiply the leaf weights of every tree of the booster by a factor, shrinking the
+    /// trees after training, such as to blend the booster with a prior model, without
+    /// retraining. The base score is not scaled. The evaluation history no longer matches
+    /// the predictions, so is cleared, and any calibration should be fit again.
+    ///
+    /// * `factor` - The finite value the leaf weights are multiplied by.
+    pub fn scale_leaves(&mut self, factor: f32) -> Result<(), ForustError> {
+        validate_scale_factor(factor)?;
+        self.trees.iter_mut().for_each(|t| t.scale_leaves(factor));
+        self.evaluation_history = None;
+        Ok(())
+    }
+
+    /// Multiply the leaf weights of a single tree of the booster by a factor,
+    /// see `scale_leaves`.
+    ///
+    /// * `tree` - The index of the tree, in the trees of the booster.
+    /// * `factor` - The finite value the leaf weights are multiplied by.
+    pub fn scale_tree_leaves(&mut self, tree: usize, factor: f32) -> Result<(), ForustError> {
+        validate_scale_factor(factor)?;
+        let n_trees = self.trees.len();
+        let t = self
+            .trees
+            .get_mut(tree)
+            .ok_or_else(|| ForustError::InvalidParameter {
+                name: "tree".to_string(),
+                constraint: format!("the index of one of the {} trees", n_trees),
+                value: tree.to_string(),
+            })?;
+        t.scale_leaves(factor);
+        self.evaluation_history = None;
+        Ok(())
+    }
+
+    /// Remove trees from the booster, whose removal doesn't worsen the metric on an evaluation
+    /// set by more than `tolerance`. Trees are tried in order of their mean absolute
+    /// contribution to the predictions, smallest first, and a tree is removed if the metric
+    /// of the pruned booster is within `tolerance` of the metric of the original booster.
+    /// Only the trees used for prediction are kept, and the pruned booster is treated as a
+    /// plain ensemble of trees, so the evaluation history, and best iteration are cleared.
+    /// Returns the number of trees removed.
+    ///
+    /// * `data` - The evaluation data.
+    /// * `y` - The target of the evaluation data.
+    /// * `sample_weight` - Instance weights of the evaluation data.
+    /// * `metric` - Metric used to evaluate the predictions, if None is passed,
+    ///   the evaluation metric of the booster will be used.
+    /// * `tolerance` - The largest degradation of the metric allowed.
+    pub fn prune_trees(
+        &mut self,
+        data: &Matrix<f64>,
+        y: &[f64],
+        sample_weight: &[f64],
+        metric: Option<Metric>,
+        tolerance: f64,
+    ) -> Result<usize, ForustError> {
+        if !(tolerance.is_finite() && tolerance >= 0.) {
+            return Err(ForustError::InvalidParameter {
+                name: "tolerance".to_string(),
+                constraint: "finite value of at least 0".to_string(),
+                value: tolerance.to_string(),
+            });
+        }
+        self.validate_inputs(data, y, sample_weight)?;
+        let (metric_fn, maximize) = metric_callables(&metric.unwrap_or_else(|| self.get_metric()));
+        let degradation = |m: f64, baseline: f64| {
+            if maximize {
+                baseline - m
+            } else {
+                m - baseline
+            }
+        };
+
+        let trees = self.get_prediction_trees();
+        let tree_preds: Vec<Vec<f64>> = trees
+            .iter()
+            .map(|t| self.predict_tree(t, data, self.parallel))
+            .collect();
+        let mut yhat = vec![self.base_score; data.rows];
+        for preds in tree_preds.iter() {
+            yhat.iter_mut().zip(preds).for_each(|(i, j)| *i += j);
+        }
+        let baseline = metric_fn(y, &yhat, sample_weight);
+
+        let w_sum: f64 = sample_weight.iter().sum();
+        let contribution = |preds: &[f64]| {
+            preds
+                .iter()
+                .zip(sample_weight)
+                .map(|(p, w)| p.abs() * w)
+                .sum::<f64>()
+                / w_sum
+        };
+        let mut order: Vec<usize> = (0..trees.len()).collect();
+        order.sort_by(|a, b| {
+            contribution(&tree_preds[*a]).total_cmp(&contribution(&tree_preds[*b]))
+        });
+
+        let mut keep = vec![true; trees.len()];
+        let mut pruned = yhat.clone();
+        for i in order {
+            pruned
+                .iter_mut()
+                .zip(&tree_preds[i])
+                .for_each(|(p, t)| *p -= t);
+            // NaN degradations are never within the tolerance.
+            if degradation(metric_fn(y, &pruned, sample_weight), baseline) <= tolerance {
+                keep[i] = false;
+            } else {
+                pruned
+                    .iter_mut()
+                    .zip(&tree_preds[i])
+                    .for_each(|(p, t)| *p += t);
+            }
+        }
+
+        let kept: Vec<Tree> = trees
+            .iter()
+            .zip(keep)
+            .filter(|(_, k)| *k)
+            .map(|(t, _)| t.clone())
+            .collect();
+        let removed = self.trees.len() - kept.len();
+        self.trees = kept;
+        self.num_parallel_tree = 1;
+        self.evaluation_history = None;
+        self.best_iteration = None;
+        self.prediction_iteration = None;
+        Ok(removed)
+    }
+
+    /// Calculate the permutation importance of every feature using the booster's predictions.
+    ///
+    /// * `data` - The evaluation data, the columns of this will be shuffled.
+    /// * `y` - The target of the evaluation data.
+    /// * `sample_weight` - Instance weights of the evaluation data.
+    /// * `metric` - Metric used to evaluate the predictions, if None is passed,
+    ///   the evaluation metric of the booster will be used.
+    /// * `n_repeats` - Number of times each feature should be shuffled.
+    /// * `seed` - Integer value used to seed the shuffling.
+    pub fn permutation_importance(
+        &self,
+        data: &Matrix<f64>,
+        y: &[f64],
+        sample_weight: &[f64],
+        metric: Option<Metric>,
+        n_repeats: usize,
+        seed: u64,
+    ) -> Result<PermutationImportance, ForustError> {
+        let metric = metric.unwrap_or_else(|| self.get_metric());
+        let mut importance = permutation_importance(
+            |d| self.predict(d, self.parallel),
+            data,
+            y,
+            sample_weight,
+            &metric,
+            n_repeats,
+            seed,
+        )?;
+        importance.feature_names = self.feature_names.clone();
+        Ok(importance)
+    }
+
+    /// The number of features the booster was fit on, which is the number of values
+    /// needed in each row to predict. It's taken from the feature names, or the cuts,
+    /// and for a booster with neither, such as one imported from another library, it's
+    /// the number of features up to the last one split on.
+    pub fn n_features(&self) -> usize {
+        if let Some(names) = &self.feature_names {
+            return names.len();
+        }
+        if let Some(cuts) = &self.cuts {
+            return cuts.cols;
+        }
+        self.trees
+            .iter()
+            .flat_map(|t| t.nodes.iter())
+            .filter(|n| !n.is_leaf)
+            .map(|n| n.split_feature + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Get the index of a feature from its name.
+    ///
+    /// * `name` - The name of the feature.
+    pub fn feature_index(&self, name: &str) -> Result<usize, ForustError> {
+        let names = self
+            .feature_names
+            .as_ref()
+            .ok_or_else(|| ForustError::InvalidParameter {
+                name: "feature".to_string(),
+                constraint: "a feature index, as the booster has no feature names".to_string(),
+                value: name.to_string(),
+            })?;
+        names
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| ForustError::InvalidParameter {
+                name: "feature".to_string(),
+                constraint: "the name of a feature of the booster".to_string(),
+                value: name.to_string(),
+            })
+    }
+
+    /// Given a value, return the partial dependence value of that value for the
+    /// named feature in the model.
+    ///
+    /// * `name` - The name of the feature.
+    /// * `value` - The value for which to calculate the partial dependence.
+    pub fn value_partial_dependence_by_name(
+        &self,
+        name: &str,
+        value: f64,
+    ) -> Result<f64, ForustError> {
+        Ok(self.value_partial_dependence(self.feature_index(name)?, value))
+    }
+
+    /// Dump the trees used for prediction as text, with the split features written with
+    /// their names, if the booster has feature names.
+    pub fn text_dump(&self) -> String {
+        self.get_prediction_trees()
+            .iter()
+            .enumerate()
+            .map(|(i, tree)| {
+                format!(
+                    "booster[{}]:\n{}",
+                    i,
+                    tree.text_dump(self.feature_names.as_deref())
+                )
+            })
+            .collect()
+    }
+
     /// Save a booster as a json object to a file.
     ///
     /// * `path` - Path to save booster.
     pub fn save_booster(&self, path: &str) -> Result<(), ForustError> {
+        self.save_json(path)
+    }
+
+    /// Save a booster as a json object to a file, the booster
+    /// can be loaded again with `load_json`.
+    ///
+    /// * `path` - Path to save booster.
+    pub fn save_json(&self, path: &str) -> Result<(), ForustError> {
         let model = self.json_dump()?;
         match fs::write(path, model) {
-            Err(e) => Err(ForustError::UnableToWrite(e.to_string())),
+            Err(e) => Err(ForustError::Io(e)),
             Ok(_) => Ok(()),
         }
     }
 
     /// Dump a booster as a json object
+    /// The json object includes the `format_version` of the model schema.
     pub fn json_dump(&self) -> Result<String, ForustError> {
-        match serde_json::to_string(self) {
+        let mut model = match serde_json::to_value(self) {
+            Ok(Value::Object(m)) => Ok(m),
+            Ok(_) => Err(ForustError::UnableToWrite(
+                "booster did not serialize to a json object".to_string(),
+            )),
+            Err(e) => Err(ForustError::Serialization(e)),
+        }?;
+        model.insert(
+            "format_version".to_string(),
+            Value::from(MODEL_FORMAT_VERSION),
+        );
+        match serde_json::to_string(&model) {
             Ok(s) => Ok(s),
-            Err(e) => Err(ForustError::UnableToWrite(e.to_string())),
+            Err(e) => Err(ForustError::Serialization(e)),
+        }
+    }
+
+    /// Dump a booster as a json object, that can be loaded by the upstream forust
+    /// package, in Python or Rust. Only the fields the upstream package knows about
+    /// are written, and the feature names are stored in the metadata, where the
+    /// upstream Python package reads them from. Models saved by the upstream package
+    /// can be loaded with `from_json`, or `load_booster`.
+    ///
+    /// Boosters with a `QuantileLoss` objective, calibration, or an imputer, can't be
+    /// exported, as the upstream package would predict differently with them.
+    pub fn upstream_json_dump(&self) -> Result<String, ForustError> {
+        let unsupported = if let ObjectiveType::QuantileLoss = self.objective_type {
+            Some("the QuantileLoss objective")
+        } else if self.calibration.is_some() {
+            Some("calibration")
+        } else if self.imputer.is_some() {
+            Some("an imputer")
+        } else {
+            None
+        };
+        if let Some(u) = unsupported {
+            return Err(ForustError::UnableToWrite(format!(
+                "a booster with {} can't be read by the upstream forust package",
+                u
+            )));
+        }
+        let mut model = match serde_json::to_value(self) {
+            Ok(Value::Object(m)) => Ok(m),
+            Ok(_) => Err(ForustError::UnableToWrite(
+                "booster did not serialize to a json object".to_string(),
+            )),
+            Err(e) => Err(ForustError::Serialization(e)),
+        }?;
+        model.retain(|k, _| UPSTREAM_FIELDS.contains(&k.as_str()));
+        // The upstream package reads the evaluation history as numbers, so a history
+        // with missing metric values is left out.
+        let history_has_nan = self
+            .evaluation_history
+            .as_ref()
+            .is_some_and(|h| h.data.iter().any(|v| v.is_nan()));
+        if history_has_nan {
+            model.insert("evaluation_history".to_string(), Value::Null);
+        }
+        if let (Some(names), Some(Value::Object(metadata))) =
+            (&self.feature_names, model.get_mut("metadata"))
+        {
+            metadata
+                .entry(UPSTREAM_FEATURE_NAMES_KEY)
+                .or_insert_with(|| Value::from(python_str_list(names)));
+        }
+        match serde_json::to_string(&model) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(ForustError::Serialization(e)),
         }
     }
 
-    /// Load a booster from Json string
+    /// Save a booster as a json object to a file, that can be loaded by the
+    /// upstream forust package, see `upstream_json_dump`.
+    ///
+    /// * `path` - Path to save booster.
+    pub fn save_upstream_json(&self, path: &str) -> Result<(), ForustError> {
+        let model = self.upstream_json_dump()?;
+        match fs::write(path, model) {
+            Err(e) => Err(ForustError::Io(e)),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Load a booster from Json string. Models saved with older versions
+    /// of the model schema are upgraded, with any fields added since then
+    /// set to their default values.
     ///
     /// * `json_str` - String object, which can be serialized to json.
     pub fn from_json(json_str: &str) -> Result<Self, ForustError> {
-        let model = serde_json::from_str::<GradientBooster>(json_str);
-        match model {
+        let mut model = match serde_json::from_str::<Value>(json_str) {
+            Ok(Value::Object(m)) => Ok(m),
+            Ok(_) => Err(ForustError::UnableToRead(
+                "booster json must be an object".to_string(),
+            )),
+            Err(e) => Err(ForustError::Serialization(e)),
+        }?;
+        // Models saved before the schema was versioned, have no version.
+        let version = match model.get("format_version") {
+            None => 0,
+            Some(v) => v.as_u64().ok_or_else(|| {
+                ForustError::UnableToRead(format!("invalid format_version {}", v))
+            })?,
+        };
+        if version > MODEL_FORMAT_VERSION {
+            return Err(ForustError::UnableToRead(format!(
+                "model format version {} is newer than the supported version {}",
+                version, MODEL_FORMAT_VERSION
+            )));
+        }
+        upgrade_model(&mut model, version);
+        match serde_json::from_value::<GradientBooster>(Value::Object(model)) {
             Ok(m) => Ok(m),
-            Err(e) => Err(ForustError::UnableToRead(e.to_string())),
+            Err(e) => Err(ForustError::Serialization(e)),
         }
     }
 
+    /// Load a checkpoint saved during training, ready to continue training. The booster
+    /// is set to warm start, with `iterations` set to the number of boosting rounds that
+    /// remained when the checkpoint was saved, so fitting it again on the same data
+    /// completes the interrupted training. The rounds after the checkpoint use a different
+    /// random sample than an uninterrupted fit would, when sampling is used.
+    ///
+    /// * `path` - Path of the checkpoint.
+    pub fn resume_from_checkpoint(path: &str) -> Result<Self, ForustError> {
+        let mut booster = Self::load_json(path)?;
+        let rounds = booster.trees.len() / booster.num_parallel_tree;
+        let target_rounds = booster
+            .checkpoint
+            .as_ref()
+            .and_then(|c| c.target_rounds)
+            .unwrap_or(booster.iterations);
+        booster.iterations = target_rounds.saturating_sub(rounds);
+        booster.warm_start = true;
+        Ok(booster)
+    }
+
+    /// Save a checkpoint of the booster during training. The booster is written to a
+    /// temporary file first, so an interruption never leaves a partial checkpoint.
+    fn save_checkpoint(&self, path: &str) -> Result<(), ForustError> {
+        let tmp_path = format!("{}.tmp", path);
+        self.save_json(&tmp_path)?;
+        fs::rename(&tmp_path, path).map_err(ForustError::Io)
+    }
+
     /// Load a booster from a path to a json booster object.
     ///
     /// * `path` - Path to load booster from.
     pub fn load_booster(path: &str) -> Result<Self, ForustError> {
+        Self::load_json(path)
+    }
+
+    /// Load a booster from a json file, saved with `save_json`.
+    ///
+    /// * `path` - Path to load booster from.
+    pub fn load_json(path: &str) -> Result<Self, ForustError> {
         let json_str = match fs::read_to_string(path) {
             Ok(s) => Ok(s),
-            Err(e) => Err(ForustError::UnableToRead(e.to_string())),
+            Err(e) => Err(ForustError::Io(e)),
         }?;
         Self::from_json(&json_str)
     }
 
-    // Set methods for paramters
-    /// Set the objective_type on the booster.
-    /// * `objective_type` - The objective type of the booster.
-    pub fn set_objective_type(mut self, objective_type: ObjectiveType) -> Self {
-        self.objective_type = objective_type;
-        self
+    /// Dump a booster in a compact binary format. The output starts with
+    /// a magic header, and a format version byte, followed by the booster.
+    pub fn binary_dump(&self) -> Result<Vec<u8>, ForustError> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.push(BINARY_FORMAT_VERSION);
+        match bincode::serialize_into(&mut bytes, self) {
+            Ok(_) => Ok(bytes),
+            Err(e) => Err(ForustError::UnableToWrite(e.to_string())),
+        }
+    }
+
+    /// Load a booster from bytes created with `binary_dump`.
+    ///
+    /// * `bytes` - Bytes of a booster saved in the binary format.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, ForustError> {
+        let header_len = BINARY_MAGIC.len() + 1;
+        if bytes.len() < header_len || &bytes[..BINARY_MAGIC.len()] != BINARY_MAGIC {
+            return Err(ForustError::UnableToRead(
+                "bytes are not a booster in the binary format".to_string(),
+            ));
+        }
+        let version = bytes[BINARY_MAGIC.len()];
+        if version != BINARY_FORMAT_VERSION {
+            return Err(ForustError::UnableToRead(format!(
+                "unsupported binary format version {}, expected {}",
+                version, BINARY_FORMAT_VERSION
+            )));
+        }
+        match bincode::deserialize(&bytes[header_len..]) {
+            Ok(m) => Ok(m),
+            Err(e) => Err(ForustError::UnableToRead(e.to_string())),
+        }
+    }
+
+    /// Save a booster to a file in the compact binary format.
+    ///
+    /// * `path` - Path to save booster.
+    pub fn save_binary(&self, path: &str) -> Result<(), ForustError> {
+        let model = self.binary_dump()?;
+        match fs::write(path, model) {
+            Err(e) => Err(ForustError::Io(e)),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Load a booster from a file saved with `save_binary`.
+    ///
+    /// * `path` - Path to load booster from.
+    pub fn load_binary(path: &str) -> Result<Self, ForustError> {
+        let bytes = match fs::read(path) {
+            Ok(b) => Ok(b),
+            Err(e) => Err(ForustError::Io(e)),
+        }?;
+        Self::from_binary(&bytes)
+    }
+
+    // Set methods for paramters
+    /// Set the objective_type on the booster.
+    /// * `objective_type` - The objective type of the booster.
+    pub fn set_objective_type(mut self, objective_type: ObjectiveType) -> Self {
+        self.objective_type = objective_type;
+        self
     }
 
     /// Set the iterations on the booster.
@@ -915,119 +3609,2711 @@ impl GradientBooster {
         self
     }
 
-    /// Set the base_score on the booster.
-    /// * `base_score` - The base score of the booster.
-    pub fn set_base_score(mut self, base_score: f64) -> Self {
-        self.base_score = base_score;
-        self
-    }
+    /// Set the base_score on the booster.
+    /// * `base_score` - The base score of the booster.
+    pub fn set_base_score(mut self, base_score: f64) -> Self {
+        self.base_score = base_score;
+        self
+    }
+
+    /// Set the base_score on the booster.
+    /// * `base_score` - The base score of the booster.
+    pub fn set_initialize_base_score(mut self, initialize_base_score: bool) -> Self {
+        self.initialize_base_score = initialize_base_score;
+        self
+    }
+
+    /// Set the parallel on the booster.
+    /// * `parallel` - Set if the booster should be trained in parallels.
+    pub fn set_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Set the allow_missing_splits on the booster.
+    /// * `allow_missing_splits` - Set if missing splits are allowed for the booster.
+    pub fn set_allow_missing_splits(mut self, allow_missing_splits: bool) -> Self {
+        self.allow_missing_splits = allow_missing_splits;
+        self
+    }
+
+    /// Set the monotone_constraints on the booster.
+    /// * `monotone_constraints` - The monotone constraints of the booster.
+    pub fn set_monotone_constraints(mut self, monotone_constraints: Option<ConstraintMap>) -> Self {
+        self.monotone_constraints = monotone_constraints;
+        self
+    }
+
+    /// Set the monotone_constraints on the booster, by the name of each feature.
+    /// The feature names must be set first.
+    /// * `monotone_constraints` - The monotone constraint of each named feature.
+    pub fn set_named_monotone_constraints(
+        self,
+        monotone_constraints: HashMap<String, Constraint>,
+    ) -> Result<Self, ForustError> {
+        let constraints = monotone_constraints
+            .iter()
+            .map(|(name, c)| Ok((self.feature_index(name)?, *c)))
+            .collect::<Result<ConstraintMap, ForustError>>()?;
+        Ok(self.set_monotone_constraints(Some(constraints)))
+    }
+
+    /// Set the monotone_constraint_method on the booster.
+    /// * `monotone_constraint_method` - How the monotone constraints are enforced.
+    pub fn set_monotone_constraint_method(
+        mut self,
+        monotone_constraint_method: MonotoneConstraintMethod,
+    ) -> Self {
+        self.monotone_constraint_method = monotone_constraint_method;
+        self
+    }
+
+    /// Set the interaction_constraints on the booster.
+    /// * `interaction_constraints` - Groups of features that are allowed to interact,
+    ///   by feature index, or feature name.
+    pub fn set_interaction_constraints(
+        mut self,
+        interaction_constraints: Option<InteractionConstraints>,
+    ) -> Self {
+        self.interaction_constraints = interaction_constraints;
+        self
+    }
+
+    /// Set the imputer of the booster, fit with `Imputer::fit`. The missing records of
+    /// the data the booster is fit, and predicts on, are replaced by the imputer, rather
+    /// than following the missing branch of each split.
+    /// * `imputer` - The fitted imputer.
+    pub fn set_imputer(mut self, imputer: Option<Imputer>) -> Self {
+        self.imputer = imputer;
+        self
+    }
+
+    /// Set the names of the features, each name is the name of a column of the data
+    /// the booster is fit on.
+    /// * `feature_names` - Names of the features.
+    pub fn set_feature_names(mut self, feature_names: Option<Vec<String>>) -> Self {
+        self.feature_names = feature_names;
+        self
+    }
+
+    /// Set the subsample on the booster.
+    /// * `subsample` - Percent of the data to randomly sample when training each tree.
+    pub fn set_subsample(mut self, subsample: f32) -> Self {
+        self.subsample = subsample;
+        self
+    }
+
+    /// Set the seed on the booster.
+    /// * `seed` - Integer value used to see any randomness used in the algorithm.
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set missing value of the booster
+    /// * `missing` - Float value to consider as missing.
+    pub fn set_missing(mut self, missing: f64) -> Self {
+        self.missing = missing;
+        self
+    }
+
+    /// Set create missing value of the booster
+    /// * `create_missing_branch` - Bool specifying if missing should get it's own
+    /// branch.
+    pub fn set_create_missing_branch(mut self, create_missing_branch: bool) -> Self {
+        self.create_missing_branch = create_missing_branch;
+        self
+    }
+
+    /// Set sample method on the booster.
+    /// * `sample_method` - Sample method.
+    pub fn set_sample_method(mut self, sample_method: SampleMethod) -> Self {
+        self.sample_method = sample_method;
+        self
+    }
+
+    /// Set sample method on the booster.
+    /// * `evaluation_metric` - Sample method.
+    pub fn set_evaluation_metric(mut self, evaluation_metric: Option<Metric>) -> Self {
+        self.evaluation_metric = evaluation_metric;
+        self
+    }
+
+    /// Set early stopping rounds.
+    /// * `early_stopping_rounds` - Early stoppings rounds.
+    pub fn set_early_stopping_rounds(mut self, early_stopping_rounds: Option<usize>) -> Self {
+        self.early_stopping_rounds = early_stopping_rounds;
+        self
+    }
+
+    /// Set the validation fraction on the booster.
+    /// * `validation_fraction` - Fraction of the training data to use as an evaluation set,
+    ///   when no evaluation data is provided.
+    pub fn set_validation_fraction(mut self, validation_fraction: Option<f64>) -> Self {
+        self.validation_fraction = validation_fraction;
+        self
+    }
+
+    /// Set if the validation fraction should be stratified on the target.
+    /// * `stratify_validation` - Sample the validation fraction for each unique target value.
+    pub fn set_stratify_validation(mut self, stratify_validation: bool) -> Self {
+        self.stratify_validation = stratify_validation;
+        self
+    }
+
+    /// Set the quantile predicted by the QuantileLoss objective.
+    /// * `quantile` - The quantile to predict.
+    pub fn set_quantile(mut self, quantile: f64) -> Self {
+        self.quantile = quantile;
+        self
+    }
+
+    /// Set the fraction of columns sampled for each tree.
+    /// * `colsample_bytree` - Fraction of the columns each tree can split on.
+    pub fn set_colsample_bytree(mut self, colsample_bytree: f64) -> Self {
+        self.colsample_bytree = colsample_bytree;
+        self
+    }
+
+    /// Set the number of trees grown at each iteration.
+    /// * `num_parallel_tree` - Number of trees averaged at each iteration.
+    pub fn set_num_parallel_tree(mut self, num_parallel_tree: usize) -> Self {
+        self.num_parallel_tree = num_parallel_tree;
+        self
+    }
+
+    /// Set if fitting should continue from the existing trees.
+    /// * `warm_start` - Add trees to the booster when fitting, rather than replacing them.
+    pub fn set_warm_start(mut self, warm_start: bool) -> Self {
+        self.warm_start = warm_start;
+        self
+    }
+
+    /// Set the class weights on the booster.
+    /// * `class_weight` - Weights for the records of each class, when using the LogLoss objective.
+    pub fn set_class_weight(mut self, class_weight: Option<ClassWeight>) -> Self {
+        self.class_weight = class_weight;
+        self
+    }
+
+    /// Set when checkpoints of the booster are saved during training.
+    /// * `checkpoint` - The path, and interval of the checkpoints.
+    pub fn set_checkpoint(mut self, checkpoint: Option<Checkpoint>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Set prediction iterations.
+    /// * `early_stopping_rounds` - Early stoppings rounds.
+    pub fn set_prediction_iteration(mut self, prediction_iteration: Option<usize>) -> Self {
+        self.prediction_iteration = prediction_iteration.map(|i| i + 1);
+        self
+    }
+
+    /// Set the number of threads used when fitting and predicting.
+    /// * `n_threads` - Number of threads, if None the global rayon thread pool is used.
+    pub fn set_n_threads(mut self, n_threads: Option<usize>) -> Self {
+        self.n_threads = n_threads;
+        self.thread_pool = OnceLock::new();
+        self
+    }
+
+    /// Set an existing thread pool to use when fitting and predicting, this replaces
+    /// any pool created from `n_threads`.
+    /// * `thread_pool` - The rayon thread pool to run on.
+    pub fn set_thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.n_threads = Some(thread_pool.current_num_threads());
+        self.thread_pool = OnceLock::from(thread_pool);
+        self
+    }
+
+    /// Set if the time spent in each phase of training should be recorded.
+    /// * `profile` - Record a profile report when fitting the booster.
+    pub fn set_profile(mut self, profile: bool) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Set the memory budget of the histograms of nodes waiting to be split.
+    /// * `max_histogram_memory` - Bytes the histograms can use before they're spilled to disk, if None they're always kept in memory.
+    pub fn set_max_histogram_memory(mut self, max_histogram_memory: Option<usize>) -> Self {
+        self.max_histogram_memory = max_histogram_memory;
+        self
+    }
+
+    /// Set if the gradients, and hessians, should be checked for values that aren't finite.
+    /// * `check_gradients` - Return an error when a gradient, or hessian, is NaN or infinite.
+    pub fn set_check_gradients(mut self, check_gradients: bool) -> Self {
+        self.check_gradients = check_gradients;
+        self
+    }
+
+    /// Set a function to adjust the sample weights of each boosting round, before its
+    /// gradients are calculated. The function is called with the round, counted from the
+    /// first tree of the booster, and a copy of the sample weights the booster is fit with,
+    /// which it changes in place. The weights must stay finite, and at least 0.
+    /// * `sample_weight_hook` - The function adjusting the weights, if None the sample weights are used as they are.
+    pub fn set_sample_weight_hook(mut self, sample_weight_hook: Option<SampleWeightHook>) -> Self {
+        self.sample_weight_hook = sample_weight_hook;
+        self
+    }
+
+    /// Get the report of the time spent in each phase of training, this is
+    /// only available after fitting a booster with `profile` set to true.
+    pub fn get_profile_report(&self) -> Option<&ProfileReport> {
+        self.profile_report.as_ref()
+    }
+
+    /// Insert metadata
+    /// * `key` - String value for the metadata key.
+    /// * `value` - value to assign to the metadata key.
+    pub fn insert_metadata(&mut self, key: String, value: String) {
+        self.metadata.insert(key, value);
+    }
+
+    /// Get Metadata
+    /// * `key` - Get the associated value for the metadata key.
+    pub fn get_metadata(&self, key: &String) -> Option<String> {
+        self.metadata.get(key).cloned()
+    }
+}
+
+/// Builder for a gradient booster, the parameters are validated when the
+/// booster is built. Parameters that are not set keep their default value.
+///
+/// ```
+/// use forust_ml::GradientBooster;
+///
+/// let booster = GradientBooster::builder()
+///     .iterations(50)
+///     .learning_rate(0.1)
+///     .build()
+///     .unwrap();
+/// assert!(GradientBooster::builder().nbins(1).build().is_err());
+/// ```
+#[derive(Default)]
+pub struct GradientBoosterBuilder {
+    booster: GradientBooster,
+}
+
+impl GradientBoosterBuilder {
+    /// Set the objective_type of the booster.
+    /// * `objective_type` - The objective function used to optimize.
+    pub fn objective_type(mut self, objective_type: ObjectiveType) -> Self {
+        self.booster.objective_type = objective_type;
+        self
+    }
+
+    /// Set the iterations of the booster.
+    /// * `iterations` - Total number of trees to train in the ensemble.
+    pub fn iterations(mut self, iterations: usize) -> Self {
+        self.booster.iterations = iterations;
+        self
+    }
+
+    /// Set the learning_rate of the booster.
+    /// * `learning_rate` - Step size to use at each iteration, must be greater than 0.
+    pub fn learning_rate(mut self, learning_rate: f32) -> Self {
+        self.booster.learning_rate = learning_rate;
+        self
+    }
+
+    /// Set the max_depth of the booster.
+    /// * `max_depth` - Maximum depth of an individual tree.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.booster.max_depth = max_depth;
+        self
+    }
+
+    /// Set the max_leaves of the booster.
+    /// * `max_leaves` - Maximum number of leaves allowed on a tree.
+    pub fn max_leaves(mut self, max_leaves: usize) -> Self {
+        self.booster.max_leaves = max_leaves;
+        self
+    }
+
+    /// Set the l2 of the booster.
+    /// * `l2` - L2 regularization term applied to the weights of the tree.
+    pub fn l2(mut self, l2: f32) -> Self {
+        self.booster.l2 = l2;
+        self
+    }
+
+    /// Set the gamma of the booster.
+    /// * `gamma` - The minimum amount of loss required to further split a node.
+    pub fn gamma(mut self, gamma: f32) -> Self {
+        self.booster.gamma = gamma;
+        self
+    }
+
+    /// Set the min_leaf_weight of the booster.
+    /// * `min_leaf_weight` - Minimum sum of the hessian values required to be in a node.
+    pub fn min_leaf_weight(mut self, min_leaf_weight: f32) -> Self {
+        self.booster.min_leaf_weight = min_leaf_weight;
+        self
+    }
+
+    /// Set the base_score of the booster.
+    /// * `base_score` - The initial prediction value of the model.
+    pub fn base_score(mut self, base_score: f64) -> Self {
+        self.booster.base_score = base_score;
+        self
+    }
+
+    /// Set the initialize_base_score of the booster.
+    /// * `initialize_base_score` - Should the base_score be calculated from the data when fitting.
+    pub fn initialize_base_score(mut self, initialize_base_score: bool) -> Self {
+        self.booster.initialize_base_score = initialize_base_score;
+        self
+    }
+
+    /// Set the nbins of the booster.
+    /// * `nbins` - Number of bins to partition the data, must be at least 2.
+    pub fn nbins(mut self, nbins: u16) -> Self {
+        self.booster.nbins = nbins;
+        self
+    }
+
+    /// Set the parallel of the booster.
+    /// * `parallel` - Should the booster be trained in parallel.
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.booster.parallel = parallel;
+        self
+    }
+
+    /// Set the allow_missing_splits of the booster.
+    /// * `allow_missing_splits` - Should splits that separate missing and non-missing values be allowed.
+    pub fn allow_missing_splits(mut self, allow_missing_splits: bool) -> Self {
+        self.booster.allow_missing_splits = allow_missing_splits;
+        self
+    }
+
+    /// Set the monotone_constraints of the booster.
+    /// * `monotone_constraints` - Constraints on the relationship between features and the target.
+    pub fn monotone_constraints(mut self, monotone_constraints: Option<ConstraintMap>) -> Self {
+        self.booster.monotone_constraints = monotone_constraints;
+        self
+    }
+
+    /// Set the monotone_constraint_method of the booster.
+    /// * `monotone_constraint_method` - How the monotone constraints are enforced, "Basic", or "Exact".
+    pub fn monotone_constraint_method(
+        mut self,
+        monotone_constraint_method: MonotoneConstraintMethod,
+    ) -> Self {
+        self.booster.monotone_constraint_method = monotone_constraint_method;
+        self
+    }
+
+    /// Set the interaction_constraints of the booster.
+    /// * `interaction_constraints` - Groups of features that are allowed to interact.
+    pub fn interaction_constraints(
+        mut self,
+        interaction_constraints: Option<InteractionConstraints>,
+    ) -> Self {
+        self.booster.interaction_constraints = interaction_constraints;
+        self
+    }
+
+    /// Set the subsample of the booster.
+    /// * `subsample` - Percent of records to sample when training a tree, must be in (0, 1].
+    pub fn subsample(mut self, subsample: f32) -> Self {
+        self.booster.subsample = subsample;
+        self
+    }
+
+    /// Set the top_rate of the booster.
+    /// * `top_rate` - Used only in goss. The retain ratio of large gradient data, must be in [0, 1].
+    pub fn top_rate(mut self, top_rate: f64) -> Self {
+        self.booster.top_rate = top_rate;
+        self
+    }
+
+    /// Set the other_rate of the booster.
+    /// * `other_rate` - Used only in goss. The retain ratio of small gradient data, must be in [0, 1].
+    pub fn other_rate(mut self, other_rate: f64) -> Self {
+        self.booster.other_rate = other_rate;
+        self
+    }
+
+    /// Set the seed of the booster.
+    /// * `seed` - Integer value used to seed any randomness used in the algorithm.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.booster.seed = seed;
+        self
+    }
+
+    /// Set the missing of the booster.
+    /// * `missing` - Value to consider missing.
+    pub fn missing(mut self, missing: f64) -> Self {
+        self.booster.missing = missing;
+        self
+    }
+
+    /// Set the create_missing_branch of the booster.
+    /// * `create_missing_branch` - Should missing be split out into its own separate branch.
+    pub fn create_missing_branch(mut self, create_missing_branch: bool) -> Self {
+        self.booster.create_missing_branch = create_missing_branch;
+        self
+    }
+
+    /// Set the sample_method of the booster.
+    /// * `sample_method` - The method used to sample records when training a tree.
+    pub fn sample_method(mut self, sample_method: SampleMethod) -> Self {
+        self.booster.sample_method = sample_method;
+        self
+    }
+
+    /// Set the grow_policy of the booster.
+    /// * `grow_policy` - The policy used to grow the trees.
+    pub fn grow_policy(mut self, grow_policy: GrowPolicy) -> Self {
+        self.booster.grow_policy = grow_policy;
+        self
+    }
+
+    /// Set the evaluation_metric of the booster.
+    /// * `evaluation_metric` - The evaluation metric to record at each iteration.
+    pub fn evaluation_metric(mut self, evaluation_metric: Option<Metric>) -> Self {
+        self.booster.evaluation_metric = evaluation_metric;
+        self
+    }
+
+    /// Set the early_stopping_rounds of the booster.
+    /// * `early_stopping_rounds` - Number of rounds the evaluation metric must improve within to keep training.
+    pub fn early_stopping_rounds(mut self, early_stopping_rounds: Option<usize>) -> Self {
+        self.booster.early_stopping_rounds = early_stopping_rounds;
+        self
+    }
+
+    /// Set the validation_fraction of the booster.
+    /// * `validation_fraction` - Fraction of the training data to use as an evaluation set, must be in (0, 1).
+    pub fn validation_fraction(mut self, validation_fraction: Option<f64>) -> Self {
+        self.booster.validation_fraction = validation_fraction;
+        self
+    }
+
+    /// Set the stratify_validation of the booster.
+    /// * `stratify_validation` - Sample the validation fraction for each unique target value.
+    pub fn stratify_validation(mut self, stratify_validation: bool) -> Self {
+        self.booster.stratify_validation = stratify_validation;
+        self
+    }
+
+    /// Set the quantile of the booster.
+    /// * `quantile` - The quantile predicted by the QuantileLoss objective, must be in (0, 1).
+    pub fn quantile(mut self, quantile: f64) -> Self {
+        self.booster.quantile = quantile;
+        self
+    }
+
+    /// Set the colsample_bytree of the booster.
+    /// * `colsample_bytree` - Fraction of the columns each tree can split on, must be in (0, 1].
+    pub fn colsample_bytree(mut self, colsample_bytree: f64) -> Self {
+        self.booster.colsample_bytree = colsample_bytree;
+        self
+    }
+
+    /// Set the num_parallel_tree of the booster.
+    /// * `num_parallel_tree` - Number of trees averaged at each iteration, must be at least 1.
+    pub fn num_parallel_tree(mut self, num_parallel_tree: usize) -> Self {
+        self.booster.num_parallel_tree = num_parallel_tree;
+        self
+    }
+
+    /// Set the warm_start of the booster.
+    /// * `warm_start` - Add trees to the booster when fitting, rather than replacing them.
+    pub fn warm_start(mut self, warm_start: bool) -> Self {
+        self.booster.warm_start = warm_start;
+        self
+    }
+
+    /// Set the class_weight of the booster.
+    /// * `class_weight` - Weights for the records of each class, only valid with the LogLoss objective.
+    pub fn class_weight(mut self, class_weight: Option<ClassWeight>) -> Self {
+        self.booster.class_weight = class_weight;
+        self
+    }
+
+    /// Set the checkpoint of the booster.
+    /// * `checkpoint` - The path, and interval of checkpoints saved during training.
+    pub fn checkpoint(mut self, checkpoint: Option<Checkpoint>) -> Self {
+        self.booster.checkpoint = checkpoint;
+        self
+    }
+
+    /// Set the n_threads of the booster.
+    /// * `n_threads` - Number of threads to use, if None the global rayon thread pool is used.
+    pub fn n_threads(mut self, n_threads: Option<usize>) -> Self {
+        self.booster = self.booster.set_n_threads(n_threads);
+        self
+    }
+
+    /// Set an existing thread pool for the booster to run on.
+    /// * `thread_pool` - The rayon thread pool to run on.
+    pub fn thread_pool(mut self, thread_pool: Arc<ThreadPool>) -> Self {
+        self.booster = self.booster.set_thread_pool(thread_pool);
+        self
+    }
+
+    /// Set the profile of the booster.
+    /// * `profile` - Should the time spent in each phase of training be recorded.
+    pub fn profile(mut self, profile: bool) -> Self {
+        self.booster.profile = profile;
+        self
+    }
+
+    /// Set the max_histogram_memory of the booster.
+    /// * `max_histogram_memory` - Bytes the histograms of nodes waiting to be split can use, before they're spilled to disk.
+    pub fn max_histogram_memory(mut self, max_histogram_memory: Option<usize>) -> Self {
+        self.booster.max_histogram_memory = max_histogram_memory;
+        self
+    }
+
+    /// Set the check_gradients of the booster.
+    /// * `check_gradients` - Should the gradients, and hessians, be checked for NaN, and infinite values.
+    pub fn check_gradients(mut self, check_gradients: bool) -> Self {
+        self.booster.check_gradients = check_gradients;
+        self
+    }
+
+    /// Set the sample_weight_hook of the booster.
+    /// * `sample_weight_hook` - A function adjusting the sample weights of each boosting round.
+    pub fn sample_weight_hook(mut self, sample_weight_hook: Option<SampleWeightHook>) -> Self {
+        self.booster.sample_weight_hook = sample_weight_hook;
+        self
+    }
+
+    /// Validate the parameters, and build the booster.
+    pub fn build(self) -> Result<GradientBooster, ForustError> {
+        self.booster.validate_parameters()?;
+        Ok(self.booster)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{row_major_to_column_major, MatrixData};
+    use crate::explain::Direction;
+    use crate::metric::log_loss;
+    use crate::preprocessing::ImputeStrategy;
+    use crate::uncertainty::predict_virtual_ensembles;
+    use std::fs;
+
+    #[test]
+    fn test_booster_fit_subsample() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        //let data = Matrix::new(data.get_col(1), 891, 1);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_nbins(300)
+            .set_max_depth(3)
+            .set_subsample(0.5)
+            .set_base_score(0.5)
+            .set_initialize_base_score(false);
+        let sample_weight = vec![1.; y.len()];
+        booster.fit(&data, &y, &sample_weight, None).unwrap();
+        let preds = booster.predict(&data, false);
+        let contribs = booster.predict_contributions(&data, ContributionsMethod::Average, false);
+        assert_eq!(contribs.len(), (data.cols + 1) * data.rows);
+        println!("{}", booster.trees[0]);
+        println!("{}", booster.trees[0].nodes.len());
+        println!("{}", booster.trees.last().unwrap().nodes.len());
+        println!("{:?}", &preds[0..10]);
+    }
+
+    #[test]
+    fn test_booster_fit_f32() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_f32: Vec<f32> = file
+            .lines()
+            .map(|x| x.parse::<f32>().unwrap_or(f32::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y_f32: Vec<f32> = file.lines().map(|x| x.parse::<f32>().unwrap()).collect();
+        // The same values, as f64, should give the same trees.
+        let data_f64: Vec<f64> = data_f32.iter().map(|v| f64::from(*v)).collect();
+        let y_f64: Vec<f64> = y_f32.iter().map(|v| f64::from(*v)).collect();
+
+        let booster = || {
+            GradientBooster::default()
+                .set_iterations(10)
+                .set_max_depth(3)
+        };
+        let mut booster_f32 = booster();
+        let data = Matrix::new(&data_f32, 891, 5);
+        let eval_data = Matrix::new(&data_f32, 891, 5);
+        booster_f32
+            .fit_unweighted(&data, &y_f32, Some(vec![(eval_data, &y_f32, &[1.; 891])]))
+            .unwrap();
+        let mut booster_f64 = booster();
+        let data_ = Matrix::new(&data_f64, 891, 5);
+        let eval_data_ = Matrix::new(&data_f64, 891, 5);
+        booster_f64
+            .fit_unweighted(&data_, &y_f64, Some(vec![(eval_data_, &y_f64, &[1.; 891])]))
+            .unwrap();
+        assert_eq!(
+            booster_f32.predict(&data, false),
+            booster_f64.predict(&data_, false)
+        );
+        assert_eq!(
+            booster_f32.predict(&data, true),
+            booster_f64.predict(&data, true)
+        );
+
+        booster_f32
+            .update(&data, &y_f32, &vec![1.; y_f32.len()], 2)
+            .unwrap();
+        booster_f64
+            .update(&data_, &y_f64, &vec![1.; y_f64.len()], 2)
+            .unwrap();
+        assert_eq!(
+            booster_f32.predict(&data, false),
+            booster_f64.predict(&data_, false)
+        );
+    }
+
+    #[test]
+    fn test_booster_fit_sparse() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        // Store the non zero values, and the missing values.
+        let (mut indptr, mut indices, mut values) = (vec![0], Vec::new(), Vec::new());
+        for j in 0..data.cols {
+            for (i, v) in data.get_col(j).iter().enumerate() {
+                if *v != 0. {
+                    indices.push(i);
+                    values.push(*v);
+                }
+            }
+            indptr.push(indices.len());
+        }
+        assert!(values.len() < data_vec.len());
+        let sparse = CscMatrix::new(&indptr, &indices, &values, data.rows).unwrap();
+        let w = vec![1.; y.len()];
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&data, &y, &w, None).unwrap();
+        let mut sparse_booster = GradientBooster::default().set_iterations(10);
+        let eval = CscMatrix::new(&indptr, &indices, &values, data.rows).unwrap();
+        sparse_booster
+            .fit_sparse(&sparse, &y, &w, Some(vec![(eval, &y, &w)]))
+            .unwrap();
+        assert_eq!(
+            booster.predict(&data, false),
+            sparse_booster.predict(&data, false)
+        );
+        assert_eq!(
+            booster.predict(&data, true),
+            sparse_booster.predict(&sparse, true)
+        );
+
+        let mut invalid = GradientBooster::default().set_validation_fraction(Some(0.2));
+        assert!(invalid.fit_sparse(&sparse, &y, &w, None).is_err());
+    }
+
+    #[test]
+    fn test_booster_fit() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        //let data = Matrix::new(data.get_col(1), 891, 1);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_nbins(300)
+            .set_max_depth(3)
+            .set_base_score(0.5)
+            .set_initialize_base_score(false);
+
+        let sample_weight = vec![1.; y.len()];
+        booster.fit(&data, &y, &sample_weight, None).unwrap();
+        let preds = booster.predict(&data, false);
+        let contribs = booster.predict_contributions(&data, ContributionsMethod::Average, false);
+        assert_eq!(contribs.len(), (data.cols + 1) * data.rows);
+        println!("{}", booster.trees[0]);
+        println!("{}", booster.trees[0].nodes.len());
+        println!("{}", booster.trees.last().unwrap().nodes.len());
+        println!("{:?}", &preds[0..10]);
+    }
+
+    #[test]
+    fn test_booster_fit_nofitted_base_score() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance-fare.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        //let data = Matrix::new(data.get_col(1), 891, 1);
+        let mut booster = GradientBooster::default()
+            .set_objective_type(ObjectiveType::SquaredLoss)
+            .set_iterations(10)
+            .set_nbins(300)
+            .set_max_depth(3)
+            .set_initialize_base_score(true);
+        let sample_weight = vec![1.; y.len()];
+        booster.fit(&data, &y, &sample_weight, None).unwrap();
+        let preds = booster.predict(&data, false);
+        let contribs = booster.predict_contributions(&data, ContributionsMethod::Average, false);
+        assert_eq!(contribs.len(), (data.cols + 1) * data.rows);
+        println!("{}", booster.trees[0]);
+        println!("{}", booster.trees[0].nodes.len());
+        println!("{}", booster.trees.last().unwrap().nodes.len());
+        println!("{:?}", &preds[0..10]);
+    }
+
+    #[test]
+    fn test_tree_save() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        //let data = Matrix::new(data.get_col(1), 891, 1);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_nbins(300)
+            .set_max_depth(3)
+            .set_base_score(0.5)
+            .set_initialize_base_score(false);
+        let sample_weight = vec![1.; y.len()];
+        booster.fit(&data, &y, &sample_weight, None).unwrap();
+        let preds = booster.predict(&data, true);
+
+        booster.save_booster("resources/model64.json").unwrap();
+        let booster2 = GradientBooster::load_booster("resources/model64.json").unwrap();
+        assert_eq!(booster2.predict(&data, true)[0..10], preds[0..10]);
+
+        // Test with non-NAN missing.
+        booster.missing = 0.;
+        booster.save_booster("resources/modelmissing.json").unwrap();
+        let booster3 = GradientBooster::load_booster("resources/modelmissing.json").unwrap();
+        assert_eq!(booster3.missing, 0.);
+        assert_eq!(booster3.missing, booster.missing);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_evaluation_metric(Some(Metric::AUC));
+        booster.insert_metadata("name".to_string(), "titanic".to_string());
+        let w = vec![1.; y.len()];
+        // AUC is undefined with a single class, so the history will contain NaN values.
+        let y_eval = vec![1.; y.len()];
+        booster
+            .fit(
+                &data,
+                &y,
+                &w,
+                Some(vec![(Matrix::new(&data_vec, 891, 5), &y_eval, &w)]),
+            )
+            .unwrap();
+        let preds = booster.predict(&data, true);
+
+        booster
+            .save_json("resources/model_round_trip.json")
+            .unwrap();
+        let booster2 = GradientBooster::load_json("resources/model_round_trip.json").unwrap();
+        assert_eq!(booster2.predict(&data, true), preds);
+        assert_eq!(booster2.json_dump().unwrap(), booster.json_dump().unwrap());
+        assert_eq!(
+            booster2.get_metadata(&"name".to_string()),
+            Some("titanic".to_string())
+        );
+        assert!(booster2.evaluation_history.unwrap().data[0].is_nan());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        let w = vec![1.; y.len()];
+        booster
+            .fit(
+                &data,
+                &y,
+                &w,
+                Some(vec![(Matrix::new(&data_vec, 891, 5), &y, &w)]),
+            )
+            .unwrap();
+        let preds = booster.predict(&data, true);
+
+        booster.save_binary("resources/model64.bin").unwrap();
+        let booster2 = GradientBooster::load_binary("resources/model64.bin").unwrap();
+        assert_eq!(booster2.predict(&data, true), preds);
+        assert_eq!(booster2.json_dump().unwrap(), booster.json_dump().unwrap());
+        let bytes = booster.binary_dump().unwrap();
+        assert!(bytes.len() < booster.json_dump().unwrap().len());
+
+        // Non-NaN missing values should also survive.
+        booster.missing = 0.;
+        let booster3 = GradientBooster::from_binary(&booster.binary_dump().unwrap()).unwrap();
+        assert_eq!(booster3.missing, 0.);
+
+        assert!(GradientBooster::from_binary(b"FRSX").is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[BINARY_MAGIC.len()] = BINARY_FORMAT_VERSION + 1;
+        assert!(GradientBooster::from_binary(&bad_version).is_err());
+    }
+
+    #[test]
+    fn test_load_older_format_version() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, true);
+
+        // Recreate a model from before the schema was versioned, without
+        // the fields that were added later on.
+        let mut model: Value = serde_json::from_str(&booster.json_dump().unwrap()).unwrap();
+        let fields = model.as_object_mut().unwrap();
+        for f in [
+            "format_version",
+            "top_rate",
+            "other_rate",
+            "sample_method",
+            "grow_policy",
+            "evaluation_metric",
+            "early_stopping_rounds",
+            "validation_fraction",
+            "stratify_validation",
+            "quantile",
+            "colsample_bytree",
+            "monotone_constraint_method",
+            "interaction_constraints",
+            "num_parallel_tree",
+            "warm_start",
+            "class_weight",
+            "checkpoint",
+            "initialize_base_score",
+            "evaluation_history",
+            "best_iteration",
+            "prediction_iteration",
+            "calibration",
+            "cuts",
+            "feature_names",
+            "imputer",
+        ] {
+            fields.remove(f);
+        }
+        let booster2 = GradientBooster::from_json(&model.to_string()).unwrap();
+        assert_eq!(booster2.predict(&data, true), preds);
+        assert_eq!(booster2.top_rate, 0.1);
+        let dumped: Value = serde_json::from_str(&booster2.json_dump().unwrap()).unwrap();
+        assert_eq!(dumped["format_version"], MODEL_FORMAT_VERSION);
+
+        // Models from a newer version can't be read.
+        let fields = model.as_object_mut().unwrap();
+        fields.insert(
+            "format_version".to_string(),
+            Value::from(MODEL_FORMAT_VERSION + 1),
+        );
+        assert!(GradientBooster::from_json(&model.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_upstream_json() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let names: Vec<String> = ["pclass", "age", "sibsp", "parch", "o'fare"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_colsample_bytree(0.8)
+            .set_feature_names(Some(names.clone()));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, true);
+
+        let model: Value = serde_json::from_str(&booster.upstream_json_dump().unwrap()).unwrap();
+        let fields = model.as_object().unwrap();
+        assert!(fields.keys().all(|k| UPSTREAM_FIELDS.contains(&k.as_str())));
+        assert_eq!(
+            model["metadata"][UPSTREAM_FEATURE_NAMES_KEY],
+            r"['pclass', 'age', 'sibsp', 'parch', 'o\'fare']"
+        );
+        // The feature names are read back from the metadata.
+        let loaded = GradientBooster::from_json(&model.to_string()).unwrap();
+        assert_eq!(loaded.predict(&data, true), preds);
+        assert_eq!(loaded.feature_names, Some(names));
+
+        assert_eq!(
+            parse_python_str_list(r#"[ 'a', "b'c" ,'d\\e']"#),
+            Some(vec!["a".to_string(), "b'c".to_string(), "d\\e".to_string()])
+        );
+        assert_eq!(parse_python_str_list("[]"), Some(Vec::new()));
+        assert_eq!(parse_python_str_list("['a', 1]"), None);
+
+        let mut booster = GradientBooster::default()
+            .set_objective_type(ObjectiveType::QuantileLoss)
+            .set_iterations(2);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert!(booster.upstream_json_dump().is_err());
+    }
+
+    #[test]
+    fn test_partial_dependence_2d() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let grid1 = [1., 2., 3.];
+        let grid2 = [5., 20., 40., 60.];
+        let surface = booster.partial_dependence_2d((0, 1), (&grid1, &grid2));
+        assert_eq!(surface.rows, grid1.len());
+        assert_eq!(surface.cols, grid2.len());
+        assert_eq!(
+            surface.get(1, 2),
+            &booster.values_partial_dependence(&[(0, 2.), (1, 40.)])
+        );
+    }
+
+    #[test]
+    fn test_data_partial_dependence() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let grid = [10., 30., 50.];
+
+        let pd = booster
+            .data_partial_dependence(1, &grid, &data, None)
+            .unwrap();
+        let mut fixed = data_vec.clone();
+        for (i, v) in grid.iter().enumerate() {
+            fixed[891..(2 * 891)].iter_mut().for_each(|x| *x = *v);
+            let preds = booster.predict(&Matrix::new(&fixed, 891, 5), false);
+            let mean = preds.iter().sum::<f64>() / 891.;
+            assert!((pd[i] - mean).abs() < 1e-9);
+        }
+        // Scaling the weights doesn't change the average.
+        let w = vec![2.; 891];
+        let weighted = booster
+            .data_partial_dependence(1, &grid, &data, Some(&w))
+            .unwrap();
+        assert!(pd.iter().zip(weighted).all(|(a, b)| (a - b).abs() < 1e-9));
+        // With all the weight on a single record, it's the prediction of that record.
+        let mut w = vec![0.; 891];
+        w[3] = 1.5;
+        let weighted = booster
+            .data_partial_dependence(1, &grid, &data, Some(&w))
+            .unwrap();
+        for (v, p) in grid.iter().zip(weighted) {
+            let mut row: Vec<f64> = (0..5).map(|j| *data.get(3, j)).collect();
+            row[1] = *v;
+            assert!((booster.predict_row(&row) - p).abs() < 1e-9);
+        }
+
+        assert!(booster
+            .data_partial_dependence(5, &grid, &data, None)
+            .is_err());
+        assert!(booster
+            .data_partial_dependence(1, &grid, &data, Some(&[1.]))
+            .is_err());
+        assert!(booster
+            .data_partial_dependence(1, &grid, &data, Some(&vec![0.; 891]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_partial_dependence_grid() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+
+        // The default grid is within the 5th, and 95th percentiles of age.
+        let pd = booster
+            .partial_dependence(1, &data, &PartialDependenceGrid::default(), None)
+            .unwrap();
+        let mut ages: Vec<f64> = data
+            .get_col(1)
+            .iter()
+            .filter(|v| !v.is_nan())
+            .copied()
+            .collect();
+        ages.sort_by(|a, b| a.total_cmp(b));
+        let (p5, p95) = (ages[ages.len() * 5 / 100], ages[ages.len() * 95 / 100]);
+        assert!(pd.len() > 10 && pd.len() <= 100);
+        assert!(pd.iter().all(|(v, _)| *v >= p5 && *v <= p95));
+        assert!(pd.windows(2).all(|w| w[0].0 < w[1].0));
+        for (v, p) in pd.iter() {
+            assert_eq!(*p, booster.value_partial_dependence(1, *v));
+        }
+
+        let grid = PartialDependenceGrid::Values(vec![40., 20.]);
+        let pd = booster.partial_dependence(1, &data, &grid, None).unwrap();
+        assert_eq!(
+            pd.iter().map(|(v, _)| *v).collect::<Vec<_>>(),
+            vec![40., 20.]
+        );
+        let w = vec![1.; 891];
+        let weighted = booster
+            .partial_dependence(1, &data, &grid, Some(&w))
+            .unwrap();
+        let expected = booster
+            .data_partial_dependence(1, &[40., 20.], &data, Some(&w))
+            .unwrap();
+        assert_eq!(
+            weighted.iter().map(|(_, p)| *p).collect::<Vec<_>>(),
+            expected
+        );
+
+        let unique = booster
+            .partial_dependence(0, &data, &PartialDependenceGrid::Unique, None)
+            .unwrap();
+        assert_eq!(
+            unique.iter().map(|(v, _)| *v).collect::<Vec<_>>(),
+            vec![1., 2., 3.]
+        );
+
+        let invalid = PartialDependenceGrid::Percentiles {
+            lower: 0.9,
+            upper: 0.1,
+            n: 10,
+        };
+        assert!(booster
+            .partial_dependence(1, &data, &invalid, None)
+            .is_err());
+        assert!(booster
+            .partial_dependence(5, &data, &PartialDependenceGrid::Unique, None)
+            .is_err());
+
+        let result = booster
+            .partial_dependence_result(1, &data, &grid, None)
+            .unwrap();
+        assert_eq!(result.values, vec![40., 20.]);
+        assert_eq!(
+            result.partial_dependence,
+            pd.iter().map(|(_, p)| *p).collect::<Vec<_>>()
+        );
+        assert!(result.feature_name.is_none());
+        let json = result.to_json().unwrap();
+        assert_eq!(PartialDependenceResult::from_json(&json).unwrap(), result);
+        let booster = booster.set_feature_names(Some(
+            ["pclass", "age", "sibsp", "parch", "fare"]
+                .iter()
+                .map(|n| n.to_string())
+                .collect(),
+        ));
+        let result = booster
+            .partial_dependence_result(1, &data, &grid, None)
+            .unwrap();
+        assert_eq!(result.feature_name.as_deref(), Some("age"));
+    }
+
+    #[test]
+    fn test_categorical_partial_dependence() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        // Passenger class is a categorical feature.
+        let pdp = booster.categorical_partial_dependence(0, &data);
+        let categories: Vec<f64> = pdp.iter().map(|(c, _)| *c).collect();
+        assert_eq!(categories, vec![1., 2., 3.]);
+        assert_eq!(pdp[1].1, booster.value_partial_dependence(0, 2.));
+        // Age has missing values, which should be the last category.
+        let pdp = booster.categorical_partial_dependence(1, &data);
+        assert!(pdp.last().unwrap().0.is_nan());
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let booster = GradientBooster::builder()
+            .iterations(3)
+            .learning_rate(0.1)
+            .nbins(2)
+            .build()
+            .unwrap();
+        assert_eq!(booster.iterations, 3);
+        assert_eq!(booster.nbins, 2);
+        for builder in [
+            GradientBooster::builder().learning_rate(0.),
+            GradientBooster::builder().learning_rate(-0.1),
+            GradientBooster::builder().subsample(0.),
+            GradientBooster::builder().subsample(1.5),
+            GradientBooster::builder().nbins(1),
+            GradientBooster::builder().top_rate(2.),
+            GradientBooster::builder()
+                .sample_method(SampleMethod::Goss)
+                .top_rate(0.8)
+                .other_rate(0.5),
+        ] {
+            assert!(matches!(
+                builder.build(),
+                Err(ForustError::InvalidParameter { .. })
+            ));
+        }
+
+        // The rates only need to sum to at most 1, when sampling with goss.
+        assert!(GradientBooster::builder()
+            .top_rate(0.8)
+            .other_rate(0.5)
+            .build()
+            .is_ok());
+        assert!(GossSampler::new(0.5, 0.5).is_ok());
+        assert!(GossSampler::new(0.6, 0.5).is_err());
+        assert!(GossSampler::new(-0.1, 0.5).is_err());
+        assert!(GossSampler::new(0.2, f64::NAN).is_err());
+
+        let data_vec = vec![1., 2., 3., 4.];
+        let data = Matrix::new(&data_vec, 4, 1);
+        let mut booster = GradientBooster::default().set_learning_rate(0.);
+        assert!(booster
+            .fit_unweighted(&data, &[0., 0., 1., 1.], None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_error_variants() {
+        let data_vec = vec![1., 2., 3., 4.];
+        let data = Matrix::new(&data_vec, 4, 1);
+        let mut booster = GradientBooster::default()
+            .set_feature_names(Some(vec!["a".to_string(), "b".to_string()]));
+        let err = booster
+            .fit_unweighted(&data, &[0., 1., 1., 0.], None)
+            .unwrap_err();
+        assert!(
+            matches!(err, ForustError::ShapeMismatch { ref name, .. } if name == "feature_names")
+        );
+        assert!(matches!(
+            GradientBooster::load_json("resources/does_not_exist.json"),
+            Err(ForustError::Io(_))
+        ));
+        assert!(matches!(
+            GradientBooster::from_json("{\"iterations\": \"ten\"}"),
+            Err(ForustError::Serialization(_))
+        ));
+        assert!(matches!(
+            GradientBooster::default().set_learning_rate(0.).validate_parameters(),
+            Err(ForustError::InvalidParameter { ref name, .. }) if name == "learning_rate"
+        ));
+        // The errors are standard errors.
+        let err: Box<dyn std::error::Error> = Box::new(err);
+        assert!(err.to_string().contains("feature_names"));
+    }
+
+    #[test]
+    fn test_empty_data() {
+        let empty: Vec<f64> = Vec::new();
+        let mut booster = GradientBooster::default();
+        assert!(matches!(
+            booster.fit_unweighted(&Matrix::new(&empty, 0, 3), &[], None),
+            Err(ForustError::EmptyData { rows: 0, cols: 3 })
+        ));
+        assert!(matches!(
+            booster.fit_unweighted(&Matrix::new(&empty, 3, 0), &[0., 1., 0.], None),
+            Err(ForustError::EmptyData { rows: 3, cols: 0 })
+        ));
+        let mut booster = GradientBooster::default()
+            .set_objective_type(ObjectiveType::QuantileLoss)
+            .set_validation_fraction(Some(0.2));
+        assert!(matches!(
+            booster.fit_unweighted(&Matrix::new(&empty, 0, 3), &[], None),
+            Err(ForustError::EmptyData { .. })
+        ));
+
+        // The second column only has missing values.
+        let data_vec = vec![1., 2., 3., 4., f64::NAN, f64::NAN, f64::NAN, f64::NAN];
+        let data = Matrix::new(&data_vec, 4, 2);
+        let mut booster = GradientBooster::default();
+        assert!(matches!(
+            booster.fit_unweighted(&data, &[0., 1., 1., 0.], None),
+            Err(ForustError::AllMissing(1))
+        ));
+        let data_vec = vec![1., 2., 3., 4., 0., 0., 0., 0.];
+        let data = Matrix::new(&data_vec, 4, 2);
+        let mut booster = GradientBooster::default().set_missing(0.);
+        assert!(matches!(
+            booster.fit_unweighted(&data, &[0., 1., 1., 0.], None),
+            Err(ForustError::AllMissing(1))
+        ));
+    }
+
+    #[test]
+    fn test_input_validation() {
+        let data_vec = vec![1., 2., 3., 4.];
+        let data = Matrix::new(&data_vec, 4, 1);
+        let w = vec![1.; 4];
+        let mut booster = GradientBooster::default();
+        assert!(matches!(
+            booster.fit(&data, &[0., 1., 1.], &w, None),
+            Err(ForustError::LengthMismatch(..))
+        ));
+        assert!(matches!(
+            booster.fit(&data, &[0., 1., 1., 0.], &w[1..], None),
+            Err(ForustError::LengthMismatch(..))
+        ));
+        assert!(matches!(
+            booster.fit(&data, &[0., 1., 2., 0.], &w, None),
+            Err(ForustError::InvalidTarget(2, ..))
+        ));
+        assert!(matches!(
+            booster.fit(&data, &[0., 1., 1., 0.], &[1., -1., 1., 1.], None),
+            Err(ForustError::InvalidWeight(1, ..))
+        ));
+        let eval_y = vec![0., 1., f64::NAN, 0.];
+        assert!(matches!(
+            booster.fit(
+                &data,
+                &[0., 1., 1., 0.],
+                &w,
+                Some(vec![(Matrix::new(&data_vec, 4, 1), &eval_y, &w)])
+            ),
+            Err(ForustError::InvalidTarget(2, ..))
+        ));
+
+        let mut booster = GradientBooster::default().set_objective_type(ObjectiveType::SquaredLoss);
+        assert!(booster.fit(&data, &[0., 1., 2., 5.], &w, None).is_ok());
+        assert!(matches!(
+            booster.predict_proba(&data, false, false),
+            Err(ForustError::UnsupportedObjective { .. })
+        ));
+        assert!(matches!(
+            booster.fit(&data, &[0., 1., f64::INFINITY, 5.], &w, None),
+            Err(ForustError::InvalidTarget(2, ..))
+        ));
+
+        let nan_vec = vec![1., f64::NAN, 3., 4.];
+        let mut booster = GradientBooster::default().set_missing(0.);
+        assert!(matches!(
+            booster.fit(&Matrix::new(&nan_vec, 4, 1), &[0., 1., 1., 0.], &w, None),
+            Err(ForustError::NANVAlueFound(..))
+        ));
+    }
+
+    #[test]
+    fn test_get_set_params() {
+        let data_vec = vec![1., 2., 3., 4., 5., 6.];
+        let data = Matrix::new(&data_vec, 6, 1);
+        let mut booster = GradientBooster::default().set_iterations(3);
+        booster
+            .fit_unweighted(&data, &[0., 0., 1., 0., 1., 1.], None)
+            .unwrap();
+        let params = booster.get_params().unwrap();
+        assert_eq!(params["iterations"], 3);
+
+        let values = serde_json::json!({"max_depth": 2, "learning_rate": 0.5});
+        booster.set_params(values.as_object().unwrap()).unwrap();
+        assert_eq!(booster.max_depth, 2);
+        assert_eq!(booster.learning_rate, 0.5);
+        assert_eq!(booster.iterations, 3);
+        assert_eq!(booster.trees.len(), 3);
+
+        let values = serde_json::json!({"learning_rate": -1.});
+        assert!(booster.set_params(values.as_object().unwrap()).is_err());
+        assert_eq!(booster.learning_rate, 0.5);
+
+        let booster = GradientBooster::from_params_map(&params).unwrap();
+        assert_eq!(booster.get_params().unwrap(), params);
+        assert!(booster.trees.is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_seed() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let fit_booster = |sample_method: SampleMethod, parallel: bool, seed: u64| {
+            let mut booster = GradientBooster::default()
+                .set_iterations(10)
+                .set_subsample(0.5)
+                .set_sample_method(sample_method)
+                .set_parallel(parallel)
+                .set_seed(seed);
+            booster.grow_policy = GrowPolicy::LossGuide;
+            booster.fit_unweighted(&data, &y, None).unwrap();
+            // Only the trees are compared, as the parallel parameter is part of the model.
+            serde_json::to_string(&booster.trees).unwrap()
+        };
+        for sample_method in [SampleMethod::Random, SampleMethod::Goss] {
+            let model = fit_booster(sample_method, true, 42);
+            assert_eq!(model, fit_booster(sample_method, true, 42));
+            assert_eq!(model, fit_booster(sample_method, false, 42));
+        }
+        assert_ne!(
+            fit_booster(SampleMethod::Random, true, 42),
+            fit_booster(SampleMethod::Random, true, 43)
+        );
+    }
+
+    #[test]
+    fn test_n_threads() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, true);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_n_threads(Some(2));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(preds, booster.predict(&data, true));
+
+        let pool = Arc::new(ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_thread_pool(Arc::clone(&pool));
+        assert_eq!(booster.n_threads, Some(1));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(preds, booster.predict(&data, true));
+
+        // The pool is kept when other parameters are set.
+        let values = serde_json::json!({"max_depth": 3});
+        booster.set_params(values.as_object().unwrap()).unwrap();
+        assert!(Arc::ptr_eq(booster.thread_pool.get().unwrap(), &pool));
+        let values = serde_json::json!({"n_threads": 2});
+        booster.set_params(values.as_object().unwrap()).unwrap();
+        assert!(booster.thread_pool.get().is_none());
+
+        let mut booster = GradientBooster::default().set_n_threads(Some(0));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+    }
+
+    #[test]
+    fn test_profile_report() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert!(booster.get_profile_report().is_none());
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_profile(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let report = booster.get_profile_report().unwrap();
+        assert_eq!(report.iterations.len(), 10);
+        let totals = report.phase_totals();
+        assert!(!totals.histogram.is_zero());
+        assert!(!totals.split_finding.is_zero());
+        assert!(report.to_string().contains("split finding"));
+    }
+
+    #[test]
+    fn test_check_gradients() {
+        let data_vec = vec![1., 2., 3., 4.];
+        let data = Matrix::new(&data_vec, 4, 1);
+        let y = vec![0., 1., 2., 5.];
+        let mut booster = GradientBooster::default()
+            .set_objective_type(ObjectiveType::SquaredLoss)
+            .set_iterations(5)
+            .set_check_gradients(true);
+        assert!(booster.fit_unweighted(&data, &y, None).is_ok());
+
+        // A base score that isn't finite, makes every gradient infinite.
+        let mut booster = booster
+            .set_initialize_base_score(false)
+            .set_base_score(f64::INFINITY);
+        match booster.fit_unweighted(&data, &y, None) {
+            Err(ForustError::NonFiniteGradient {
+                objective,
+                iteration,
+                row,
+                yhat,
+            }) => {
+                assert_eq!(objective, "SquaredLoss");
+                assert_eq!((iteration, row), (0, 0));
+                assert_eq!(yhat, f64::INFINITY);
+            }
+            _ => panic!("Expected a NonFiniteGradient error"),
+        }
+        assert!(booster.trees.is_empty());
+        let booster = GradientBooster::builder().check_gradients(true).build();
+        assert!(booster.unwrap().check_gradients);
+    }
+
+    #[test]
+    fn test_monotone_constraint_method() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let constraints =
+            ConstraintMap::from([(1, Constraint::Negative), (4, Constraint::Positive)]);
+
+        // Each record, with a constrained feature set to each of its values.
+        let is_monotone = |booster: &GradientBooster, feature: usize, increasing: bool| {
+            let mut values: Vec<f64> = data
+                .get_col(feature)
+                .iter()
+                .filter(|v| !v.is_nan())
+                .copied()
+                .collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            values.dedup();
+            (0..data.rows).step_by(20).all(|i| {
+                let preds: Vec<f64> = values
+                    .iter()
+                    .map(|v| {
+                        let mut row: Vec<f64> = (0..data.cols).map(|j| *data.get(i, j)).collect();
+                        row[feature] = *v;
+                        booster.predict(&Matrix::new(&row, 1, data.cols), false)[0]
+                    })
+                    .collect();
+                preds.windows(2).all(|w| {
+                    if increasing {
+                        w[0] <= w[1]
+                    } else {
+                        w[0] >= w[1]
+                    }
+                })
+            })
+        };
+        // A third of the records are held out, to compare the accuracy of the methods.
+        let (train, test): (Vec<usize>, Vec<usize>) = (0..data.rows).partition(|i| i % 3 != 0);
+        let select = |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+        let train_vec = data.select_rows(&train);
+        let train_data = Matrix::new(&train_vec, train.len(), data.cols);
+        let test_vec = data.select_rows(&test);
+        let test_data = Matrix::new(&test_vec, test.len(), data.cols);
+        let (train_y, test_y) = (select(&y, &train), select(&y, &test));
+        for create_missing_branch in [false, true] {
+            let mut losses = Vec::new();
+            for method in [
+                MonotoneConstraintMethod::Basic,
+                MonotoneConstraintMethod::Exact,
+            ] {
+                let mut booster = GradientBooster::default()
+                    .set_iterations(20)
+                    .set_max_depth(3)
+                    .set_create_missing_branch(create_missing_branch)
+                    .set_monotone_constraints(Some(constraints.clone()))
+                    .set_monotone_constraint_method(method);
+                booster.fit_unweighted(&train_data, &train_y, None).unwrap();
+                let preds = booster.predict(&test_data, false);
+                losses.push(log_loss(&test_y, &preds, &vec![1.; test_y.len()]));
+                if method == MonotoneConstraintMethod::Exact {
+                    assert!(is_monotone(&booster, 1, false));
+                    assert!(is_monotone(&booster, 4, true));
+                }
+            }
+            assert!(losses.iter().all(|l| l.is_finite()));
+            // The basic method doesn't bound splits on unconstrained features, so breaks
+            // the constraints to fit the training data, but the exact method, with its
+            // looser bounds, should predict the held out records at least as well.
+            assert!(losses[1] <= losses[0], "{:?}", losses);
+        }
+        let booster = GradientBooster::builder()
+            .monotone_constraint_method(MonotoneConstraintMethod::Exact)
+            .build()
+            .unwrap();
+        assert_eq!(
+            booster.params().monotone_constraint_method,
+            MonotoneConstraintMethod::Exact
+        );
+    }
+
+    #[test]
+    fn test_interaction_constraints() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let names: Vec<String> = ["pclass", "age", "sibsp", "parch", "fare"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+
+        // The names of the features split on along each branch of the trees.
+        fn branches(booster: &GradientBooster) -> Vec<Vec<String>> {
+            fn walk(
+                booster: &GradientBooster,
+                tree: &Tree,
+                i: usize,
+                path: Vec<String>,
+                out: &mut Vec<Vec<String>>,
+            ) {
+                let n = &tree.nodes[i];
+                if n.is_leaf {
+                    out.push(path);
+                    return;
+                }
+                let mut path = path;
+                let name = &booster.feature_names.as_ref().unwrap()[n.split_feature];
+                if !path.contains(name) {
+                    path.push(name.clone());
+                }
+                for child in [n.left_child, n.right_child, n.missing_node] {
+                    walk(booster, tree, child, path.clone(), out);
+                }
+            }
+            let mut out = Vec::new();
+            for tree in booster.trees.iter() {
+                walk(booster, tree, 0, Vec::new(), &mut out);
+            }
+            out
+        }
+        let groups = [vec!["pclass", "age"], vec!["fare", "age"]];
+        let allowed = |path: &Vec<String>| {
+            path.len() <= 1
+                || groups
+                    .iter()
+                    .any(|g| path.iter().all(|f| g.contains(&f.as_str())))
+        };
+        let constraints = InteractionConstraints::Names(
+            groups
+                .iter()
+                .map(|g| g.iter().map(|n| n.to_string()).collect())
+                .collect(),
+        );
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_feature_names(Some(names.clone()))
+            .set_interaction_constraints(Some(constraints.clone()));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let paths = branches(&booster);
+        assert!(paths.iter().all(allowed));
+        assert!(paths.iter().any(|p| p.len() > 1));
+        // sibsp, and parch are in no group, so are only split on alone.
+        assert!(paths
+            .iter()
+            .filter(|p| p.iter().any(|f| f == "sibsp" || f == "parch"))
+            .all(|p| p.len() == 1));
+        let mut unconstrained = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_feature_names(Some(names.clone()));
+        unconstrained.fit_unweighted(&data, &y, None).unwrap();
+        assert!(!branches(&unconstrained).iter().all(allowed));
+
+        // The groups are saved with the model, and resolved by name, so they
+        // hold with the columns of the data in a different order.
+        let loaded = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        assert_eq!(loaded.interaction_constraints, Some(constraints.clone()));
+        let loaded_binary = GradientBooster::from_binary(&booster.binary_dump().unwrap()).unwrap();
+        assert_eq!(
+            loaded_binary.interaction_constraints,
+            Some(constraints.clone())
+        );
+        assert_eq!(
+            loaded_binary.predict(&data, true),
+            booster.predict(&data, true)
+        );
+        let params: BoosterParams =
+            serde_json::from_str(r#"{"interaction_constraints": {"Indices": [[0, 1]]}}"#).unwrap();
+        assert_eq!(
+            params.interaction_constraints,
+            Some(InteractionConstraints::Indices(vec![vec![0, 1]]))
+        );
+        let order = [4, 2, 0, 3, 1];
+        let reordered_vec: Vec<f64> = order
+            .iter()
+            .flat_map(|j| data.get_col(*j).to_vec())
+            .collect();
+        let reordered = Matrix::new(&reordered_vec, 891, 5);
+        let mut booster =
+            loaded.set_feature_names(Some(order.iter().map(|j| names[*j].clone()).collect()));
+        booster.fit_unweighted(&reordered, &y, None).unwrap();
+        assert!(branches(&booster).iter().all(allowed));
+        let toml = booster.params().to_toml().unwrap();
+        let params = BoosterParams::from_toml(&toml).unwrap();
+        assert_eq!(params.interaction_constraints, Some(constraints.clone()));
+
+        // Indices work without feature names, names don't.
+        let mut booster = GradientBooster::default()
+            .set_iterations(2)
+            .set_interaction_constraints(Some(InteractionConstraints::Indices(vec![vec![0, 1]])));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let mut booster = GradientBooster::default().set_interaction_constraints(Some(constraints));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+        let mut booster = GradientBooster::default()
+            .set_feature_names(Some(names))
+            .set_interaction_constraints(Some(InteractionConstraints::Names(vec![vec![
+                "cabin".to_string()
+            ]])));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+        let mut booster = GradientBooster::default()
+            .set_interaction_constraints(Some(InteractionConstraints::Indices(vec![vec![0, 5]])));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+    }
+
+    #[test]
+    fn test_warm_start_learning_rate() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_max_depth(3)
+            .set_learning_rate(0.3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let first = serde_json::to_string(&booster.trees).unwrap();
+        let first_preds = booster.predict(&data, false);
+
+        // Continue with a smaller learning rate, on samples of the records and columns.
+        let mut booster = booster
+            .set_learning_rate(0.1)
+            .set_subsample(0.8)
+            .set_colsample_bytree(0.6)
+            .set_warm_start(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(booster.trees.len(), 10);
+        assert_eq!(serde_json::to_string(&booster.trees[..5]).unwrap(), first);
+        let mut expected = vec![Some(0.3); 5];
+        expected.extend(vec![Some(0.1); 5]);
+        assert_eq!(booster.get_tree_learning_rates(), expected);
+        // The leaf weights of the trees are scaled when they are fit, so the prediction
+        // is the sum of the trees, whatever the current learning rate.
+        let preds = booster.predict(&data, false);
+        let mut summed = first_preds.clone();
+        for tree in booster.trees[5..].iter() {
+            let p = tree.predict(&data, false, &booster.missing);
+            summed.iter_mut().zip(p).for_each(|(s, p)| *s += p);
+        }
+        for (p, s) in preds.iter().zip(summed) {
+            assert!((p - s).abs() < 1e-9);
+        }
+        let booster = booster.set_learning_rate(0.5);
+        assert_eq!(booster.predict(&data, false), preds);
+
+        let loaded = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        assert_eq!(loaded.get_tree_learning_rates(), expected);
+        assert_eq!(loaded.predict(&data, false), preds);
+        // Trees saved before the learning rate was recorded.
+        let mut model: Value = serde_json::from_str(&booster.json_dump().unwrap()).unwrap();
+        for tree in model["trees"].as_array_mut().unwrap() {
+            tree.as_object_mut().unwrap().remove("learning_rate");
+        }
+        let loaded = GradientBooster::from_json(&model.to_string()).unwrap();
+        assert_eq!(loaded.get_tree_learning_rates(), vec![None; 10]);
+        assert_eq!(loaded.predict(&data, false), preds);
+    }
+
+    #[test]
+    fn test_sample_weight_hook() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, false);
+
+        // A hook that leaves the weights as they are, doesn't change the model.
+        let rounds = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&rounds);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_sample_weight_hook(Some(Arc::new(move |round, _: &mut [f64]| {
+                seen.lock().unwrap().push(round)
+            })));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(preds, booster.predict(&data, false));
+        // The gradients of the first tree, and each tree after it, are weighted.
+        assert_eq!(*rounds.lock().unwrap(), (0..=10).collect::<Vec<usize>>());
+
+        // Weighting the later records more as the iterations progress.
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_sample_weight_hook(Some(Arc::new(|round, w: &mut [f64]| {
+                let n = w.len();
+                w.iter_mut()
+                    .enumerate()
+                    .for_each(|(i, w)| *w *= 1. + (round * i / n) as f64);
+            })));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_ne!(preds, booster.predict(&data, false));
+
+        let mut booster = GradientBooster::builder()
+            .iterations(10)
+            .max_depth(3)
+            .sample_weight_hook(Some(Arc::new(|round, w: &mut [f64]| {
+                if round == 3 {
+                    w[7] = -1.
+                }
+            })))
+            .build()
+            .unwrap();
+        match booster.fit_unweighted(&data, &y, None) {
+            Err(ForustError::InvalidWeight(7, w)) => assert_eq!(w, -1.),
+            _ => panic!("Expected an InvalidWeight error"),
+        }
+    }
+
+    #[test]
+    fn test_max_histogram_memory() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        for grow_policy in [GrowPolicy::DepthWise, GrowPolicy::LossGuide] {
+            let mut booster = GradientBooster::default().set_iterations(10);
+            booster.grow_policy = grow_policy;
+            booster.max_leaves = 8;
+            booster.fit_unweighted(&data, &y, None).unwrap();
+            let expected = booster.predict(&data, true);
+
+            // Spilling the histograms of every node, or of some, grows the same trees.
+            for max_memory in [0, 4096] {
+                booster.max_histogram_memory = Some(max_memory);
+                booster.fit_unweighted(&data, &y, None).unwrap();
+                assert_eq!(booster.predict(&data, true), expected);
+            }
+        }
+        // The files of nodes that were never split are removed.
+        let prefix = format!("forust-histogram-{}-", std::process::id());
+        let remaining = fs::read_dir(std::env::temp_dir())
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .is_ok_and(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            })
+            .count();
+        assert_eq!(remaining, 0);
+    }
+
+    #[test]
+    fn test_validation_fraction() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let (train_index, valid_index) = validation_split(&y, 0.2, true, 0);
+        assert_eq!(train_index.len() + valid_index.len(), y.len());
+        let pos_rate =
+            |index: &[usize]| index.iter().map(|i| y[*i]).sum::<f64>() / index.len() as f64;
+        assert!((pos_rate(&train_index) - pos_rate(&valid_index)).abs() < 0.01);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(100)
+            .set_early_stopping_rounds(Some(5))
+            .set_validation_fraction(Some(0.2))
+            .set_stratify_validation(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let history = booster.evaluation_history.as_ref().unwrap();
+        assert_eq!(history.cols, 1);
+        assert!(booster.best_iteration.unwrap() < 99);
+
+        let mut booster = GradientBooster::default().set_validation_fraction(Some(1.));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+    }
+
+    #[test]
+    fn test_class_weight() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let w = ClassWeight::Balanced
+            .sample_weight(&y, &vec![1.; y.len()])
+            .unwrap();
+        let class_total = |c: f64| {
+            y.iter()
+                .zip(w.iter())
+                .filter(|(v, _)| **v == c)
+                .map(|(_, w)| w)
+                .sum::<f64>()
+        };
+        assert!((class_total(0.) - class_total(1.)).abs() < 1e-9);
+        assert!((class_total(0.) + class_total(1.) - y.len() as f64).abs() < 1e-9);
+
+        // Class weights are the same as passing the equivalent sample weights.
+        let mut booster = GradientBooster::default()
+            .set_class_weight(Some(ClassWeight::Weights(vec![1., 3.])))
+            .set_initialize_base_score(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let sample_weight: Vec<f64> = y.iter().map(|v| if *v == 1. { 3. } else { 1. }).collect();
+        let mut booster2 = GradientBooster::default().set_initialize_base_score(true);
+        booster2.fit(&data, &y, &sample_weight, None).unwrap();
+        assert_eq!(booster.predict(&data, true), booster2.predict(&data, true));
+
+        // The evaluation data is weighted by class too.
+        let mut booster = GradientBooster::default()
+            .set_iterations(3)
+            .set_class_weight(Some(ClassWeight::Balanced));
+        let w = vec![1.; y.len()];
+        booster
+            .fit(
+                &data,
+                &y,
+                &w,
+                Some(vec![(Matrix::new(&data_vec, 891, 5), &y, &w)]),
+            )
+            .unwrap();
+        let class_w = ClassWeight::Balanced.sample_weight(&y, &w).unwrap();
+        let history = booster.evaluation_history.as_ref().unwrap();
+        assert_eq!(
+            *history.get(2, 0),
+            log_loss(&y, &booster.predict(&data, true), &class_w)
+        );
+
+        assert!(ClassWeight::Weights(vec![1.])
+            .sample_weight(&y, &sample_weight)
+            .is_err());
+        assert!(ClassWeight::Balanced
+            .sample_weight(&[0., 0.5], &[1., 1.])
+            .is_err());
+        let mut booster = GradientBooster::default()
+            .set_objective_type(ObjectiveType::SquaredLoss)
+            .set_class_weight(Some(ClassWeight::Balanced));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+    }
+
+    #[test]
+    fn test_num_parallel_tree() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_num_parallel_tree(4)
+            .set_sample_method(SampleMethod::Random)
+            .set_subsample(0.5)
+            .set_colsample_bytree(0.4);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(booster.trees.len(), 20);
+        // Each tree only splits on the 2 columns it sampled.
+        for tree in booster.trees.iter() {
+            let mut features: Vec<usize> = tree
+                .nodes
+                .iter()
+                .filter(|n| !n.is_leaf)
+                .map(|n| n.split_feature)
+                .collect();
+            features.sort();
+            features.dedup();
+            assert!(features.len() <= 2);
+        }
+
+        // A single tree per round, is the same as the default.
+        let mut booster = GradientBooster::default().set_num_parallel_tree(1);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let mut booster2 = GradientBooster::default();
+        booster2.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(booster.predict(&data, true), booster2.predict(&data, true));
+
+        // Identical parallel trees, average to the single tree.
+        let mut booster = GradientBooster::default().set_num_parallel_tree(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(booster.trees.len(), 30);
+        let preds = booster.predict(&data, true);
+        let preds2 = booster2.predict(&data, true);
+        assert!(preds.iter().zip(preds2).all(|(a, b)| (a - b).abs() < 1e-5));
+
+        let mut booster = GradientBooster::default().set_num_parallel_tree(0);
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+        let mut booster = GradientBooster::default().set_colsample_bytree(0.);
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let fit = |seed| {
+            let mut booster = GradientBooster::default()
+                .set_seed(seed)
+                .set_sample_method(SampleMethod::Random)
+                .set_subsample(0.5)
+                .set_initialize_base_score(true);
+            booster.fit_unweighted(&data, &y, None).unwrap();
+            booster
+        };
+        let boosters: Vec<GradientBooster> = (0..3).map(fit).collect();
+        let weights = [0.5, 0.3, 0.2];
+        let merged = GradientBooster::merge(&boosters, Some(&weights)).unwrap();
+        assert_eq!(merged.trees.len(), 30);
+        let preds: Vec<Vec<f64>> = boosters.iter().map(|b| b.predict(&data, true)).collect();
+        let merged_preds = merged.predict(&data, true);
+        for (i, p) in merged_preds.iter().enumerate() {
+            let expected: f64 = preds.iter().zip(weights).map(|(b, w)| b[i] * w).sum();
+            assert!((p - expected).abs() < 1e-5);
+        }
+
+        // The merged booster survives a round trip.
+        let loaded = GradientBooster::from_json(&merged.json_dump().unwrap()).unwrap();
+        assert_eq!(loaded.predict(&data, true), merged_preds);
+
+        assert!(GradientBooster::merge(&[], None).is_err());
+        assert!(GradientBooster::merge(&boosters, Some(&[1.])).is_err());
+        let other = GradientBooster::default().set_objective_type(ObjectiveType::SquaredLoss);
+        assert!(GradientBooster::merge(&[other, GradientBooster::default()], None).is_err());
+
+        let mut calibrated = fit(0);
+        calibrated.calibrate_sigmoid(&data, &y, None).unwrap();
+        assert!(GradientBooster::merge(&[fit(1), calibrated], None).is_err());
+        let named = fit(0).set_feature_names(Some((0..5).map(|i| format!("f{}", i)).collect()));
+        assert!(GradientBooster::merge(&[fit(1), named], None).is_err());
+        let narrow_data = Matrix::new(&data_vec[..891 * 4], 891, 4);
+        let mut narrow = GradientBooster::default();
+        narrow.fit_unweighted(&narrow_data, &y, None).unwrap();
+        assert!(matches!(
+            GradientBooster::merge(&[fit(0), narrow], None),
+            Err(ForustError::ShapeMismatch { .. })
+        ));
+
+        // A shared imputer is kept, but boosters with different imputers can't be merged.
+        let imputed = |strategy| {
+            let imputer = Imputer::fit(&data, strategy, f64::NAN).unwrap();
+            let mut booster = GradientBooster::default().set_imputer(Some(imputer));
+            booster.fit_unweighted(&data, &y, None).unwrap();
+            booster
+        };
+        let boosters = [imputed(ImputeStrategy::Mean), imputed(ImputeStrategy::Mean)];
+        let merged = GradientBooster::merge(&boosters, None).unwrap();
+        assert_eq!(merged.imputer, boosters[0].imputer);
+        let preds = merged.predict(&data, true);
+        let expected = boosters[0].predict(&data, true);
+        assert!(preds
+            .iter()
+            .zip(expected)
+            .all(|(p, e)| (p - e).abs() < 1e-5));
+        let boosters = [
+            imputed(ImputeStrategy::Mean),
+            imputed(ImputeStrategy::Median),
+        ];
+        assert!(GradientBooster::merge(&boosters, None).is_err());
+    }
+
+    #[test]
+    fn test_update() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let first: Vec<usize> = (0..445).collect();
+        let second: Vec<usize> = (445..891).collect();
+        let select = |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+        let first_vec = data.select_rows(&first);
+        let first_data = Matrix::new(&first_vec, first.len(), data.cols);
+        let second_vec = data.select_rows(&second);
+        let second_data = Matrix::new(&second_vec, second.len(), data.cols);
+        let (first_y, second_y) = (select(&y, &first), select(&y, &second));
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        assert!(booster
+            .update(&first_data, &first_y, &vec![1.; first_y.len()], 5)
+            .is_err());
+        booster.fit_unweighted(&first_data, &first_y, None).unwrap();
+        let cuts = booster.cuts.clone().unwrap();
+        let w = vec![1.; second_y.len()];
+        let before = log_loss(&second_y, &booster.predict(&second_data, true), &w);
+
+        booster.update(&second_data, &second_y, &w, 5).unwrap();
+        assert_eq!(booster.trees.len(), 15);
+        assert_eq!(booster.iterations, 10);
+        assert!(!booster.warm_start);
+        // The cuts of the first batch are kept.
+        assert_eq!(booster.cuts.as_ref().unwrap().data, cuts.data);
+        let after = log_loss(&second_y, &booster.predict(&second_data, true), &w);
+        assert!(after < before);
+
+        // The cuts are saved with the booster.
+        let loaded = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        assert_eq!(loaded.cuts.unwrap().data, cuts.data);
+
+        let narrow = Matrix::new(&second_vec[..second.len() * 4], second.len(), 4);
+        assert!(booster.update(&narrow, &second_y, &w, 5).is_err());
+    }
+
+    #[test]
+    fn test_fit_from_binned() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        let mut booster = GradientBooster::default().set_nbins(50);
+        booster.fit(&data, &y, &w, None).unwrap();
+
+        // Fitting on the same bins, grows the same trees.
+        let binned_data = bin_matrix(&data, &w, 50, f64::NAN).unwrap();
+        let mut binned_booster = GradientBooster::default();
+        binned_booster
+            .fit_from_binned(&binned_data, &y, &w)
+            .unwrap();
+        assert_eq!(
+            binned_booster.predict(&data, true),
+            booster.predict(&data, true)
+        );
+        assert_eq!(
+            binned_booster.cuts.as_ref().unwrap().data,
+            binned_data.cuts.data
+        );
+
+        assert!(binned_booster
+            .fit_from_binned(&binned_data, &y[1..], &w[1..])
+            .is_err());
+    }
+
+    #[test]
+    fn test_predict_row() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&data, &y, &w, None).unwrap();
+        let preds = booster.predict(&data, false);
+        for (i, pred) in preds.iter().enumerate() {
+            let row: Vec<f64> = (0..data.cols).map(|j| *data.get(i, j)).collect();
+            assert_eq!(booster.predict_row(&row), *pred);
+        }
+
+        // Missing values are imputed, the same as when predicting on a matrix.
+        let imputer = Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).unwrap();
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_imputer(Some(imputer));
+        booster.fit(&data, &y, &w, None).unwrap();
+        let preds = booster.predict(&data, false);
+        let row: Vec<f32> = (0..data.cols).map(|j| *data.get(5, j) as f32).collect();
+        assert!(row.iter().any(|v| v.is_nan()));
+        assert!((booster.predict_row(&row) - preds[5]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_explain_row() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, false);
+        for (i, pred) in preds.iter().enumerate().take(50) {
+            let row: Vec<f64> = (0..data.cols).map(|j| *data.get(i, j)).collect();
+            let paths = booster.explain_row(&row);
+            assert_eq!(paths.len(), booster.trees.len());
+            let total = paths
+                .iter()
+                .fold(booster.base_score, |p, path| p + path.leaf_value);
+            assert_eq!(total, *pred);
+            for d in paths.iter().flat_map(|p| p.decisions.iter()) {
+                assert!(d.value.is_nan() || d.value == row[d.feature]);
+                match d.direction {
+                    Direction::Less => assert!(d.value < d.threshold),
+                    Direction::GreaterOrEqual => assert!(d.value >= d.threshold),
+                    Direction::Missing => assert!(d.value.is_nan()),
+                }
+            }
+            assert!(paths.iter().all(|p| p.decisions.len() <= 3));
+        }
+    }
+
+    #[test]
+    fn test_predict_iter() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&data, &y, &w, None).unwrap();
+        let preds = booster.predict(&data, false);
+
+        // Rows are predicted as they are produced, whether they're owned, or borrowed.
+        let rows =
+            (0..data.rows).map(|i| (0..data.cols).map(|j| *data.get(i, j)).collect::<Vec<_>>());
+        assert_eq!(booster.predict_iter(rows).collect::<Vec<_>>(), preds);
+        // Transposing the 5 columns, gives the values of each row one after the other.
+        let row_major = row_major_to_column_major(&data_vec, 5, 891).unwrap();
+        let streamed: Vec<f64> = booster.predict_iter(row_major.chunks(5)).collect();
+        assert_eq!(streamed, preds);
+    }
+
+    #[test]
+    fn test_imputer() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        // Fitting with an imputer, is the same as fitting on the imputed data.
+        let imputer = Imputer::fit(&data, ImputeStrategy::Median, f64::NAN).unwrap();
+        let imputed = imputer.transform(&data, f64::NAN);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&imputed.as_matrix(), &y, &w, None).unwrap();
+        let mut imputed_booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_imputer(Some(imputer));
+        imputed_booster.fit(&data, &y, &w, None).unwrap();
+        let preds = booster.predict(&imputed.as_matrix(), true);
+        assert_eq!(imputed_booster.predict(&data, true), preds);
+        assert_eq!(
+            imputed_booster.predict_contributions(&data, ContributionsMethod::Average, true),
+            booster.predict_contributions(&imputed.as_matrix(), ContributionsMethod::Average, true)
+        );
+
+        // The imputer is saved with the booster.
+        let loaded = GradientBooster::from_json(&imputed_booster.json_dump().unwrap()).unwrap();
+        assert_eq!(loaded.imputer, imputed_booster.imputer);
+        assert_eq!(loaded.predict(&data, true), preds);
+
+        // The virtual ensembles, updating, and pruning, impute the data too.
+        assert_eq!(
+            predict_virtual_ensembles(&imputed_booster, &data, 2, true).unwrap(),
+            predict_virtual_ensembles(&booster, &imputed.as_matrix(), 2, true).unwrap()
+        );
+        let mut updated = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        updated.update(&imputed.as_matrix(), &y, &w, 2).unwrap();
+        let mut imputed_updated = loaded;
+        imputed_updated.update(&data, &y, &w, 2).unwrap();
+        assert_eq!(
+            imputed_updated.predict(&data, true),
+            updated.predict(&imputed.as_matrix(), true)
+        );
+        let pruned = updated
+            .prune_trees(&imputed.as_matrix(), &y, &w, None, 0.001)
+            .unwrap();
+        assert_eq!(
+            imputed_updated
+                .prune_trees(&data, &y, &w, None, 0.001)
+                .unwrap(),
+            pruned
+        );
+        assert_eq!(
+            imputed_updated.predict(&data, true),
+            updated.predict(&imputed.as_matrix(), true)
+        );
+
+        // Checkpoints saved while fitting hold the imputer.
+        let path = "resources/model_checkpoint_imputer.json";
+        let mut checkpointed = GradientBooster::default()
+            .set_iterations(10)
+            .set_imputer(imputed_booster.imputer.clone())
+            .set_checkpoint(Some(Checkpoint {
+                path: path.to_string(),
+                iterations: Some(4),
+                seconds: None,
+                target_rounds: None,
+            }));
+        checkpointed.fit(&data, &y, &w, None).unwrap();
+        let mut resumed = GradientBooster::resume_from_checkpoint(path).unwrap();
+        assert_eq!(resumed.imputer, imputed_booster.imputer);
+        resumed.fit(&data, &y, &w, None).unwrap();
+        assert_eq!(resumed.predict(&data, true), preds);
+
+        let binned_data = bin_matrix(&data, &w, 50, f64::NAN).unwrap();
+        assert!(imputed_booster
+            .fit_from_binned(&binned_data, &y, &w)
+            .is_err());
+        assert!(imputed_booster.imputer.is_some());
+    }
+
+    #[test]
+    fn test_fit_dataset() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&data, &y, &w, None).unwrap();
+
+        let names: Vec<String> = ["pclass", "age", "sibsp", "parch", "fare"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        let features = MatrixData {
+            data: data_vec.clone(),
+            rows: 891,
+            cols: 5,
+        };
+        let dataset = Dataset::new(features, y.clone())
+            .unwrap()
+            .set_feature_names(names.clone());
+        let mut dataset_booster = GradientBooster::default().set_iterations(10);
+        dataset_booster
+            .fit_dataset(&dataset, Some(&[&dataset]))
+            .unwrap();
+        assert_eq!(
+            dataset_booster.predict(&data, true),
+            booster.predict(&data, true)
+        );
+        assert_eq!(dataset_booster.feature_names, Some(names));
+        assert!(dataset_booster.evaluation_history.is_some());
+
+        // The age has too many values to be categorical, with 10 bins.
+        let mut categorical = vec![false; 5];
+        categorical[1] = true;
+        let too_many = dataset.clone().set_categorical(categorical);
+        let mut small_booster = GradientBooster::default().set_nbins(10);
+        assert!(small_booster.fit_dataset(&too_many, None).is_err());
+
+        let constrained =
+            dataset.set_monotone_constraints(&ConstraintMap::from([(4, Constraint::Positive)]));
+        dataset_booster.fit_dataset(&constrained, None).unwrap();
+        assert_eq!(
+            dataset_booster.monotone_constraints.as_ref().unwrap().len(),
+            1
+        );
+        let invalid = constrained.set_categorical(vec![false]);
+        assert!(dataset_booster.fit_dataset(&invalid, None).is_err());
+    }
+
+    #[test]
+    fn test_fit_view() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        // Fitting on a view, grows the same trees as fitting on a copy of the columns.
+        let columns = vec![4, 0, 2];
+        let copied: Vec<f64> = columns
+            .iter()
+            .flat_map(|c| data.get_col(*c).to_vec())
+            .collect();
+        let copied_data = Matrix::new(&copied, 891, 3);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&copied_data, &y, &w, None).unwrap();
+
+        let view = MatrixView::new(&data, columns).unwrap();
+        let mut view_booster = GradientBooster::default().set_iterations(10);
+        let eval_view = MatrixView::new(&data, view.columns().to_vec()).unwrap();
+        view_booster
+            .fit_view(&view, &y, &w, Some(vec![(eval_view, &y, &w)]))
+            .unwrap();
+        assert_eq!(
+            view_booster.predict(&view, true),
+            booster.predict(&copied_data, true)
+        );
+        assert!(view_booster.evaluation_history.is_some());
 
-    /// Set the base_score on the booster.
-    /// * `base_score` - The base score of the booster.
-    pub fn set_initialize_base_score(mut self, initialize_base_score: bool) -> Self {
-        self.initialize_base_score = initialize_base_score;
-        self
+        let mut split_booster = GradientBooster::default().set_validation_fraction(Some(0.2));
+        assert!(split_booster.fit_view(&view, &y, &w, None).is_err());
     }
 
-    /// Set the parallel on the booster.
-    /// * `parallel` - Set if the booster should be trained in parallels.
-    pub fn set_parallel(mut self, parallel: bool) -> Self {
-        self.parallel = parallel;
-        self
-    }
+    #[test]
+    fn test_fit_row_major() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&data, &y, &w, None).unwrap();
 
-    /// Set the allow_missing_splits on the booster.
-    /// * `allow_missing_splits` - Set if missing splits are allowed for the booster.
-    pub fn set_allow_missing_splits(mut self, allow_missing_splits: bool) -> Self {
-        self.allow_missing_splits = allow_missing_splits;
-        self
-    }
+        // Fitting on the row major layout of the data, grows the same trees.
+        let row_major: Vec<f64> = (0..891)
+            .flat_map(|i| data.get_row_iter(i).copied().collect::<Vec<_>>())
+            .collect();
+        let view = RowMajorView::new(&row_major, 891, 5).unwrap();
+        assert_eq!(view.get_row(3), data.get_row(3));
+        let mut row_major_booster = GradientBooster::default().set_iterations(10);
+        row_major_booster
+            .fit_row_major(&view, &y, &w, None)
+            .unwrap();
+        assert_eq!(
+            row_major_booster.predict(&view, true),
+            booster.predict(&data, true)
+        );
 
-    /// Set the monotone_constraints on the booster.
-    /// * `monotone_constraints` - The monotone constraints of the booster.
-    pub fn set_monotone_constraints(mut self, monotone_constraints: Option<ConstraintMap>) -> Self {
-        self.monotone_constraints = monotone_constraints;
-        self
+        assert!(RowMajorView::new(&row_major, 891, 4).is_err());
+        let mut split_booster = GradientBooster::default().set_validation_fraction(Some(0.2));
+        assert!(split_booster.fit_row_major(&view, &y, &w, None).is_err());
     }
 
-    /// Set the subsample on the booster.
-    /// * `subsample` - Percent of the data to randomly sample when training each tree.
-    pub fn set_subsample(mut self, subsample: f32) -> Self {
-        self.subsample = subsample;
-        self
-    }
+    #[test]
+    fn test_fit_rows() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
 
-    /// Set the seed on the booster.
-    /// * `seed` - Integer value used to see any randomness used in the algorithm.
-    pub fn set_seed(mut self, seed: u64) -> Self {
-        self.seed = seed;
-        self
-    }
+        // Fitting on a view of a fold, grows the same trees as fitting on a copy of it.
+        let fold: Vec<usize> = (0..891).filter(|i| i % 3 != 0).collect();
+        let fold_y: Vec<f64> = fold.iter().map(|i| y[*i]).collect();
+        let fold_w = vec![1.; fold.len()];
+        let copied = data.select_rows(&fold);
+        let copied_data = Matrix::new(&copied, fold.len(), 5);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&copied_data, &fold_y, &fold_w, None).unwrap();
 
-    /// Set missing value of the booster
-    /// * `missing` - Float value to consider as missing.
-    pub fn set_missing(mut self, missing: f64) -> Self {
-        self.missing = missing;
-        self
-    }
+        let view = RowView::new(&data, fold).unwrap();
+        let mut view_booster = GradientBooster::default().set_iterations(10);
+        view_booster
+            .fit_rows(&view, &fold_y, &fold_w, None)
+            .unwrap();
+        assert_eq!(
+            view_booster.predict(&data, true),
+            booster.predict(&data, true)
+        );
 
-    /// Set create missing value of the booster
-    /// * `create_missing_branch` - Bool specifying if missing should get it's own
-    /// branch.
-    pub fn set_create_missing_branch(mut self, create_missing_branch: bool) -> Self {
-        self.create_missing_branch = create_missing_branch;
-        self
+        // The validation split is a view of the same data.
+        let all = RowView::new(&data, (0..891).collect()).unwrap();
+        let mut split_booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_validation_fraction(Some(0.2));
+        split_booster.fit_rows(&all, &y, &w, None).unwrap();
+        assert!(split_booster.evaluation_history.is_some());
     }
 
-    /// Set sample method on the booster.
-    /// * `sample_method` - Sample method.
-    pub fn set_sample_method(mut self, sample_method: SampleMethod) -> Self {
-        self.sample_method = sample_method;
-        self
-    }
+    #[test]
+    fn test_fit_integer() {
+        let file = fs::read_to_string("resources/contiguous_no_missing.csv")
+            .expect("Something went wrong reading the file");
+        // Scale the data, so the integer values keep the order of the floats.
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| (x.parse::<f64>().unwrap() * 100.).round())
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
 
-    /// Set sample method on the booster.
-    /// * `evaluation_metric` - Sample method.
-    pub fn set_evaluation_metric(mut self, evaluation_metric: Option<Metric>) -> Self {
-        self.evaluation_metric = evaluation_metric;
-        self
-    }
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit(&data, &y, &w, None).unwrap();
+        let preds = booster.predict(&data, true);
 
-    /// Set early stopping rounds.
-    /// * `early_stopping_rounds` - Early stoppings rounds.
-    pub fn set_early_stopping_rounds(mut self, early_stopping_rounds: Option<usize>) -> Self {
-        self.early_stopping_rounds = early_stopping_rounds;
-        self
-    }
+        // Integer data is binned, and predicted on, the same as the floats.
+        let int_vec: Vec<i32> = data_vec.iter().map(|v| *v as i32).collect();
+        let int_data = Matrix::new(&int_vec, 891, 5);
+        let mut int_booster = GradientBooster::default().set_iterations(10);
+        int_booster.fit_integer(&int_data, &y, &w, None).unwrap();
+        assert_eq!(int_booster.predict(&int_data, true), preds);
 
-    /// Set prediction iterations.
-    /// * `early_stopping_rounds` - Early stoppings rounds.
-    pub fn set_prediction_iteration(mut self, prediction_iteration: Option<usize>) -> Self {
-        self.prediction_iteration = prediction_iteration.map(|i| i + 1);
-        self
+        let long_vec: Vec<i64> = data_vec.iter().map(|v| *v as i64).collect();
+        let long_data = Matrix::new(&long_vec, 891, 5);
+        let mut long_booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_validation_fraction(Some(0.2));
+        long_booster.fit_integer(&long_data, &y, &w, None).unwrap();
+        assert_eq!(long_booster.predict(&long_data, true).len(), 891);
     }
 
-    /// Insert metadata
-    /// * `key` - String value for the metadata key.
-    /// * `value` - value to assign to the metadata key.
-    pub fn insert_metadata(&mut self, key: String, value: String) {
-        self.metadata.insert(key, value);
-    }
+    #[test]
+    fn test_fit_from_chunks() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
 
-    /// Get Metadata
-    /// * `key` - Get the associated value for the metadata key.
-    pub fn get_metadata(&self, key: &String) -> Option<String> {
-        self.metadata.get(key).cloned()
-    }
-}
+        let mut booster = GradientBooster::default().set_nbins(50);
+        booster.fit(&data, &y, &w, None).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
+        // Fitting on chunks of rows, grows the same trees.
+        let chunks = || {
+            (0..data.rows).step_by(200).map(|start| {
+                let end = (start + 200).min(data.rows);
+                let columns = (0..data.cols)
+                    .map(|j| data.get_col(j)[start..end].to_vec())
+                    .collect();
+                DataChunk {
+                    data: MatrixData::from_columns(columns).unwrap(),
+                    y: y[start..end].to_vec(),
+                    sample_weight: w[start..end].to_vec(),
+                }
+            })
+        };
+        let mut chunked_booster = GradientBooster::default().set_nbins(50);
+        chunked_booster.fit_from_chunks(chunks).unwrap();
+        assert_eq!(
+            chunked_booster.predict(&data, true),
+            booster.predict(&data, true)
+        );
+    }
 
     #[test]
-    fn test_booster_fit_subsample() {
+    #[cfg(feature = "mmap")]
+    fn test_fit_from_mmap() {
         let file = fs::read_to_string("resources/contiguous_with_missing.csv")
             .expect("Something went wrong reading the file");
         let data_vec: Vec<f64> = file
@@ -1037,29 +6323,90 @@ mod tests {
         let file = fs::read_to_string("resources/performance.csv")
             .expect("Something went wrong reading the file");
         let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        let binned_data = bin_matrix(&data, &w, 50, f64::NAN).unwrap();
+        let mut booster = GradientBooster::default();
+        booster.fit_from_binned(&binned_data, &y, &w).unwrap();
+
+        // Training on the memory mapped bins, grows the same trees.
+        let path = "resources/fit_from_mmap.bin";
+        binned_data.save(path).unwrap();
+        let mapped = MmapBinnedData::open(path).unwrap();
+        let mut mmap_booster = GradientBooster::default();
+        mmap_booster.fit_from_mmap(&mapped, &y, &w).unwrap();
+        assert_eq!(
+            mmap_booster.predict(&data, true),
+            booster.predict(&data, true)
+        );
+    }
 
+    #[test]
+    fn test_checkpoint() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
         let data = Matrix::new(&data_vec, 891, 5);
-        //let data = Matrix::new(data.get_col(1), 891, 1);
+
+        let path = "resources/model_checkpoint.json";
         let mut booster = GradientBooster::default()
             .set_iterations(10)
-            .set_nbins(300)
-            .set_max_depth(3)
-            .set_subsample(0.5)
-            .set_base_score(0.5)
-            .set_initialize_base_score(false);
-        let sample_weight = vec![1.; y.len()];
-        booster.fit(&data, &y, &sample_weight, None).unwrap();
-        let preds = booster.predict(&data, false);
-        let contribs = booster.predict_contributions(&data, ContributionsMethod::Average, false);
-        assert_eq!(contribs.len(), (data.cols + 1) * data.rows);
-        println!("{}", booster.trees[0]);
-        println!("{}", booster.trees[0].nodes.len());
-        println!("{}", booster.trees.last().unwrap().nodes.len());
-        println!("{:?}", &preds[0..10]);
+            .set_checkpoint(Some(Checkpoint {
+                path: path.to_string(),
+                iterations: Some(4),
+                seconds: None,
+                target_rounds: None,
+            }));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+
+        // The last checkpoint was saved after 8 rounds.
+        let mut resumed = GradientBooster::resume_from_checkpoint(path).unwrap();
+        assert_eq!(resumed.trees.len(), 8);
+        assert_eq!(resumed.iterations, 2);
+        assert!(resumed.warm_start);
+        resumed.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(resumed.trees.len(), 10);
+        assert_eq!(resumed.predict(&data, true), booster.predict(&data, true));
+
+        // A warm started fit is resumed with the rounds left of that fit.
+        let mut warm = GradientBooster::default().set_iterations(5);
+        warm.fit_unweighted(&data, &y, None).unwrap();
+        let mut warm = warm
+            .set_iterations(10)
+            .set_warm_start(true)
+            .set_checkpoint(Some(Checkpoint {
+                path: path.to_string(),
+                iterations: Some(4),
+                seconds: None,
+                target_rounds: None,
+            }));
+        warm.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(warm.trees.len(), 15);
+        let mut resumed = GradientBooster::resume_from_checkpoint(path).unwrap();
+        assert_eq!(resumed.trees.len(), 13);
+        assert_eq!(resumed.iterations, 2);
+        resumed.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(resumed.trees.len(), 15);
+        assert_eq!(resumed.predict(&data, true), warm.predict(&data, true));
+
+        let mut invalid = GradientBooster::default().set_checkpoint(Some(Checkpoint {
+            path: path.to_string(),
+            iterations: None,
+            seconds: None,
+            target_rounds: None,
+        }));
+        assert!(invalid.fit_unweighted(&data, &y, None).is_err());
     }
 
     #[test]
-    fn test_booster_fit() {
+    fn test_feature_names() {
         let file = fs::read_to_string("resources/contiguous_with_missing.csv")
             .expect("Something went wrong reading the file");
         let data_vec: Vec<f64> = file
@@ -1069,60 +6416,107 @@ mod tests {
         let file = fs::read_to_string("resources/performance.csv")
             .expect("Something went wrong reading the file");
         let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
-
         let data = Matrix::new(&data_vec, 891, 5);
-        //let data = Matrix::new(data.get_col(1), 891, 1);
+
+        let names: Vec<String> = ["pclass", "age", "sibsp", "parch", "fare"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
         let mut booster = GradientBooster::default()
-            .set_iterations(10)
-            .set_nbins(300)
+            .set_iterations(5)
             .set_max_depth(3)
-            .set_base_score(0.5)
-            .set_initialize_base_score(false);
+            .set_feature_names(Some(names.clone()))
+            .set_named_monotone_constraints(HashMap::from([(
+                "fare".to_string(),
+                Constraint::Positive,
+            )]))
+            .unwrap();
+        assert!(matches!(
+            booster.monotone_constraints.as_ref().unwrap().get(&4),
+            Some(Constraint::Positive)
+        ));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+
+        assert_eq!(booster.feature_index("age").unwrap(), 1);
+        assert!(booster.feature_index("name").is_err());
+        assert_eq!(
+            booster
+                .value_partial_dependence_by_name("age", 30.)
+                .unwrap(),
+            booster.value_partial_dependence(1, 30.)
+        );
+        let dump = booster.text_dump();
+        assert!(dump.starts_with("booster[0]:"));
+        assert!(names.iter().any(|n| dump.contains(&format!("[{} < ", n))));
 
         let sample_weight = vec![1.; y.len()];
-        booster.fit(&data, &y, &sample_weight, None).unwrap();
-        let preds = booster.predict(&data, false);
-        let contribs = booster.predict_contributions(&data, ContributionsMethod::Average, false);
-        assert_eq!(contribs.len(), (data.cols + 1) * data.rows);
-        println!("{}", booster.trees[0]);
-        println!("{}", booster.trees[0].nodes.len());
-        println!("{}", booster.trees.last().unwrap().nodes.len());
-        println!("{:?}", &preds[0..10]);
+        let importance = booster
+            .permutation_importance(&data, &y, &sample_weight, None, 1, 0)
+            .unwrap();
+        assert_eq!(importance.importances_mean_by_name().unwrap().len(), 5);
+
+        // The names are saved with the booster.
+        let loaded = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        assert_eq!(loaded.feature_names, Some(names));
+
+        let mut booster =
+            GradientBooster::default().set_feature_names(Some(vec!["age".to_string()]));
+        assert!(booster.fit_unweighted(&data, &y, None).is_err());
+        assert!(GradientBooster::default()
+            .set_named_monotone_constraints(HashMap::from([(
+                "fare".to_string(),
+                Constraint::Positive
+            )]))
+            .is_err());
     }
 
     #[test]
-    fn test_booster_fit_nofitted_base_score() {
+    fn test_scale_leaves() {
         let file = fs::read_to_string("resources/contiguous_with_missing.csv")
             .expect("Something went wrong reading the file");
         let data_vec: Vec<f64> = file
             .lines()
             .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
             .collect();
-        let file = fs::read_to_string("resources/performance-fare.csv")
+        let file = fs::read_to_string("resources/performance.csv")
             .expect("Something went wrong reading the file");
         let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
-
         let data = Matrix::new(&data_vec, 891, 5);
-        //let data = Matrix::new(data.get_col(1), 891, 1);
+
         let mut booster = GradientBooster::default()
-            .set_objective_type(ObjectiveType::SquaredLoss)
             .set_iterations(10)
-            .set_nbins(300)
-            .set_max_depth(3)
-            .set_initialize_base_score(true);
-        let sample_weight = vec![1.; y.len()];
-        booster.fit(&data, &y, &sample_weight, None).unwrap();
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
         let preds = booster.predict(&data, false);
-        let contribs = booster.predict_contributions(&data, ContributionsMethod::Average, false);
-        assert_eq!(contribs.len(), (data.cols + 1) * data.rows);
-        println!("{}", booster.trees[0]);
-        println!("{}", booster.trees[0].nodes.len());
-        println!("{}", booster.trees.last().unwrap().nodes.len());
-        println!("{:?}", &preds[0..10]);
+        let base = booster.base_score;
+
+        // Halving every tree halves the distance of the predictions from the base score.
+        let mut scaled = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        scaled.scale_leaves(0.5).unwrap();
+        for (p, s) in preds.iter().zip(scaled.predict(&data, false)) {
+            assert!((base + (p - base) * 0.5 - s).abs() < 1e-6);
+        }
+        scaled.scale_leaves(0.).unwrap();
+        assert!(scaled.predict(&data, false).iter().all(|p| *p == base));
+
+        // Scaling a single tree, only changes its part of the predictions.
+        let tree_preds = booster.trees[3].predict(&data, false, &booster.missing);
+        let mut scaled = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        scaled.scale_tree_leaves(3, 2.).unwrap();
+        for ((p, t), s) in preds
+            .iter()
+            .zip(tree_preds)
+            .zip(scaled.predict(&data, false))
+        {
+            assert!((p + t - s).abs() < 1e-6);
+        }
+        assert!(scaled.scale_tree_leaves(10, 2.).is_err());
+        assert!(scaled.scale_leaves(f32::NAN).is_err());
+        assert!(scaled.scale_tree_leaves(0, f32::INFINITY).is_err());
     }
 
     #[test]
-    fn test_tree_save() {
+    fn test_prune_trees() {
         let file = fs::read_to_string("resources/contiguous_with_missing.csv")
             .expect("Something went wrong reading the file");
         let data_vec: Vec<f64> = file
@@ -1132,28 +6526,123 @@ mod tests {
         let file = fs::read_to_string("resources/performance.csv")
             .expect("Something went wrong reading the file");
         let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let w = vec![1.; y.len()];
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(50)
+            .set_learning_rate(0.1);
+        booster.fit(&data, &y, &w, None).unwrap();
+        let before = log_loss(&y, &booster.predict(&data, true), &w);
+
+        let removed = booster
+            .prune_trees(&data, &y, &w, Some(Metric::LogLoss), 0.005)
+            .unwrap();
+        assert!(removed > 0);
+        assert_eq!(booster.trees.len(), 50 - removed);
+        let after = log_loss(&y, &booster.predict(&data, true), &w);
+        assert!(after - before <= 0.005);
+
+        // With no tolerance, only trees that don't worsen the metric are removed.
+        let after_removed = booster
+            .prune_trees(&data, &y, &w, Some(Metric::LogLoss), 0.)
+            .unwrap();
+        assert!(log_loss(&y, &booster.predict(&data, true), &w) <= after);
+        assert_eq!(booster.trees.len(), 50 - removed - after_removed);
+
+        assert!(booster.prune_trees(&data, &y, &w, None, f64::NAN).is_err());
+    }
 
+    #[test]
+    fn test_calibrate_sigmoid() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
         let data = Matrix::new(&data_vec, 891, 5);
-        //let data = Matrix::new(data.get_col(1), 891, 1);
+
+        let (train_index, valid_index) = validation_split(&y, 0.3, true, 0);
+        let select = |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+        let train_vec = data.select_rows(&train_index);
+        let train_data = Matrix::new(&train_vec, train_index.len(), data.cols);
+        let valid_vec = data.select_rows(&valid_index);
+        let valid_data = Matrix::new(&valid_vec, valid_index.len(), data.cols);
+        let (train_y, valid_y) = (select(&y, &train_index), select(&y, &valid_index));
+
+        // An overfit booster is overconfident on held out data.
         let mut booster = GradientBooster::default()
-            .set_iterations(10)
-            .set_nbins(300)
-            .set_max_depth(3)
-            .set_base_score(0.5)
-            .set_initialize_base_score(false);
-        let sample_weight = vec![1.; y.len()];
-        booster.fit(&data, &y, &sample_weight, None).unwrap();
-        let preds = booster.predict(&data, true);
+            .set_iterations(100)
+            .set_max_depth(8);
+        booster.fit_unweighted(&train_data, &train_y, None).unwrap();
+        let uncalibrated = booster.predict_proba(&valid_data, true, true).unwrap();
+        booster
+            .calibrate_sigmoid(&valid_data, &valid_y, None)
+            .unwrap();
+        let calibrated = booster.predict_proba(&valid_data, true, true).unwrap();
+        let w = vec![1.; valid_y.len()];
+        let logit = |p: &[f64]| p.iter().map(|p| (p / (1. - p)).ln()).collect::<Vec<_>>();
+        assert!(
+            log_loss(&valid_y, &logit(&calibrated), &w)
+                < log_loss(&valid_y, &logit(&uncalibrated), &w)
+        );
+        assert_eq!(
+            booster.predict_proba(&valid_data, true, false).unwrap(),
+            uncalibrated
+        );
 
-        booster.save_booster("resources/model64.json").unwrap();
-        let booster2 = GradientBooster::load_booster("resources/model64.json").unwrap();
-        assert_eq!(booster2.predict(&data, true)[0..10], preds[0..10]);
+        // The calibration is saved with the booster.
+        let loaded = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        assert_eq!(
+            loaded.predict_proba(&valid_data, true, true).unwrap(),
+            calibrated
+        );
+        let loaded = GradientBooster::from_binary(&booster.binary_dump().unwrap()).unwrap();
+        assert_eq!(
+            loaded.predict_proba(&valid_data, true, true).unwrap(),
+            calibrated
+        );
+        // The target, and weights, need a value for each row.
+        assert!(booster
+            .calibrate_sigmoid(&valid_data, &valid_y[1..], None)
+            .is_err());
+        assert!(booster
+            .calibrate_sigmoid(&valid_data, &valid_y, Some(&w[1..]))
+            .is_err());
 
-        // Test with non-NAN missing.
-        booster.missing = 0.;
-        booster.save_booster("resources/modelmissing.json").unwrap();
-        let booster3 = GradientBooster::load_booster("resources/modelmissing.json").unwrap();
-        assert_eq!(booster3.missing, 0.);
-        assert_eq!(booster3.missing, booster.missing);
+        // An isotonic calibration is saved with the booster too.
+        booster
+            .calibrate_isotonic(&valid_data, &valid_y, None)
+            .unwrap();
+        let isotonic = booster.predict_proba(&valid_data, true, true).unwrap();
+        assert!(isotonic.iter().all(|p| (0. ..=1.).contains(p)));
+        let loaded = GradientBooster::from_json(&booster.json_dump().unwrap()).unwrap();
+        assert_eq!(
+            loaded.predict_proba(&valid_data, true, true).unwrap(),
+            isotonic
+        );
+        let loaded = GradientBooster::from_binary(&booster.binary_dump().unwrap()).unwrap();
+        assert_eq!(
+            loaded.predict_proba(&valid_data, true, true).unwrap(),
+            isotonic
+        );
+        assert!(booster
+            .calibrate_isotonic(&valid_data, &valid_y[1..], None)
+            .is_err());
+        assert!(booster
+            .calibrate_isotonic(&valid_data, &valid_y, Some(&w[1..]))
+            .is_err());
+
+        let mut booster = GradientBooster::default().set_objective_type(ObjectiveType::SquaredLoss);
+        assert!(booster
+            .calibrate_sigmoid(&valid_data, &valid_y, None)
+            .is_err());
+        assert!(booster
+            .calibrate_isotonic(&valid_data, &valid_y, None)
+            .is_err());
     }
 }
@@ -1,10 +1,19 @@
+use crate::errors::ForustError;
 use crate::node::SplittableNode;
 use std::collections::BinaryHeap;
+use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter making the names of the files each `SpillingGrower` spills to unique.
+static SPILLING_GROWERS: AtomicUsize = AtomicUsize::new(0);
 
 pub trait Grower {
     fn add_node(&mut self, node: SplittableNode);
-    fn get_next_node(&mut self) -> SplittableNode;
+    fn get_next_node(&mut self) -> Result<SplittableNode, ForustError>;
     fn is_empty(&self) -> bool;
 }
 
@@ -13,8 +22,8 @@ impl Grower for BinaryHeap<SplittableNode> {
         self.push(node);
     }
 
-    fn get_next_node(&mut self) -> SplittableNode {
-        self.pop().expect("Grower should not be empty")
+    fn get_next_node(&mut self) -> Result<SplittableNode, ForustError> {
+        Ok(self.pop().expect("Grower should not be empty"))
     }
 
     fn is_empty(&self) -> bool {
@@ -27,11 +36,134 @@ impl Grower for VecDeque<SplittableNode> {
         self.push_front(node);
     }
 
-    fn get_next_node(&mut self) -> SplittableNode {
-        self.pop_back().expect("Grower should not be empty")
+    fn get_next_node(&mut self) -> Result<SplittableNode, ForustError> {
+        Ok(self.pop_back().expect("Grower should not be empty"))
     }
 
     fn is_empty(&self) -> bool {
         self.is_empty()
     }
 }
+
+/// A grower, that keeps the histograms of the nodes waiting to be split within a memory
+/// budget. The histograms of nodes added once the budget is used up are written to the
+/// temporary directory, and read back when the node is split. With depth wise growth,
+/// these are the nodes split last. Histograms that can't be written are kept in memory.
+pub struct SpillingGrower {
+    grower: Box<dyn Grower>,
+    max_memory: usize,
+    memory: usize,
+    id: usize,
+    spilled: HashMap<usize, PathBuf>,
+}
+
+impl SpillingGrower {
+    /// * `grower` - The grower that orders the nodes.
+    /// * `max_memory` - The budget in bytes, for the histograms of nodes kept in memory.
+    pub fn new(grower: Box<dyn Grower>, max_memory: usize) -> Self {
+        SpillingGrower {
+            grower,
+            max_memory,
+            memory: 0,
+            id: SPILLING_GROWERS.fetch_add(1, Ordering::Relaxed),
+            spilled: HashMap::new(),
+        }
+    }
+
+    fn spill_path(&self, num: usize) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "forust-histogram-{}-{}-{}.bin",
+            process::id(),
+            self.id,
+            num
+        ))
+    }
+}
+
+impl Grower for SpillingGrower {
+    fn add_node(&mut self, mut node: SplittableNode) {
+        let size = node.histograms.size();
+        if self.memory + size > self.max_memory {
+            let path = self.spill_path(node.num);
+            if node.histograms.spill(&path).is_ok() {
+                self.spilled.insert(node.num, path);
+                self.grower.add_node(node);
+                return;
+            }
+            // The partially written file is removed, and the histograms stay in memory.
+            let _ = fs::remove_file(path);
+        }
+        self.memory += size;
+        self.grower.add_node(node);
+    }
+
+    fn get_next_node(&mut self) -> Result<SplittableNode, ForustError> {
+        let mut node = self.grower.get_next_node()?;
+        match self.spilled.remove(&node.num) {
+            Some(path) => {
+                let restored = node.histograms.restore(&path);
+                // The file is removed when it's read, but not if reading it failed.
+                if restored.is_err() {
+                    let _ = fs::remove_file(&path);
+                }
+                restored.map_err(ForustError::Io)?
+            }
+            None => self.memory -= node.histograms.size(),
+        }
+        Ok(node)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.grower.is_empty()
+    }
+}
+
+impl Drop for SpillingGrower {
+    /// Remove the files of nodes that were never split.
+    fn drop(&mut self) {
+        for path in self.spilled.values() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binning::bin_matrix;
+    use crate::data::Matrix;
+    use crate::histogram::HistogramMatrix;
+
+    #[test]
+    fn test_spilled_histograms_unreadable() {
+        let data_vec: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let data = Matrix::new(&data_vec, 10, 2);
+        let w = vec![1.; data.rows];
+        let b = bin_matrix(&data, &w, 10, f64::NAN).unwrap();
+        let bdata = Matrix::new(&b.binned_data, data.rows, data.cols);
+        let (g, h) = (vec![0.5; data.rows], vec![0.25; data.rows]);
+        let hists = HistogramMatrix::new(&bdata, &b.cuts, &g, &h, &bdata.index, false, false);
+        let node = SplittableNode::new(
+            0,
+            hists,
+            0.,
+            0.,
+            5.,
+            2.5,
+            0,
+            0,
+            data.rows,
+            f32::NEG_INFINITY,
+            f32::INFINITY,
+        );
+
+        // With no memory, the histograms are spilled, and an error is returned
+        // when the file can't be read back.
+        let mut grower = SpillingGrower::new(Box::<VecDeque<SplittableNode>>::default(), 0);
+        grower.add_node(node);
+        let path = grower.spill_path(0);
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(grower.get_next_node(), Err(ForustError::Io(_))));
+    }
+}
@@ -0,0 +1,258 @@
+use crate::data::Matrix;
+use crate::errors::ForustError;
+use crate::metric::{metric_callables, Metric};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Results of a permutation importance calculation.
+///
+/// * `baseline` - The metric value calculated on the unshuffled data.
+/// * `importances` - For every feature, the metric degradation of each repeat.
+/// * `importances_mean` - Mean degradation of every feature across repeats.
+/// * `importances_std` - Standard deviation of the degradation of every feature across repeats.
+/// * `feature_names` - Names of the features, if the model has feature names.
+#[derive(Debug)]
+pub struct PermutationImportance {
+    pub baseline: f64,
+    pub importances: Vec<Vec<f64>>,
+    pub importances_mean: Vec<f64>,
+    pub importances_std: Vec<f64>,
+    pub feature_names: Option<Vec<String>>,
+}
+
+impl PermutationImportance {
+    /// The mean degradation of every feature, keyed by the feature name.
+    /// Returns None if there are no feature names.
+    pub fn importances_mean_by_name(&self) -> Option<HashMap<String, f64>> {
+        self.feature_names.as_ref().map(|names| {
+            names
+                .iter()
+                .cloned()
+                .zip(self.importances_mean.iter().copied())
+                .collect()
+        })
+    }
+
+    /// A serializable report of the importances, with the features ordered from the
+    /// most to the least important, see `FeatureImportanceReport`.
+    pub fn report(&self) -> FeatureImportanceReport {
+        let mut features: Vec<FeatureImportance> = (0..self.importances.len())
+            .map(|i| FeatureImportance {
+                feature: i,
+                feature_name: self.feature_names.as_ref().and_then(|n| n.get(i).cloned()),
+                importance: self.importances_mean[i],
+                importance_std: self.importances_std[i],
+                repeats: self.importances[i].clone(),
+            })
+            .collect();
+        features.sort_by(|a, b| b.importance.total_cmp(&a.importance));
+        FeatureImportanceReport {
+            method: "permutation".to_string(),
+            baseline: self.baseline,
+            features,
+        }
+    }
+}
+
+/// The importance of a single feature, in a `FeatureImportanceReport`.
+///
+/// * `feature` - The index of the feature.
+/// * `feature_name` - The name of the feature, if the model has feature names.
+/// * `importance` - The mean importance of the feature.
+/// * `importance_std` - Standard deviation of the importance across repeats.
+/// * `repeats` - The importance of each repeat.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FeatureImportance {
+    pub feature: usize,
+    pub feature_name: Option<String>,
+    pub importance: f64,
+    pub importance_std: f64,
+    pub repeats: Vec<f64>,
+}
+
+/// A serializable report of feature importances, for consumers such as dashboards.
+///
+/// * `method` - How the importances were calculated, such as "permutation".
+/// * `baseline` - The metric value calculated on the unshuffled data.
+/// * `features` - The importance of every feature, from the most to the least important.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FeatureImportanceReport {
+    pub method: String,
+    pub baseline: f64,
+    pub features: Vec<FeatureImportance>,
+}
+
+impl FeatureImportanceReport {
+    /// Dump the report as a json string.
+    pub fn to_json(&self) -> Result<String, ForustError> {
+        serde_json::to_string_pretty(self).map_err(ForustError::Serialization)
+    }
+
+    /// Load a report from a json string.
+    /// * `json_str` - String object, which can be serialized to json.
+    pub fn from_json(json_str: &str) -> Result<Self, ForustError> {
+        serde_json::from_str(json_str).map_err(ForustError::Serialization)
+    }
+}
+
+/// Calculate the permutation importance of every feature in a dataset.
+/// This is model agnostic, any function that generates predictions for
+/// a matrix of data can be used. Each column is shuffled one at a time,
+/// and the degradation of the metric relative to the unshuffled data is
+/// recorded. Positive values mean the model relied on the feature.
+///
+/// * `predict` - Function used to generate predictions for a matrix of data.
+/// * `data` - The evaluation data, the columns of this will be shuffled.
+/// * `y` - The target of the evaluation data.
+/// * `sample_weight` - Instance weights of the evaluation data.
+/// * `metric` - Metric used to evaluate the predictions.
+/// * `n_repeats` - Number of times each feature should be shuffled.
+/// * `seed` - Integer value used to seed the shuffling.
+pub fn permutation_importance<F>(
+    predict: F,
+    data: &Matrix<f64>,
+    y: &[f64],
+    sample_weight: &[f64],
+    metric: &Metric,
+    n_repeats: usize,
+    seed: u64,
+) -> Result<PermutationImportance, ForustError>
+where
+    F: Fn(&Matrix<f64>) -> Vec<f64>,
+{
+    if n_repeats == 0 {
+        return Err(ForustError::InvalidParameter {
+            name: "n_repeats".to_string(),
+            constraint: "a value greater than 0".to_string(),
+            value: n_repeats.to_string(),
+        });
+    }
+    if y.len() != data.rows || sample_weight.len() != data.rows {
+        return Err(ForustError::InvalidParameter {
+            name: "y and sample_weight".to_string(),
+            constraint: format!("{} records", data.rows),
+            value: format!("{} and {}", y.len(), sample_weight.len()),
+        });
+    }
+    let (metric_fn, maximize) = metric_callables(metric);
+    let degradation = |permuted: f64, baseline: f64| {
+        if maximize {
+            baseline - permuted
+        } else {
+            permuted - baseline
+        }
+    };
+
+    let baseline = metric_fn(y, &predict(data), sample_weight);
+
+    // Work on a copy of the data, so one column at a time
+    // can be shuffled, and then restored.
+    let mut shuffled = data.data.to_vec();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut importances = Vec::with_capacity(data.cols);
+    for col in 0..data.cols {
+        let start = col * data.rows;
+        let stop = start + data.rows;
+        let mut col_importances = Vec::with_capacity(n_repeats);
+        for _ in 0..n_repeats {
+            shuffled[start..stop].shuffle(&mut rng);
+            let m = Matrix::new(&shuffled, data.rows, data.cols);
+            let permuted = metric_fn(y, &predict(&m), sample_weight);
+            col_importances.push(degradation(permuted, baseline));
+        }
+        shuffled[start..stop].copy_from_slice(data.get_col(col));
+        importances.push(col_importances);
+    }
+
+    let importances_mean: Vec<f64> = importances
+        .iter()
+        .map(|v| v.iter().sum::<f64>() / v.len() as f64)
+        .collect();
+    let importances_std = importances
+        .iter()
+        .zip(importances_mean.iter())
+        .map(|(v, mean)| {
+            (v.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / v.len() as f64).sqrt()
+        })
+        .collect();
+
+    Ok(PermutationImportance {
+        baseline,
+        importances,
+        importances_mean,
+        importances_std,
+        feature_names: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gradientbooster::GradientBooster;
+    use std::fs;
+
+    #[test]
+    fn test_permutation_importance() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3);
+        let sample_weight = vec![1.; y.len()];
+        booster.fit(&data, &y, &sample_weight, None).unwrap();
+
+        let importance =
+            booster.permutation_importance(&data, &y, &sample_weight, Some(Metric::AUC), 3, 0);
+        let importance = importance.unwrap();
+        assert_eq!(importance.importances.len(), data.cols);
+        assert!(importance.importances.iter().all(|v| v.len() == 3));
+        assert!(importance.importances_mean.iter().any(|v| v > &0.));
+
+        // The feature whose splits gained the most should matter most.
+        let mut gains = vec![0.; data.cols];
+        booster
+            .trees
+            .iter()
+            .flat_map(|t| t.nodes.iter())
+            .filter(|n| !n.is_leaf)
+            .for_each(|n| gains[n.split_feature] += f64::from(n.split_gain));
+        let argmax = |v: &[f64]| {
+            (0..v.len())
+                .max_by(|a, b| v[*a].partial_cmp(&v[*b]).unwrap())
+                .unwrap()
+        };
+        assert_eq!(argmax(&importance.importances_mean), argmax(&gains));
+
+        // The same seed should produce the same results.
+        let importance2 = booster
+            .permutation_importance(&data, &y, &sample_weight, Some(Metric::AUC), 3, 0)
+            .unwrap();
+        assert_eq!(importance.importances, importance2.importances);
+
+        let report = importance.report();
+        assert_eq!(report.method, "permutation");
+        assert_eq!(report.features.len(), data.cols);
+        assert!(report
+            .features
+            .windows(2)
+            .all(|w| w[0].importance >= w[1].importance));
+        let top = &report.features[0];
+        assert_eq!(top.importance, importance.importances_mean[top.feature]);
+        assert_eq!(top.repeats, importance.importances[top.feature]);
+        assert!(top.feature_name.is_none());
+        let json = report.to_json().unwrap();
+        assert_eq!(FeatureImportanceReport::from_json(&json).unwrap(), report);
+    }
+}
@@ -3,19 +3,46 @@ mod node;
 mod partial_dependence;
 
 // Modules
+pub mod arena;
+#[cfg(feature = "arrow")]
+pub mod arrow;
 pub mod binning;
+pub mod calibration;
+pub mod codegen;
 pub mod constraints;
 pub mod data;
+pub mod dataset;
 pub mod errors;
+pub mod explain;
 pub mod gradientbooster;
 pub mod grower;
+pub mod importance;
+pub mod lightgbm;
+#[cfg(feature = "linfa")]
+pub mod linfa;
+pub mod lookup;
+pub mod memory;
 pub mod metric;
+pub mod model_selection;
 pub mod objective;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod params;
+pub mod pmml;
+pub mod preprocessing;
+pub mod profiler;
 pub mod sampler;
+#[cfg(feature = "serve")]
+pub mod serve;
 pub mod splitter;
 pub mod tree;
+pub mod uncertainty;
 pub mod utils;
+pub mod xgboost;
 
 // Individual classes, and functions
-pub use data::Matrix;
-pub use gradientbooster::GradientBooster;
+pub use data::{CscMatrix, IntegerData, Matrix, MatrixView, RowMajorView, RowView};
+pub use dataset::{Dataset, FeatureMeta, FeatureType};
+pub use gradientbooster::{GradientBooster, GradientBoosterBuilder};
+pub use params::BoosterParams;
+pub use partial_dependence::PartialDependenceGrid;
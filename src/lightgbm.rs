@@ -0,0 +1,405 @@
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::node::Node;
+use crate::objective::ObjectiveType;
+use crate::tree::Tree;
+use crate::utils::next_up;
+use std::collections::HashMap;
+use std::fs;
+
+/// Load a LightGBM model text file, as saved by `save_model`, as a gradient booster.
+///
+/// * `path` - Path to the LightGBM model file.
+pub fn load_lightgbm_model(path: &str) -> Result<GradientBooster, ForustError> {
+    let model_str = match fs::read_to_string(path) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(ForustError::Io(e)),
+    }?;
+    from_lightgbm_model(&model_str)
+}
+
+/// Convert a LightGBM model string into a gradient booster, for inference. Only
+/// single output models with numeric splits, and a `binary`, `cross_entropy`
+/// or `regression` objective are supported. LightGBM adds the initial score to
+/// the first tree, so the base score of the booster is zero.
+///
+/// * `model_str` - The LightGBM model, in the `model.txt` format.
+pub fn from_lightgbm_model(model_str: &str) -> Result<GradientBooster, ForustError> {
+    let mut lines = model_str.lines().map(|l| l.trim());
+    let header = read_block(&mut lines);
+
+    if let Some(num_class) = header.get("num_class") {
+        if *num_class != "1" {
+            return Err(ForustError::UnableToRead(
+                "multi-class LightGBM models are not supported".to_string(),
+            ));
+        }
+    }
+    let objective = header.get("objective").copied().unwrap_or_default();
+    let mut objective_parts = objective.split_whitespace();
+    let objective_type = match objective_parts.next() {
+        Some("binary") | Some("cross_entropy") | Some("xentropy") => {
+            // The sigmoid parameter scales the raw score.
+            if objective_parts.any(|p| p.starts_with("sigmoid:") && p != "sigmoid:1") {
+                return Err(ForustError::UnsupportedObjective {
+                    objective: objective.to_string(),
+                    reason: "only LightGBM models with a sigmoid of 1 can be imported".to_string(),
+                });
+            }
+            ObjectiveType::LogLoss
+        }
+        Some("regression") | Some("regression_l2") | Some("l2") | Some("mean_squared_error") => {
+            ObjectiveType::SquaredLoss
+        }
+        _ => {
+            return Err(ForustError::UnsupportedObjective {
+                objective: objective.to_string(),
+                reason: "only binary, and regression LightGBM models can be imported".to_string(),
+            })
+        }
+    };
+
+    let mut trees = Vec::new();
+    loop {
+        let block = read_block(&mut lines);
+        if block.is_empty() {
+            break;
+        }
+        if block.contains_key("Tree") {
+            trees.push(lightgbm_tree(&block)?);
+        }
+    }
+
+    let mut booster = GradientBooster::default()
+        .set_objective_type(objective_type)
+        .set_iterations(trees.len())
+        .set_base_score(0.)
+        .set_initialize_base_score(false);
+    booster.trees = trees;
+    booster.feature_names = header
+        .get("feature_names")
+        .map(|names| names.split_whitespace().map(|n| n.to_string()).collect());
+    Ok(booster)
+}
+
+/// Read the next block of `key=value` lines, blocks are separated by empty lines.
+/// Reading stops at the end of the trees.
+fn read_block<'a, I: Iterator<Item = &'a str>>(lines: &mut I) -> HashMap<&'a str, &'a str> {
+    let mut block = HashMap::new();
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            if block.is_empty() {
+                continue;
+            }
+            break;
+        }
+        if line == "end of trees" {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            block.insert(key, value);
+        }
+    }
+    block
+}
+
+fn lightgbm_tree(block: &HashMap<&str, &str>) -> Result<Tree, ForustError> {
+    let num_leaves: usize = parse_values(block, "num_leaves")?
+        .first()
+        .copied()
+        .unwrap_or(0);
+    if block.get("is_linear").copied().unwrap_or("0") != "0" {
+        return Err(ForustError::UnableToRead(
+            "linear LightGBM trees are not supported".to_string(),
+        ));
+    }
+    let leaf_value: Vec<f64> = parse_values(block, "leaf_value")?;
+    if num_leaves == 0 || leaf_value.len() != num_leaves {
+        return Err(ForustError::UnableToRead(
+            "LightGBM tree must have a value for every leaf".to_string(),
+        ));
+    }
+    if num_leaves == 1 {
+        return Ok(Tree {
+            nodes: vec![leaf_node(0, leaf_value[0], 0., 0)],
+            learning_rate: None,
+        });
+    }
+
+    let n_internal = num_leaves - 1;
+    let split_feature: Vec<usize> = parse_values(block, "split_feature")?;
+    let split_gain: Vec<f32> = parse_values(block, "split_gain")?;
+    let threshold: Vec<f64> = parse_values(block, "threshold")?;
+    let decision_type: Vec<u8> = parse_values(block, "decision_type")?;
+    let left_child: Vec<i64> = parse_values(block, "left_child")?;
+    let right_child: Vec<i64> = parse_values(block, "right_child")?;
+    let internal_value: Vec<f64> = parse_values(block, "internal_value")?;
+    // The sum of the hessian is stored in the weight, older models only have counts.
+    let (internal_weight, leaf_weight): (Vec<f64>, Vec<f64>) = if block.contains_key("leaf_weight")
+    {
+        (
+            parse_values(block, "internal_weight")?,
+            parse_values(block, "leaf_weight")?,
+        )
+    } else {
+        (
+            parse_values(block, "internal_count")?,
+            parse_values(block, "leaf_count")?,
+        )
+    };
+    if [
+        split_feature.len(),
+        split_gain.len(),
+        threshold.len(),
+        decision_type.len(),
+        left_child.len(),
+        right_child.len(),
+        internal_value.len(),
+        internal_weight.len(),
+    ]
+    .iter()
+    .any(|l| *l != n_internal)
+        || leaf_weight.len() != num_leaves
+    {
+        return Err(ForustError::UnableToRead(
+            "LightGBM tree arrays do not match the number of leaves".to_string(),
+        ));
+    }
+
+    // Internal nodes keep their index, leaves are stored after them.
+    let node_index = |child: i64| -> Result<usize, ForustError> {
+        let idx = if child < 0 {
+            n_internal + (!child) as usize
+        } else {
+            child as usize
+        };
+        if idx >= n_internal + num_leaves {
+            Err(ForustError::UnableToRead(
+                "LightGBM tree references a node that does not exist".to_string(),
+            ))
+        } else {
+            Ok(idx)
+        }
+    };
+
+    let mut nodes = Vec::with_capacity(n_internal + num_leaves);
+    for i in 0..n_internal {
+        let dt = decision_type[i];
+        if dt & 1 != 0 {
+            return Err(ForustError::UnableToRead(
+                "categorical LightGBM splits are not supported".to_string(),
+            ));
+        }
+        let left = node_index(left_child[i])?;
+        let right = node_index(right_child[i])?;
+        let default_left = dt & 2 != 0;
+        // LightGBM sends values less than or equal to the threshold left.
+        let zero_goes_left = 0. <= threshold[i];
+        let missing_node = match (dt >> 2) & 3 {
+            // Without a missing type, missing values are treated as zero.
+            0 => {
+                if zero_goes_left {
+                    left
+                } else {
+                    right
+                }
+            }
+            // Zero values are considered missing, this can only be represented
+            // if zero already follows the default direction.
+            1 if default_left != zero_goes_left => {
+                return Err(ForustError::UnableToRead(
+                    "LightGBM splits treating zero as missing are not supported".to_string(),
+                ))
+            }
+            _ => {
+                if default_left {
+                    left
+                } else {
+                    right
+                }
+            }
+        };
+        nodes.push(Node {
+            num: i,
+            weight_value: internal_value[i] as f32,
+            hessian_sum: internal_weight[i] as f32,
+            depth: 0,
+            split_value: next_up(threshold[i]),
+            split_feature: split_feature[i],
+            split_gain: split_gain[i],
+            missing_node,
+            left_child: left,
+            right_child: right,
+            is_leaf: false,
+        });
+    }
+    for l in 0..num_leaves {
+        nodes.push(leaf_node(n_internal + l, leaf_value[l], leaf_weight[l], 0));
+    }
+
+    // Set the depth of every node, guarding against cycles in malformed trees.
+    let mut stack = vec![(0, 0)];
+    let mut visited = 0;
+    while let Some((idx, depth)) = stack.pop() {
+        visited += 1;
+        if visited > nodes.len() {
+            return Err(ForustError::UnableToRead(
+                "LightGBM tree is not a valid tree".to_string(),
+            ));
+        }
+        let node: &mut Node = &mut nodes[idx];
+        node.depth = depth;
+        if !node.is_leaf {
+            stack.push((node.left_child, depth + 1));
+            stack.push((node.right_child, depth + 1));
+        }
+    }
+    Ok(Tree {
+        nodes,
+        learning_rate: None,
+    })
+}
+
+fn leaf_node(num: usize, value: f64, hessian_sum: f64, depth: usize) -> Node {
+    Node {
+        num,
+        weight_value: value as f32,
+        hessian_sum: hessian_sum as f32,
+        depth,
+        split_value: 0.,
+        split_feature: 0,
+        split_gain: 0.,
+        missing_node: 0,
+        left_child: 0,
+        right_child: 0,
+        is_leaf: true,
+    }
+}
+
+fn parse_values<T: std::str::FromStr>(
+    block: &HashMap<&str, &str>,
+    key: &str,
+) -> Result<Vec<T>, ForustError> {
+    let values = block
+        .get(key)
+        .ok_or_else(|| ForustError::UnableToRead(format!("LightGBM tree is missing {}", key)))?;
+    values
+        .split_whitespace()
+        .map(|v| {
+            v.parse::<T>()
+                .map_err(|_| ForustError::UnableToRead(format!("invalid value {} in {}", v, key)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+
+    const LIGHTGBM_MODEL: &str = "tree
+version=v3
+num_class=1
+num_tree_per_iteration=1
+label_index=0
+max_feature_idx=1
+objective=binary sigmoid:1
+feature_names=Column_0 Column_1
+feature_infos=[0:3] [0:5]
+tree_sizes=400 300
+
+Tree=0
+num_leaves=3
+num_cat=0
+split_feature=0 1
+split_gain=10.5 2.25
+threshold=1.0000000000000002 2.5
+decision_type=8 10
+left_child=-1 -2
+right_child=1 -3
+leaf_value=-0.5 0.25 0.75
+leaf_weight=4 3 3
+leaf_count=4 3 3
+internal_value=0 0.5
+internal_weight=10 6
+internal_count=10 6
+is_linear=0
+shrinkage=1
+
+
+Tree=1
+num_leaves=1
+num_cat=0
+split_feature=
+split_gain=
+threshold=
+decision_type=
+left_child=
+right_child=
+leaf_value=0.125
+leaf_weight=
+leaf_count=
+internal_value=
+internal_weight=
+internal_count=
+is_linear=0
+shrinkage=0.1
+
+
+end of trees
+
+feature_importances:
+Column_0=1
+Column_1=1
+
+parameters:
+[boosting: gbdt]
+end of parameters
+";
+
+    #[test]
+    fn test_from_lightgbm_model() {
+        let booster = from_lightgbm_model(LIGHTGBM_MODEL).unwrap();
+        assert_eq!(booster.trees.len(), 2);
+        assert_eq!(booster.trees[0].nodes.len(), 5);
+        assert_eq!(
+            booster.feature_names,
+            Some(vec!["Column_0".to_string(), "Column_1".to_string()])
+        );
+        // Column major data.
+        let data_vec = vec![
+            1.0000000000000002,
+            3.,
+            3.,
+            3.,
+            f64::NAN,
+            0.,
+            2.5,
+            3.,
+            f64::NAN,
+            1.,
+        ];
+        let data = Matrix::new(&data_vec, 5, 2);
+        let preds = booster.predict(&data, false);
+        let (l0, l1, l2, t1) = (
+            -0.5f32 as f64,
+            0.25f32 as f64,
+            0.75f32 as f64,
+            0.125f32 as f64,
+        );
+        // Values equal to the threshold go left, missing follows the default direction,
+        // which is right for the root node with a NaN missing type, and left for the
+        // second node.
+        assert_eq!(preds, vec![l0 + t1, l1 + t1, l2 + t1, l1 + t1, l1 + t1]);
+    }
+
+    #[test]
+    fn test_from_lightgbm_model_unsupported() {
+        let model = LIGHTGBM_MODEL.replace("num_class=1", "num_class=3");
+        assert!(from_lightgbm_model(&model).is_err());
+        let model = LIGHTGBM_MODEL.replace("objective=binary sigmoid:1", "objective=lambdarank");
+        assert!(from_lightgbm_model(&model).is_err());
+        let model = LIGHTGBM_MODEL.replace("decision_type=8 10", "decision_type=1 10");
+        assert!(from_lightgbm_model(&model).is_err());
+    }
+}
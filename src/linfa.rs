@@ -0,0 +1,121 @@
+//! Implementations of the `linfa` traits, so forust can be used in `linfa` pipelines,
+//! cross validation, and comparisons with other `linfa` models.
+//!
+//! `BoosterParams` implements `Fit`, producing a fitted `GradientBooster`, which
+//! implements `PredictInplace`, and so `Predict`. As `GradientBooster` has its own
+//! `predict` method, the trait method is called as `Predict::predict(&booster, &x)`.
+//!
+//! ```ignore
+//! use forust_ml::params::BoosterParams;
+//! use linfa::prelude::*;
+//!
+//! let params = BoosterParams {
+//!     iterations: 50,
+//!     ..Default::default()
+//! };
+//! let booster = params.fit(&train)?;
+//! let preds: Array1<f64> = Predict::predict(&booster, &valid);
+//! ```
+use crate::data::{Matrix, MatrixData};
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::params::BoosterParams;
+use ::linfa::dataset::{AsTargets, DatasetBase};
+use ::linfa::traits::{Fit, PredictInplace};
+use ndarray::{Array1, ArrayBase, Data, Ix1, Ix2};
+
+impl<D, T> Fit<ArrayBase<D, Ix2>, T, ForustError> for BoosterParams
+where
+    D: Data<Elem = f64>,
+    T: AsTargets<Elem = f64, Ix = Ix1>,
+{
+    type Object = GradientBooster;
+
+    /// Fit a booster with these parameters, on the records, targets, and weights of
+    /// a dataset. Records are weighted equally if the dataset has no weights.
+    fn fit(
+        &self,
+        dataset: &DatasetBase<ArrayBase<D, Ix2>, T>,
+    ) -> Result<GradientBooster, ForustError> {
+        let records = dataset.records();
+        let y: Vec<f64> = dataset.targets().as_targets().iter().copied().collect();
+        let sample_weight: Vec<f64> = match dataset.weights() {
+            Some(w) => w.iter().map(|w| f64::from(*w)).collect(),
+            None => vec![1.; records.nrows()],
+        };
+        let mut booster = GradientBooster::from_params(self.clone())?;
+        match Matrix::try_from(records.view()) {
+            Ok(data) => booster.fit(&data, &y, &sample_weight, None)?,
+            Err(_) => {
+                let data = MatrixData::from(records.view());
+                booster.fit(&data.as_matrix(), &y, &sample_weight, None)?
+            }
+        }
+        Ok(booster)
+    }
+}
+
+impl<D: Data<Elem = f64>> PredictInplace<ArrayBase<D, Ix2>, Array1<f64>> for GradientBooster {
+    /// Predict on the records, column major records are read without copying them.
+    fn predict_inplace(&self, x: &ArrayBase<D, Ix2>, y: &mut Array1<f64>) {
+        assert_eq!(
+            x.nrows(),
+            y.len(),
+            "The number of data points must match the number of output targets."
+        );
+        let preds = match Matrix::try_from(x.view()) {
+            Ok(data) => self.predict(&data, self.parallel),
+            Err(_) => self.predict(&MatrixData::from(x.view()).as_matrix(), self.parallel),
+        };
+        y.iter_mut().zip(preds).for_each(|(y, p)| *y = p);
+    }
+
+    fn default_target(&self, x: &ArrayBase<D, Ix2>) -> Array1<f64> {
+        Array1::zeros(x.nrows())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::linfa::traits::Predict;
+    use ::linfa::Dataset;
+    use ndarray::Array2;
+    use std::fs;
+
+    #[test]
+    fn test_linfa_fit_predict() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let expected = booster.predict(&data, true);
+
+        let records = Array2::from(MatrixData {
+            data: data_vec.clone(),
+            rows: 891,
+            cols: 5,
+        });
+        let dataset = Dataset::new(records, Array1::from(y));
+        let params = BoosterParams {
+            iterations: 10,
+            ..Default::default()
+        };
+        let fitted = params.fit(&dataset).unwrap();
+        let preds: Array1<f64> = Predict::predict(&fitted, dataset.records());
+        assert_eq!(preds.to_vec(), expected);
+        // Row major records are copied, and predicted the same.
+        let row_major = dataset.records().as_standard_layout().to_owned();
+        let preds: Array1<f64> = Predict::predict(&fitted, &row_major);
+        assert_eq!(preds.to_vec(), expected);
+    }
+}
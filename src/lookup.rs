@@ -0,0 +1,266 @@
+use crate::data::{DataMatrix, FloatData};
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::preprocessing::Imputer;
+use crate::tree::Tree;
+use crate::utils::is_missing;
+use rayon::prelude::*;
+
+/// The largest number of split outcomes a table can be indexed by.
+const MAX_TABLE_BITS: usize = 24;
+
+/// The leaf values of a tree, indexed by the outcome of each of its distinct splits.
+struct LookupTable {
+    /// The distinct splits of the tree, as the feature, and the split value. The bit of
+    /// a split is set, if the value isn't missing, and is less than the split value.
+    conditions: Vec<(usize, f64)>,
+    /// The features with a split that doesn't send missing values right. The bit of
+    /// a feature, after the bits of the splits, is set if its value is missing.
+    missing_features: Vec<usize>,
+    values: Vec<f32>,
+}
+
+impl LookupTable {
+    /// Build the table of a tree, if it is indexed by at most `max_bits` bits.
+    fn from_tree(tree: &Tree, max_bits: usize) -> Option<Self> {
+        let mut conditions: Vec<(usize, f64)> = Vec::new();
+        let mut missing_features: Vec<usize> = Vec::new();
+        for n in tree.nodes.iter().filter(|n| !n.is_leaf) {
+            let condition = (n.split_feature, n.split_value);
+            if !conditions
+                .iter()
+                .any(|(f, v)| *f == condition.0 && v.to_bits() == condition.1.to_bits())
+            {
+                conditions.push(condition);
+            }
+            if n.missing_node != n.right_child && !missing_features.contains(&n.split_feature) {
+                missing_features.push(n.split_feature);
+            }
+        }
+        let bits = conditions.len() + missing_features.len();
+        if bits > max_bits {
+            return None;
+        }
+        let values = (0..(1usize << bits))
+            .map(|index| {
+                let is_set = |bit: usize| index & (1 << bit) != 0;
+                let mut node = &tree.nodes[0];
+                while !node.is_leaf {
+                    let feature_missing = missing_features
+                        .iter()
+                        .position(|f| *f == node.split_feature)
+                        .is_some_and(|i| is_set(conditions.len() + i));
+                    let less = conditions
+                        .iter()
+                        .position(|(f, v)| {
+                            *f == node.split_feature && v.to_bits() == node.split_value.to_bits()
+                        })
+                        .is_some_and(is_set);
+                    let child = if feature_missing {
+                        node.missing_node
+                    } else if less {
+                        node.left_child
+                    } else {
+                        node.right_child
+                    };
+                    node = &tree.nodes[child];
+                }
+                node.weight_value
+            })
+            .collect();
+        Some(LookupTable {
+            conditions,
+            missing_features,
+            values,
+        })
+    }
+
+    fn predict_row_with<F: Fn(usize) -> f64>(&self, value: &F, missing: &f64) -> f64 {
+        let mut index = 0;
+        for (bit, (feature, split_value)) in self.conditions.iter().enumerate() {
+            let v = value(*feature);
+            if !is_missing(&v, missing) && v < *split_value {
+                index |= 1 << bit;
+            }
+        }
+        let offset = self.conditions.len();
+        for (bit, feature) in self.missing_features.iter().enumerate() {
+            if is_missing(&value(*feature), missing) {
+                index |= 1 << (offset + bit);
+            }
+        }
+        f64::from(self.values[index])
+    }
+}
+
+/// A tree is evaluated with its lookup table, or by following its nodes if the table
+/// would be too large.
+enum TreeEvaluator {
+    Table(LookupTable),
+    Nodes(Tree),
+}
+
+/// The trees of a booster, with each shallow tree replaced by a table of its leaf values,
+/// indexed by the outcomes of its splits. Predicting a record with a table takes a
+/// comparison for each distinct split of the tree, without branching on the outcomes,
+/// and a single lookup, which is faster than following the nodes of shallow trees,
+/// especially trees that split on few distinct values, such as oblivious trees,
+/// which split on the same value at each depth. The predictions are the same as
+/// the predictions of the booster.
+pub struct LookupTables {
+    trees: Vec<TreeEvaluator>,
+    base_score: f64,
+    missing: f64,
+    imputer: Option<Imputer>,
+}
+
+impl LookupTables {
+    /// Build the lookup tables of the trees a booster uses for prediction.
+    ///
+    /// * `booster` - A fitted booster.
+    /// * `max_bits` - The largest number of bits a table is indexed by, one for each distinct
+    ///   split of the tree, and one for each feature that sends missing values left, or to a
+    ///   missing branch. A table has `2^bits` values, trees with more bits follow their nodes.
+    ///   A tree of depth 6 has at least 6 bits, and at most 63, and 10 is a reasonable default.
+    pub fn from_booster(booster: &GradientBooster, max_bits: usize) -> Result<Self, ForustError> {
+        if max_bits > MAX_TABLE_BITS {
+            return Err(ForustError::InvalidParameter {
+                name: "max_bits".to_string(),
+                constraint: format!("at most {}", MAX_TABLE_BITS),
+                value: max_bits.to_string(),
+            });
+        }
+        let trees = booster
+            .get_prediction_trees()
+            .iter()
+            .map(|tree| match LookupTable::from_tree(tree, max_bits) {
+                Some(table) => TreeEvaluator::Table(table),
+                None => TreeEvaluator::Nodes(tree.clone()),
+            })
+            .collect();
+        Ok(LookupTables {
+            trees,
+            base_score: booster.base_score,
+            missing: booster.missing,
+            imputer: booster.imputer.clone(),
+        })
+    }
+
+    /// Generate predictions on data, the same as the booster the tables were built from.
+    ///
+    /// * `data` - The records to predict.
+    /// * `parallel` - Predict the records in parallel.
+    pub fn predict<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        match &self.imputer {
+            Some(imputer) => self.predict_rows(&imputer.view(data, self.missing), parallel),
+            None => self.predict_rows(data, parallel),
+        }
+    }
+
+    /// Predict a single record, including the imputation of missing values, if the
+    /// booster the tables were built from has an imputer.
+    ///
+    /// * `row` - The value of each feature of the record.
+    pub fn predict_row<T: FloatData<T>>(&self, row: &[T]) -> f64 {
+        self.predict_row_with(|feature| {
+            let v = row[feature].to_f64();
+            match &self.imputer {
+                Some(imputer) if is_missing(&v, &self.missing) => imputer.values[feature],
+                _ => v,
+            }
+        })
+    }
+
+    /// The number of trees, that are evaluated with a lookup table.
+    pub fn n_tables(&self) -> usize {
+        self.trees
+            .iter()
+            .filter(|t| matches!(t, TreeEvaluator::Table(_)))
+            .count()
+    }
+
+    fn predict_rows<D: DataMatrix>(&self, data: &D, parallel: bool) -> Vec<f64> {
+        if parallel {
+            (0..data.rows())
+                .into_par_iter()
+                .map(|i| self.predict_row_with(|feature| data.value(i, feature)))
+                .collect()
+        } else {
+            (0..data.rows())
+                .map(|i| self.predict_row_with(|feature| data.value(i, feature)))
+                .collect()
+        }
+    }
+
+    fn predict_row_with<F: Fn(usize) -> f64>(&self, value: F) -> f64 {
+        self.trees.iter().fold(self.base_score, |p, tree| {
+            p + match tree {
+                TreeEvaluator::Table(table) => table.predict_row_with(&value, &self.missing),
+                TreeEvaluator::Nodes(tree) => tree.predict_row_with(&value, &self.missing),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use crate::preprocessing::ImputeStrategy;
+    use std::fs;
+
+    #[test]
+    fn test_lookup_tables() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        // A depth 3 tree has at most 7 distinct splits, on at most 5 features.
+        let mut booster = GradientBooster::default()
+            .set_iterations(20)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, false);
+        let tables = LookupTables::from_booster(&booster, 12).unwrap();
+        assert_eq!(tables.n_tables(), 20);
+        assert_eq!(tables.predict(&data, false), preds);
+        assert_eq!(tables.predict(&data, true), preds);
+        let row: Vec<f64> = (0..data.cols).map(|j| *data.get(5, j)).collect();
+        assert_eq!(tables.predict_row(&row), preds[5]);
+
+        // Trees with too many splits follow their nodes instead.
+        let tables = LookupTables::from_booster(&booster, 0).unwrap();
+        assert_eq!(tables.n_tables(), 0);
+        assert_eq!(tables.predict(&data, true), preds);
+        let tables = LookupTables::from_booster(&booster, 6).unwrap();
+        assert!(tables.n_tables() < 20);
+        assert_eq!(tables.predict(&data, true), preds);
+        assert!(LookupTables::from_booster(&booster, 32).is_err());
+
+        // Missing branches, and imputed values.
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_max_depth(3)
+            .set_create_missing_branch(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        // Splits in the missing branch, make for more splits than the depth suggests.
+        let tables = LookupTables::from_booster(&booster, 16).unwrap();
+        assert!(tables.n_tables() > 0);
+        assert_eq!(tables.predict(&data, true), booster.predict(&data, true));
+        let imputer = Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).unwrap();
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_imputer(Some(imputer));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let tables = LookupTables::from_booster(&booster, 12).unwrap();
+        assert_eq!(tables.predict(&data, true), booster.predict(&data, true));
+    }
+}
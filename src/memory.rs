@@ -0,0 +1,120 @@
+use crate::histogram::Bin;
+use std::fmt::{self, Display};
+use std::mem::size_of;
+
+/// Estimate of the peak memory used when training a booster, in bytes, split by the
+/// buffers allocated during training. The training data itself is not included, as
+/// it's owned by the caller.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryEstimate {
+    /// The binned copy of the training data.
+    pub binned_data: usize,
+    /// The cut values of the bins of each column.
+    pub cuts: usize,
+    /// The histograms of the nodes waiting to be split, in the largest tree.
+    pub histograms: usize,
+    /// The gradient and hessian of each record.
+    pub gradients: usize,
+    /// The predictions of each record, and the predictions of the last tree.
+    pub predictions: usize,
+    /// The row indices of the data, and of the records sampled for a tree.
+    pub index: usize,
+}
+
+impl MemoryEstimate {
+    /// Estimated peak memory, summed over all buffers.
+    pub fn total(&self) -> usize {
+        self.binned_data
+            + self.cuts
+            + self.histograms
+            + self.gradients
+            + self.predictions
+            + self.index
+    }
+}
+
+impl Display for MemoryEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mb = |b: usize| b as f64 / (1024. * 1024.);
+        writeln!(f, "{:<14}{:>14}", "buffer", "MB")?;
+        for (name, b) in [
+            ("binned data", self.binned_data),
+            ("cuts", self.cuts),
+            ("histograms", self.histograms),
+            ("gradients", self.gradients),
+            ("predictions", self.predictions),
+            ("index", self.index),
+        ] {
+            writeln!(f, "{:<14}{:>14.2}", name, mb(b))?;
+        }
+        write!(f, "{:<14}{:>14.2}", "total", mb(self.total()))
+    }
+}
+
+/// Estimate the peak memory used when training on a dataset, before binning or
+/// training on it. Histograms are held for every node waiting to be split, so the
+/// estimate assumes the trees grow to their full size, which is limited by the
+/// `max_depth`, `max_leaves`, and the number of rows.
+///
+/// * `rows` - Number of rows of the training data.
+/// * `cols` - Number of columns of the training data.
+/// * `nbins` - The number of bins each column is binned into.
+/// * `max_depth` - Maximum depth of an individual tree.
+/// * `max_leaves` - Maximum number of leaves allowed on a tree.
+pub fn estimate_training_memory(
+    rows: usize,
+    cols: usize,
+    nbins: u16,
+    max_depth: usize,
+    max_leaves: usize,
+) -> MemoryEstimate {
+    // Columns have at most nbins cuts, and the f64::MAX cut, and
+    // their histograms have a bin for missing values as well.
+    let cuts_per_col = usize::from(nbins) + 1;
+    let bins_per_col = cuts_per_col + 1;
+    let depth_leaves = if max_depth < usize::BITS as usize {
+        1 << max_depth
+    } else {
+        usize::MAX
+    };
+    // The nodes waiting to be split, and the node being split.
+    let live_nodes = depth_leaves.min(max_leaves).min(rows.max(1)) + 1;
+    MemoryEstimate {
+        binned_data: rows * cols * size_of::<u16>(),
+        cuts: cols * cuts_per_col * size_of::<f64>(),
+        histograms: live_nodes.saturating_mul(cols * bins_per_col * size_of::<Bin<f32>>()),
+        gradients: 2 * rows * size_of::<f32>(),
+        predictions: 2 * rows * size_of::<f64>(),
+        // Both the data, and binned data matrices index their rows.
+        index: 3 * rows * size_of::<usize>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_training_memory() {
+        let estimate = estimate_training_memory(1_000_000, 100, 256, 5, usize::MAX);
+        assert_eq!(estimate.binned_data, 200_000_000);
+        assert_eq!(estimate.gradients, 8_000_000);
+        assert_eq!(estimate.histograms, 33 * 100 * 258 * size_of::<Bin<f32>>());
+        assert_eq!(
+            estimate.total(),
+            estimate.binned_data
+                + estimate.cuts
+                + estimate.histograms
+                + estimate.gradients
+                + estimate.predictions
+                + estimate.index
+        );
+        // Trees can't have more leaves than rows.
+        let small = estimate_training_memory(4, 2, 256, 10, usize::MAX);
+        assert_eq!(small.histograms, 5 * 2 * 258 * size_of::<Bin<f32>>());
+        // An unlimited depth is limited by the number of leaves.
+        let unlimited = estimate_training_memory(1000, 2, 256, usize::MAX, 8);
+        assert_eq!(unlimited.histograms, 9 * 2 * 258 * size_of::<Bin<f32>>());
+        assert!(format!("{}", estimate).contains("total"));
+    }
+}
@@ -282,4 +282,35 @@ mod tests {
         let auc_score = roc_auc_score(&y, &yhat, &sample_weight);
         assert!(auc_score.is_nan());
     }
+
+    #[test]
+    fn test_metrics_weighted() {
+        // Weighting a record by 2, is the same as including it twice.
+        let y = vec![1., 0., 1., 0., 0., 0., 0.];
+        let yhat = vec![0.5, 0.01, -0., 1.05, 0., 4., 0.3];
+        let sample_weight = vec![1., 1., 1., 2., 1., 2., 1.];
+        let mut y_rep = Vec::new();
+        let mut yhat_rep = Vec::new();
+        for ((y_, yhat_), w) in y.iter().zip(yhat.iter()).zip(sample_weight.iter()) {
+            for _ in 0..(*w as usize) {
+                y_rep.push(*y_);
+                yhat_rep.push(*yhat_);
+            }
+        }
+        let ones = vec![1.; y_rep.len()];
+        for metric in [
+            Metric::AUC,
+            Metric::LogLoss,
+            Metric::RootMeanSquaredLogError,
+            Metric::RootMeanSquaredError,
+        ] {
+            let (metric_fn, _) = metric_callables(&metric);
+            assert_eq!(
+                precision_round(metric_fn(&y, &yhat, &sample_weight), 10),
+                precision_round(metric_fn(&y_rep, &yhat_rep, &ones), 10),
+                "{:?}",
+                metric
+            );
+        }
+    }
 }
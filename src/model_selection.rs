@@ -0,0 +1,911 @@
+use crate::data::{DataMatrix, Matrix, MatrixData, RowView};
+use crate::errors::ForustError;
+use crate::gradientbooster::{validation_split, GradientBooster};
+use crate::metric::{is_comparison_better, metric_callables, Metric};
+use crate::params::BoosterParams;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Results of k-fold cross validation.
+///
+/// * `fold_histories` - The metric on the held out fold, at each iteration, for every fold.
+/// * `mean_history` - The mean of the metric across the folds, at each iteration.
+/// * `std_history` - The standard deviation of the metric across the folds, at each iteration.
+/// * `models` - The booster trained on each fold, if they were requested.
+pub struct CrossValidationResult {
+    pub fold_histories: Vec<Vec<f64>>,
+    pub mean_history: Vec<f64>,
+    pub std_history: Vec<f64>,
+    pub models: Option<Vec<GradientBooster>>,
+}
+
+impl CrossValidationResult {
+    /// The iteration with the best mean metric, and the metric value.
+    ///
+    /// * `maximize` - Is a larger metric value better.
+    pub fn best_iteration(&self, maximize: bool) -> Option<(usize, f64)> {
+        self.mean_history
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, m)| !m.is_nan())
+            .reduce(|best, m| {
+                if (maximize && m.1 > best.1) || (!maximize && m.1 < best.1) {
+                    m
+                } else {
+                    best
+                }
+            })
+    }
+}
+
+/// Split the records into `k` folds of nearly equal size, shuffled using the `seed`.
+/// Each fold is returned as a sorted index.
+pub fn k_fold_index(rows: usize, k: usize, seed: u64) -> Vec<Vec<usize>> {
+    let mut index: Vec<usize> = (0..rows).collect();
+    index.shuffle(&mut StdRng::seed_from_u64(seed));
+    let mut folds: Vec<Vec<usize>> = (0..k)
+        .map(|f| index.iter().skip(f).step_by(k).copied().collect())
+        .collect();
+    folds.iter_mut().for_each(|f| f.sort());
+    folds
+}
+
+/// How the records are assigned to the train and test sets, by `train_test_split`.
+pub enum SplitMethod<'a> {
+    /// Shuffle the records, and take the test set from the shuffled records.
+    Shuffle,
+    /// Shuffle the records with each label separately, so the train and test sets
+    /// have the same proportion of each label.
+    Stratified(&'a [f64]),
+    /// Take the test set from the last records, without shuffling, so a model is
+    /// tested on records that come after the records it's trained on.
+    TimeOrdered,
+}
+
+/// The records in the train and test sets, of a `train_test_split`. Each index is sorted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainTestSplit {
+    pub train_index: Vec<usize>,
+    pub test_index: Vec<usize>,
+}
+
+impl TrainTestSplit {
+    /// Views of the train and test rows of the data, without copying it.
+    ///
+    /// * `data` - The data that was split.
+    pub fn views<'a, D: DataMatrix>(
+        &self,
+        data: &'a D,
+    ) -> Result<(RowView<'a, D>, RowView<'a, D>), ForustError> {
+        Ok((
+            RowView::new(data, self.train_index.clone())?,
+            RowView::new(data, self.test_index.clone())?,
+        ))
+    }
+
+    /// Copies of the train and test rows of the data.
+    ///
+    /// * `data` - The data that was split.
+    pub fn matrices<T: Copy>(&self, data: &Matrix<T>) -> (MatrixData<T>, MatrixData<T>) {
+        let select = |index: &[usize]| MatrixData {
+            data: data.select_rows(index),
+            rows: index.len(),
+            cols: data.cols,
+        };
+        (select(&self.train_index), select(&self.test_index))
+    }
+
+    /// The train and test values of a column, such as the target, or sample weight.
+    ///
+    /// * `values` - A value for each record that was split.
+    pub fn select<T: Copy>(&self, values: &[T]) -> (Vec<T>, Vec<T>) {
+        let select = |index: &[usize]| index.iter().map(|i| values[*i]).collect();
+        (select(&self.train_index), select(&self.test_index))
+    }
+}
+
+/// Split records into a train and a test set, seeded so the split can be repeated.
+///
+/// * `rows` - The number of records to split.
+/// * `test_fraction` - The fraction of the records to put in the test set, between 0 and 1.
+/// * `method` - How the records are assigned to each set.
+/// * `seed` - Seed used to shuffle the records.
+pub fn train_test_split(
+    rows: usize,
+    test_fraction: f64,
+    method: SplitMethod,
+    seed: u64,
+) -> Result<TrainTestSplit, ForustError> {
+    if !(test_fraction > 0. && test_fraction < 1.) {
+        return Err(ForustError::InvalidParameter {
+            name: "test_fraction".to_string(),
+            constraint: "a value greater than 0, and less than 1".to_string(),
+            value: test_fraction.to_string(),
+        });
+    }
+    let (train_index, test_index) = match method {
+        SplitMethod::Shuffle => validation_split(&vec![0.; rows], test_fraction, false, seed),
+        SplitMethod::Stratified(labels) => {
+            if labels.len() != rows {
+                return Err(ForustError::LengthMismatch(
+                    "labels".to_string(),
+                    labels.len(),
+                    rows,
+                ));
+            }
+            validation_split(labels, test_fraction, true, seed)
+        }
+        SplitMethod::TimeOrdered => {
+            let n_test = (rows as f64 * test_fraction).round() as usize;
+            (
+                (0..(rows - n_test)).collect(),
+                ((rows - n_test)..rows).collect(),
+            )
+        }
+    };
+    if train_index.is_empty() || test_index.is_empty() {
+        return Err(ForustError::InvalidParameter {
+            name: "test_fraction".to_string(),
+            constraint: "a fraction that leaves records for both training and testing".to_string(),
+            value: test_fraction.to_string(),
+        });
+    }
+    Ok(TrainTestSplit {
+        train_index,
+        test_index,
+    })
+}
+
+// Check the target, and weights, have a value for every row of the data.
+fn validate_lengths(
+    rows: usize,
+    y: &[f64],
+    sample_weight: Option<&[f64]>,
+) -> Result<(), ForustError> {
+    for (name, len) in [
+        ("y", Some(y.len())),
+        ("sample_weight", sample_weight.map(|w| w.len())),
+    ] {
+        if let Some(len) = len {
+            if len != rows {
+                return Err(ForustError::LengthMismatch(name.to_string(), len, rows));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Train a booster on each of `k` folds of the data, evaluating it on the held
+/// out fold at every iteration. The folds are shuffled using the `seed` of the
+/// parameters, and trained in parallel.
+///
+/// * `params` - The parameters used to train every booster.
+/// * `data` - The training data.
+/// * `y` - The target variable.
+/// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+/// * `k` - Number of folds, must be at least 2.
+/// * `metric` - Metric to record, if None the evaluation metric of the parameters,
+///   or the default metric of the objective is used.
+/// * `return_models` - Should the booster trained on each fold be returned.
+pub fn cross_validate(
+    params: &BoosterParams,
+    data: &Matrix<f64>,
+    y: &[f64],
+    sample_weight: Option<&[f64]>,
+    k: usize,
+    metric: Option<Metric>,
+    return_models: bool,
+) -> Result<CrossValidationResult, ForustError> {
+    if k < 2 || k > data.rows {
+        return Err(ForustError::InvalidParameter {
+            name: "k".to_string(),
+            constraint: format!("an integer between 2 and the number of rows {}", data.rows),
+            value: k.to_string(),
+        });
+    }
+    validate_lengths(data.rows, y, sample_weight)?;
+    let ones;
+    let sample_weight = match sample_weight {
+        Some(w) => w,
+        None => {
+            ones = vec![1.; y.len()];
+            &ones
+        }
+    };
+    let mut params = params.clone();
+    // The held out fold is always used for evaluation.
+    params.validation_fraction = None;
+    if metric.is_some() {
+        params.evaluation_metric = metric;
+    }
+
+    let folds = k_fold_index(data.rows, k, params.seed);
+    let models = folds
+        .par_iter()
+        .map(|valid_index| {
+            let mut is_valid = vec![false; data.rows];
+            valid_index.iter().for_each(|i| is_valid[*i] = true);
+            let train_index: Vec<usize> = (0..data.rows).filter(|i| !is_valid[*i]).collect();
+            let select =
+                |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+
+            let train_vec = data.select_rows(&train_index);
+            let train_data = Matrix::new(&train_vec, train_index.len(), data.cols);
+            let valid_vec = data.select_rows(valid_index);
+            let valid_data = Matrix::new(&valid_vec, valid_index.len(), data.cols);
+            let (valid_y, valid_w) = (select(y, valid_index), select(sample_weight, valid_index));
+
+            let mut booster = GradientBooster::from_params(params.clone())?;
+            booster.fit(
+                &train_data,
+                &select(y, &train_index),
+                &select(sample_weight, &train_index),
+                Some(vec![(valid_data, &valid_y, &valid_w)]),
+            )?;
+            Ok(booster)
+        })
+        .collect::<Result<Vec<GradientBooster>, ForustError>>()?;
+
+    let fold_histories: Vec<Vec<f64>> = models
+        .iter()
+        .map(|m| match &m.evaluation_history {
+            Some(h) => (0..h.rows).map(|i| *h.get(i, 0)).collect(),
+            None => Vec::new(),
+        })
+        .collect();
+    // Folds can stop early at different iterations, so each iteration
+    // is summarized over the folds that reached it.
+    let n_iterations = fold_histories.iter().map(|h| h.len()).max().unwrap_or(0);
+    let (mean_history, std_history) = (0..n_iterations)
+        .map(|i| {
+            let values: Vec<f64> = fold_histories
+                .iter()
+                .filter_map(|h| h.get(i))
+                .copied()
+                .collect();
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+            (mean, var.sqrt())
+        })
+        .unzip();
+
+    Ok(CrossValidationResult {
+        fold_histories,
+        mean_history,
+        std_history,
+        models: if return_models { Some(models) } else { None },
+    })
+}
+
+/// A distribution to sample a parameter value from, in a random search.
+pub enum ParamDistribution {
+    /// Choose one of the values, with equal probability.
+    Choice(Vec<Value>),
+    /// A real value, uniformly distributed between the bounds.
+    Uniform(f64, f64),
+    /// A real value, whose logarithm is uniformly distributed between the
+    /// logarithm of the bounds. The bounds must be positive.
+    LogUniform(f64, f64),
+    /// An integer value, uniformly distributed between the bounds, inclusive.
+    IntUniform(i64, i64),
+}
+
+// Whether the bounds are not NaN, and `min <= low <= high`.
+fn ordered(min: f64, low: f64, high: f64) -> bool {
+    matches!(
+        (min.partial_cmp(&low), low.partial_cmp(&high)),
+        (
+            Some(Ordering::Less | Ordering::Equal),
+            Some(Ordering::Less | Ordering::Equal)
+        )
+    )
+}
+
+impl ParamDistribution {
+    fn sample(&self, rng: &mut StdRng) -> Result<Value, ForustError> {
+        let invalid = |expected: &str| {
+            Err(ForustError::InvalidParameter {
+                name: "distribution".to_string(),
+                constraint: expected.to_string(),
+                value: "invalid bounds".to_string(),
+            })
+        };
+        match self {
+            ParamDistribution::Choice(values) => match values.choose(rng) {
+                Some(v) => Ok(v.clone()),
+                None => invalid("at least one value to choose from"),
+            },
+            ParamDistribution::Uniform(low, high) => {
+                if !ordered(f64::NEG_INFINITY, *low, *high) {
+                    return invalid("a lower bound less than the upper bound");
+                }
+                Ok(Value::from(rng.gen_range(*low..=*high)))
+            }
+            ParamDistribution::LogUniform(low, high) => {
+                if !ordered(f64::MIN_POSITIVE, *low, *high) {
+                    return invalid("positive bounds, with the lower less than the upper");
+                }
+                Ok(Value::from(rng.gen_range(low.ln()..=high.ln()).exp()))
+            }
+            ParamDistribution::IntUniform(low, high) => {
+                if low > high {
+                    return invalid("a lower bound less than the upper bound");
+                }
+                Ok(Value::from(rng.gen_range(*low..=*high)))
+            }
+        }
+    }
+}
+
+/// The cross validated score of a set of candidate parameters.
+///
+/// * `values` - The parameter values that were searched over.
+/// * `params` - All of the parameters of the candidate.
+/// * `score` - The best mean metric across the folds.
+/// * `score_std` - The standard deviation of the metric across the folds, at the best iteration.
+/// * `best_iteration` - The iteration with the best mean metric.
+pub struct SearchResult {
+    pub values: Map<String, Value>,
+    pub params: BoosterParams,
+    pub score: f64,
+    pub score_std: f64,
+    pub best_iteration: usize,
+}
+
+/// Cross validate every combination of the parameter values in the grid, returning
+/// the results ranked from best to worst.
+///
+/// * `params` - The parameters used for anything not in the grid.
+/// * `grid` - Map of parameter names, to the values to try for that parameter.
+/// * `data` - The training data.
+/// * `y` - The target variable.
+/// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+/// * `k` - Number of folds used to cross validate each candidate.
+/// * `metric` - Metric used to rank the candidates, if None the evaluation metric of
+///   the parameters, or the default metric of the objective is used.
+pub fn grid_search(
+    params: &BoosterParams,
+    grid: &BTreeMap<String, Vec<Value>>,
+    data: &Matrix<f64>,
+    y: &[f64],
+    sample_weight: Option<&[f64]>,
+    k: usize,
+    metric: Option<Metric>,
+) -> Result<Vec<SearchResult>, ForustError> {
+    let candidates = grid_candidates(grid);
+    search_candidates(params, candidates, data, y, sample_weight, k, metric)
+}
+
+/// Every combination of the parameter values in the grid.
+///
+/// * `grid` - Map of parameter names, to the values to try for that parameter.
+pub fn grid_candidates(grid: &BTreeMap<String, Vec<Value>>) -> Vec<Map<String, Value>> {
+    let mut candidates = vec![Map::new()];
+    for (name, values) in grid {
+        candidates = candidates
+            .iter()
+            .flat_map(|c| {
+                values.iter().map(move |v| {
+                    let mut c = c.clone();
+                    c.insert(name.to_string(), v.clone());
+                    c
+                })
+            })
+            .collect();
+    }
+    candidates
+}
+
+/// Sample `n_iter` sets of parameter values from the distributions.
+///
+/// * `distributions` - Map of parameter names, to the distribution to sample them from.
+/// * `n_iter` - Number of candidates to sample.
+/// * `seed` - Integer value used to seed the sampling of the candidates.
+pub fn sample_candidates(
+    distributions: &BTreeMap<String, ParamDistribution>,
+    n_iter: usize,
+    seed: u64,
+) -> Result<Vec<Map<String, Value>>, ForustError> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n_iter)
+        .map(|_| {
+            distributions
+                .iter()
+                .map(|(name, d)| Ok((name.to_string(), d.sample(&mut rng)?)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Cross validate `n_iter` candidates, with parameter values sampled from the
+/// distributions, returning the results ranked from best to worst.
+///
+/// * `params` - The parameters used for anything without a distribution.
+/// * `distributions` - Map of parameter names, to the distribution to sample them from.
+/// * `n_iter` - Number of candidates to sample.
+/// * `data` - The training data.
+/// * `y` - The target variable.
+/// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+/// * `k` - Number of folds used to cross validate each candidate.
+/// * `metric` - Metric used to rank the candidates, if None the evaluation metric of
+///   the parameters, or the default metric of the objective is used.
+/// * `seed` - Integer value used to seed the sampling of the candidates.
+#[allow(clippy::too_many_arguments)]
+pub fn random_search(
+    params: &BoosterParams,
+    distributions: &BTreeMap<String, ParamDistribution>,
+    n_iter: usize,
+    data: &Matrix<f64>,
+    y: &[f64],
+    sample_weight: Option<&[f64]>,
+    k: usize,
+    metric: Option<Metric>,
+    seed: u64,
+) -> Result<Vec<SearchResult>, ForustError> {
+    let candidates = sample_candidates(distributions, n_iter, seed)?;
+    search_candidates(params, candidates, data, y, sample_weight, k, metric)
+}
+
+fn search_candidates(
+    params: &BoosterParams,
+    candidates: Vec<Map<String, Value>>,
+    data: &Matrix<f64>,
+    y: &[f64],
+    sample_weight: Option<&[f64]>,
+    k: usize,
+    metric: Option<Metric>,
+) -> Result<Vec<SearchResult>, ForustError> {
+    let mut results = Vec::with_capacity(candidates.len());
+    let mut maximize = false;
+    for values in candidates {
+        let mut candidate = params.with_values(&values)?;
+        if metric.is_some() {
+            candidate.evaluation_metric = metric;
+        }
+        let candidate_metric = GradientBooster::from_params(candidate.clone())?.get_metric();
+        maximize = metric_callables(&candidate_metric).1;
+        let cv = cross_validate(&candidate, data, y, sample_weight, k, None, false)?;
+        let (best_iteration, score) = cv.best_iteration(maximize).unwrap_or((0, f64::NAN));
+        let score_std = cv
+            .std_history
+            .get(best_iteration)
+            .copied()
+            .unwrap_or(f64::NAN);
+        results.push(SearchResult {
+            values,
+            params: candidate,
+            score,
+            score_std,
+            best_iteration,
+        });
+    }
+    // Rank the best candidates first, with NaN scores last.
+    results.sort_by(|a, b| compare_scores(a.score, b.score, maximize));
+    Ok(results)
+}
+
+// Order scores from best to worst, with NaN scores last.
+fn compare_scores(a: f64, b: f64, maximize: bool) -> Ordering {
+    if is_comparison_better(a, b, maximize) {
+        Ordering::Greater
+    } else if is_comparison_better(b, a, maximize) {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+/// The result of a candidate in a successive halving search.
+///
+/// * `values` - The parameter values that were searched over.
+/// * `params` - All of the parameters of the candidate, with `iterations` set to
+///   the number of trees the candidate was trained for.
+/// * `score` - The best metric on the evaluation set.
+/// * `best_iteration` - The iteration with the best metric.
+/// * `booster` - The booster trained for the candidate.
+pub struct HalvingResult {
+    pub values: Map<String, Value>,
+    pub params: BoosterParams,
+    pub score: f64,
+    pub best_iteration: usize,
+    pub booster: GradientBooster,
+}
+
+/// Search over the candidates with successive halving. Every candidate is trained
+/// for `min_iterations` trees, then the best `1 / reduction_factor` of the candidates
+/// are trained for `reduction_factor` times as many trees, until a single candidate
+/// remains, or `max_iterations` is reached. Candidates are trained with warm start,
+/// so a promoted candidate only adds trees to its booster. The candidates are scored
+/// on an evaluation set sampled from the data, and are returned ranked by the number
+/// of trees they reached, and then by score.
+///
+/// * `params` - The parameters used for anything not in the candidates.
+/// * `candidates` - The parameter values of each candidate, see `grid_candidates`
+///   and `sample_candidates`.
+/// * `data` - The training data.
+/// * `y` - The target variable.
+/// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+/// * `min_iterations` - Number of trees every candidate is trained for.
+/// * `max_iterations` - Maximum number of trees a candidate is trained for.
+/// * `reduction_factor` - Factor the candidates are reduced by, and the number
+///   of trees is increased by, at each round. Must be at least 2.
+/// * `validation_fraction` - Fraction of the data to use as the evaluation set, the
+///   set is sampled using the `seed` and `stratify_validation` of the parameters.
+/// * `metric` - Metric used to rank the candidates, if None the evaluation metric of
+///   the parameters, or the default metric of the objective is used.
+#[allow(clippy::too_many_arguments)]
+pub fn successive_halving(
+    params: &BoosterParams,
+    candidates: &[Map<String, Value>],
+    data: &Matrix<f64>,
+    y: &[f64],
+    sample_weight: Option<&[f64]>,
+    min_iterations: usize,
+    max_iterations: usize,
+    reduction_factor: usize,
+    validation_fraction: f64,
+    metric: Option<Metric>,
+) -> Result<Vec<HalvingResult>, ForustError> {
+    if min_iterations == 0 || min_iterations > max_iterations {
+        return Err(ForustError::InvalidParameter {
+            name: "min_iterations".to_string(),
+            constraint: "an integer between 1 and max_iterations".to_string(),
+            value: min_iterations.to_string(),
+        });
+    }
+    if reduction_factor < 2 {
+        return Err(ForustError::InvalidParameter {
+            name: "reduction_factor".to_string(),
+            constraint: "an integer of at least 2".to_string(),
+            value: reduction_factor.to_string(),
+        });
+    }
+    validate_lengths(data.rows, y, sample_weight)?;
+    let ones;
+    let sample_weight = match sample_weight {
+        Some(w) => w,
+        None => {
+            ones = vec![1.; y.len()];
+            &ones
+        }
+    };
+    let (train_index, valid_index) = validation_split(
+        y,
+        validation_fraction,
+        params.stratify_validation,
+        params.seed,
+    );
+    if train_index.is_empty() || valid_index.is_empty() {
+        return Err(ForustError::InvalidParameter {
+            name: "validation_fraction".to_string(),
+            constraint: "a fraction that leaves records for both training and evaluation"
+                .to_string(),
+            value: validation_fraction.to_string(),
+        });
+    }
+    let select = |v: &[f64], index: &[usize]| index.iter().map(|i| v[*i]).collect::<Vec<_>>();
+    let train_vec = data.select_rows(&train_index);
+    let train_data = Matrix::new(&train_vec, train_index.len(), data.cols);
+    let valid_vec = data.select_rows(&valid_index);
+    let (train_y, train_w) = (select(y, &train_index), select(sample_weight, &train_index));
+    let (valid_y, valid_w) = (select(y, &valid_index), select(sample_weight, &valid_index));
+
+    let mut results = candidates
+        .iter()
+        .map(|values| {
+            let mut candidate = params.with_values(values)?;
+            // The evaluation set is provided directly.
+            candidate.validation_fraction = None;
+            candidate.warm_start = true;
+            if metric.is_some() {
+                candidate.evaluation_metric = metric;
+            }
+            let booster = GradientBooster::from_params(candidate.clone())?;
+            Ok(HalvingResult {
+                values: values.clone(),
+                params: candidate,
+                score: f64::NAN,
+                best_iteration: 0,
+                booster,
+            })
+        })
+        .collect::<Result<Vec<_>, ForustError>>()?;
+    let maximize = match results.first() {
+        Some(r) => metric_callables(&r.booster.get_metric()).1,
+        None => return Ok(results),
+    };
+
+    let mut remaining = results.len();
+    let mut iterations = min_iterations;
+    loop {
+        results[..remaining]
+            .par_iter_mut()
+            .map(|r| {
+                r.booster.iterations =
+                    iterations - r.booster.trees.len() / r.booster.num_parallel_tree;
+                let valid_data = Matrix::new(&valid_vec, valid_index.len(), data.cols);
+                r.booster.fit(
+                    &train_data,
+                    &train_y,
+                    &train_w,
+                    Some(vec![(valid_data, &valid_y, &valid_w)]),
+                )?;
+                r.params.iterations = iterations;
+                if let Some(h) = &r.booster.evaluation_history {
+                    let best = (0..h.rows).map(|i| (i, *h.get(i, 0))).reduce(|best, m| {
+                        if is_comparison_better(best.1, m.1, maximize) {
+                            m
+                        } else {
+                            best
+                        }
+                    });
+                    (r.best_iteration, r.score) = best.unwrap_or((0, f64::NAN));
+                }
+                Ok(())
+            })
+            .collect::<Result<(), ForustError>>()?;
+        results[..remaining].sort_by(|a, b| compare_scores(a.score, b.score, maximize));
+        if remaining == 1 || iterations == max_iterations {
+            break;
+        }
+        remaining = remaining.div_ceil(reduction_factor);
+        iterations = (iterations * reduction_factor).min(max_iterations);
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_k_fold_index() {
+        let folds = k_fold_index(10, 3, 0);
+        assert_eq!(
+            folds.iter().map(|f| f.len()).collect::<Vec<_>>(),
+            vec![4, 3, 3]
+        );
+        let mut all: Vec<usize> = folds.concat();
+        all.sort();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_train_test_split() {
+        let split = train_test_split(100, 0.2, SplitMethod::Shuffle, 0).unwrap();
+        assert_eq!((split.train_index.len(), split.test_index.len()), (80, 20));
+        let mut all = [split.train_index.clone(), split.test_index.clone()].concat();
+        all.sort();
+        assert_eq!(all, (0..100).collect::<Vec<_>>());
+        assert_eq!(
+            train_test_split(100, 0.2, SplitMethod::Shuffle, 0).unwrap(),
+            split
+        );
+
+        // Each label is split in the same proportion.
+        let labels: Vec<f64> = (0..100).map(|i| if i < 10 { 1. } else { 0. }).collect();
+        let split = train_test_split(100, 0.2, SplitMethod::Stratified(&labels), 0).unwrap();
+        let (_, test_labels) = split.select(&labels);
+        assert_eq!(test_labels.iter().sum::<f64>(), 2.);
+
+        let split = train_test_split(100, 0.25, SplitMethod::TimeOrdered, 0).unwrap();
+        assert_eq!(split.test_index, (75..100).collect::<Vec<_>>());
+
+        let v: Vec<f64> = (0..200).map(f64::from).collect();
+        let data = Matrix::new(&v, 100, 2);
+        let (train, test) = split.views(&data).unwrap();
+        let (train_data, test_data) = split.matrices(&data);
+        assert_eq!((train.rows(), test.rows()), (75, 25));
+        assert_eq!(test.value(0, 1), 175.);
+        assert_eq!(test_data.as_matrix().get_col(1), test.column(1).as_ref());
+        assert_eq!(train_data.rows, 75);
+
+        assert!(train_test_split(100, 1., SplitMethod::Shuffle, 0).is_err());
+        assert!(train_test_split(1, 0.2, SplitMethod::Shuffle, 0).is_err());
+        assert!(train_test_split(10, 0.2, SplitMethod::Stratified(&labels), 0).is_err());
+    }
+
+    #[test]
+    fn test_cross_validate() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let params = BoosterParams {
+            iterations: 10,
+            ..Default::default()
+        };
+        let result = cross_validate(&params, &data, &y, None, 3, Some(Metric::AUC), true).unwrap();
+        assert_eq!(result.fold_histories.len(), 3);
+        assert!(result.fold_histories.iter().all(|h| h.len() == 10));
+        assert_eq!(result.mean_history.len(), 10);
+        assert!(result.mean_history.iter().all(|m| *m > 0.5 && *m <= 1.));
+        assert_eq!(result.models.as_ref().unwrap().len(), 3);
+        let (best, _) = result.best_iteration(true).unwrap();
+        assert!(best < 10);
+
+        assert!(cross_validate(&params, &data, &y, None, 1, None, false).is_err());
+        assert!(cross_validate(&params, &data, &y[1..], None, 3, None, false).is_err());
+        let w = vec![1.; y.len() - 1];
+        assert!(cross_validate(&params, &data, &y, Some(&w), 3, None, false).is_err());
+    }
+
+    #[test]
+    fn test_grid_and_random_search() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let params = BoosterParams {
+            iterations: 5,
+            ..Default::default()
+        };
+
+        let grid = BTreeMap::from([
+            (
+                "max_depth".to_string(),
+                vec![Value::from(1), Value::from(3)],
+            ),
+            (
+                "learning_rate".to_string(),
+                vec![Value::from(0.1), Value::from(0.3)],
+            ),
+        ]);
+        let results = grid_search(&params, &grid, &data, &y, None, 3, None).unwrap();
+        assert_eq!(results.len(), 4);
+        // The default metric for LogLoss is minimized.
+        assert!(results.windows(2).all(|w| w[0].score <= w[1].score));
+
+        let distributions = BTreeMap::from([
+            ("max_depth".to_string(), ParamDistribution::IntUniform(1, 4)),
+            (
+                "learning_rate".to_string(),
+                ParamDistribution::LogUniform(0.01, 0.5),
+            ),
+        ]);
+        let results = random_search(
+            &params,
+            &distributions,
+            3,
+            &data,
+            &y,
+            None,
+            3,
+            Some(Metric::AUC),
+            0,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+        assert!(results
+            .iter()
+            .all(|r| (1..=4).contains(&r.params.max_depth)));
+
+        let grid = BTreeMap::from([("depth".to_string(), vec![Value::from(1)])]);
+        assert!(grid_search(&params, &grid, &data, &y, None, 3, None).is_err());
+    }
+
+    #[test]
+    fn test_successive_halving() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let grid = BTreeMap::from([
+            (
+                "max_depth".to_string(),
+                vec![Value::from(1), Value::from(2), Value::from(3)],
+            ),
+            (
+                "learning_rate".to_string(),
+                vec![Value::from(0.05), Value::from(0.3), Value::from(0.6)],
+            ),
+        ]);
+        let candidates = grid_candidates(&grid);
+        assert_eq!(candidates.len(), 9);
+        let results = successive_halving(
+            &BoosterParams::default(),
+            &candidates,
+            &data,
+            &y,
+            None,
+            2,
+            20,
+            3,
+            0.2,
+            None,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 9);
+        // 9 candidates are trained for 2 trees, 3 for 6 trees, and 1 for 18 trees.
+        let trees: Vec<usize> = results.iter().map(|r| r.booster.trees.len()).collect();
+        assert_eq!(trees, vec![18, 6, 6, 2, 2, 2, 2, 2, 2]);
+        assert!(results
+            .iter()
+            .all(|r| r.params.iterations == r.booster.trees.len()));
+        assert!(results[3..].windows(2).all(|w| w[0].score <= w[1].score));
+
+        // Continuing from the existing trees, matches training all of the trees at once.
+        let best = &results[0];
+        let mut booster = GradientBooster::from_params(BoosterParams {
+            warm_start: false,
+            validation_fraction: Some(0.2),
+            ..best.params.clone()
+        })
+        .unwrap();
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert_eq!(
+            booster.predict(&data, true),
+            best.booster.predict(&data, true)
+        );
+
+        assert!(successive_halving(
+            &BoosterParams::default(),
+            &candidates,
+            &data,
+            &y,
+            None,
+            2,
+            20,
+            1,
+            0.2,
+            None,
+        )
+        .is_err());
+        assert!(successive_halving(
+            &BoosterParams::default(),
+            &candidates,
+            &data,
+            &y[1..],
+            None,
+            2,
+            20,
+            3,
+            0.2,
+            None,
+        )
+        .is_err());
+        let w = vec![1.; y.len() - 1];
+        assert!(successive_halving(
+            &BoosterParams::default(),
+            &candidates,
+            &data,
+            &y,
+            Some(&w),
+            2,
+            20,
+            3,
+            0.2,
+            None,
+        )
+        .is_err());
+    }
+}
@@ -27,9 +27,11 @@ pub struct SplittableNode {
     pub upper_bound: f32,
     pub is_leaf: bool,
     pub is_missing_leaf: bool,
+    /// The features split on from the root of the tree to the node.
+    pub path_features: Vec<usize>,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Node {
     pub num: usize,
     pub weight_value: f32,
@@ -45,8 +47,12 @@ pub struct Node {
 }
 
 impl Ord for SplittableNode {
+    // Nodes with equal gain are ordered so the node created first is
+    // split first, this keeps the tree growth deterministic.
     fn cmp(&self, other: &Self) -> Ordering {
-        self.gain_value.total_cmp(&other.gain_value)
+        self.gain_value
+            .total_cmp(&other.gain_value)
+            .then_with(|| other.num.cmp(&self.num))
     }
 }
 
@@ -58,7 +64,7 @@ impl PartialOrd for SplittableNode {
 
 impl PartialEq for SplittableNode {
     fn eq(&self, other: &Self) -> bool {
-        self.gain_value == other.gain_value
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -122,6 +128,7 @@ impl SplittableNode {
             upper_bound: node_info.bounds.1,
             is_leaf: true,
             is_missing_leaf: false,
+            path_features: Vec::new(),
         }
     }
 
@@ -161,6 +168,7 @@ impl SplittableNode {
             upper_bound,
             is_leaf: true,
             is_missing_leaf: false,
+            path_features: Vec::new(),
         }
     }
 
@@ -185,6 +193,15 @@ impl SplittableNode {
         self.missing_node = missing_child;
         self.is_leaf = false;
     }
+    /// The features split on from the root of the tree to the children of the node.
+    pub fn child_path_features(&self) -> Vec<usize> {
+        let mut features = self.path_features.clone();
+        if !features.contains(&self.split_feature) {
+            features.push(self.split_feature);
+        }
+        features
+    }
+
     pub fn as_node(&self) -> Node {
         Node {
             num: self.num,
@@ -202,9 +219,10 @@ impl SplittableNode {
     }
 }
 
-impl fmt::Display for Node {
-    // This trait requires `fmt` with this exact signature.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Node {
+    /// Write the node as text, naming the split feature with `feature_name`,
+    /// or with the index of the feature, if it's None.
+    pub fn write_text(&self, f: &mut impl fmt::Write, feature_name: Option<&str>) -> fmt::Result {
         if self.is_leaf {
             write!(
                 f,
@@ -212,11 +230,15 @@ impl fmt::Display for Node {
                 self.num, self.weight_value, self.hessian_sum
             )
         } else {
+            let feature = match feature_name {
+                Some(name) => name.to_string(),
+                None => self.split_feature.to_string(),
+            };
             write!(
                 f,
                 "{}:[{} < {}] yes={},no={},missing={},gain={},cover={}",
                 self.num,
-                self.split_feature,
+                feature,
                 self.split_value,
                 self.left_child,
                 self.right_child,
@@ -227,3 +249,10 @@ impl fmt::Display for Node {
         }
     }
 }
+
+impl fmt::Display for Node {
+    // This trait requires `fmt` with this exact signature.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_text(f, None)
+    }
+}
@@ -1,14 +1,26 @@
 use std::str::FromStr;
 
-use crate::{data::FloatData, errors::ForustError, metric::Metric, utils::items_to_strings};
+use crate::{
+    data::FloatData,
+    errors::ForustError,
+    metric::Metric,
+    utils::{items_to_strings, percentiles},
+};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-type ObjFn = fn(&[f64], &[f64], &[f64]) -> (Vec<f32>, Vec<f32>);
+type ObjFn = Box<dyn Fn(&[f64], &[f64], &[f64]) -> (Vec<f32>, Vec<f32>) + Send + Sync>;
+type InitFn = Box<dyn Fn(&[f64], &[f64]) -> f64 + Send + Sync>;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// The number of records in each chunk, the gradient and hessian are calculated
+/// for in parallel. Data with fewer records is calculated sequentially.
+const PARALLEL_CHUNK_SIZE: usize = 65_536;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 pub enum ObjectiveType {
     LogLoss,
     SquaredLoss,
+    QuantileLoss,
 }
 
 impl FromStr for ObjectiveType {
@@ -18,29 +30,97 @@ impl FromStr for ObjectiveType {
         match s {
             "LogLoss" => Ok(ObjectiveType::LogLoss),
             "SquaredLoss" => Ok(ObjectiveType::SquaredLoss),
+            "QuantileLoss" => Ok(ObjectiveType::QuantileLoss),
             _ => Err(ForustError::ParseString(
                 s.to_string(),
                 "ObjectiveType".to_string(),
-                items_to_strings(vec!["LogLoss", "SquaredLoss"]),
+                items_to_strings(vec!["LogLoss", "SquaredLoss", "QuantileLoss"]),
             )),
         }
     }
 }
 
-pub fn gradient_hessian_callables(objective_type: &ObjectiveType) -> ObjFn {
+/// The gradient and hessian function of the objective, the `quantile`
+/// is only used by the "QuantileLoss" objective.
+pub fn gradient_hessian_callables(objective_type: &ObjectiveType, quantile: f64) -> ObjFn {
     match objective_type {
-        ObjectiveType::LogLoss => LogLoss::calc_grad_hess,
-        ObjectiveType::SquaredLoss => SquaredLoss::calc_grad_hess,
+        ObjectiveType::LogLoss => Box::new(LogLoss::calc_grad_hess),
+        ObjectiveType::SquaredLoss => Box::new(SquaredLoss::calc_grad_hess),
+        ObjectiveType::QuantileLoss => {
+            let objective = QuantileLoss { quantile };
+            Box::new(move |y, yhat, w| objective.calc_grad_hess(y, yhat, w))
+        }
+    }
+}
+
+/// Calculate the gradient and hessian with a function of the objective, such as
+/// `gradient_hessian_callables` returns, in parallel over chunks of the records.
+/// The result is the same as calling the function on all of the records.
+///
+/// * `calc_grad_hess` - The gradient and hessian function of the objective.
+/// * `y` - The target.
+/// * `yhat` - The current predictions.
+/// * `sample_weight` - Instance weights.
+pub fn parallel_grad_hess<F>(
+    calc_grad_hess: F,
+    y: &[f64],
+    yhat: &[f64],
+    sample_weight: &[f64],
+) -> (Vec<f32>, Vec<f32>)
+where
+    F: Fn(&[f64], &[f64], &[f64]) -> (Vec<f32>, Vec<f32>) + Sync,
+{
+    if y.len() <= PARALLEL_CHUNK_SIZE {
+        return calc_grad_hess(y, yhat, sample_weight);
+    }
+    let chunks: Vec<(Vec<f32>, Vec<f32>)> = y
+        .par_chunks(PARALLEL_CHUNK_SIZE)
+        .zip(yhat.par_chunks(PARALLEL_CHUNK_SIZE))
+        .zip(sample_weight.par_chunks(PARALLEL_CHUNK_SIZE))
+        .map(|((y, yhat), w)| calc_grad_hess(y, yhat, w))
+        .collect();
+    let mut grad = Vec::with_capacity(y.len());
+    let mut hess = Vec::with_capacity(y.len());
+    for (g, h) in chunks {
+        grad.extend(g);
+        hess.extend(h);
     }
+    (grad, hess)
 }
 
-pub fn calc_init_callables(objective_type: &ObjectiveType) -> fn(&[f64], &[f64]) -> f64 {
+/// The initial prediction function of the objective, the `quantile`
+/// is only used by the "QuantileLoss" objective.
+pub fn calc_init_callables(objective_type: &ObjectiveType, quantile: f64) -> InitFn {
     match objective_type {
-        ObjectiveType::LogLoss => LogLoss::calc_init,
-        ObjectiveType::SquaredLoss => SquaredLoss::calc_init,
+        ObjectiveType::LogLoss => Box::new(LogLoss::calc_init),
+        ObjectiveType::SquaredLoss => Box::new(SquaredLoss::calc_init),
+        ObjectiveType::QuantileLoss => {
+            let objective = QuantileLoss { quantile };
+            Box::new(move |y, w| objective.calc_init(y, w))
+        }
+    }
+}
+
+/// Check each input has a value for each record of `y`.
+///
+/// * `y` - The target values.
+/// * `inputs` - The name, and length, of each input.
+fn validate_lengths(y: &[f64], inputs: &[(&str, usize)]) -> Result<(), ForustError> {
+    for (name, len) in inputs {
+        if *len != y.len() {
+            return Err(ForustError::ShapeMismatch {
+                name: name.to_string(),
+                expected: format!("{} values, one for each target value", y.len()),
+                got: format!("{} values", len),
+            });
+        }
     }
+    Ok(())
 }
 
+/// The `calc_*` functions assume `y`, `yhat`, and `sample_weight` have the
+/// same length, and stop at the shortest of them otherwise. The `try_calc_*`
+/// functions check the lengths first, and return an error if they differ.
 pub trait ObjectiveFunction {
     fn calc_loss(y: &[f64], yhat: &[f64], sample_weight: &[f64]) -> Vec<f32>;
     fn calc_grad_hess(y: &[f64], yhat: &[f64], sample_weight: &[f64]) -> (Vec<f32>, Vec<f32>);
@@ -48,6 +128,59 @@ pub trait ObjectiveFunction {
     fn calc_hess(y: &[f64], yhat: &[f64], sample_weight: &[f64]) -> Vec<f32>;
     fn calc_init(y: &[f64], sample_weight: &[f64]) -> f64;
     fn default_metric() -> Metric;
+
+    fn try_calc_loss(
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<Vec<f32>, ForustError> {
+        validate_lengths(
+            y,
+            &[("yhat", yhat.len()), ("sample_weight", sample_weight.len())],
+        )?;
+        Ok(Self::calc_loss(y, yhat, sample_weight))
+    }
+
+    fn try_calc_grad_hess(
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<(Vec<f32>, Vec<f32>), ForustError> {
+        validate_lengths(
+            y,
+            &[("yhat", yhat.len()), ("sample_weight", sample_weight.len())],
+        )?;
+        Ok(Self::calc_grad_hess(y, yhat, sample_weight))
+    }
+
+    fn try_calc_grad(
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<Vec<f32>, ForustError> {
+        validate_lengths(
+            y,
+            &[("yhat", yhat.len()), ("sample_weight", sample_weight.len())],
+        )?;
+        Ok(Self::calc_grad(y, yhat, sample_weight))
+    }
+
+    fn try_calc_hess(
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<Vec<f32>, ForustError> {
+        validate_lengths(
+            y,
+            &[("yhat", yhat.len()), ("sample_weight", sample_weight.len())],
+        )?;
+        Ok(Self::calc_hess(y, yhat, sample_weight))
+    }
+
+    fn try_calc_init(y: &[f64], sample_weight: &[f64]) -> Result<f64, ForustError> {
+        validate_lengths(y, &[("sample_weight", sample_weight.len())])?;
+        Ok(Self::calc_init(y, sample_weight))
+    }
 }
 
 #[derive(Default)]
@@ -170,6 +303,94 @@ impl ObjectiveFunction for SquaredLoss {
     }
 }
 
+/// The pinball loss of a quantile. The gradient of the loss is constant within a
+/// leaf, so after a tree is grown, its leaf weights are replaced by the quantile of
+/// the residuals of the records in each leaf, see `GradientBooster::fit`.
+pub struct QuantileLoss {
+    pub quantile: f64,
+}
+
+impl QuantileLoss {
+    pub fn calc_loss(&self, y: &[f64], yhat: &[f64], sample_weight: &[f64]) -> Vec<f32> {
+        y.iter()
+            .zip(yhat)
+            .zip(sample_weight)
+            .map(|((y_, yhat_), w_)| {
+                let s = *y_ - *yhat_;
+                let l = if s >= 0. {
+                    self.quantile * s
+                } else {
+                    (self.quantile - 1.) * s
+                };
+                (l * *w_) as f32
+            })
+            .collect()
+    }
+
+    pub fn calc_init(&self, y: &[f64], sample_weight: &[f64]) -> f64 {
+        percentiles(y, sample_weight, &[self.quantile])[0]
+    }
+
+    #[inline]
+    pub fn calc_grad_hess(
+        &self,
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> (Vec<f32>, Vec<f32>) {
+        y.iter()
+            .zip(yhat)
+            .zip(sample_weight)
+            .map(|((y_, yhat_), w_)| {
+                let g = if *y_ < *yhat_ {
+                    1. - self.quantile
+                } else {
+                    -self.quantile
+                };
+                ((g * *w_) as f32, *w_ as f32)
+            })
+            .unzip()
+    }
+
+    /// `calc_loss`, with an error if the lengths of the inputs differ.
+    pub fn try_calc_loss(
+        &self,
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<Vec<f32>, ForustError> {
+        validate_lengths(
+            y,
+            &[("yhat", yhat.len()), ("sample_weight", sample_weight.len())],
+        )?;
+        Ok(self.calc_loss(y, yhat, sample_weight))
+    }
+
+    /// `calc_init`, with an error if the lengths of the inputs differ.
+    pub fn try_calc_init(&self, y: &[f64], sample_weight: &[f64]) -> Result<f64, ForustError> {
+        validate_lengths(y, &[("sample_weight", sample_weight.len())])?;
+        Ok(self.calc_init(y, sample_weight))
+    }
+
+    /// `calc_grad_hess`, with an error if the lengths of the inputs differ.
+    pub fn try_calc_grad_hess(
+        &self,
+        y: &[f64],
+        yhat: &[f64],
+        sample_weight: &[f64],
+    ) -> Result<(Vec<f32>, Vec<f32>), ForustError> {
+        validate_lengths(
+            y,
+            &[("yhat", yhat.len()), ("sample_weight", sample_weight.len())],
+        )?;
+        Ok(self.calc_grad_hess(y, yhat, sample_weight))
+    }
+
+    pub fn default_metric() -> Metric {
+        Metric::RootMeanSquaredError
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,4 +466,68 @@ mod tests {
         let l4 = SquaredLoss::calc_init(&y, &w);
         assert!(l4 == 0.);
     }
+
+    #[test]
+    fn test_quantile_loss() {
+        let y = vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 10.];
+        let w = vec![1.; y.len()];
+        let objective = QuantileLoss { quantile: 0.75 };
+        assert_eq!(objective.calc_init(&y, &w), 8.);
+        let (g, h) = objective.calc_grad_hess(&y, &vec![5.5; y.len()], &w);
+        assert_eq!(h, vec![1.; y.len()]);
+        // Half of the records are below the prediction, so the
+        // gradient pushes the prediction up.
+        assert!(g.iter().sum::<f32>() < 0.);
+        let l1: f32 = objective.calc_loss(&y, &vec![8.; y.len()], &w).iter().sum();
+        let l2: f32 = objective
+            .calc_loss(&y, &vec![5.5; y.len()], &w)
+            .iter()
+            .sum();
+        assert!(l1 < l2);
+    }
+
+    #[test]
+    fn test_try_calc_lengths() {
+        let y = vec![0., 1., 1.];
+        let yhat = vec![0.5; 3];
+        let w = vec![1.; 3];
+        assert_eq!(
+            LogLoss::try_calc_grad(&y, &yhat, &w).unwrap(),
+            LogLoss::calc_grad(&y, &yhat, &w)
+        );
+        assert!(SquaredLoss::try_calc_grad_hess(&y, &yhat, &w).is_ok());
+        assert!(matches!(
+            LogLoss::try_calc_loss(&y, &yhat[1..], &w),
+            Err(ForustError::ShapeMismatch { ref name, .. }) if name == "yhat"
+        ));
+        assert!(matches!(
+            SquaredLoss::try_calc_hess(&y, &yhat, &[1.; 4]),
+            Err(ForustError::ShapeMismatch { ref name, .. }) if name == "sample_weight"
+        ));
+        assert!(LogLoss::try_calc_init(&y, &w[..2]).is_err());
+        let objective = QuantileLoss { quantile: 0.5 };
+        assert!(objective.try_calc_grad_hess(&y, &yhat, &w).is_ok());
+        assert!(objective.try_calc_grad_hess(&y[..2], &yhat, &w).is_err());
+        assert!(objective.try_calc_loss(&y, &yhat, &w[1..]).is_err());
+        assert!(objective.try_calc_init(&y, &w).is_ok());
+    }
+
+    #[test]
+    fn test_parallel_grad_hess() {
+        let n = PARALLEL_CHUNK_SIZE * 3 + 17;
+        let y: Vec<f64> = (0..n).map(|i| (i % 2) as f64).collect();
+        let yhat: Vec<f64> = (0..n).map(|i| ((i % 13) as f64 - 6.) / 3.).collect();
+        let w: Vec<f64> = (0..n).map(|i| 1. + (i % 5) as f64).collect();
+        for objective_type in [
+            ObjectiveType::LogLoss,
+            ObjectiveType::SquaredLoss,
+            ObjectiveType::QuantileLoss,
+        ] {
+            let calc_grad_hess = gradient_hessian_callables(&objective_type, 0.25);
+            assert_eq!(
+                parallel_grad_hess(&calc_grad_hess, &y, &yhat, &w),
+                calc_grad_hess(&y, &yhat, &w)
+            );
+        }
+    }
 }
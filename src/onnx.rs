@@ -0,0 +1,499 @@
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::objective::ObjectiveType;
+use crate::tree::Tree;
+use std::fs;
+
+const ONNX_IR_VERSION: u64 = 8;
+const ONNX_OPSET_VERSION: u64 = 15;
+const ONNX_ML_OPSET_VERSION: u64 = 3;
+
+// ONNX tensor element types.
+const TENSOR_FLOAT: u64 = 1;
+const TENSOR_INT64: u64 = 7;
+
+// ONNX attribute types.
+const ATTRIBUTE_FLOAT: u64 = 1;
+const ATTRIBUTE_INT: u64 = 2;
+const ATTRIBUTE_STRING: u64 = 3;
+const ATTRIBUTE_FLOATS: u64 = 6;
+const ATTRIBUTE_INTS: u64 = 7;
+const ATTRIBUTE_STRINGS: u64 = 8;
+
+/// Save a gradient booster as an ONNX model.
+///
+/// * `booster` - The booster to export.
+/// * `path` - Path to save the ONNX model.
+pub fn save_onnx_model(booster: &GradientBooster, path: &str) -> Result<(), ForustError> {
+    let model = to_onnx_model(booster)?;
+    match fs::write(path, model) {
+        Err(e) => Err(ForustError::Io(e)),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Convert a gradient booster into a serialized ONNX model. A `SquaredLoss` booster
+/// becomes a `TreeEnsembleRegressor`, with a single `variable` output. A `LogLoss`
+/// booster becomes a `TreeEnsembleClassifier` with a logistic post transform, and
+/// a `label` and `probabilities` output. The graph has a single float `input`
+/// of shape `[N, num_features]`. If the booster has an imputer, missing values are
+/// replaced by an `Imputer` operator, before they reach the trees.
+/// ONNX evaluates trees in single precision, so thresholds are rounded to the nearest
+/// f32, values within f32 precision below a threshold may be routed differently.
+/// Boosters trained with `create_missing_branch`, or a missing value other
+/// than NaN, can't be represented in ONNX.
+///
+/// * `booster` - The booster to export.
+pub fn to_onnx_model(booster: &GradientBooster) -> Result<Vec<u8>, ForustError> {
+    if !booster.missing.is_nan() {
+        return Err(ForustError::UnableToWrite(
+            "only boosters with a missing value of NaN can be exported to ONNX".to_string(),
+        ));
+    }
+    let trees = booster.get_prediction_trees();
+    if trees
+        .iter()
+        .any(|t| t.nodes.iter().any(|n| !n.is_leaf && n.has_missing_branch()))
+    {
+        return Err(ForustError::UnableToWrite(
+            "trees with a separate missing branch can't be exported to ONNX".to_string(),
+        ));
+    }
+
+    let ensemble = TreeEnsemble::new(trees);
+    let mut attributes = ensemble.node_attributes();
+    let base_values = float_attribute_list("base_values", &[booster.base_score as f32]);
+    let (op_type, outputs) = match booster.objective_type {
+        ObjectiveType::SquaredLoss | ObjectiveType::QuantileLoss => {
+            attributes.push(int_attribute("n_targets", 1));
+            attributes.push(string_attribute("aggregate_function", "SUM"));
+            attributes.push(string_attribute("post_transform", "NONE"));
+            attributes.push(base_values);
+            attributes.extend(ensemble.leaf_attributes("target"));
+            (
+                "TreeEnsembleRegressor",
+                vec![value_info("variable", TENSOR_FLOAT, &[None, Some(1)])],
+            )
+        }
+        ObjectiveType::LogLoss => {
+            attributes.push(int_attribute_list("classlabels_int64s", &[0, 1]));
+            attributes.push(string_attribute("post_transform", "LOGISTIC"));
+            attributes.push(base_values);
+            attributes.extend(ensemble.leaf_attributes("class"));
+            (
+                "TreeEnsembleClassifier",
+                vec![
+                    value_info("label", TENSOR_INT64, &[None]),
+                    value_info("probabilities", TENSOR_FLOAT, &[None, Some(2)]),
+                ],
+            )
+        }
+    };
+
+    let mut graph = ProtoWriter::default();
+    let mut input = "input";
+    let mut num_features = ensemble.num_features;
+    if let Some(imputer) = &booster.imputer {
+        let values: Vec<f32> = imputer.values.iter().map(|v| *v as f32).collect();
+        let mut node = ProtoWriter::default();
+        node.string(1, input);
+        node.string(2, "imputed");
+        node.string(3, "forust_imputer");
+        node.string(4, "Imputer");
+        node.message(5, &float_attribute_list("imputed_value_floats", &values));
+        node.message(5, &float_attribute("replaced_value_float", f32::NAN));
+        node.string(7, "ai.onnx.ml");
+        graph.message(1, &node);
+        input = "imputed";
+        // The imputer has a value for every column, so it sets the width of the input.
+        num_features = num_features.max(values.len());
+    }
+
+    let mut node = ProtoWriter::default();
+    node.string(1, input);
+    for (name, _) in outputs.iter() {
+        node.string(2, name);
+    }
+    node.string(3, "forust_ensemble");
+    node.string(4, op_type);
+    for attribute in attributes.iter() {
+        node.message(5, attribute);
+    }
+    node.string(7, "ai.onnx.ml");
+
+    graph.message(1, &node);
+    graph.string(2, "forust");
+    graph.message(
+        11,
+        &value_info("input", TENSOR_FLOAT, &[None, Some(num_features)]).1,
+    );
+    for (_, output) in outputs.iter() {
+        graph.message(12, output);
+    }
+
+    let mut model = ProtoWriter::default();
+    model.uint64(1, ONNX_IR_VERSION);
+    model.string(2, "forust");
+    model.string(3, env!("CARGO_PKG_VERSION"));
+    model.message(7, &graph);
+    for (domain, version) in [
+        ("", ONNX_OPSET_VERSION),
+        ("ai.onnx.ml", ONNX_ML_OPSET_VERSION),
+    ] {
+        let mut opset = ProtoWriter::default();
+        opset.string(1, domain);
+        opset.uint64(2, version);
+        model.message(8, &opset);
+    }
+    Ok(model.buf)
+}
+
+/// The flattened node arrays, shared by the ONNX tree ensemble operators.
+struct TreeEnsemble {
+    tree_ids: Vec<i64>,
+    node_ids: Vec<i64>,
+    feature_ids: Vec<i64>,
+    values: Vec<f32>,
+    modes: Vec<&'static str>,
+    true_node_ids: Vec<i64>,
+    false_node_ids: Vec<i64>,
+    missing_tracks_true: Vec<i64>,
+    leaf_tree_ids: Vec<i64>,
+    leaf_node_ids: Vec<i64>,
+    leaf_weights: Vec<f32>,
+    num_features: usize,
+}
+
+impl TreeEnsemble {
+    fn new(trees: &[Tree]) -> Self {
+        let mut ensemble = TreeEnsemble {
+            tree_ids: Vec::new(),
+            node_ids: Vec::new(),
+            feature_ids: Vec::new(),
+            values: Vec::new(),
+            modes: Vec::new(),
+            true_node_ids: Vec::new(),
+            false_node_ids: Vec::new(),
+            missing_tracks_true: Vec::new(),
+            leaf_tree_ids: Vec::new(),
+            leaf_node_ids: Vec::new(),
+            leaf_weights: Vec::new(),
+            num_features: 0,
+        };
+        for (tree_id, tree) in trees.iter().enumerate() {
+            for n in tree.nodes.iter() {
+                ensemble.tree_ids.push(tree_id as i64);
+                ensemble.node_ids.push(n.num as i64);
+                if n.is_leaf {
+                    ensemble.feature_ids.push(0);
+                    ensemble.values.push(0.);
+                    ensemble.modes.push("LEAF");
+                    ensemble.true_node_ids.push(0);
+                    ensemble.false_node_ids.push(0);
+                    ensemble.missing_tracks_true.push(0);
+                    ensemble.leaf_tree_ids.push(tree_id as i64);
+                    ensemble.leaf_node_ids.push(n.num as i64);
+                    ensemble.leaf_weights.push(n.weight_value);
+                } else {
+                    ensemble.feature_ids.push(n.split_feature as i64);
+                    // Forust sends values less than the split value left.
+                    ensemble.values.push(n.split_value as f32);
+                    ensemble.modes.push("BRANCH_LT");
+                    ensemble.true_node_ids.push(n.left_child as i64);
+                    ensemble.false_node_ids.push(n.right_child as i64);
+                    ensemble
+                        .missing_tracks_true
+                        .push(i64::from(n.missing_node == n.left_child));
+                    ensemble.num_features = ensemble.num_features.max(n.split_feature + 1);
+                }
+            }
+        }
+        ensemble
+    }
+
+    fn node_attributes(&self) -> Vec<ProtoWriter> {
+        vec![
+            int_attribute_list("nodes_treeids", &self.tree_ids),
+            int_attribute_list("nodes_nodeids", &self.node_ids),
+            int_attribute_list("nodes_featureids", &self.feature_ids),
+            float_attribute_list("nodes_values", &self.values),
+            string_attribute_list("nodes_modes", &self.modes),
+            int_attribute_list("nodes_truenodeids", &self.true_node_ids),
+            int_attribute_list("nodes_falsenodeids", &self.false_node_ids),
+            int_attribute_list("nodes_missing_value_tracks_true", &self.missing_tracks_true),
+        ]
+    }
+
+    /// The leaf weights, `prefix` is either `target` or `class`.
+    fn leaf_attributes(&self, prefix: &str) -> Vec<ProtoWriter> {
+        vec![
+            int_attribute_list(&format!("{}_treeids", prefix), &self.leaf_tree_ids),
+            int_attribute_list(&format!("{}_nodeids", prefix), &self.leaf_node_ids),
+            int_attribute_list(
+                &format!("{}_ids", prefix),
+                &vec![0; self.leaf_node_ids.len()],
+            ),
+            float_attribute_list(&format!("{}_weights", prefix), &self.leaf_weights),
+        ]
+    }
+}
+
+fn int_attribute(name: &str, value: i64) -> ProtoWriter {
+    let mut attribute = ProtoWriter::default();
+    attribute.string(1, name);
+    attribute.uint64(3, value as u64);
+    attribute.uint64(20, ATTRIBUTE_INT);
+    attribute
+}
+
+fn string_attribute(name: &str, value: &str) -> ProtoWriter {
+    let mut attribute = ProtoWriter::default();
+    attribute.string(1, name);
+    attribute.string(4, value);
+    attribute.uint64(20, ATTRIBUTE_STRING);
+    attribute
+}
+
+fn float_attribute(name: &str, value: f32) -> ProtoWriter {
+    let mut attribute = ProtoWriter::default();
+    attribute.string(1, name);
+    attribute.fixed32(2, value.to_le_bytes());
+    attribute.uint64(20, ATTRIBUTE_FLOAT);
+    attribute
+}
+
+fn float_attribute_list(name: &str, values: &[f32]) -> ProtoWriter {
+    let mut attribute = ProtoWriter::default();
+    attribute.string(1, name);
+    attribute.packed_floats(7, values);
+    attribute.uint64(20, ATTRIBUTE_FLOATS);
+    attribute
+}
+
+fn int_attribute_list(name: &str, values: &[i64]) -> ProtoWriter {
+    let mut attribute = ProtoWriter::default();
+    attribute.string(1, name);
+    attribute.packed_ints(8, values);
+    attribute.uint64(20, ATTRIBUTE_INTS);
+    attribute
+}
+
+fn string_attribute_list(name: &str, values: &[&str]) -> ProtoWriter {
+    let mut attribute = ProtoWriter::default();
+    attribute.string(1, name);
+    for v in values {
+        attribute.string(9, v);
+    }
+    attribute.uint64(20, ATTRIBUTE_STRINGS);
+    attribute
+}
+
+/// A named tensor value info, dimensions of `None` are left symbolic.
+fn value_info<'a>(name: &'a str, elem_type: u64, dims: &[Option<usize>]) -> (&'a str, ProtoWriter) {
+    let mut shape = ProtoWriter::default();
+    for dim in dims {
+        let mut d = ProtoWriter::default();
+        match dim {
+            Some(v) => d.uint64(1, *v as u64),
+            None => d.string(2, "N"),
+        }
+        shape.message(1, &d);
+    }
+    let mut tensor = ProtoWriter::default();
+    tensor.uint64(1, elem_type);
+    tensor.message(2, &shape);
+    let mut type_proto = ProtoWriter::default();
+    type_proto.message(1, &tensor);
+    let mut info = ProtoWriter::default();
+    info.string(1, name);
+    info.message(2, &type_proto);
+    (name, info)
+}
+
+/// A minimal protocol buffers encoder, covering the wire types used by ONNX.
+#[derive(Default)]
+struct ProtoWriter {
+    buf: Vec<u8>,
+}
+
+impl ProtoWriter {
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn uint64(&mut self, field: u32, v: u64) {
+        self.key(field, 0);
+        self.varint(v);
+    }
+
+    fn fixed32(&mut self, field: u32, v: [u8; 4]) {
+        self.key(field, 5);
+        self.buf.extend_from_slice(&v);
+    }
+
+    fn bytes(&mut self, field: u32, v: &[u8]) {
+        self.key(field, 2);
+        self.varint(v.len() as u64);
+        self.buf.extend_from_slice(v);
+    }
+
+    fn string(&mut self, field: u32, v: &str) {
+        self.bytes(field, v.as_bytes());
+    }
+
+    fn message(&mut self, field: u32, v: &ProtoWriter) {
+        self.bytes(field, &v.buf);
+    }
+
+    fn packed_floats(&mut self, field: u32, v: &[f32]) {
+        let packed: Vec<u8> = v.iter().flat_map(|f| f.to_le_bytes()).collect();
+        self.bytes(field, &packed);
+    }
+
+    fn packed_ints(&mut self, field: u32, v: &[i64]) {
+        let mut packed = ProtoWriter::default();
+        for i in v {
+            packed.varint(*i as u64);
+        }
+        self.bytes(field, &packed.buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use crate::preprocessing::{ImputeStrategy, Imputer};
+
+    /// Read the top level fields of a message, as field number and raw bytes.
+    fn read_fields(mut buf: &[u8]) -> Vec<(u64, Vec<u8>)> {
+        fn read_varint(buf: &mut &[u8]) -> u64 {
+            let mut v = 0;
+            let mut shift = 0;
+            loop {
+                let b = buf[0];
+                *buf = &buf[1..];
+                v |= ((b & 0x7f) as u64) << shift;
+                if b < 0x80 {
+                    return v;
+                }
+                shift += 7;
+            }
+        }
+        let mut fields = Vec::new();
+        while !buf.is_empty() {
+            let key = read_varint(&mut buf);
+            let value = match key & 7 {
+                0 => read_varint(&mut buf).to_le_bytes().to_vec(),
+                5 => {
+                    let (v, rest) = buf.split_at(4);
+                    buf = rest;
+                    v.to_vec()
+                }
+                2 => {
+                    let len = read_varint(&mut buf) as usize;
+                    let (v, rest) = buf.split_at(len);
+                    buf = rest;
+                    v.to_vec()
+                }
+                t => panic!("unexpected wire type {}", t),
+            };
+            fields.push((key >> 3, value));
+        }
+        fields
+    }
+
+    #[test]
+    fn test_proto_writer() {
+        let mut w = ProtoWriter::default();
+        w.uint64(1, 300);
+        w.string(2, "ab");
+        w.packed_ints(8, &[1, -1]);
+        assert_eq!(
+            w.buf,
+            vec![
+                0x08, 0xac, 0x02, 0x12, 0x02, b'a', b'b', 0x42, 0x0b, 0x01, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff, 0xff, 0xff, 0xff, 0x01
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_onnx_model() {
+        let data_vec = vec![1., 2., 3., 4., 5., 6., 7., f64::NAN];
+        let y = vec![0., 0., 0., 1., 1., 1., 1., 1.];
+        let data = Matrix::new(&data_vec, 8, 1);
+        let mut booster = GradientBooster::default()
+            .set_iterations(3)
+            .set_min_leaf_weight(0.);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let model = to_onnx_model(&booster).unwrap();
+
+        let fields = read_fields(&model);
+        let field_numbers: Vec<u64> = fields.iter().map(|f| f.0).collect();
+        assert_eq!(field_numbers, vec![1, 2, 3, 7, 8, 8]);
+        let graph = read_fields(&fields[3].1);
+        let node = read_fields(&graph[0].1);
+        let op_type = node.iter().find(|f| f.0 == 4).unwrap();
+        assert_eq!(op_type.1, b"TreeEnsembleClassifier");
+        let n_nodes: usize = booster.trees.iter().map(|t| t.nodes.len()).sum();
+        let attribute = |name: &[u8]| {
+            node.iter()
+                .filter(|f| f.0 == 5)
+                .map(|f| read_fields(&f.1))
+                .find(|a| a[0].1 == name)
+                .unwrap()
+        };
+        let values = attribute(b"nodes_values");
+        assert_eq!(values[1].1.len(), n_nodes * 4);
+        let modes = attribute(b"nodes_modes");
+        assert_eq!(modes.iter().filter(|f| f.0 == 9).count(), n_nodes);
+
+        let booster = GradientBooster::default().set_missing(0.);
+        assert!(to_onnx_model(&booster).is_err());
+    }
+
+    #[test]
+    fn test_to_onnx_model_imputer() {
+        let data_vec = vec![1., 2., 3., 4., 5., 6., 7., f64::NAN];
+        let y = vec![0., 0., 0., 1., 1., 1., 1., 1.];
+        let data = Matrix::new(&data_vec, 8, 1);
+        let imputer = Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).unwrap();
+        let mut booster = GradientBooster::default()
+            .set_iterations(3)
+            .set_min_leaf_weight(0.)
+            .set_imputer(Some(imputer));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let model = to_onnx_model(&booster).unwrap();
+
+        let fields = read_fields(&model);
+        let graph = read_fields(&fields[3].1);
+        let nodes: Vec<Vec<(u64, Vec<u8>)>> = graph
+            .iter()
+            .filter(|f| f.0 == 1)
+            .map(|f| read_fields(&f.1))
+            .collect();
+        assert_eq!(nodes.len(), 2);
+        let field = |node: &[(u64, Vec<u8>)], number: u64| {
+            node.iter().find(|f| f.0 == number).unwrap().1.clone()
+        };
+        // The imputer feeds the trees.
+        assert_eq!(field(&nodes[0], 4), b"Imputer");
+        assert_eq!(field(&nodes[0], 2), field(&nodes[1], 1));
+        let values = nodes[0]
+            .iter()
+            .filter(|f| f.0 == 5)
+            .map(|f| read_fields(&f.1))
+            .find(|a| a[0].1 == b"imputed_value_floats")
+            .unwrap();
+        assert_eq!(values[1].1, 4_f32.to_le_bytes());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::constraints::{
+    Constraint, ConstraintMap, InteractionConstraints, MonotoneConstraintMethod,
+};
+use crate::errors::ForustError;
+use crate::gradientbooster::{
+    parse_missing, serialize_missing, Checkpoint, ClassWeight, GradientBooster, GrowPolicy,
+};
+use crate::metric::Metric;
+use crate::objective::ObjectiveType;
+use crate::sampler::SampleMethod;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// The training parameters of a gradient booster, without any fitted state.
+/// Parameters that are not provided when deserializing, keep their default value.
+/// See `GradientBooster` for a description of each parameter.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BoosterParams {
+    pub objective_type: ObjectiveType,
+    pub iterations: usize,
+    pub learning_rate: f32,
+    pub max_depth: usize,
+    /// An unlimited number of leaves is written as an empty value, as TOML
+    /// integers can't hold the maximum usize value.
+    #[serde(
+        serialize_with = "serialize_max_leaves",
+        deserialize_with = "parse_max_leaves"
+    )]
+    pub max_leaves: usize,
+    pub l2: f32,
+    pub gamma: f32,
+    pub min_leaf_weight: f32,
+    pub base_score: f64,
+    pub initialize_base_score: bool,
+    pub nbins: u16,
+    pub parallel: bool,
+    pub allow_missing_splits: bool,
+    pub subsample: f32,
+    pub top_rate: f64,
+    pub other_rate: f64,
+    pub seed: u64,
+    #[serde(
+        serialize_with = "serialize_missing",
+        deserialize_with = "parse_missing"
+    )]
+    pub missing: f64,
+    pub create_missing_branch: bool,
+    pub sample_method: SampleMethod,
+    pub grow_policy: GrowPolicy,
+    pub evaluation_metric: Option<Metric>,
+    pub early_stopping_rounds: Option<usize>,
+    pub validation_fraction: Option<f64>,
+    pub stratify_validation: bool,
+    pub quantile: f64,
+    pub colsample_bytree: f64,
+    pub monotone_constraint_method: MonotoneConstraintMethod,
+    pub interaction_constraints: Option<InteractionConstraints>,
+    pub num_parallel_tree: usize,
+    pub warm_start: bool,
+    pub class_weight: Option<ClassWeight>,
+    pub n_threads: Option<usize>,
+    pub checkpoint: Option<Checkpoint>,
+    // Tables have to come after all values in TOML.
+    #[serde(
+        serialize_with = "serialize_monotone_constraints",
+        deserialize_with = "parse_monotone_constraints"
+    )]
+    pub monotone_constraints: Option<ConstraintMap>,
+}
+
+fn serialize_max_leaves<S>(max_leaves: &usize, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let max_leaves = if *max_leaves == usize::MAX {
+        None
+    } else {
+        Some(*max_leaves)
+    };
+    max_leaves.serialize(s)
+}
+
+fn parse_max_leaves<'de, D>(d: D) -> Result<usize, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Deserialize::deserialize(d).map(|x: Option<_>| x.unwrap_or(usize::MAX))
+}
+
+// TOML only allows string keys, so the feature indices are written as strings.
+fn serialize_monotone_constraints<S>(
+    constraints: &Option<ConstraintMap>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    constraints
+        .as_ref()
+        .map(|c| {
+            c.iter()
+                .map(|(k, v)| (k.to_string(), *v))
+                .collect::<BTreeMap<String, Constraint>>()
+        })
+        .serialize(s)
+}
+
+fn parse_monotone_constraints<'de, D>(d: D) -> Result<Option<ConstraintMap>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let constraints: Option<HashMap<String, Constraint>> = Deserialize::deserialize(d)?;
+    constraints
+        .map(|c| {
+            c.into_iter()
+                .map(|(k, v)| {
+                    k.parse::<usize>()
+                        .map(|k| (k, v))
+                        .map_err(|_| D::Error::custom(format!("invalid feature index {}", k)))
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+impl Default for BoosterParams {
+    fn default() -> Self {
+        GradientBooster::default().params()
+    }
+}
+
+impl BoosterParams {
+    /// Dump the parameters as a json string.
+    pub fn to_json(&self) -> Result<String, ForustError> {
+        serde_json::to_string_pretty(self).map_err(ForustError::Serialization)
+    }
+
+    /// Load parameters from a json string.
+    /// * `json_str` - String object, which can be serialized to json.
+    pub fn from_json(json_str: &str) -> Result<Self, ForustError> {
+        serde_json::from_str(json_str).map_err(ForustError::Serialization)
+    }
+
+    /// Dump the parameters as a TOML string.
+    pub fn to_toml(&self) -> Result<String, ForustError> {
+        toml::to_string(self).map_err(|e| ForustError::UnableToWrite(e.to_string()))
+    }
+
+    /// Load parameters from a TOML string.
+    /// * `toml_str` - String object, which can be serialized to TOML.
+    pub fn from_toml(toml_str: &str) -> Result<Self, ForustError> {
+        toml::from_str(toml_str).map_err(|e| ForustError::UnableToRead(e.to_string()))
+    }
+
+    /// Get the parameters as a map of parameter names, to their json values.
+    pub fn to_map(&self) -> Result<Map<String, Value>, ForustError> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(m)) => Ok(m),
+            Ok(_) => unreachable!(),
+            Err(e) => Err(ForustError::Serialization(e)),
+        }
+    }
+
+    /// Create parameters from a map of parameter names, to their json values.
+    /// Parameters missing from the map keep their default value.
+    /// * `values` - Map of parameter names, to their values.
+    pub fn from_map(values: &Map<String, Value>) -> Result<Self, ForustError> {
+        Self::default().with_values(values)
+    }
+
+    /// Create a copy of the parameters, with the named parameters replaced by the
+    /// provided json values, such as `{"max_depth": 3, "learning_rate": 0.1}`.
+    /// * `values` - Map of parameter names, to their new values.
+    pub fn with_values(&self, values: &Map<String, Value>) -> Result<Self, ForustError> {
+        let mut params = self.to_map()?;
+        for (name, value) in values {
+            match params.get_mut(name) {
+                Some(v) => *v = value.clone(),
+                None => {
+                    return Err(ForustError::InvalidParameter {
+                        name: name.to_string(),
+                        constraint: "the name of a booster parameter".to_string(),
+                        value: name.to_string(),
+                    })
+                }
+            }
+        }
+        serde_json::from_value(Value::Object(params)).map_err(|e| ForustError::InvalidParameter {
+            name: "values".to_string(),
+            constraint: "values of the same type as the parameters".to_string(),
+            value: e.to_string(),
+        })
+    }
+
+    /// Save the parameters to a file, the format is TOML if the path ends
+    /// with `.toml`, and json otherwise.
+    /// * `path` - Path to save the parameters.
+    pub fn save(&self, path: &str) -> Result<(), ForustError> {
+        let params = if path.ends_with(".toml") {
+            self.to_toml()?
+        } else {
+            self.to_json()?
+        };
+        match fs::write(path, params) {
+            Err(e) => Err(ForustError::Io(e)),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    /// Load parameters from a file, the format is TOML if the path ends
+    /// with `.toml`, and json otherwise.
+    /// * `path` - Path to load the parameters from.
+    pub fn load(path: &str) -> Result<Self, ForustError> {
+        let params = match fs::read_to_string(path) {
+            Ok(s) => Ok(s),
+            Err(e) => Err(ForustError::Io(e)),
+        }?;
+        if path.ends_with(".toml") {
+            Self::from_toml(&params)
+        } else {
+            Self::from_json(&params)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_round_trip() {
+        let params = GradientBooster::default()
+            .set_objective_type(ObjectiveType::SquaredLoss)
+            .set_learning_rate(0.1)
+            .set_max_depth(3)
+            .set_missing(-1.)
+            .set_evaluation_metric(Some(Metric::RootMeanSquaredError))
+            .set_monotone_constraints(Some(HashMap::from([(1, Constraint::Positive)])))
+            .params();
+        let params = BoosterParams {
+            class_weight: Some(ClassWeight::Weights(vec![1., 2.])),
+            checkpoint: Some(Checkpoint {
+                path: "model.json".to_string(),
+                iterations: Some(10),
+                seconds: None,
+                target_rounds: None,
+            }),
+            ..params
+        };
+
+        let json = params.to_json().unwrap();
+        let from_json = BoosterParams::from_json(&json).unwrap();
+        assert_eq!(json, from_json.to_json().unwrap());
+
+        let toml = params.to_toml().unwrap();
+        let from_toml = BoosterParams::from_toml(&toml).unwrap();
+        assert_eq!(json, from_toml.to_json().unwrap());
+        assert_eq!(from_toml.max_leaves, usize::MAX);
+        assert_eq!(from_toml.missing, -1.);
+
+        // Missing fields take the default value.
+        let params = BoosterParams::from_toml("iterations = 5\n").unwrap();
+        assert_eq!(params.iterations, 5);
+        assert!(params.missing.is_nan());
+        let booster = GradientBooster::from_params(params).unwrap();
+        assert_eq!(booster.iterations, 5);
+
+        let params = BoosterParams::from_toml("learning_rate = 0.0\n").unwrap();
+        assert!(GradientBooster::from_params(params).is_err());
+
+        let values = serde_json::json!({"max_depth": 2, "learning_rate": 0.5});
+        let params = BoosterParams::default()
+            .with_values(values.as_object().unwrap())
+            .unwrap();
+        assert_eq!(params.max_depth, 2);
+        assert_eq!(params.learning_rate, 0.5);
+        let map = params.to_map().unwrap();
+        assert_eq!(map["max_depth"], 2);
+        assert_eq!(
+            BoosterParams::from_map(&map).unwrap().to_map().unwrap(),
+            map
+        );
+        let values = serde_json::json!({"max_dpeth": 2});
+        assert!(BoosterParams::default()
+            .with_values(values.as_object().unwrap())
+            .is_err());
+        let values = serde_json::json!({"max_depth": "deep"});
+        assert!(BoosterParams::default()
+            .with_values(values.as_object().unwrap())
+            .is_err());
+    }
+}
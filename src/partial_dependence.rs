@@ -1,4 +1,140 @@
-use crate::{tree::Tree, utils::is_missing};
+use crate::errors::ForustError;
+use crate::{tree::Tree, utils::is_missing, utils::percentiles};
+use serde::{Deserialize, Serialize};
+
+/// The values of a feature that partial dependence is calculated for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartialDependenceGrid {
+    /// The values given, in the order given.
+    Values(Vec<f64>),
+    /// `n` values, at evenly spaced percentiles of the feature from `lower` to `upper`,
+    /// such as from 0.05 to 0.95, so the sparse tails of the feature are left out.
+    Percentiles { lower: f64, upper: f64, n: usize },
+    /// Every unique value of the feature.
+    Unique,
+}
+
+impl Default for PartialDependenceGrid {
+    /// 100 values, from the 5th to the 95th percentile.
+    fn default() -> Self {
+        PartialDependenceGrid::Percentiles {
+            lower: 0.05,
+            upper: 0.95,
+            n: 100,
+        }
+    }
+}
+
+impl PartialDependenceGrid {
+    /// The values of the grid, for a feature. Missing values of the feature are left
+    /// out, and the values of percentile, and unique grids are sorted, without duplicates.
+    ///
+    /// * `values` - The values of the feature.
+    /// * `sample_weight` - Instance weights of the values, the percentiles are weighted
+    ///   by them. If None a weight of 1 is used for every value.
+    /// * `missing` - Value to consider missing.
+    pub fn values(
+        &self,
+        values: &[f64],
+        sample_weight: Option<&[f64]>,
+        missing: &f64,
+    ) -> Result<Vec<f64>, ForustError> {
+        let (lower, upper, n) = match self {
+            PartialDependenceGrid::Values(v) => return Ok(v.clone()),
+            PartialDependenceGrid::Unique => (0., 1., 0),
+            PartialDependenceGrid::Percentiles { lower, upper, n } => {
+                if !(0. ..=1.).contains(lower) || !(*lower..=1.).contains(upper) || *n == 0 {
+                    return Err(ForustError::InvalidParameter {
+                        name: "grid".to_string(),
+                        constraint:
+                            "percentiles with 0 <= lower <= upper <= 1, and at least 1 value"
+                                .to_string(),
+                        value: format!("{:?}", self),
+                    });
+                }
+                (*lower, *upper, *n)
+            }
+        };
+        if let Some(w) = sample_weight {
+            if w.len() != values.len() {
+                return Err(ForustError::LengthMismatch(
+                    "sample_weight".to_string(),
+                    w.len(),
+                    values.len(),
+                ));
+            }
+        }
+        let (present, weights): (Vec<f64>, Vec<f64>) = values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| !is_missing(v, missing))
+            .map(|(i, v)| (*v, sample_weight.map_or(1., |w| w[i])))
+            .unzip();
+        if present.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut grid = match self {
+            PartialDependenceGrid::Unique => present,
+            _ => {
+                let pcts: Vec<f64> = (0..n)
+                    .map(|i| {
+                        if n == 1 {
+                            lower
+                        } else {
+                            lower + (upper - lower) * i as f64 / (n - 1) as f64
+                        }
+                    })
+                    .collect();
+                percentiles(&present, &weights, &pcts)
+            }
+        };
+        grid.sort_by(|a, b| a.total_cmp(b));
+        grid.dedup();
+        Ok(grid)
+    }
+}
+
+/// A serializable partial dependence result of a feature, for consumers such as dashboards.
+///
+/// * `feature` - The index of the feature.
+/// * `feature_name` - The name of the feature, if the model has feature names.
+/// * `values` - The values of the feature the partial dependence is calculated for.
+/// * `partial_dependence` - The partial dependence of each value.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PartialDependenceResult {
+    pub feature: usize,
+    pub feature_name: Option<String>,
+    pub values: Vec<f64>,
+    pub partial_dependence: Vec<f64>,
+}
+
+impl PartialDependenceResult {
+    /// Create a result from pairs of each value of the feature, and its partial dependence.
+    ///
+    /// * `feature` - The index of the feature.
+    /// * `feature_name` - The name of the feature.
+    /// * `pd` - Pairs of each value, and its partial dependence.
+    pub fn new(feature: usize, feature_name: Option<String>, pd: &[(f64, f64)]) -> Self {
+        let (values, partial_dependence) = pd.iter().copied().unzip();
+        PartialDependenceResult {
+            feature,
+            feature_name,
+            values,
+            partial_dependence,
+        }
+    }
+
+    /// Dump the result as a json string.
+    pub fn to_json(&self) -> Result<String, ForustError> {
+        serde_json::to_string_pretty(self).map_err(ForustError::Serialization)
+    }
+
+    /// Load a result from a json string.
+    /// * `json_str` - String object, which can be serialized to json.
+    pub fn from_json(json_str: &str) -> Result<Self, ForustError> {
+        serde_json::from_str(json_str).map_err(ForustError::Serialization)
+    }
+}
 
 /// Partial Dependence Calculator
 // struct PDCalculator {
@@ -19,19 +155,32 @@ pub fn tree_partial_dependence(
     value: f64,
     proportion: f32,
     missing: &f64,
+) -> f64 {
+    tree_joint_partial_dependence(tree, node_idx, &[(feature, value)], proportion, missing)
+}
+
+/// Partial dependence of a tree, for a set of features fixed at specific values.
+/// Splits on any of the provided features follow the value of that feature,
+/// all other splits are weighted by the cover of their children.
+pub fn tree_joint_partial_dependence(
+    tree: &Tree,
+    node_idx: usize,
+    values: &[(usize, f64)],
+    proportion: f32,
+    missing: &f64,
 ) -> f64 {
     let n = &tree.nodes[node_idx];
     if n.is_leaf {
         f64::from(proportion * n.weight_value)
-    } else if n.split_feature == feature {
-        let child = if is_missing(&value, missing) {
+    } else if let Some((_, value)) = values.iter().find(|(f, _)| *f == n.split_feature) {
+        let child = if is_missing(value, missing) {
             n.missing_node
-        } else if value < n.split_value {
+        } else if *value < n.split_value {
             n.left_child
         } else {
             n.right_child
         };
-        tree_partial_dependence(tree, child, feature, value, proportion, missing)
+        tree_joint_partial_dependence(tree, child, values, proportion, missing)
     } else {
         let left_cover = get_node_cover(tree, n.left_child);
         let right_cover = get_node_cover(tree, n.right_child);
@@ -42,29 +191,26 @@ pub fn tree_partial_dependence(
         };
         let total_cover = left_cover + right_cover + missing_cover;
         let missing_pd = if n.has_missing_branch() {
-            tree_partial_dependence(
+            tree_joint_partial_dependence(
                 tree,
                 n.missing_node,
-                feature,
-                value,
+                values,
                 proportion * (missing_cover / total_cover),
                 missing,
             )
         } else {
             0.
         };
-        tree_partial_dependence(
+        tree_joint_partial_dependence(
             tree,
             n.left_child,
-            feature,
-            value,
+            values,
             proportion * (left_cover / total_cover),
             missing,
-        ) + tree_partial_dependence(
+        ) + tree_joint_partial_dependence(
             tree,
             n.right_child,
-            feature,
-            value,
+            values,
             proportion * (right_cover / total_cover),
             missing,
         ) + missing_pd
@@ -75,7 +221,7 @@ pub fn tree_partial_dependence(
 mod tests {
     use super::*;
     use crate::binning::bin_matrix;
-    use crate::constraints::ConstraintMap;
+    use crate::constraints::{ConstraintMap, MonotoneConstraintMethod};
     use crate::data::Matrix;
     use crate::gradientbooster::GrowPolicy;
     use crate::objective::{LogLoss, ObjectiveFunction};
@@ -104,6 +250,8 @@ mod tests {
             learning_rate: 0.3,
             allow_missing_splits: true,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let mut tree = Tree::new();
 
@@ -122,10 +270,83 @@ mod tests {
             true,
             &SampleMethod::None,
             &GrowPolicy::LossGuide,
-        );
+        )
+        .unwrap();
         let pdp1 = tree_partial_dependence(&tree, 0, 0, 1.0, 1.0, &f64::NAN);
         let pdp2 = tree_partial_dependence(&tree, 0, 0, 2.0, 1.0, &f64::NAN);
         let pdp3 = tree_partial_dependence(&tree, 0, 0, 3.0, 1.0, &f64::NAN);
         println!("{}, {}, {}", pdp1, pdp2, pdp3);
     }
+    #[test]
+    fn test_joint_partial_dependence() {
+        let file = fs::read_to_string("resources/contiguous_no_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let yhat = vec![0.5; y.len()];
+        let w = vec![1.; y.len()];
+        let g = LogLoss::calc_grad(&y, &yhat, &w);
+        let h = LogLoss::calc_hess(&y, &yhat, &w);
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let splitter = MissingImputerSplitter {
+            l2: 1.0,
+            gamma: 3.0,
+            min_leaf_weight: 1.0,
+            learning_rate: 0.3,
+            allow_missing_splits: true,
+            constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
+        };
+        let mut tree = Tree::new();
+
+        let b = bin_matrix(&data, &w, 300, f64::NAN).unwrap();
+        let bdata = Matrix::new(&b.binned_data, data.rows, data.cols);
+
+        tree.fit(
+            &bdata,
+            data.index.to_owned(),
+            &b.cuts,
+            &g,
+            &h,
+            &splitter,
+            usize::MAX,
+            5,
+            true,
+            &SampleMethod::None,
+            &GrowPolicy::LossGuide,
+        )
+        .unwrap();
+
+        // A single fixed feature is the one way partial dependence.
+        let pdp = tree_partial_dependence(&tree, 0, 0, 2.0, 1.0, &f64::NAN);
+        let joint = tree_joint_partial_dependence(&tree, 0, &[(0, 2.0)], 1.0, &f64::NAN);
+        assert_eq!(joint, pdp);
+
+        // Fixing a feature the tree never splits on, should have no impact.
+        let unused = (0..data.cols).find(|f| {
+            !tree
+                .nodes
+                .iter()
+                .any(|n| !n.is_leaf && n.split_feature == *f)
+        });
+        if let Some(f) = unused {
+            let joint =
+                tree_joint_partial_dependence(&tree, 0, &[(0, 2.0), (f, 1.0)], 1.0, &f64::NAN);
+            assert_eq!(joint, pdp);
+        }
+
+        // With every feature fixed, there is a single path through the tree,
+        // so the partial dependence is the prediction for that record.
+        for row in [0, 1, 100, 890] {
+            let values: Vec<(usize, f64)> =
+                (0..data.cols).map(|f| (f, *data.get(row, f))).collect();
+            let joint = tree_joint_partial_dependence(&tree, 0, &values, 1.0, &f64::NAN);
+            let pred = tree.predict_row_from_row_slice(&data.get_row(row), &f64::NAN);
+            assert!((joint - pred).abs() < 1e-6);
+        }
+    }
 }
@@ -0,0 +1,447 @@
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::objective::ObjectiveType;
+use crate::tree::Tree;
+use std::fmt::Write;
+use std::fs;
+
+/// Save a gradient booster as a PMML 4.4 document.
+///
+/// * `booster` - The booster to export.
+/// * `path` - Path to save the PMML document.
+pub fn save_pmml(booster: &GradientBooster, path: &str) -> Result<(), ForustError> {
+    let model = to_pmml(booster)?;
+    match fs::write(path, model) {
+        Err(e) => Err(ForustError::Io(e)),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Convert a gradient booster into a PMML 4.4 document. The trees are written as a
+/// `MiningModel` summing `TreeModel` segments, the features are named with the feature
+/// names of the booster, or `x0`, `x1`... if it has none, and the target is named `y`,
+/// with underscores appended if a feature already has this name. For a `LogLoss`
+/// booster, the summed log odds are passed through a `RegressionModel` with a logit
+/// normalization, that outputs the probability of each class. Missing values, and a
+/// missing value other than NaN, are routed to the same child as in forust, or are
+/// replaced with the values of the imputer of the booster, if it has one.
+///
+/// * `booster` - The booster to export.
+pub fn to_pmml(booster: &GradientBooster) -> Result<String, ForustError> {
+    let trees = booster.get_prediction_trees();
+    let num_features = trees
+        .iter()
+        .flat_map(|t| t.nodes.iter())
+        .filter(|n| !n.is_leaf)
+        .map(|n| n.split_feature + 1)
+        .max()
+        .unwrap_or(0);
+    let features: Vec<String> = match &booster.feature_names {
+        Some(names) => names.clone(),
+        None => (0..num_features).map(|i| format!("x{}", i)).collect(),
+    };
+    if features.len() < num_features {
+        return Err(ForustError::ShapeMismatch {
+            name: "feature_names".to_string(),
+            expected: format!("at least {} names, for the features split on", num_features),
+            got: format!("{} names", features.len()),
+        });
+    }
+    let target = unique_name("y", &features);
+    let log_odds = unique_name("log_odds", &features);
+    let features: Vec<String> = features.iter().map(|f| escape_xml(f)).collect();
+    let num_features = features.len();
+    let replacements = booster.imputer.as_ref().map(|i| i.values.as_slice());
+
+    let mut pmml = String::new();
+    // Writing to a string can't fail, so the results are ignored throughout.
+    let _ = writeln!(pmml, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        pmml,
+        r#"<PMML xmlns="http://www.dmg.org/PMML-4_4" version="4.4">"#
+    );
+    let _ = writeln!(
+        pmml,
+        r#"<Header><Application name="forust" version="{}"/></Header>"#,
+        env!("CARGO_PKG_VERSION")
+    );
+
+    let _ = writeln!(
+        pmml,
+        r#"<DataDictionary numberOfFields="{}">"#,
+        num_features + 1
+    );
+    for f in features.iter() {
+        if booster.missing.is_nan() {
+            let _ = writeln!(
+                pmml,
+                r#"<DataField name="{}" optype="continuous" dataType="double"/>"#,
+                f
+            );
+        } else {
+            let _ = writeln!(
+                pmml,
+                r#"<DataField name="{}" optype="continuous" dataType="double"><Value value="{}" property="missing"/></DataField>"#,
+                f, booster.missing
+            );
+        }
+    }
+    match booster.objective_type {
+        ObjectiveType::LogLoss => {
+            let _ = writeln!(
+                pmml,
+                r#"<DataField name="{target}" optype="categorical" dataType="integer"><Value value="0"/><Value value="1"/></DataField>"#
+            );
+        }
+        ObjectiveType::SquaredLoss | ObjectiveType::QuantileLoss => {
+            let _ = writeln!(
+                pmml,
+                r#"<DataField name="{target}" optype="continuous" dataType="double"/>"#
+            );
+        }
+    }
+    let _ = writeln!(pmml, "</DataDictionary>");
+
+    match booster.objective_type {
+        ObjectiveType::SquaredLoss | ObjectiveType::QuantileLoss => {
+            write_tree_ensemble(
+                &mut pmml,
+                trees,
+                &features,
+                replacements,
+                &target,
+                booster.base_score,
+                None,
+            );
+        }
+        ObjectiveType::LogLoss => {
+            let _ = writeln!(pmml, r#"<MiningModel functionName="classification">"#);
+            write_mining_schema(&mut pmml, &features, replacements, Some(&target));
+            let _ = writeln!(pmml, "<Output>");
+            for c in ["0", "1"] {
+                let _ = writeln!(
+                    pmml,
+                    r#"<OutputField name="probability({c})" optype="continuous" dataType="double" feature="probability" value="{c}"/>"#
+                );
+            }
+            let _ = writeln!(pmml, "</Output>");
+            let _ = writeln!(pmml, r#"<Segmentation multipleModelMethod="modelChain">"#);
+            let _ = writeln!(pmml, r#"<Segment id="1"><True/>"#);
+            write_tree_ensemble(
+                &mut pmml,
+                trees,
+                &features,
+                replacements,
+                &target,
+                booster.base_score,
+                Some(&log_odds),
+            );
+            let _ = writeln!(pmml, "</Segment>");
+            let _ = writeln!(pmml, r#"<Segment id="2"><True/>"#);
+            let _ = writeln!(
+                pmml,
+                r#"<RegressionModel functionName="classification" normalizationMethod="logit">"#
+            );
+            let _ = writeln!(
+                pmml,
+                r#"<MiningSchema><MiningField name="{target}" usageType="target"/><MiningField name="{log_odds}"/></MiningSchema>"#
+            );
+            let _ = writeln!(
+                pmml,
+                r#"<RegressionTable intercept="0" targetCategory="1"><NumericPredictor name="{log_odds}" coefficient="1"/></RegressionTable>"#
+            );
+            let _ = writeln!(
+                pmml,
+                r#"<RegressionTable intercept="0" targetCategory="0"/>"#
+            );
+            let _ = writeln!(pmml, "</RegressionModel>");
+            let _ = writeln!(pmml, "</Segment>");
+            let _ = writeln!(pmml, "</Segmentation>");
+            let _ = writeln!(pmml, "</MiningModel>");
+        }
+    }
+    let _ = writeln!(pmml, "</PMML>");
+    Ok(pmml)
+}
+
+/// Write the sum of the trees, plus the base score, as a regression `MiningModel`.
+/// If `output` is provided, the sum is exposed as an intermediate output field
+/// with this name, and the model has no target.
+fn write_tree_ensemble(
+    pmml: &mut String,
+    trees: &[Tree],
+    features: &[String],
+    replacements: Option<&[f64]>,
+    target: &str,
+    base_score: f64,
+    output: Option<&str>,
+) {
+    let _ = writeln!(pmml, r#"<MiningModel functionName="regression">"#);
+    write_mining_schema(
+        pmml,
+        features,
+        replacements,
+        output.map_or(Some(target), |_| None),
+    );
+    if let Some(name) = output {
+        let _ = writeln!(
+            pmml,
+            r#"<Output><OutputField name="{}" optype="continuous" dataType="double" feature="predictedValue" isFinalResult="false"/></Output>"#,
+            name
+        );
+    }
+    let _ = writeln!(
+        pmml,
+        r#"<Targets><Target rescaleConstant="{}"/></Targets>"#,
+        base_score
+    );
+    let _ = writeln!(pmml, r#"<Segmentation multipleModelMethod="sum">"#);
+    for (i, tree) in trees.iter().enumerate() {
+        let _ = writeln!(pmml, r#"<Segment id="{}"><True/>"#, i + 1);
+        let _ = writeln!(
+            pmml,
+            r#"<TreeModel functionName="regression" missingValueStrategy="defaultChild" noTrueChildStrategy="returnLastPrediction">"#
+        );
+        write_mining_schema(pmml, features, replacements, None);
+        write_node(pmml, tree, 0, features, "<True/>".to_string());
+        let _ = writeln!(pmml, "</TreeModel>");
+        let _ = writeln!(pmml, "</Segment>");
+    }
+    let _ = writeln!(pmml, "</Segmentation>");
+    let _ = writeln!(pmml, "</MiningModel>");
+}
+
+/// Append underscores to `name`, until no feature has this name.
+fn unique_name(name: &str, features: &[String]) -> String {
+    let mut name = name.to_string();
+    while features.contains(&name) {
+        name.push('_');
+    }
+    name
+}
+
+/// Escape the characters that can't appear as is, in an XML attribute value.
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Write the fields a model uses, if `replacements` is provided, missing values of
+/// each feature are replaced with its value.
+fn write_mining_schema(
+    pmml: &mut String,
+    features: &[String],
+    replacements: Option<&[f64]>,
+    target: Option<&str>,
+) {
+    let _ = write!(pmml, "<MiningSchema>");
+    if let Some(t) = target {
+        let _ = write!(pmml, r#"<MiningField name="{}" usageType="target"/>"#, t);
+    }
+    for (i, f) in features.iter().enumerate() {
+        match replacements.and_then(|r| r.get(i)) {
+            Some(v) => {
+                let _ = write!(
+                    pmml,
+                    r#"<MiningField name="{}" missingValueReplacement="{}"/>"#,
+                    f, v
+                );
+            }
+            None => {
+                let _ = write!(pmml, r#"<MiningField name="{}"/>"#, f);
+            }
+        }
+    }
+    let _ = writeln!(pmml, "</MiningSchema>");
+}
+
+fn write_node(pmml: &mut String, tree: &Tree, idx: usize, features: &[String], predicate: String) {
+    let node = &tree.nodes[idx];
+    // Leaf weights are f32, they are written as f64 so they are read back exactly.
+    let score = node.weight_value as f64;
+    if node.is_leaf {
+        let _ = writeln!(
+            pmml,
+            r#"<Node id="{}" score="{}">{}</Node>"#,
+            node.num, score, predicate
+        );
+        return;
+    }
+    let _ = writeln!(
+        pmml,
+        r#"<Node id="{}" score="{}" defaultChild="{}">{}"#,
+        node.num, score, node.missing_node, predicate
+    );
+    let field = &features[node.split_feature];
+    // Children are evaluated in order, so the missing branch has to come first.
+    if node.has_missing_branch() {
+        write_node(
+            pmml,
+            tree,
+            node.missing_node,
+            features,
+            format!(
+                r#"<SimplePredicate field="{}" operator="isMissing"/>"#,
+                field
+            ),
+        );
+    }
+    write_node(
+        pmml,
+        tree,
+        node.left_child,
+        features,
+        format!(
+            r#"<SimplePredicate field="{}" operator="lessThan" value="{}"/>"#,
+            field, node.split_value
+        ),
+    );
+    write_node(
+        pmml,
+        tree,
+        node.right_child,
+        features,
+        format!(
+            r#"<SimplePredicate field="{}" operator="greaterOrEqual" value="{}"/>"#,
+            field, node.split_value
+        ),
+    );
+    let _ = writeln!(pmml, "</Node>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use crate::preprocessing::{ImputeStrategy, Imputer};
+    use std::fs;
+
+    #[test]
+    fn test_to_pmml() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_max_depth(3)
+            .set_create_missing_branch(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let pmml = to_pmml(&booster).unwrap();
+        assert!(pmml.starts_with("<?xml"));
+        assert!(pmml.contains(r#"normalizationMethod="logit""#));
+        assert!(pmml.contains(r#"operator="isMissing""#));
+        assert_eq!(pmml.matches("<TreeModel ").count(), booster.trees.len());
+        let n_nodes: usize = booster.trees.iter().map(|t| t.nodes.len()).sum();
+        assert_eq!(pmml.matches("<Node ").count(), n_nodes);
+        // Every element that is opened, is also closed.
+        for tag in [
+            "PMML",
+            "MiningModel",
+            "Segmentation",
+            "Segment",
+            "TreeModel",
+        ] {
+            assert_eq!(
+                pmml.matches(&format!("<{} ", tag)).count()
+                    + pmml.matches(&format!("<{}>", tag)).count(),
+                pmml.matches(&format!("</{}>", tag)).count()
+            );
+        }
+
+        let booster = GradientBooster::default()
+            .set_objective_type(ObjectiveType::SquaredLoss)
+            .set_missing(-1.);
+        let pmml = to_pmml(&booster).unwrap();
+        assert!(!pmml.contains("normalizationMethod"));
+        assert!(pmml.contains(r#"<DataField name="y" optype="continuous""#));
+    }
+
+    #[test]
+    fn test_to_pmml_feature_names() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_max_depth(3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        booster.feature_names = Some(
+            ["a&b", "<c>", "\"d\"", "y", "log_odds"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        );
+        let pmml = to_pmml(&booster).unwrap();
+        assert!(pmml.contains(r#"<DataField name="a&amp;b""#));
+        assert!(pmml.contains(r#"<DataField name="&lt;c&gt;""#));
+        assert!(pmml.contains(r#"<DataField name="&quot;d&quot;""#));
+        assert!(!pmml.contains("a&b"));
+        // The target and log odds don't take the names of features.
+        assert!(pmml.contains(r#"<DataField name="y_" optype="categorical""#));
+        assert!(pmml.contains(r#"<MiningField name="y_" usageType="target"/>"#));
+        assert!(pmml.contains(r#"<NumericPredictor name="log_odds_""#));
+
+        booster.feature_names = Some(vec!["a".to_string()]);
+        assert!(matches!(
+            to_pmml(&booster),
+            Err(ForustError::ShapeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_pmml_imputer() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let imputer = Imputer::fit(&data, ImputeStrategy::Median, f64::NAN).unwrap();
+        let mut booster = GradientBooster::default()
+            .set_iterations(5)
+            .set_max_depth(3)
+            .set_imputer(Some(imputer.clone()));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let pmml = to_pmml(&booster).unwrap();
+        // The inputs of every model have their missing values replaced.
+        let n_models = pmml.matches("<MiningSchema>").count() - 1;
+        for (i, v) in imputer.values.iter().enumerate() {
+            let field = format!(
+                r#"<MiningField name="x{}" missingValueReplacement="{}"/>"#,
+                i, v
+            );
+            assert_eq!(pmml.matches(&field).count(), n_models);
+        }
+    }
+}
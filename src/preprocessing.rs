@@ -0,0 +1,278 @@
+use crate::data::{DataMatrix, MatrixData};
+use crate::errors::ForustError;
+use crate::utils::is_missing;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// How a category that wasn't seen when an encoder was fit is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum UnseenCategory {
+    /// Encode the category as missing, so it follows the missing branch of each split.
+    Missing,
+    /// Return an error.
+    Error,
+}
+
+/// Encode a categorical column, of strings, or integers, as the integer codes the
+/// booster splits on. Each category is given a code from 0, in the sorted order of
+/// the categories, so the codes don't depend on the order of the records. The encoder
+/// can be serialized, to encode the data the same way at prediction time.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CategoricalEncoder<K: Ord> {
+    codes: BTreeMap<K, usize>,
+    pub unseen: UnseenCategory,
+}
+
+impl<K: Ord + Clone + Display> CategoricalEncoder<K> {
+    /// Fit the encoder on the categories of a column.
+    ///
+    /// * `values` - The category of each record.
+    /// * `unseen` - How categories not in `values` are encoded.
+    pub fn fit(values: &[K], unseen: UnseenCategory) -> Self {
+        let mut codes: BTreeMap<K, usize> = values.iter().map(|v| (v.clone(), 0)).collect();
+        codes.values_mut().enumerate().for_each(|(i, c)| *c = i);
+        CategoricalEncoder { codes, unseen }
+    }
+
+    /// The number of categories the encoder was fit on.
+    pub fn n_categories(&self) -> usize {
+        self.codes.len()
+    }
+
+    /// The categories, in the order of their codes.
+    pub fn categories(&self) -> Vec<&K> {
+        self.codes.keys().collect()
+    }
+
+    /// Encode a single category.
+    ///
+    /// * `value` - The category to encode.
+    pub fn encode(&self, value: &K) -> Result<f64, ForustError> {
+        match (self.codes.get(value), self.unseen) {
+            (Some(c), _) => Ok(*c as f64),
+            (None, UnseenCategory::Missing) => Ok(f64::NAN),
+            (None, UnseenCategory::Error) => Err(ForustError::InvalidParameter {
+                name: "value".to_string(),
+                constraint: "a category the encoder was fit on".to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    /// Encode a column of categories, as a column of the data.
+    ///
+    /// * `values` - The category of each record.
+    pub fn transform(&self, values: &[K]) -> Result<Vec<f64>, ForustError> {
+        values.iter().map(|v| self.encode(v)).collect()
+    }
+
+    /// The category of a code, if the code is one of the encoder's codes.
+    ///
+    /// * `code` - A code from `encode`.
+    pub fn decode(&self, code: f64) -> Option<&K> {
+        if code.fract() != 0. || code < 0. {
+            return None;
+        }
+        self.codes.keys().nth(code as usize)
+    }
+}
+
+/// The value missing records of a column are replaced with, by an `Imputer`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum ImputeStrategy {
+    /// Replace missing records with a constant.
+    Constant(f64),
+    /// Replace missing records with the mean of the column.
+    Mean,
+    /// Replace missing records with the median of the column.
+    Median,
+}
+
+/// Replace the missing records of each column with a value fit on the training data,
+/// for when imputation is preferred over the missing branch of each split. An imputer
+/// set on a booster with `set_imputer`, is applied to the data the booster is fit, and
+/// predicts on, and is saved with the booster.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Imputer {
+    pub strategy: ImputeStrategy,
+    /// The value missing records of each column are replaced with.
+    pub values: Vec<f64>,
+}
+
+impl Imputer {
+    /// Fit the value each column is imputed with, from the records that aren't missing.
+    ///
+    /// * `data` - The training data.
+    /// * `strategy` - How the value of each column is chosen.
+    /// * `missing` - Float value to consider as missing.
+    pub fn fit<D: DataMatrix>(
+        data: &D,
+        strategy: ImputeStrategy,
+        missing: f64,
+    ) -> Result<Self, ForustError> {
+        let values = (0..data.cols())
+            .map(|j| {
+                let column = data.column(j);
+                let mut present: Vec<f64> = column
+                    .iter()
+                    .copied()
+                    .filter(|v| !is_missing(v, &missing))
+                    .collect();
+                let value = match strategy {
+                    ImputeStrategy::Constant(c) => c,
+                    ImputeStrategy::Mean => present.iter().sum::<f64>() / present.len() as f64,
+                    ImputeStrategy::Median => {
+                        present.sort_unstable_by(|a, b| a.total_cmp(b));
+                        let n = present.len();
+                        match n {
+                            0 => f64::NAN,
+                            _ if n % 2 == 1 => present[n / 2],
+                            _ => (present[n / 2 - 1] + present[n / 2]) / 2.,
+                        }
+                    }
+                };
+                if value.is_finite() {
+                    Ok(value)
+                } else {
+                    Err(ForustError::InvalidParameter {
+                        name: "data".to_string(),
+                        constraint: format!("a finite value to impute column {} with", j),
+                        value: value.to_string(),
+                    })
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Imputer { strategy, values })
+    }
+
+    /// View the data with the missing records imputed, without copying it.
+    ///
+    /// * `data` - The data to impute.
+    /// * `missing` - Float value to consider as missing.
+    pub fn view<'a, D: DataMatrix>(&'a self, data: &'a D, missing: f64) -> ImputedData<'a, D> {
+        ImputedData {
+            data,
+            imputer: self,
+            missing,
+        }
+    }
+
+    /// Copy the data, with the missing records imputed.
+    ///
+    /// * `data` - The data to impute.
+    /// * `missing` - Float value to consider as missing.
+    pub fn transform<D: DataMatrix>(&self, data: &D, missing: f64) -> MatrixData<f64> {
+        let imputed = self.view(data, missing);
+        MatrixData {
+            data: (0..imputed.cols())
+                .flat_map(|j| imputed.column(j).into_owned())
+                .collect(),
+            rows: imputed.rows(),
+            cols: imputed.cols(),
+        }
+    }
+}
+
+/// Data with the missing records replaced by an `Imputer`, as they are read.
+pub struct ImputedData<'a, D> {
+    data: &'a D,
+    imputer: &'a Imputer,
+    missing: f64,
+}
+
+impl<'a, D: DataMatrix> DataMatrix for ImputedData<'a, D> {
+    fn rows(&self) -> usize {
+        self.data.rows()
+    }
+    fn cols(&self) -> usize {
+        self.data.cols()
+    }
+    fn value(&self, row: usize, col: usize) -> f64 {
+        let v = self.data.value(row, col);
+        if is_missing(&v, &self.missing) {
+            self.imputer.values[col]
+        } else {
+            v
+        }
+    }
+    fn column(&self, col: usize) -> Cow<'_, [f64]> {
+        let column = self.data.column(col);
+        if !column.iter().any(|v| is_missing(v, &self.missing)) {
+            return column;
+        }
+        let value = self.imputer.values[col];
+        Cow::Owned(
+            column
+                .iter()
+                .map(|v| {
+                    if is_missing(v, &self.missing) {
+                        value
+                    } else {
+                        *v
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+
+    #[test]
+    fn test_imputer() {
+        let v = vec![1., f64::NAN, 3., 10., f64::NAN, 2., f64::NAN, 4., 6., 20.];
+        let data = Matrix::new(&v, 5, 2);
+        let mean = Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).unwrap();
+        assert_eq!(mean.values, vec![14. / 3., 8.]);
+        let median = Imputer::fit(&data, ImputeStrategy::Median, f64::NAN).unwrap();
+        assert_eq!(median.values, vec![3., 5.]);
+        let constant = Imputer::fit(&data, ImputeStrategy::Constant(-1.), f64::NAN).unwrap();
+        assert_eq!(constant.values, vec![-1., -1.]);
+
+        let imputed = median.transform(&data, f64::NAN);
+        assert_eq!(imputed.data, vec![1., 3., 3., 10., 3., 2., 5., 4., 6., 20.]);
+        assert_eq!(median.view(&data, f64::NAN).value(1, 0), 3.);
+
+        let json = serde_json::to_string(&median).unwrap();
+        assert_eq!(serde_json::from_str::<Imputer>(&json).unwrap(), median);
+
+        let all_missing = vec![f64::NAN; 3];
+        let data = Matrix::new(&all_missing, 3, 1);
+        assert!(Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_categorical_encoder() {
+        let values: Vec<String> = ["S", "C", "Q", "S", "C"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        let encoder = CategoricalEncoder::fit(&values, UnseenCategory::Missing);
+        assert_eq!(encoder.n_categories(), 3);
+        assert_eq!(
+            encoder.transform(&values).unwrap(),
+            vec![2., 0., 1., 2., 0.]
+        );
+        assert!(encoder.encode(&"X".to_string()).unwrap().is_nan());
+        assert_eq!(encoder.decode(1.), Some(&"Q".to_string()));
+        assert_eq!(encoder.decode(3.), None);
+
+        let json = serde_json::to_string(&encoder).unwrap();
+        let loaded: CategoricalEncoder<String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, encoder);
+
+        // Integer categories, such as IDs, are encoded in their sorted order.
+        let ids = vec![40_i64, 7, 1_000, 7];
+        let encoder = CategoricalEncoder::fit(&ids, UnseenCategory::Error);
+        assert_eq!(encoder.transform(&ids).unwrap(), vec![1., 0., 2., 0.]);
+        assert!(encoder.encode(&8).is_err());
+        let json = serde_json::to_string(&encoder).unwrap();
+        let loaded: CategoricalEncoder<i64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, encoder);
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt::{self, Display};
+use std::ops::AddAssign;
+use std::time::{Duration, Instant};
+
+/// The phases of an iteration, that are timed when profiling.
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    /// Calculating the gradient and hessian of the loss.
+    Gradient,
+    /// Partitioning the records of a node, and building the histograms of the children.
+    Histogram,
+    /// Searching the histograms for the best split.
+    SplitFinding,
+    /// Updating the training and evaluation predictions with the new tree.
+    Prediction,
+}
+
+/// Time spent in each phase of a single iteration.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseTimings {
+    pub gradient: Duration,
+    pub histogram: Duration,
+    pub split_finding: Duration,
+    pub prediction: Duration,
+}
+
+impl PhaseTimings {
+    /// Total time spent across all phases.
+    pub fn total(&self) -> Duration {
+        self.gradient + self.histogram + self.split_finding + self.prediction
+    }
+}
+
+impl AddAssign for PhaseTimings {
+    fn add_assign(&mut self, other: Self) {
+        self.gradient += other.gradient;
+        self.histogram += other.histogram;
+        self.split_finding += other.split_finding;
+        self.prediction += other.prediction;
+    }
+}
+
+/// Report of the time spent in each phase of training, recorded by `fit`
+/// when profiling is enabled on the booster.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileReport {
+    /// Time spent binning the training data.
+    pub binning: Duration,
+    /// Time spent in each phase, for every iteration.
+    pub iterations: Vec<PhaseTimings>,
+}
+
+impl ProfileReport {
+    /// Time spent in each phase, summed over all iterations.
+    pub fn phase_totals(&self) -> PhaseTimings {
+        let mut totals = PhaseTimings::default();
+        for t in self.iterations.iter() {
+            totals += *t;
+        }
+        totals
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let totals = self.phase_totals();
+        let total = self.binning + totals.total();
+        let pct = |d: Duration| {
+            if total.is_zero() {
+                0.
+            } else {
+                100. * d.as_secs_f64() / total.as_secs_f64()
+            }
+        };
+        writeln!(f, "{:<14}{:>14}{:>8}", "phase", "seconds", "%")?;
+        for (name, d) in [
+            ("binning", self.binning),
+            ("gradient", totals.gradient),
+            ("histogram", totals.histogram),
+            ("split finding", totals.split_finding),
+            ("prediction", totals.prediction),
+        ] {
+            writeln!(f, "{:<14}{:>14.6}{:>8.2}", name, d.as_secs_f64(), pct(d))?;
+        }
+        write!(
+            f,
+            "{:<14}{:>14.6} over {} iterations",
+            "total",
+            total.as_secs_f64(),
+            self.iterations.len()
+        )
+    }
+}
+
+/// Accumulates the time spent in each phase of the current iteration.
+/// When disabled, the phases are run without being timed.
+#[derive(Default)]
+pub struct Profiler {
+    enabled: bool,
+    timings: PhaseTimings,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Profiler {
+            enabled,
+            timings: PhaseTimings::default(),
+        }
+    }
+
+    /// Run `f`, adding the time it takes to the `phase`.
+    pub fn time<R, F: FnOnce() -> R>(&mut self, phase: Phase, f: F) -> R {
+        if !self.enabled {
+            return f();
+        }
+        let start = Instant::now();
+        let r = f();
+        let elapsed = start.elapsed();
+        match phase {
+            Phase::Gradient => self.timings.gradient += elapsed,
+            Phase::Histogram => self.timings.histogram += elapsed,
+            Phase::SplitFinding => self.timings.split_finding += elapsed,
+            Phase::Prediction => self.timings.prediction += elapsed,
+        }
+        r
+    }
+
+    /// Take the timings recorded so far, resetting them for the next iteration.
+    pub fn take(&mut self) -> PhaseTimings {
+        std::mem::take(&mut self.timings)
+    }
+}
@@ -1,11 +1,11 @@
 use crate::errors::ForustError;
-use crate::utils::items_to_strings;
+use crate::utils::{items_to_strings, validate_float_parameter};
 use rand::rngs::StdRng;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 pub enum SampleMethod {
     None,
     Random,
@@ -28,17 +28,41 @@ impl FromStr for SampleMethod {
     }
 }
 
+/// The records sampled to fit a tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// The records chosen for training.
+    pub chosen: Vec<usize>,
+    /// The records excluded from training.
+    pub excluded: Vec<usize>,
+    /// The factor the gradient, and hessian, of each chosen record is multiplied by,
+    /// in the same order as `chosen`. None if the gradients are used as they are.
+    pub factors: Option<Vec<f32>>,
+}
+
+impl Sample {
+    /// The gradient, and hessian, with the factor of each chosen record applied,
+    /// or None if the sample has no factors, and the gradients can be used as they are.
+    ///
+    /// * `grad` - The gradient of each record.
+    /// * `hess` - The hessian of each record.
+    pub fn reweight(&self, grad: &[f32], hess: &[f32]) -> Option<(Vec<f32>, Vec<f32>)> {
+        let factors = self.factors.as_ref()?;
+        let (mut grad, mut hess) = (grad.to_vec(), hess.to_vec());
+        for (i, f) in self.chosen.iter().zip(factors) {
+            grad[*i] *= f;
+            hess[*i] *= f;
+        }
+        Some((grad, hess))
+    }
+}
+
 // A sampler can be used to subset the data prior to fitting a new tree.
 pub trait Sampler {
-    /// Sample the data, returning a tuple, where the first item is the samples
-    /// chosen for training, and the second are the samples excluded.
-    fn sample(
-        &mut self,
-        rng: &mut StdRng,
-        index: &[usize],
-        grad: &mut [f32],
-        hess: &mut [f32],
-    ) -> (Vec<usize>, Vec<usize>);
+    /// Sample the data, returning the records chosen for training, the records
+    /// excluded, and the factors to reweight the gradients of the chosen records by.
+    /// The gradients aren't changed, the caller applies the factors, with `Sample::reweight`.
+    fn sample(&mut self, rng: &mut StdRng, index: &[usize], grad: &[f32], hess: &[f32]) -> Sample;
 }
 
 pub struct RandomSampler {
@@ -57,9 +81,9 @@ impl Sampler for RandomSampler {
         &mut self,
         rng: &mut StdRng,
         index: &[usize],
-        _grad: &mut [f32],
-        _hess: &mut [f32],
-    ) -> (Vec<usize>, Vec<usize>) {
+        _grad: &[f32],
+        _hess: &[f32],
+    ) -> Sample {
         let subsample = self.subsample;
         let mut chosen = Vec::new();
         let mut excluded = Vec::new();
@@ -70,7 +94,11 @@ impl Sampler for RandomSampler {
                 excluded.push(*i)
             }
         }
-        (chosen, excluded)
+        Sample {
+            chosen,
+            excluded,
+            factors: None,
+        }
     }
 }
 
@@ -86,44 +114,103 @@ impl Default for GossSampler {
     }
 }
 
-#[allow(dead_code)]
 impl GossSampler {
-    pub fn new(a: f64, b: f64) -> Self {
-        GossSampler { a, b }
+    /// Create a sampler, that keeps the records with the largest gradients, and a
+    /// random sample of the rest.
+    ///
+    /// * `a` - The fraction of records with the largest gradients to keep, the `top_rate`.
+    /// * `b` - The fraction of records to sample from the rest, the `other_rate`.
+    pub fn new(a: f64, b: f64) -> Result<Self, ForustError> {
+        validate_float_parameter(a, 0., 1., "top_rate")?;
+        validate_float_parameter(b, 0., 1., "other_rate")?;
+        if a + b > 1. {
+            return Err(ForustError::InvalidParameter {
+                name: "other_rate".to_string(),
+                constraint: format!("a value of at most 1 - top_rate, {}", 1. - a),
+                value: b.to_string(),
+            });
+        }
+        Ok(GossSampler { a, b })
     }
 }
 
 impl Sampler for GossSampler {
-    fn sample(
-        &mut self,
-        rng: &mut StdRng,
-        index: &[usize],
-        grad: &mut [f32],
-        hess: &mut [f32],
-    ) -> (Vec<usize>, Vec<usize>) {
+    fn sample(&mut self, rng: &mut StdRng, index: &[usize], grad: &[f32], _hess: &[f32]) -> Sample {
         let fact = ((1. - self.a) / self.b) as f32;
         let top_n = (self.a * index.len() as f64) as usize;
         let rand_n = (self.b * index.len() as f64) as usize;
 
-        // sort gradient by absolute value from highest to lowest
+        // sort gradient by absolute value from highest to lowest,
+        // ties are broken on the index, so the sample only depends on the seed.
         let mut sorted = (0..index.len()).collect::<Vec<_>>();
-        sorted.sort_unstable_by(|&a, &b| grad[b].abs().total_cmp(&grad[a].abs()));
+        sorted.sort_unstable_by(|&a, &b| {
+            grad[index[b]]
+                .abs()
+                .total_cmp(&grad[index[a]].abs())
+                .then_with(|| a.cmp(&b))
+        });
 
         // select the topN largest gradients
-        let mut used_set = sorted[0..top_n].to_vec();
+        let mut used_set = sorted[0..top_n]
+            .iter()
+            .map(|i| index[*i])
+            .collect::<Vec<_>>();
+        let mut factors = vec![1.; top_n];
 
         // sample the rest based on randN
         let subsample = rand_n as f64 / (index.len() as f64 - top_n as f64);
 
         // weight the sampled "small gradients" by fact and append indices to used_set
+        let mut excluded = Vec::new();
         for i in &sorted[top_n..sorted.len()] {
             if rng.gen_range(0.0..1.0) < subsample {
-                grad[*i] *= fact;
-                hess[*i] *= fact;
-                used_set.push(*i);
+                used_set.push(index[*i]);
+                factors.push(fact);
+            } else {
+                excluded.push(index[*i]);
             }
         }
 
-        (used_set, Vec::new())
+        Sample {
+            chosen: used_set,
+            excluded,
+            factors: Some(factors),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_goss_sample() {
+        let index: Vec<usize> = (0..100).collect();
+        let grad: Vec<f32> = (0..100).map(|i| i as f32 - 50.).collect();
+        let hess = vec![1.; 100];
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = GossSampler::new(0.2, 0.3)
+            .unwrap()
+            .sample(&mut rng, &index, &grad, &hess);
+        assert_eq!(sample.chosen.len() + sample.excluded.len(), 100);
+        // The records with the largest gradients are kept, as they are.
+        let factors = sample.factors.as_ref().unwrap();
+        assert_eq!(factors.len(), sample.chosen.len());
+        assert!(sample.chosen[..20].iter().all(|i| (grad[*i]).abs() >= 40.));
+        assert!(factors[..20].iter().all(|f| *f == 1.));
+        assert!(factors[20..].iter().all(|f| *f == (0.8 / 0.3) as f32));
+
+        let (g, h) = sample.reweight(&grad, &hess).unwrap();
+        for (i, f) in sample.chosen.iter().zip(factors) {
+            assert_eq!(g[*i], grad[*i] * f);
+            assert_eq!(h[*i], *f);
+        }
+        for i in sample.excluded.iter() {
+            assert_eq!(h[*i], 1.);
+        }
+
+        let sample = RandomSampler::new(0.5).sample(&mut rng, &index, &grad, &hess);
+        assert!(sample.reweight(&grad, &hess).is_none());
     }
 }
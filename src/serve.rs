@@ -0,0 +1,384 @@
+use crate::data::RowMajorView;
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// A record to score, either the value of each feature, in the order the booster was
+/// trained on, or the value of each feature by name, if the booster has feature names.
+/// Null, and absent values are missing.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Record {
+    Values(Vec<Option<f64>>),
+    Named(HashMap<String, Option<f64>>),
+}
+
+/// The body of a request to the `/predict` endpoint.
+#[derive(Deserialize)]
+struct PredictRequest {
+    records: Vec<Record>,
+}
+
+#[derive(Serialize)]
+struct PredictResponse {
+    predictions: Vec<f64>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// A minimal HTTP server, that scores records with a persisted booster, so a model
+/// can be deployed without writing a wrapper service.
+///
+/// * `POST /predict` - Score a json body of `{"records": [[1.0, null, 3.0], ...]}`,
+///   or `{"records": [{"age": 22.0, "fare": 7.25}, ...]}`, responding with
+///   `{"predictions": [...]}`.
+/// * `GET /health` - Respond with `ok`, once the model is loaded.
+///
+/// Requests that are waiting when a batch is scored, are scored together, up to
+/// `max_batch_records` records, so many small concurrent requests share a single
+/// parallel prediction. A request that can't be scored is answered with an error,
+/// and the server keeps serving.
+pub struct PredictionServer {
+    booster: GradientBooster,
+    server: Server,
+    /// The number of values in each record.
+    n_features: usize,
+    /// The maximum number of records scored together in a batch.
+    pub max_batch_records: usize,
+    /// The maximum size of the body of a request, in bytes, larger requests are
+    /// answered with 413.
+    pub max_body_bytes: usize,
+}
+
+impl PredictionServer {
+    /// Start a server listening on an address, such as `0.0.0.0:8080`.
+    ///
+    /// * `booster` - The booster used to score records.
+    /// * `addr` - The address to listen on, port 0 picks any free port.
+    pub fn new(booster: GradientBooster, addr: &str) -> Result<Self, ForustError> {
+        let server = Server::http(addr).map_err(|e| ForustError::UnableToRead(e.to_string()))?;
+        let n_features = booster.n_features();
+        Ok(PredictionServer {
+            booster,
+            server,
+            n_features,
+            max_batch_records: 10_000,
+            max_body_bytes: 16 * 1024 * 1024,
+        })
+    }
+
+    /// Set the maximum number of records scored together in a batch.
+    /// * `max_batch_records` - The maximum number of records in a batch.
+    pub fn set_max_batch_records(mut self, max_batch_records: usize) -> Self {
+        self.max_batch_records = max_batch_records;
+        self
+    }
+
+    /// Set the maximum size of the body of a request.
+    /// * `max_body_bytes` - The maximum number of bytes in the body of a request.
+    pub fn set_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /// The address the server is listening on.
+    pub fn addr(&self) -> String {
+        self.server.server_addr().to_string()
+    }
+
+    /// Serve requests until the process is stopped, or no more requests can be
+    /// received.
+    pub fn serve(&self) -> Result<(), ForustError> {
+        loop {
+            self.serve_batch()?;
+        }
+    }
+
+    /// Wait for a request, and serve it, with any other requests that are waiting.
+    pub fn serve_batch(&self) -> Result<(), ForustError> {
+        let mut request = self.server.recv().map_err(ForustError::Io)?;
+        // Each valid request, with the number of records in it, and their values.
+        let mut batch: Vec<(Request, usize, Vec<f64>)> = Vec::new();
+        let mut n_records = 0;
+        loop {
+            if let Some((request, (n, values))) = self.route(request) {
+                n_records += n;
+                batch.push((request, n, values));
+            }
+            if n_records >= self.max_batch_records {
+                break;
+            }
+            match self.server.try_recv() {
+                Ok(Some(r)) => request = r,
+                _ => break,
+            }
+        }
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let data: Vec<f64> = batch
+            .iter()
+            .flat_map(|(_, _, v)| v.iter().copied())
+            .collect();
+        let view = match RowMajorView::new(&data, n_records, self.n_features) {
+            Ok(view) => view,
+            Err(e) => {
+                for (request, _, _) in batch {
+                    let error = e.to_string();
+                    respond(request, 500, &ErrorResponse { error });
+                }
+                return Ok(());
+            }
+        };
+        let mut predictions = self.booster.predict(&view, true).into_iter();
+        for (request, n, _) in batch {
+            let response = PredictResponse {
+                predictions: predictions.by_ref().take(n).collect(),
+            };
+            respond(request, 200, &response);
+        }
+        Ok(())
+    }
+
+    /// Respond to any request that isn't a valid prediction request, returning the
+    /// values of the records of a valid one, in row major order.
+    fn route(&self, mut request: Request) -> Option<(Request, (usize, Vec<f64>))> {
+        let endpoint = (request.method().clone(), request.url().to_string());
+        match (endpoint.0, endpoint.1.as_str()) {
+            (Method::Post, "/predict") => {
+                let too_large = ErrorResponse {
+                    error: format!("The body is larger than {} bytes.", self.max_body_bytes),
+                };
+                if request
+                    .body_length()
+                    .is_some_and(|n| n > self.max_body_bytes)
+                {
+                    respond(request, 413, &too_large);
+                    return None;
+                }
+                // The length may not be known, so no more than the limit is read.
+                let mut body = String::new();
+                let limit = self.max_body_bytes as u64 + 1;
+                if let Err(e) = request.as_reader().take(limit).read_to_string(&mut body) {
+                    respond(
+                        request,
+                        400,
+                        &ErrorResponse {
+                            error: e.to_string(),
+                        },
+                    );
+                    return None;
+                }
+                if body.len() > self.max_body_bytes {
+                    respond(request, 413, &too_large);
+                    return None;
+                }
+                match self.parse_records(&body) {
+                    Ok(values) => Some((request, values)),
+                    Err(error) => {
+                        respond(request, 400, &ErrorResponse { error });
+                        None
+                    }
+                }
+            }
+            (Method::Get, "/health") => {
+                // The client may have disconnected, there's no one to report the error to.
+                let _ = request.respond(Response::from_string("ok"));
+                None
+            }
+            _ => {
+                let error = format!("No endpoint at {}", endpoint.1);
+                respond(request, 404, &ErrorResponse { error });
+                None
+            }
+        }
+    }
+
+    /// Parse the records of a prediction request, returning the number of records, and
+    /// their values in row major order.
+    fn parse_records(&self, body: &str) -> Result<(usize, Vec<f64>), String> {
+        let request: PredictRequest = serde_json::from_str(body).map_err(|e| e.to_string())?;
+        let missing = self.booster.missing;
+        let n_records = request.records.len();
+        let mut values = Vec::with_capacity(n_records * self.n_features);
+        for (i, record) in request.records.into_iter().enumerate() {
+            match record {
+                Record::Values(v) => {
+                    let valid = match &self.booster.feature_names {
+                        Some(_) => v.len() == self.n_features,
+                        None => v.len() >= self.n_features,
+                    };
+                    if !valid {
+                        return Err(format!(
+                            "Record {} has {} values, but the model has {} features.",
+                            i,
+                            v.len(),
+                            self.n_features
+                        ));
+                    }
+                    values.extend(
+                        v.into_iter()
+                            .take(self.n_features)
+                            .map(|x| x.unwrap_or(missing)),
+                    );
+                }
+                Record::Named(m) => {
+                    let names = self.booster.feature_names.as_ref().ok_or_else(|| {
+                        "Records can only be named, if the model has feature names.".to_string()
+                    })?;
+                    if let Some(key) = m.keys().find(|k| !names.contains(k)) {
+                        return Err(format!(
+                            "Record {} has the value of {}, which isn't a feature of the model.",
+                            i, key
+                        ));
+                    }
+                    values.extend(
+                        names
+                            .iter()
+                            .map(|n| m.get(n).copied().flatten().unwrap_or(missing)),
+                    );
+                }
+            }
+        }
+        Ok((n_records, values))
+    }
+}
+
+fn respond<T: Serialize>(request: Request, status: u16, body: &T) {
+    let body = serde_json::to_string(body).unwrap_or_default();
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    // The client may have disconnected, there's no one to report the error to.
+    let _ = request.respond(
+        Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn send(addr: &str, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap().to_string();
+        (status, body)
+    }
+
+    #[test]
+    fn test_prediction_server() {
+        let file = fs::read_to_string("resources/contiguous_no_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+        let mut booster = GradientBooster::default().set_iterations(10);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let row: Vec<f64> = (0..5).map(|j| *data.get(0, j)).collect();
+        let mut missing_row = row.clone();
+        missing_row[1] = f64::NAN;
+        let expected = vec![booster.predict_row(&row), booster.predict_row(&missing_row)];
+
+        let server = PredictionServer::new(booster, "127.0.0.1:0").unwrap();
+        let addr = server.addr();
+        // Requests that arrive together are served in one batch, so the server runs
+        // until the test ends.
+        std::thread::spawn(move || server.serve());
+
+        let body = format!(
+            r#"{{"records": [{:?}, [{}, null, {}, {}, {}]]}}"#,
+            row, row[0], row[2], row[3], row[4]
+        );
+        let (status, body) = send(&addr, "POST", "/predict", &body);
+        assert_eq!(status, 200);
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let predictions: Vec<f64> =
+            serde_json::from_value(response["predictions"].clone()).unwrap();
+        assert_eq!(predictions, expected);
+
+        let (status, body) = send(&addr, "POST", "/predict", r#"{"records": [[1.0]]}"#);
+        assert_eq!(status, 400);
+        assert!(body.contains("has 1 values"));
+        assert_eq!(send(&addr, "GET", "/health", "").0, 200);
+        assert_eq!(send(&addr, "GET", "/other", "").0, 404);
+    }
+
+    #[test]
+    fn test_prediction_server_invalid_requests() {
+        let file = fs::read_to_string("resources/contiguous_no_missing.csv")
+            .expect("Something went wrong reading the file");
+        let mut data_vec: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        // The last feature is never split on.
+        data_vec.extend(vec![0.; 891]);
+        let data = Matrix::new(&data_vec, 891, 6);
+        let names: Vec<String> = (0..6).map(|i| format!("f{}", i)).collect();
+        let mut named = GradientBooster::default()
+            .set_iterations(10)
+            .set_feature_names(Some(names));
+        named.fit_unweighted(&data, &y, None).unwrap();
+        let mut unnamed = GradientBooster::from_json(&named.json_dump().unwrap()).unwrap();
+        unnamed.feature_names = None;
+        let row: Vec<f64> = (0..6).map(|j| *data.get(0, j)).collect();
+        let expected = named.predict_row(&row);
+
+        let server = PredictionServer::new(unnamed, "127.0.0.1:0")
+            .unwrap()
+            .set_max_body_bytes(200);
+        let addr = server.addr();
+        std::thread::spawn(move || server.serve());
+        let (status, body) = send(
+            &addr,
+            "POST",
+            "/predict",
+            r#"{"records": [[1, 2, 3, 4, 5]]}"#,
+        );
+        assert_eq!(status, 400);
+        assert!(body.contains("has 5 values, but the model has 6 features"));
+        let large = format!(r#"{{"records": [{:?}]}}"#, vec![row.clone(); 10]);
+        assert_eq!(send(&addr, "POST", "/predict", &large).0, 413);
+        // The server keeps serving after invalid requests.
+        let body = format!(r#"{{"records": [{:?}]}}"#, row);
+        let (status, body) = send(&addr, "POST", "/predict", &body);
+        assert_eq!(status, 200);
+        assert!(body.contains(&expected.to_string()));
+
+        let server = PredictionServer::new(named, "127.0.0.1:0").unwrap();
+        let addr = server.addr();
+        std::thread::spawn(move || server.serve());
+        let (status, body) = send(
+            &addr,
+            "POST",
+            "/predict",
+            r#"{"records": [{"f0": 1.0, "age": 22.0}]}"#,
+        );
+        assert_eq!(status, 400);
+        assert!(body.contains("age"));
+    }
+}
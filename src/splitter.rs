@@ -1,9 +1,12 @@
-use crate::constraints::{Constraint, ConstraintMap};
-use crate::data::{JaggedMatrix, Matrix};
+use crate::binning::{BinColumn, BinnedMatrix};
+use crate::constraints::{
+    interaction_allowed, Constraint, ConstraintMap, MonotoneConstraintMethod,
+};
+use crate::data::JaggedMatrix;
 use crate::histogram::HistogramMatrix;
 use crate::node::SplittableNode;
 use crate::utils::{
-    constrained_weight, cull_gain, gain_given_weight, pivot_on_split,
+    constrained_weight, cull_gain, gain_given_weight, monotone_weights, pivot_on_split,
     pivot_on_split_exclude_missing, weight,
 };
 
@@ -37,19 +40,32 @@ pub enum MissingInfo {
 
 pub trait Splitter {
     fn get_constraint(&self, feature: &usize) -> Option<&Constraint>;
+    fn get_monotone_constraint_method(&self) -> MonotoneConstraintMethod;
+    fn get_interaction_constraints(&self) -> Option<&[Vec<usize>]>;
     // fn get_allow_missing_splits(&self) -> bool;
     fn get_gamma(&self) -> f32;
     fn get_l2(&self) -> f32;
     fn get_learning_rate(&self) -> f32;
 
     /// Find the best possible split, considering all feature histograms.
-    /// If we wanted to add Column sampling, this is probably where
-    /// we would need to do it, otherwise, it would be at the tree level.
     fn best_split(&self, node: &SplittableNode) -> Option<SplitInfo> {
+        let HistogramMatrix(histograms) = &node.histograms;
+        let col_index: Vec<usize> = (0..histograms.cols).collect();
+        self.best_split_columns(node, &col_index)
+    }
+
+    /// Find the best possible split, considering only the histograms
+    /// of the sampled columns.
+    fn best_split_columns(&self, node: &SplittableNode, col_index: &[usize]) -> Option<SplitInfo> {
         let mut best_split_info = None;
         let mut best_gain = 0.0;
-        let HistogramMatrix(histograms) = &node.histograms;
-        for i in 0..histograms.cols {
+        let interaction_constraints = self.get_interaction_constraints();
+        for i in col_index.iter().copied() {
+            if let Some(groups) = interaction_constraints {
+                if !interaction_allowed(groups, &node.path_features, i) {
+                    continue;
+                }
+            }
             let split_info = self.best_feature_split(node, i);
             match split_info {
                 Some(info) => {
@@ -89,18 +105,23 @@ pub trait Splitter {
 
         // We also know we will have a missing bin.
         let missing = &histogram[0];
-        let mut cuml_grad = 0.0; // first_bin.gradient_sum;
-        let mut cuml_hess = 0.0; // first_bin.hessian_sum;
+        // Gradients and hessians are stored as f32, but accumulated as f64, the same as
+        // the histograms, and the sums of the root node, so the precision doesn't
+        // degrade with the number of bins.
+        let mut cuml_grad: f64 = 0.0; // first_bin.gradient_sum;
+        let mut cuml_hess: f64 = 0.0; // first_bin.hessian_sum;
         let constraint = self.get_constraint(&feature);
 
         let elements = histogram.len();
         assert!(elements == histogram.len());
 
         for (i, bin) in histogram[1..].iter().enumerate() {
-            let left_gradient = cuml_grad;
-            let left_hessian = cuml_hess;
-            let right_gradient = node.gradient_sum - cuml_grad - missing.gradient_sum;
-            let right_hessian = node.hessian_sum - cuml_hess - missing.hessian_sum;
+            let left_gradient = cuml_grad as f32;
+            let left_hessian = cuml_hess as f32;
+            let right_gradient =
+                (f64::from(node.gradient_sum) - cuml_grad - f64::from(missing.gradient_sum)) as f32;
+            let right_hessian =
+                (f64::from(node.hessian_sum) - cuml_hess - f64::from(missing.hessian_sum)) as f32;
 
             let (mut left_node_info, mut right_node_info, mut missing_info) = match self
                 .evaluate_split(
@@ -115,8 +136,8 @@ pub trait Splitter {
                     constraint,
                 ) {
                 None => {
-                    cuml_grad += bin.gradient_sum;
-                    cuml_hess += bin.hessian_sum;
+                    cuml_grad += f64::from(bin.gradient_sum);
+                    cuml_hess += f64::from(bin.hessian_sum);
                     continue;
                 }
                 Some(v) => v,
@@ -134,18 +155,22 @@ pub trait Splitter {
                 - node.gain_value)
                 - self.get_gamma();
 
-            // Check monotonicity holds
-            let split_gain = cull_gain(
-                split_gain,
-                left_node_info.weight,
-                right_node_info.weight,
-                constraint,
-            );
+            // Check monotonicity holds, the exact method holds it
+            // by recalculating the weights of the children.
+            let split_gain = match self.get_monotone_constraint_method() {
+                MonotoneConstraintMethod::Basic => cull_gain(
+                    split_gain,
+                    left_node_info.weight,
+                    right_node_info.weight,
+                    constraint,
+                ),
+                MonotoneConstraintMethod::Exact => split_gain,
+            };
 
             if split_gain <= 0.0 {
                 // Update for new value
-                cuml_grad += bin.gradient_sum;
-                cuml_hess += bin.hessian_sum;
+                cuml_grad += f64::from(bin.gradient_sum);
+                cuml_hess += f64::from(bin.hessian_sum);
                 continue;
             }
 
@@ -182,8 +207,8 @@ pub trait Splitter {
                 });
             }
             // Update for new value
-            cuml_grad += bin.gradient_sum;
-            cuml_hess += bin.hessian_sum;
+            cuml_grad += f64::from(bin.gradient_sum);
+            cuml_hess += f64::from(bin.hessian_sum);
         }
         split_info
     }
@@ -192,13 +217,13 @@ pub trait Splitter {
     /// will return a vector of new splitable nodes, that can be added to the
     /// growable stack, and further split, or converted to leaf nodes.
     #[allow(clippy::too_many_arguments)]
-    fn handle_split_info(
+    fn handle_split_info<B: BinnedMatrix>(
         &self,
         split_info: SplitInfo,
         n_nodes: &usize,
         node: &mut SplittableNode,
         index: &mut [usize],
-        data: &Matrix<u16>,
+        data: &B,
         cuts: &JaggedMatrix<f64>,
         grad: &[f32],
         hess: &[f32],
@@ -208,12 +233,12 @@ pub trait Splitter {
     /// Split the node, if we cant find a best split, we will need to
     /// return an empty vector, this node is a leaf.
     #[allow(clippy::too_many_arguments)]
-    fn split_node(
+    fn split_node<B: BinnedMatrix>(
         &self,
         n_nodes: &usize,
         node: &mut SplittableNode,
         index: &mut [usize],
-        data: &Matrix<u16>,
+        data: &B,
         cuts: &JaggedMatrix<f64>,
         grad: &[f32],
         hess: &[f32],
@@ -240,6 +265,8 @@ pub struct MissingBranchSplitter {
     pub learning_rate: f32,
     pub allow_missing_splits: bool,
     pub constraints_map: ConstraintMap,
+    pub monotone_constraint_method: MonotoneConstraintMethod,
+    pub interaction_constraints: Option<Vec<Vec<usize>>>,
 }
 
 impl Splitter for MissingBranchSplitter {
@@ -247,6 +274,14 @@ impl Splitter for MissingBranchSplitter {
         self.constraints_map.get(feature)
     }
 
+    fn get_monotone_constraint_method(&self) -> MonotoneConstraintMethod {
+        self.monotone_constraint_method
+    }
+
+    fn get_interaction_constraints(&self) -> Option<&[Vec<usize>]> {
+        self.interaction_constraints.as_deref()
+    }
+
     fn get_gamma(&self) -> f32 {
         self.gamma
     }
@@ -280,22 +315,36 @@ impl Splitter for MissingBranchSplitter {
             return None;
         }
 
-        let left_weight = constrained_weight(
-            &self.l2,
-            left_gradient,
-            left_hessian,
-            lower_bound,
-            upper_bound,
-            constraint,
-        );
-        let right_weight = constrained_weight(
-            &self.l2,
-            right_gradient,
-            right_hessian,
-            lower_bound,
-            upper_bound,
-            constraint,
-        );
+        let (left_weight, right_weight) = match self.monotone_constraint_method {
+            MonotoneConstraintMethod::Basic => (
+                constrained_weight(
+                    &self.l2,
+                    left_gradient,
+                    left_hessian,
+                    lower_bound,
+                    upper_bound,
+                    constraint,
+                ),
+                constrained_weight(
+                    &self.l2,
+                    right_gradient,
+                    right_hessian,
+                    lower_bound,
+                    upper_bound,
+                    constraint,
+                ),
+            ),
+            MonotoneConstraintMethod::Exact => monotone_weights(
+                &self.l2,
+                left_gradient,
+                left_hessian,
+                right_gradient,
+                right_hessian,
+                lower_bound,
+                upper_bound,
+                constraint,
+            ),
+        };
 
         let left_gain = gain_given_weight(&self.l2, left_gradient, left_hessian, left_weight);
         let right_gain = gain_given_weight(&self.l2, right_gradient, right_hessian, right_weight);
@@ -317,6 +366,15 @@ impl Splitter for MissingBranchSplitter {
             missing_gradient + left_gradient + right_gradient,
             missing_hessian + left_hessian + right_hessian,
         ); // weight(&self.get_l2(), missing_gradient, missing_hessian);
+           // With the exact method, the missing branch is bounded the same as its parent,
+           // so the splits of the branch hold the constraints too.
+        let (missing_weight, missing_bounds) = match self.monotone_constraint_method {
+            MonotoneConstraintMethod::Basic => (missing_weight, (f32::NEG_INFINITY, f32::INFINITY)),
+            MonotoneConstraintMethod::Exact => (
+                missing_weight.max(lower_bound).min(upper_bound),
+                (lower_bound, upper_bound),
+            ),
+        };
         let missing_gain = gain_given_weight(
             &self.get_l2(),
             missing_gradient,
@@ -328,7 +386,7 @@ impl Splitter for MissingBranchSplitter {
             gain: missing_gain,
             cover: missing_hessian,
             weight: missing_weight,
-            bounds: (f32::NEG_INFINITY, f32::INFINITY),
+            bounds: missing_bounds,
         };
         let missing_node = // Check Missing direction
         if ((missing_gradient != 0.0) || (missing_hessian != 0.0)) && self.allow_missing_splits {
@@ -364,13 +422,13 @@ impl Splitter for MissingBranchSplitter {
         ))
     }
 
-    fn handle_split_info(
+    fn handle_split_info<B: BinnedMatrix>(
         &self,
         split_info: SplitInfo,
         n_nodes: &usize,
         node: &mut SplittableNode,
         index: &mut [usize],
-        data: &Matrix<u16>,
+        data: &B,
         cuts: &JaggedMatrix<f64>,
         grad: &[f32],
         hess: &[f32],
@@ -398,7 +456,7 @@ impl Splitter for MissingBranchSplitter {
         // Missing all falls to the bottom.
         let (mut missing_split_idx, mut split_idx) = pivot_on_split_exclude_missing(
             &mut index[node.start_idx..node.stop_idx],
-            data.get_col(split_info.split_feature),
+            &BinColumn::new(data, split_info.split_feature),
             split_info.split_bin,
         );
         // Calculate histograms
@@ -574,10 +632,13 @@ pub struct MissingImputerSplitter {
     pub learning_rate: f32,
     pub allow_missing_splits: bool,
     pub constraints_map: ConstraintMap,
+    pub monotone_constraint_method: MonotoneConstraintMethod,
+    pub interaction_constraints: Option<Vec<Vec<usize>>>,
 }
 
 impl MissingImputerSplitter {
     /// Generate a new missing imputer splitter object.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         l2: f32,
         gamma: f32,
@@ -585,6 +646,8 @@ impl MissingImputerSplitter {
         learning_rate: f32,
         allow_missing_splits: bool,
         constraints_map: ConstraintMap,
+        monotone_constraint_method: MonotoneConstraintMethod,
+        interaction_constraints: Option<Vec<Vec<usize>>>,
     ) -> Self {
         MissingImputerSplitter {
             l2,
@@ -593,6 +656,8 @@ impl MissingImputerSplitter {
             learning_rate,
             allow_missing_splits,
             constraints_map,
+            monotone_constraint_method,
+            interaction_constraints,
         }
     }
 }
@@ -602,6 +667,14 @@ impl Splitter for MissingImputerSplitter {
         self.constraints_map.get(feature)
     }
 
+    fn get_monotone_constraint_method(&self) -> MonotoneConstraintMethod {
+        self.monotone_constraint_method
+    }
+
+    fn get_interaction_constraints(&self) -> Option<&[Vec<usize>]> {
+        self.interaction_constraints.as_deref()
+    }
+
     fn get_gamma(&self) -> f32 {
         self.gamma
     }
@@ -646,22 +719,36 @@ impl Splitter for MissingImputerSplitter {
         let mut right_gradient = right_gradient;
         let mut right_hessian = right_hessian;
 
-        let mut left_weight = constrained_weight(
-            &self.l2,
-            left_gradient,
-            left_hessian,
-            lower_bound,
-            upper_bound,
-            constraint,
-        );
-        let mut right_weight = constrained_weight(
-            &self.l2,
-            right_gradient,
-            right_hessian,
-            lower_bound,
-            upper_bound,
-            constraint,
-        );
+        let (mut left_weight, mut right_weight) = match self.monotone_constraint_method {
+            MonotoneConstraintMethod::Basic => (
+                constrained_weight(
+                    &self.l2,
+                    left_gradient,
+                    left_hessian,
+                    lower_bound,
+                    upper_bound,
+                    constraint,
+                ),
+                constrained_weight(
+                    &self.l2,
+                    right_gradient,
+                    right_hessian,
+                    lower_bound,
+                    upper_bound,
+                    constraint,
+                ),
+            ),
+            MonotoneConstraintMethod::Exact => monotone_weights(
+                &self.l2,
+                left_gradient,
+                left_hessian,
+                right_gradient,
+                right_hessian,
+                lower_bound,
+                upper_bound,
+                constraint,
+            ),
+        };
 
         let mut left_gain = gain_given_weight(&self.l2, left_gradient, left_hessian, left_weight);
         let mut right_gain =
@@ -676,10 +763,68 @@ impl Splitter for MissingImputerSplitter {
             }
         }
 
+        let has_missing = (missing_gradient != 0.0) || (missing_hessian != 0.0);
+        let exact = self.monotone_constraint_method == MonotoneConstraintMethod::Exact;
+
         // Check Missing direction
         // Don't even worry about it, if there are no missing values
         // in this bin.
-        if (missing_gradient != 0.0) || (missing_hessian != 0.0) {
+        if has_missing && exact {
+            // The weights of both children depend on the side missing goes,
+            // so the gains of both children are compared.
+            let (missing_left_weight, other_right_weight) = monotone_weights(
+                &self.l2,
+                left_gradient + missing_gradient,
+                left_hessian + missing_hessian,
+                right_gradient,
+                right_hessian,
+                lower_bound,
+                upper_bound,
+                constraint,
+            );
+            let missing_left_gain = gain_given_weight(
+                &self.l2,
+                left_gradient + missing_gradient,
+                left_hessian + missing_hessian,
+                missing_left_weight,
+            );
+            let other_right_gain =
+                gain_given_weight(&self.l2, right_gradient, right_hessian, other_right_weight);
+            let (other_left_weight, missing_right_weight) = monotone_weights(
+                &self.l2,
+                left_gradient,
+                left_hessian,
+                right_gradient + missing_gradient,
+                right_hessian + missing_hessian,
+                lower_bound,
+                upper_bound,
+                constraint,
+            );
+            let other_left_gain =
+                gain_given_weight(&self.l2, left_gradient, left_hessian, other_left_weight);
+            let missing_right_gain = gain_given_weight(
+                &self.l2,
+                right_gradient + missing_gradient,
+                right_hessian + missing_hessian,
+                missing_right_weight,
+            );
+
+            if (other_left_gain + missing_right_gain) < (missing_left_gain + other_right_gain) {
+                // Missing goes left
+                left_gradient += missing_gradient;
+                left_hessian += missing_hessian;
+                (left_gain, right_gain) = (missing_left_gain, other_right_gain);
+                (left_weight, right_weight) = (missing_left_weight, other_right_weight);
+                missing_info = MissingInfo::Left;
+            } else {
+                // Missing goes right
+                right_gradient += missing_gradient;
+                right_hessian += missing_hessian;
+                (left_gain, right_gain) = (other_left_gain, missing_right_gain);
+                (left_weight, right_weight) = (other_left_weight, missing_right_weight);
+                missing_info = MissingInfo::Right;
+            }
+        } else if has_missing {
             // TODO: Consider making this safer, by casting to f64, summing, and then
             // back to f32...
 
@@ -768,13 +913,13 @@ impl Splitter for MissingImputerSplitter {
         ))
     }
 
-    fn handle_split_info(
+    fn handle_split_info<B: BinnedMatrix>(
         &self,
         split_info: SplitInfo,
         n_nodes: &usize,
         node: &mut SplittableNode,
         index: &mut [usize],
-        data: &Matrix<u16>,
+        data: &B,
         cuts: &JaggedMatrix<f64>,
         grad: &[f32],
         hess: &[f32],
@@ -797,7 +942,7 @@ impl Splitter for MissingImputerSplitter {
         // separate missing branch.
         let mut split_idx = pivot_on_split(
             &mut index[node.start_idx..node.stop_idx],
-            data.get_col(split_info.split_feature),
+            &BinColumn::new(data, split_info.split_feature),
             split_info.split_bin,
             missing_right,
         );
@@ -895,6 +1040,8 @@ mod tests {
             learning_rate: 1.0,
             allow_missing_splits: true,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         // println!("{:?}", hists);
         let mut n = SplittableNode::new(
@@ -943,6 +1090,8 @@ mod tests {
             learning_rate: 1.0,
             allow_missing_splits: true,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let mut n = SplittableNode::new(
             0,
@@ -969,6 +1118,57 @@ mod tests {
         assert_eq!(s.split_gain, 3.86);
     }
 
+    #[test]
+    fn test_exact_monotone_split() {
+        // The only split breaks the constraint, but separates the missing values.
+        let d = vec![1., 1., 2., 2., f64::NAN, f64::NAN, f64::NAN, f64::NAN];
+        let data = Matrix::new(&d, 8, 1);
+        let y = vec![1., 1., 0., 0., 1., 1., 1., 1.];
+        let yhat = vec![0.; 8];
+        let w = vec![1.; y.len()];
+        let grad = LogLoss::calc_grad(&y, &yhat, &w);
+        let hess = LogLoss::calc_hess(&y, &yhat, &w);
+        let b = bin_matrix(&data, &w, 10, f64::NAN).unwrap();
+        let bdata = Matrix::new(&b.binned_data, data.rows, data.cols);
+        let index = data.index.to_owned();
+        let gradient_sum = grad.iter().sum::<f32>();
+        let hessian_sum = hess.iter().sum::<f32>();
+        let split = |method| {
+            let splitter = MissingBranchSplitter {
+                l2: 0.0,
+                gamma: 0.0,
+                min_leaf_weight: 0.0,
+                learning_rate: 1.0,
+                allow_missing_splits: true,
+                constraints_map: ConstraintMap::from([(0, Constraint::Positive)]),
+                monotone_constraint_method: method,
+                interaction_constraints: None,
+            };
+            let hists = HistogramMatrix::new(&bdata, &b.cuts, &grad, &hess, &index, true, true);
+            let mut n = SplittableNode::new(
+                0,
+                hists,
+                weight(&0.0, gradient_sum, hessian_sum),
+                gain(&0.0, gradient_sum, hessian_sum),
+                gradient_sum,
+                hessian_sum,
+                0,
+                0,
+                grad.len(),
+                f32::NEG_INFINITY,
+                f32::INFINITY,
+            );
+            splitter.best_feature_split(&mut n, 0)
+        };
+        // The basic method discards the split.
+        assert!(split(MonotoneConstraintMethod::Basic).is_none());
+        // The exact method scores it with the children at their midpoint.
+        let s = split(MonotoneConstraintMethod::Exact).unwrap();
+        assert_eq!(s.left_node.weight, 0.0);
+        assert_eq!(s.right_node.weight, 0.0);
+        assert_eq!(s.split_gain, 1.0);
+    }
+
     #[test]
     fn test_data_split() {
         let file = fs::read_to_string("resources/contiguous_no_missing.csv")
@@ -989,6 +1189,8 @@ mod tests {
             learning_rate: 0.3,
             allow_missing_splits: true,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let gradient_sum = grad.iter().copied().sum();
         let hessian_sum = hess.iter().copied().sum();
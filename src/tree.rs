@@ -1,21 +1,33 @@
-use crate::data::{JaggedMatrix, Matrix};
+use crate::binning::BinnedMatrix;
+use crate::constraints::{Constraint, MonotoneConstraintMethod};
+use crate::data::{DataMatrix, FloatData, JaggedMatrix, Matrix};
+use crate::errors::ForustError;
+use crate::explain::{Decision, DecisionPath, Direction};
 use crate::gradientbooster::GrowPolicy;
-use crate::grower::Grower;
+use crate::grower::{Grower, SpillingGrower};
 use crate::histogram::HistogramMatrix;
 use crate::node::{Node, SplittableNode};
-use crate::partial_dependence::tree_partial_dependence;
+use crate::partial_dependence::{tree_joint_partial_dependence, tree_partial_dependence};
+use crate::profiler::{Phase, Profiler};
 use crate::sampler::SampleMethod;
 use crate::splitter::Splitter;
-use crate::utils::fast_f64_sum;
+use crate::utils::{fast_f64_sum, is_missing};
 use crate::utils::{gain, weight};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::{BinaryHeap, VecDeque};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::fmt::{self, Display};
 
-#[derive(Deserialize, Serialize)]
+/// The values of a feature that reach a node, when no split on it is above the node.
+const FULL_RANGE: (f64, f64, bool) = (f64::NEG_INFINITY, f64::INFINITY, true);
+
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Tree {
     pub nodes: Vec<Node>,
+    /// The learning rate the leaf weights of the tree were scaled by when it was fit,
+    /// or None if it isn't known, such as for imported trees.
+    #[serde(default)]
+    pub learning_rate: Option<f32>,
 }
 
 impl Default for Tree {
@@ -26,14 +38,58 @@ impl Default for Tree {
 
 impl Tree {
     pub fn new() -> Self {
-        Tree { nodes: Vec::new() }
+        Tree {
+            nodes: Vec::new(),
+            learning_rate: None,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn fit<T: Splitter + Sync, B: BinnedMatrix>(
+        &mut self,
+        data: &B,
+        index: Vec<usize>,
+        cuts: &JaggedMatrix<f64>,
+        grad: &[f32],
+        hess: &[f32],
+        splitter: &T,
+        max_leaves: usize,
+        max_depth: usize,
+        parallel: bool,
+        sample_method: &SampleMethod,
+        grow_policy: &GrowPolicy,
+    ) -> Result<(), ForustError> {
+        let col_index: Vec<usize> = (0..data.cols()).collect();
+        self.fit_profiled(
+            data,
+            index,
+            &col_index,
+            cuts,
+            grad,
+            hess,
+            splitter,
+            max_leaves,
+            max_depth,
+            parallel,
+            sample_method,
+            grow_policy,
+            None,
+            &mut Profiler::default(),
+        )
     }
 
+    /// Fit the tree, recording the time spent building histograms,
+    /// and finding splits with the profiler. Only the columns in
+    /// `col_index` are considered when splitting. If `max_histogram_memory`
+    /// is set, the histograms of nodes waiting to be split past this many
+    /// bytes are spilled to disk, and an error is returned if they can't
+    /// be read back.
     #[allow(clippy::too_many_arguments)]
-    pub fn fit<T: Splitter>(
+    pub fn fit_profiled<T: Splitter + Sync, B: BinnedMatrix>(
         &mut self,
-        data: &Matrix<u16>,
+        data: &B,
         mut index: Vec<usize>,
+        col_index: &[usize],
         cuts: &JaggedMatrix<f64>,
         grad: &[f32],
         hess: &[f32],
@@ -43,7 +99,9 @@ impl Tree {
         parallel: bool,
         sample_method: &SampleMethod,
         grow_policy: &GrowPolicy,
-    ) {
+        max_histogram_memory: Option<usize>,
+        profiler: &mut Profiler,
+    ) -> Result<(), ForustError> {
         // Recreating the index for each tree, ensures that the tree construction is faster
         // for the root node. This also ensures that sorting the records is always fast,
         // because we are starting from a nearly sorted array.
@@ -68,7 +126,9 @@ impl Tree {
         let root_gain = gain(&splitter.get_l2(), gradient_sum, hessian_sum);
         let root_weight = weight(&splitter.get_l2(), gradient_sum, hessian_sum);
         // Calculate the histograms for the root node.
-        let root_hists = HistogramMatrix::new(data, cuts, grad, hess, &index, parallel, sort);
+        let root_hists = profiler.time(Phase::Histogram, || {
+            HistogramMatrix::new(data, cuts, grad, hess, &index, parallel, sort)
+        });
         let root_node = SplittableNode::new(
             0,
             root_hists,
@@ -84,14 +144,35 @@ impl Tree {
         );
         // Add the first node to the tree nodes.
         self.nodes.push(root_node.as_node());
+
+        // Without a limit on the number of leaves, growing depth wise splits every
+        // node of a level, so the nodes of a level can be split at the same time.
+        let exact = splitter.get_monotone_constraint_method() == MonotoneConstraintMethod::Exact;
+        if matches!(grow_policy, GrowPolicy::DepthWise)
+            && parallel
+            && max_leaves == usize::MAX
+            && max_histogram_memory.is_none()
+            && !exact
+        {
+            self.grow_levels(
+                root_node, &mut index, col_index, data, cuts, grad, hess, splitter, max_depth,
+                profiler,
+            );
+            return Ok(());
+        }
         let mut n_leaves = 1;
 
         let mut growable: Box<dyn Grower> = match grow_policy {
             GrowPolicy::DepthWise => Box::<VecDeque<SplittableNode>>::default(),
             GrowPolicy::LossGuide => Box::<BinaryHeap<SplittableNode>>::default(),
         };
+        if let Some(max_memory) = max_histogram_memory {
+            growable = Box::new(SpillingGrower::new(growable, max_memory));
+        }
 
         growable.add_node(root_node);
+        // The parent of each node, the root is its own parent.
+        let mut parents = vec![0];
         while !growable.is_empty() {
             if n_leaves >= max_leaves {
                 break;
@@ -102,7 +183,7 @@ impl Tree {
             // Grab a splitable node from the stack
             // If we can split it, and update the corresponding
             // tree nodes children.
-            let mut node = growable.get_next_node();
+            let mut node = growable.get_next_node()?;
             let n_idx = node.num;
             // This will only be splittable nodes
 
@@ -123,18 +204,41 @@ impl Tree {
             // more, then just add 1 back to n_leaves
             n_leaves -= 1;
 
-            let new_nodes = splitter.split_node(
-                &n_nodes, &mut node, &mut index, data, cuts, grad, hess, parallel,
-            );
+            // The bounds are on the scale of the leaf weights, after the learning rate.
+            let bounds = exact.then(|| self.monotone_bounds(splitter, &parents, n_idx));
+            if let Some((lower, upper)) = bounds {
+                node.lower_bound = lower / splitter.get_learning_rate();
+                node.upper_bound = upper / splitter.get_learning_rate();
+            }
+
+            // This is the same as split_node, with each step timed separately.
+            let new_nodes = match profiler.time(Phase::SplitFinding, || {
+                splitter.best_split_columns(&node, col_index)
+            }) {
+                Some(split_info) => profiler.time(Phase::Histogram, || {
+                    splitter.handle_split_info(
+                        split_info, &n_nodes, &mut node, &mut index, data, cuts, grad, hess,
+                        parallel,
+                    )
+                }),
+                None => Vec::new(),
+            };
 
             let n_new_nodes = new_nodes.len();
             if n_new_nodes == 0 {
                 n_leaves += 1;
             } else {
+                let path_features = node.child_path_features();
                 self.nodes[n_idx].make_parent_node(node);
                 n_leaves += n_new_nodes;
                 n_nodes += n_new_nodes;
-                for n in new_nodes {
+                for mut n in new_nodes {
+                    n.path_features = path_features.clone();
+                    // Applying the learning rate can round a weight past a bound.
+                    if let Some((lower, upper)) = bounds {
+                        n.weight_value = n.weight_value.max(lower).min(upper);
+                    }
+                    parents.push(n_idx);
                     self.nodes.push(n.as_node());
                     if !n.is_missing_leaf {
                         growable.add_node(n)
@@ -142,6 +246,209 @@ impl Tree {
                 }
             }
         }
+        Ok(())
+    }
+
+    /// Grow the tree depth wise, a level at a time. The splits of the nodes of a level
+    /// are found, and the nodes split, in parallel, on the work stealing thread pool,
+    /// so all threads stay busy, even when the nodes hold very different numbers of
+    /// records. The new nodes are numbered in the same order as when the nodes are
+    /// split one at a time, so the tree is the same.
+    #[allow(clippy::too_many_arguments)]
+    fn grow_levels<T: Splitter + Sync, B: BinnedMatrix>(
+        &mut self,
+        root_node: SplittableNode,
+        index: &mut [usize],
+        col_index: &[usize],
+        data: &B,
+        cuts: &JaggedMatrix<f64>,
+        grad: &[f32],
+        hess: &[f32],
+        splitter: &T,
+        max_depth: usize,
+        profiler: &mut Profiler,
+    ) {
+        let mut n_nodes = 1;
+        let mut level = vec![root_node];
+        // All nodes of a level have the same depth.
+        while !level.is_empty() && level[0].depth < max_depth {
+            let split_infos: Vec<_> = profiler.time(Phase::SplitFinding, || {
+                level
+                    .par_iter()
+                    .map(|node| splitter.best_split_columns(node, col_index))
+                    .collect()
+            });
+
+            // The records of the nodes of a level are disjoint parts of the index,
+            // so each node is split on its own part, indexed from zero.
+            let mut order: Vec<usize> = (0..level.len()).collect();
+            order.sort_unstable_by_key(|i| level[*i].start_idx);
+            let mut parts: Vec<&mut [usize]> = Vec::with_capacity(level.len());
+            let mut rest = &mut index[..];
+            let mut offset = 0;
+            for i in order.iter() {
+                let node = &level[*i];
+                let (_, tail) = rest.split_at_mut(node.start_idx - offset);
+                let (part, tail) = tail.split_at_mut(node.stop_idx - node.start_idx);
+                parts.push(part);
+                rest = tail;
+                offset = node.stop_idx;
+            }
+            let mut parts: Vec<(usize, &mut [usize])> = order.into_iter().zip(parts).collect();
+            parts.sort_unstable_by_key(|(i, _)| *i);
+
+            let new_nodes: Vec<Vec<SplittableNode>> = profiler.time(Phase::Histogram, || {
+                level
+                    .par_iter_mut()
+                    .zip(split_infos)
+                    .zip(parts)
+                    .map(|((node, split_info), (_, part))| match split_info {
+                        Some(split_info) => {
+                            let start = node.start_idx;
+                            node.start_idx = 0;
+                            node.stop_idx -= start;
+                            let mut new_nodes = splitter.handle_split_info(
+                                split_info, &0, node, part, data, cuts, grad, hess, true,
+                            );
+                            node.start_idx += start;
+                            node.stop_idx += start;
+                            for n in new_nodes.iter_mut() {
+                                n.start_idx += start;
+                                n.stop_idx += start;
+                            }
+                            new_nodes
+                        }
+                        None => Vec::new(),
+                    })
+                    .collect()
+            });
+
+            // The new nodes were numbered from zero, so are renumbered in order.
+            let mut next_level = Vec::new();
+            for (mut node, new_nodes) in level.into_iter().zip(new_nodes) {
+                if new_nodes.is_empty() {
+                    continue;
+                }
+                let first = n_nodes;
+                node.left_child += first;
+                node.right_child += first;
+                node.missing_node += first;
+                let path_features = node.child_path_features();
+                let n_idx = node.num;
+                self.nodes[n_idx].make_parent_node(node);
+                n_nodes += new_nodes.len();
+                for mut n in new_nodes {
+                    n.num += first;
+                    n.path_features = path_features.clone();
+                    self.nodes.push(n.as_node());
+                    if !n.is_missing_leaf {
+                        next_level.push(n);
+                    }
+                }
+            }
+            level = next_level;
+        }
+    }
+
+    /// The bounds of the weights of the children of a node, for the tree to hold its
+    /// monotone constraints. For each split on a constrained feature above the node,
+    /// the weights are bounded by the leaves on the other side of the split, that
+    /// records only differing from the records of the node in this feature can reach.
+    fn monotone_bounds<T: Splitter>(
+        &self,
+        splitter: &T,
+        parents: &[usize],
+        idx: usize,
+    ) -> (f32, f32) {
+        let mut lower = f32::NEG_INFINITY;
+        let mut upper = f32::INFINITY;
+        let region = self.region(parents, idx);
+        let mut child = idx;
+        while child != 0 {
+            let parent = &self.nodes[parents[child]];
+            // Missing values aren't ordered, so the missing branch isn't bounded.
+            let (other, is_left) = if child == parent.left_child {
+                (parent.right_child, true)
+            } else if child == parent.right_child {
+                (parent.left_child, false)
+            } else {
+                child = parent.num;
+                continue;
+            };
+            let increasing = match splitter.get_constraint(&parent.split_feature) {
+                Some(Constraint::Positive) => true,
+                Some(Constraint::Negative) => false,
+                None | Some(Constraint::Unconstrained) => {
+                    child = parent.num;
+                    continue;
+                }
+            };
+            for leaf in self.leaves(other) {
+                let leaf_region = self.region(parents, leaf);
+                let overlaps = region
+                    .keys()
+                    .chain(leaf_region.keys())
+                    .filter(|f| **f != parent.split_feature)
+                    .all(|f| {
+                        let (lo, hi, missing) = region.get(f).unwrap_or(&FULL_RANGE);
+                        let (leaf_lo, leaf_hi, leaf_missing) =
+                            leaf_region.get(f).unwrap_or(&FULL_RANGE);
+                        lo.max(*leaf_lo) < hi.min(*leaf_hi) || (*missing && *leaf_missing)
+                    });
+                if !overlaps {
+                    continue;
+                }
+                let w = self.nodes[leaf].weight_value;
+                if is_left == increasing {
+                    upper = upper.min(w);
+                } else {
+                    lower = lower.max(w);
+                }
+            }
+            child = parent.num;
+        }
+        (lower, upper)
+    }
+
+    /// The values of each feature split on above a node, that reach the node, as the
+    /// range of values, and if missing values do.
+    fn region(&self, parents: &[usize], idx: usize) -> HashMap<usize, (f64, f64, bool)> {
+        let mut region = HashMap::new();
+        let mut child = idx;
+        while child != 0 {
+            let parent = &self.nodes[parents[child]];
+            let (lo, hi, missing) = region.entry(parent.split_feature).or_insert(FULL_RANGE);
+            if child == parent.left_child {
+                *hi = hi.min(parent.split_value);
+                *missing &= parent.missing_node == child;
+            } else if child == parent.right_child {
+                *lo = lo.max(parent.split_value);
+                *missing &= parent.missing_node == child;
+            } else {
+                (*lo, *hi) = (f64::INFINITY, f64::NEG_INFINITY);
+            }
+            child = parent.num;
+        }
+        region
+    }
+
+    /// The leaves below a node.
+    fn leaves(&self, idx: usize) -> Vec<usize> {
+        let node = &self.nodes[idx];
+        if node.is_leaf {
+            return vec![idx];
+        }
+        let mut leaves = self.leaves(node.left_child);
+        leaves.extend(self.leaves(node.right_child));
+        if node.has_missing_branch() {
+            leaves.extend(self.leaves(node.missing_node));
+        }
+        leaves
+    }
+
+    /// Multiply the weight of every node by a factor.
+    pub fn scale_leaves(&mut self, factor: f32) {
+        self.nodes.iter_mut().for_each(|n| n.weight_value *= factor);
     }
 
     // Branch average difference predictions
@@ -484,45 +791,121 @@ impl Tree {
         }
     }
 
-    fn predict_row(&self, data: &Matrix<f64>, row: usize, missing: &f64) -> f64 {
+    fn predict_row<D: DataMatrix>(&self, data: &D, row: usize, missing: &f64) -> f64 {
         let mut node_idx = 0;
         loop {
             let node = &self.nodes[node_idx];
             if node.is_leaf {
                 return node.weight_value as f64;
             } else {
-                node_idx = node.get_child_idx(data.get(row, node.split_feature), missing);
+                let v = data.value(row, node.split_feature);
+                node_idx = node.get_child_idx(&v, missing);
+            }
+        }
+    }
+
+    /// Add the prediction of each row of a block of the data to `preds`.
+    ///
+    /// * `data` - The data.
+    /// * `start` - The first row of the block, `preds` holds a value for each row of the block.
+    /// * `preds` - The predictions of the block, the prediction of the tree is added to each.
+    /// * `missing` - Float value to consider as missing.
+    pub fn add_block_predictions<D: DataMatrix>(
+        &self,
+        data: &D,
+        start: usize,
+        preds: &mut [f64],
+        missing: &f64,
+    ) {
+        for (i, p) in preds.iter_mut().enumerate() {
+            *p += self.predict_row(data, start + i, missing);
+        }
+    }
+
+    /// The index of the leaf node a row of the data is routed to.
+    pub fn predict_leaf<D: DataMatrix>(&self, data: &D, row: usize, missing: &f64) -> usize {
+        let mut node_idx = 0;
+        loop {
+            let node = &self.nodes[node_idx];
+            if node.is_leaf {
+                return node_idx;
             }
+            let v = data.value(row, node.split_feature);
+            node_idx = node.get_child_idx(&v, missing);
         }
     }
 
-    pub fn predict_row_from_row_slice(&self, row: &[f64], missing: &f64) -> f64 {
+    pub fn predict_row_from_row_slice<T: FloatData<T>>(&self, row: &[T], missing: &f64) -> f64 {
+        self.predict_row_with(|feature| row[feature].to_f64(), missing)
+    }
+
+    /// Predict a single record, reading the value of each feature the record is split
+    /// on with `value`, so the record doesn't have to be stored in a matrix.
+    ///
+    /// * `value` - Get the value of a feature of the record.
+    /// * `missing` - Float value to consider as missing.
+    pub fn predict_row_with<F: Fn(usize) -> f64>(&self, value: F, missing: &f64) -> f64 {
         let mut node_idx = 0;
         loop {
             let node = &self.nodes[node_idx];
             if node.is_leaf {
                 return node.weight_value as f64;
             } else {
-                node_idx = node.get_child_idx(&row[node.split_feature], missing);
+                node_idx = node.get_child_idx(&value(node.split_feature), missing);
             }
         }
     }
 
-    fn predict_single_threaded(&self, data: &Matrix<f64>, missing: &f64) -> Vec<f64> {
-        data.index
-            .iter()
-            .map(|i| self.predict_row(data, *i, missing))
+    /// The decisions a single record follows from the root of the tree to a leaf,
+    /// reading the value of each feature the record is split on with `value`.
+    ///
+    /// * `value` - Get the value of a feature of the record.
+    /// * `missing` - Float value to consider as missing.
+    pub fn decision_path<F: Fn(usize) -> f64>(&self, value: F, missing: &f64) -> DecisionPath {
+        let mut decisions = Vec::new();
+        let mut node_idx = 0;
+        loop {
+            let node = &self.nodes[node_idx];
+            if node.is_leaf {
+                return DecisionPath {
+                    decisions,
+                    leaf_value: node.weight_value as f64,
+                };
+            }
+            let v = value(node.split_feature);
+            let direction = if is_missing(&v, missing) {
+                Direction::Missing
+            } else if v < node.split_value {
+                Direction::Less
+            } else {
+                Direction::GreaterOrEqual
+            };
+            decisions.push(Decision {
+                feature: node.split_feature,
+                threshold: node.split_value,
+                value: v,
+                direction,
+            });
+            node_idx = node.get_child_idx(&v, missing);
+        }
+    }
+
+    fn predict_single_threaded<D: DataMatrix>(&self, data: &D, missing: &f64) -> Vec<f64> {
+        (0..data.rows())
+            .map(|i| self.predict_row(data, i, missing))
             .collect()
     }
 
-    fn predict_parallel(&self, data: &Matrix<f64>, missing: &f64) -> Vec<f64> {
-        data.index
-            .par_iter()
-            .map(|i| self.predict_row(data, *i, missing))
+    fn predict_parallel<D: DataMatrix>(&self, data: &D, missing: &f64) -> Vec<f64> {
+        (0..data.rows())
+            .into_par_iter()
+            .map(|i| self.predict_row(data, i, missing))
             .collect()
     }
 
-    pub fn predict(&self, data: &Matrix<f64>, parallel: bool, missing: &f64) -> Vec<f64> {
+    /// Predict with the tree, on dense, or sparse data of any float type. Values
+    /// are compared to the split values as f64.
+    pub fn predict<D: DataMatrix>(&self, data: &D, parallel: bool, missing: &f64) -> Vec<f64> {
         if parallel {
             self.predict_parallel(data, missing)
         } else {
@@ -533,6 +916,12 @@ impl Tree {
     pub fn value_partial_dependence(&self, feature: usize, value: f64, missing: &f64) -> f64 {
         tree_partial_dependence(self, 0, feature, value, 1.0, missing)
     }
+
+    /// Partial dependence of the tree, with multiple features
+    /// fixed at the provided values.
+    pub fn values_partial_dependence(&self, values: &[(usize, f64)], missing: &f64) -> f64 {
+        tree_joint_partial_dependence(self, 0, values, 1.0, missing)
+    }
     fn distribute_node_leaf_weights(&self, i: usize, weights: &mut [f64]) -> f64 {
         let node = &self.nodes[i];
         let mut w = node.weight_value as f64;
@@ -559,11 +948,12 @@ impl Tree {
         self.distribute_node_leaf_weights(0, &mut weights);
         weights
     }
-}
 
-impl Display for Tree {
-    // This trait requires `fmt` with this exact signature.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    /// Dump the tree as text, one node per line, indented by depth. Split features
+    /// are written with their name, if `feature_names` are provided.
+    ///
+    /// * `feature_names` - Names of the features, indexed by the feature index.
+    pub fn text_dump(&self, feature_names: Option<&[String]>) -> String {
         let mut print_buffer: Vec<usize> = vec![0];
         let mut r = String::new();
         while !print_buffer.is_empty() {
@@ -571,10 +961,14 @@ impl Display for Tree {
             // that the buffer is not empty.
             let idx = print_buffer.pop().unwrap();
             let node = &self.nodes[idx];
-            if node.is_leaf {
-                r += format!("{}{}\n", "      ".repeat(node.depth).as_str(), node).as_str();
-            } else {
-                r += format!("{}{}\n", "      ".repeat(node.depth).as_str(), node).as_str();
+            let name = feature_names
+                .and_then(|names| names.get(node.split_feature))
+                .map(|n| n.as_str());
+            r += "      ".repeat(node.depth).as_str();
+            // Writing to a string can't fail.
+            let _ = node.write_text(&mut r, name);
+            r += "\n";
+            if !node.is_leaf {
                 print_buffer.push(node.right_child);
                 print_buffer.push(node.left_child);
                 if node.has_missing_branch() {
@@ -582,7 +976,14 @@ impl Display for Tree {
                 }
             }
         }
-        write!(f, "{}", r)
+        r
+    }
+}
+
+impl Display for Tree {
+    // This trait requires `fmt` with this exact signature.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text_dump(None))
     }
 }
 
@@ -590,10 +991,10 @@ impl Display for Tree {
 mod tests {
     use super::*;
     use crate::binning::bin_matrix;
-    use crate::constraints::{Constraint, ConstraintMap};
+    use crate::constraints::{Constraint, ConstraintMap, MonotoneConstraintMethod};
     use crate::objective::{LogLoss, ObjectiveFunction};
     use crate::sampler::{RandomSampler, Sampler};
-    use crate::splitter::MissingImputerSplitter;
+    use crate::splitter::{MissingBranchSplitter, MissingImputerSplitter};
     use crate::utils::precision_round;
     use rand::rngs::StdRng;
     use rand::SeedableRng;
@@ -608,8 +1009,8 @@ mod tests {
         let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
         let yhat = vec![0.5; y.len()];
         let w = vec![1.; y.len()];
-        let mut g = LogLoss::calc_grad(&y, &yhat, &w);
-        let mut h = LogLoss::calc_hess(&y, &yhat, &w);
+        let g = LogLoss::calc_grad(&y, &yhat, &w);
+        let h = LogLoss::calc_hess(&y, &yhat, &w);
 
         let data = Matrix::new(&data_vec, 891, 5);
         let splitter = MissingImputerSplitter {
@@ -619,15 +1020,17 @@ mod tests {
             learning_rate: 0.3,
             allow_missing_splits: true,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let mut tree = Tree::new();
 
         let b = bin_matrix(&data, &w, 300, f64::NAN).unwrap();
         let bdata = Matrix::new(&b.binned_data, data.rows, data.cols);
         let mut rng = StdRng::seed_from_u64(0);
-        let (index, excluded) =
-            RandomSampler::new(0.5).sample(&mut rng, &data.index, &mut g, &mut h);
-        assert!(excluded.len() > 0);
+        let sample = RandomSampler::new(0.5).sample(&mut rng, &data.index, &g, &h);
+        assert!(!sample.excluded.is_empty());
+        let index = sample.chosen;
         tree.fit(
             &bdata,
             index,
@@ -640,7 +1043,97 @@ mod tests {
             true,
             &SampleMethod::Random,
             &GrowPolicy::DepthWise,
-        );
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_tree_fit_levels() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let yhat = vec![0.5; y.len()];
+        let w = vec![1.; y.len()];
+        let g = LogLoss::calc_grad(&y, &yhat, &w);
+        let h = LogLoss::calc_hess(&y, &yhat, &w);
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let b = bin_matrix(&data, &w, 300, f64::NAN).unwrap();
+        let bdata = Matrix::new(&b.binned_data, data.rows, data.cols);
+        let mut rng = StdRng::seed_from_u64(0);
+        let index = RandomSampler::new(0.7)
+            .sample(&mut rng, &data.index, &g, &h)
+            .chosen;
+
+        // Splitting the nodes of each level in parallel, grows the same tree as
+        // splitting the nodes one at a time.
+        let fit = |splitter: &dyn Fn(&mut Tree, bool), parallel: bool| {
+            let mut tree = Tree::new();
+            splitter(&mut tree, parallel);
+            serde_json::to_string(&tree).unwrap()
+        };
+        let imputer = MissingImputerSplitter {
+            l2: 1.0,
+            gamma: 0.0,
+            min_leaf_weight: 1.0,
+            learning_rate: 0.3,
+            allow_missing_splits: true,
+            constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
+        };
+        let fit_imputer = |tree: &mut Tree, parallel: bool| {
+            tree.fit(
+                &bdata,
+                index.clone(),
+                &b.cuts,
+                &g,
+                &h,
+                &imputer,
+                usize::MAX,
+                6,
+                parallel,
+                &SampleMethod::Random,
+                &GrowPolicy::DepthWise,
+            )
+            .unwrap()
+        };
+        assert_eq!(fit(&fit_imputer, true), fit(&fit_imputer, false));
+        let branch = MissingBranchSplitter {
+            l2: 1.0,
+            gamma: 0.0,
+            min_leaf_weight: 1.0,
+            learning_rate: 0.3,
+            allow_missing_splits: true,
+            constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
+        };
+        let fit_branch = |tree: &mut Tree, parallel: bool| {
+            tree.fit(
+                &bdata,
+                index.clone(),
+                &b.cuts,
+                &g,
+                &h,
+                &branch,
+                usize::MAX,
+                3,
+                parallel,
+                &SampleMethod::Random,
+                &GrowPolicy::DepthWise,
+            )
+            .unwrap()
+        };
+        let tree = fit(&fit_branch, true);
+        assert_eq!(tree, fit(&fit_branch, false));
+        assert!(tree.matches("\"num\"").count() > 8);
     }
 
     #[test]
@@ -664,6 +1157,8 @@ mod tests {
             learning_rate: 0.3,
             allow_missing_splits: true,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let mut tree = Tree::new();
 
@@ -681,7 +1176,8 @@ mod tests {
             true,
             &SampleMethod::None,
             &GrowPolicy::DepthWise,
-        );
+        )
+        .unwrap();
 
         // println!("{}", tree);
         // let preds = tree.predict(&data, false);
@@ -749,6 +1245,8 @@ mod tests {
             learning_rate: 0.3,
             allow_missing_splits: true,
             constraints_map: map,
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let mut tree = Tree::new();
 
@@ -767,7 +1265,8 @@ mod tests {
             true,
             &SampleMethod::None,
             &GrowPolicy::DepthWise,
-        );
+        )
+        .unwrap();
 
         // println!("{}", tree);
         let mut pred_data_vec = data.get_col(0).to_owned();
@@ -831,6 +1330,8 @@ mod tests {
             learning_rate: 0.3,
             allow_missing_splits: false,
             constraints_map: ConstraintMap::new(),
+            monotone_constraint_method: MonotoneConstraintMethod::Basic,
+            interaction_constraints: None,
         };
         let mut tree = Tree::new();
 
@@ -848,7 +1349,8 @@ mod tests {
             true,
             &SampleMethod::None,
             &GrowPolicy::LossGuide,
-        );
+        )
+        .unwrap();
 
         println!("{}", tree);
         // let preds = tree.predict(&data, false);
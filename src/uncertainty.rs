@@ -0,0 +1,283 @@
+use crate::data::Matrix;
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::objective::ObjectiveType;
+use crate::params::BoosterParams;
+use serde::{Deserialize, Serialize};
+
+/// A prediction interval for a single row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub lower: f64,
+    pub median: f64,
+    pub upper: f64,
+}
+
+/// The lower and upper quantile boosters of a single interval.
+#[derive(Deserialize, Serialize)]
+pub struct IntervalBoosters {
+    /// The interval covers `1 - alpha` of the target distribution.
+    pub alpha: f64,
+    /// Booster predicting the `alpha / 2` quantile.
+    pub lower: GradientBooster,
+    /// Booster predicting the `1 - alpha / 2` quantile.
+    pub upper: GradientBooster,
+}
+
+/// Prediction intervals, from a median booster, and a pair of quantile
+/// boosters for each interval, all trained with the "QuantileLoss" objective.
+#[derive(Deserialize, Serialize)]
+pub struct PredictionIntervals {
+    /// Booster predicting the median of the target.
+    pub median: GradientBooster,
+    /// The boosters of each interval.
+    pub intervals: Vec<IntervalBoosters>,
+}
+
+impl PredictionIntervals {
+    /// Fit a median booster, and the lower and upper quantile boosters of
+    /// an interval for each alpha.
+    ///
+    /// * `params` - Training parameters of the boosters, the objective and quantile are
+    ///   overwritten for each booster.
+    /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
+    /// * `y` - Target data to predict the intervals of.
+    /// * `sample_weight` - Instance weights, if None a weight of 1 is used for every record.
+    /// * `alphas` - For each alpha an interval covering `1 - alpha` of the target
+    ///   distribution is trained, such as 0.1 for a 90% interval.
+    pub fn fit(
+        params: &BoosterParams,
+        data: &Matrix<f64>,
+        y: &[f64],
+        sample_weight: Option<&[f64]>,
+        alphas: &[f64],
+    ) -> Result<Self, ForustError> {
+        for alpha in alphas {
+            if !(*alpha > 0. && *alpha < 1.) {
+                return Err(ForustError::InvalidParameter {
+                    name: "alpha".to_string(),
+                    constraint: "real value between 0 and 1, exclusive".to_string(),
+                    value: alpha.to_string(),
+                });
+            }
+        }
+        let fit_quantile = |quantile: f64| -> Result<GradientBooster, ForustError> {
+            let mut booster = GradientBooster::from_params(BoosterParams {
+                objective_type: ObjectiveType::QuantileLoss,
+                quantile,
+                ..params.clone()
+            })?;
+            match sample_weight {
+                Some(w) => booster.fit(data, y, w, None)?,
+                None => booster.fit_unweighted(data, y, None)?,
+            }
+            Ok(booster)
+        };
+        let median = fit_quantile(0.5)?;
+        let intervals = alphas
+            .iter()
+            .map(|alpha| {
+                Ok(IntervalBoosters {
+                    alpha: *alpha,
+                    lower: fit_quantile(alpha / 2.)?,
+                    upper: fit_quantile(1. - alpha / 2.)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ForustError>>()?;
+        Ok(PredictionIntervals { median, intervals })
+    }
+
+    /// The alphas of the trained intervals.
+    pub fn alphas(&self) -> Vec<f64> {
+        self.intervals.iter().map(|i| i.alpha).collect()
+    }
+
+    /// Predict the interval of each row. The quantiles are fit by independent boosters,
+    /// so they are sorted per row, to guarantee that the bounds don't cross the median.
+    ///
+    /// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
+    /// * `alpha` - The alpha of a trained interval.
+    /// * `parallel` - Predict in parallel.
+    pub fn predict_interval(
+        &self,
+        data: &Matrix<f64>,
+        alpha: f64,
+        parallel: bool,
+    ) -> Result<Vec<Interval>, ForustError> {
+        let boosters = self
+            .intervals
+            .iter()
+            .find(|i| (i.alpha - alpha).abs() < 1e-12)
+            .ok_or_else(|| ForustError::InvalidParameter {
+                name: "alpha".to_string(),
+                constraint: format!("one of the trained alphas {:?}", self.alphas()),
+                value: alpha.to_string(),
+            })?;
+        let lower = boosters.lower.predict(data, parallel);
+        let median = self.median.predict(data, parallel);
+        let upper = boosters.upper.predict(data, parallel);
+        Ok(lower
+            .iter()
+            .zip(median.iter())
+            .zip(upper.iter())
+            .map(|((l, m), u)| {
+                let mut q = [*l, *m, *u];
+                q.sort_by(|a, b| a.total_cmp(b));
+                Interval {
+                    lower: q[0],
+                    median: q[1],
+                    upper: q[2],
+                }
+            })
+            .collect())
+    }
+}
+
+/// Predictions of the virtual ensembles of a booster, the sub-ensembles formed by
+/// truncating the trained trees at `n_ensembles` evenly spaced boosting rounds over
+/// the second half of the model, as in CatBoost. The last ensemble is the full model.
+/// Returns a vector of predictions of each row, for each ensemble.
+///
+/// * `booster` - A fitted booster.
+/// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
+/// * `n_ensembles` - The number of virtual ensembles, at least 2, and at most half
+///   of the boosting rounds of the booster.
+/// * `parallel` - Predict in parallel.
+pub fn predict_virtual_ensembles(
+    booster: &GradientBooster,
+    data: &Matrix<f64>,
+    n_ensembles: usize,
+    parallel: bool,
+) -> Result<Vec<Vec<f64>>, ForustError> {
+    let trees = booster.get_prediction_trees();
+    // Truncate at whole rounds, so no ensemble holds part of the parallel trees of a round.
+    let rounds = trees.len() / booster.num_parallel_tree;
+    let half = rounds / 2;
+    if n_ensembles < 2 || n_ensembles > rounds - half {
+        return Err(ForustError::InvalidParameter {
+            name: "n_ensembles".to_string(),
+            constraint: format!("integer between 2 and {}", rounds - half),
+            value: n_ensembles.to_string(),
+        });
+    }
+    let step = (rounds - half) / n_ensembles;
+    let ends = (0..n_ensembles)
+        .map(|j| (rounds - (n_ensembles - 1 - j) * step) * booster.num_parallel_tree)
+        .collect::<Vec<_>>();
+
+    let mut yhat = vec![booster.base_score; data.rows];
+    let mut ensembles = Vec::with_capacity(n_ensembles);
+    let mut start = 0;
+    for end in ends {
+        for tree in &trees[start..end] {
+            yhat.iter_mut()
+                .zip(booster.predict_tree(tree, data, parallel))
+                .for_each(|(i, j)| *i += j);
+        }
+        ensembles.push(yhat.clone());
+        start = end;
+    }
+    Ok(ensembles)
+}
+
+/// The variance of the predictions of the virtual ensembles of a booster, for each row.
+/// This is a proxy of the epistemic uncertainty of the model, which doesn't require
+/// training more models. See `predict_virtual_ensembles` for how the ensembles are formed.
+///
+/// * `booster` - A fitted booster.
+/// * `data` -  Either a pandas DataFrame, or a 2 dimensional numpy array.
+/// * `n_ensembles` - The number of virtual ensembles.
+/// * `parallel` - Predict in parallel.
+pub fn virtual_ensembles_variance(
+    booster: &GradientBooster,
+    data: &Matrix<f64>,
+    n_ensembles: usize,
+    parallel: bool,
+) -> Result<Vec<f64>, ForustError> {
+    let ensembles = predict_virtual_ensembles(booster, data, n_ensembles, parallel)?;
+    let n = n_ensembles as f64;
+    Ok((0..data.rows)
+        .map(|i| {
+            let mean = ensembles.iter().map(|e| e[i]).sum::<f64>() / n;
+            ensembles.iter().map(|e| (e[i] - mean).powi(2)).sum::<f64>() / n
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_prediction_intervals() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance-fare.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let params = BoosterParams {
+            iterations: 50,
+            learning_rate: 0.1,
+            max_depth: 3,
+            ..Default::default()
+        };
+        let model = PredictionIntervals::fit(&params, &data, &y, None, &[0.2]).unwrap();
+        assert_eq!(model.median.objective_type, ObjectiveType::QuantileLoss);
+        assert_eq!(model.intervals[0].lower.quantile, 0.1);
+
+        let intervals = model.predict_interval(&data, 0.2, true).unwrap();
+        assert_eq!(intervals.len(), y.len());
+        assert!(intervals
+            .iter()
+            .all(|i| i.lower <= i.median && i.median <= i.upper));
+        // Roughly 80% of the training targets fall inside the interval.
+        let covered = intervals
+            .iter()
+            .zip(y.iter())
+            .filter(|(i, y_)| i.lower <= **y_ && **y_ <= i.upper)
+            .count() as f64
+            / y.len() as f64;
+        assert!(covered > 0.7 && covered < 0.95, "{}", covered);
+
+        assert!(model.predict_interval(&data, 0.1, true).is_err());
+        assert!(PredictionIntervals::fit(&params, &data, &y, None, &[1.]).is_err());
+    }
+
+    #[test]
+    fn test_virtual_ensembles() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+        let data = Matrix::new(&data_vec, 891, 5);
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(20)
+            .set_learning_rate(0.3);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+
+        let ensembles = predict_virtual_ensembles(&booster, &data, 5, true).unwrap();
+        assert_eq!(ensembles.len(), 5);
+        assert_eq!(ensembles[4], booster.predict(&data, true));
+
+        let variance = virtual_ensembles_variance(&booster, &data, 5, true).unwrap();
+        assert_eq!(variance.len(), data.rows);
+        assert!(variance.iter().all(|v| *v >= 0.));
+        assert!(variance.iter().any(|v| *v > 0.));
+
+        assert!(predict_virtual_ensembles(&booster, &data, 1, true).is_err());
+        assert!(predict_virtual_ensembles(&booster, &data, 11, true).is_err());
+    }
+}
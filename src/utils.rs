@@ -4,6 +4,7 @@ use crate::errors::ForustError;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
 use std::convert::TryInto;
+use std::ops::Index;
 
 /// Create a string of all available items.
 pub fn items_to_strings(items: Vec<&str>) -> String {
@@ -32,11 +33,11 @@ pub fn validate_float_parameter<T: FloatData<T>>(
     if value.is_nan() || value < min || max < value {
         msg.push_str(&value.to_string());
         let ex_msg = format!("real value within rang {} and {}", min, max);
-        Err(ForustError::InvalidParameter(
-            parameter.to_string(),
-            ex_msg,
-            value.to_string(),
-        ))
+        Err(ForustError::InvalidParameter {
+            name: parameter.to_string(),
+            constraint: ex_msg,
+            value: value.to_string(),
+        })
     } else {
         Ok(())
     }
@@ -92,6 +93,44 @@ pub fn constrained_weight(
     }
 }
 
+/// Calculate the weights of a pair of children, clipped to their bounds. If the
+/// weights break the constraint, both are recalculated at their midpoint, weighted by
+/// the hessian of each child, which is the best pair of weights that holds the
+/// constraint, so the split can still be scored, rather than being discarded. Unlike
+/// `constrained_weight`, the weights are clipped even if the feature split on is
+/// unconstrained, as the bounds come from the splits on constrained features above.
+#[allow(clippy::too_many_arguments)]
+#[inline]
+pub fn monotone_weights(
+    l2: &f32,
+    left_gradient: f32,
+    left_hessian: f32,
+    right_gradient: f32,
+    right_hessian: f32,
+    lower_bound: f32,
+    upper_bound: f32,
+    constraint: Option<&Constraint>,
+) -> (f32, f32) {
+    let left_weight = weight(l2, left_gradient, left_hessian);
+    let right_weight = weight(l2, right_gradient, right_hessian);
+    let broken = match constraint {
+        None | Some(Constraint::Unconstrained) => false,
+        Some(Constraint::Negative) => left_weight < right_weight,
+        Some(Constraint::Positive) => left_weight > right_weight,
+    };
+    let (left_weight, right_weight) = if broken {
+        // Each child has its own l2 penalty.
+        let mid = -((left_gradient + right_gradient) / (left_hessian + right_hessian + 2.0 * l2));
+        (mid, mid)
+    } else {
+        (left_weight, right_weight)
+    };
+    (
+        left_weight.max(lower_bound).min(upper_bound),
+        right_weight.max(lower_bound).min(upper_bound),
+    )
+}
+
 /// Calculate the gain given the gradient and hessian of the node.
 #[inline]
 pub fn gain(l2: &f32, gradient_sum: f32, hessian_sum: f32) -> f32 {
@@ -201,8 +240,9 @@ pub fn naive_sum<T: FloatData<T>>(values: &[T]) -> T {
 
 /// Naive weighted percentiles calculation.
 ///
-/// Currently this function does not support missing values.
-///   
+/// NaN values, and their weights, are left out. If all values are NaN,
+/// no percentiles are returned.
+///
 /// * `v` - A Vector of which to find percentiles for.
 /// * `sample_weight` - Sample weights for the instances of the vector.
 /// * `percentiles` - Percentiles to look for in the data. This should be
@@ -211,8 +251,11 @@ pub fn percentiles<T>(v: &[T], sample_weight: &[T], percentiles: &[T]) -> Vec<T>
 where
     T: FloatData<T>,
 {
-    let mut idx: Vec<usize> = (0..v.len()).collect();
-    idx.sort_unstable_by(|a, b| v[*a].partial_cmp(&v[*b]).unwrap());
+    let mut idx: Vec<usize> = (0..v.len()).filter(|i| !v[*i].is_nan()).collect();
+    if idx.is_empty() {
+        return Vec::new();
+    }
+    idx.sort_unstable_by(|a, b| v[*a].total_cmp(&v[*b]));
 
     // Setup percentiles
     let mut pcts = VecDeque::from_iter(percentiles.iter());
@@ -222,7 +265,11 @@ where
     let mut p = Vec::new();
     let mut cuml_pct = T::ZERO;
     let mut current_value = v[idx[0]];
-    let total_values = fast_sum(sample_weight);
+    let total_values = if idx.len() == v.len() {
+        fast_sum(sample_weight)
+    } else {
+        idx.iter().map(|i| sample_weight[*i]).sum()
+    };
 
     for i in idx.iter() {
         if current_value != v[*i] {
@@ -287,9 +334,9 @@ pub fn map_bin<T: std::cmp::PartialOrd>(x: &[T], v: &T) -> Option<u16> {
 /// * `missing_right` - Should missing values go to the left, or
 ///    to the right of the split value.
 #[inline]
-pub fn pivot_on_split(
+pub fn pivot_on_split<F: Index<usize, Output = u16> + ?Sized>(
     index: &mut [usize],
-    feature: &[u16],
+    feature: &F,
     split_value: u16,
     missing_right: bool,
 ) -> usize {
@@ -342,9 +389,9 @@ pub fn pivot_on_split(
 /// * `feature` - The feature vector to use to sort the index by.
 /// * `split_value` - the split value to use to pivot on.
 #[inline]
-pub fn pivot_on_split_exclude_missing(
+pub fn pivot_on_split_exclude_missing<F: Index<usize, Output = u16> + ?Sized>(
     index: &mut [usize],
-    feature: &[u16],
+    feature: &F,
     split_value: u16,
 ) -> (usize, usize) {
     // I think we can do this in O(n) time...
@@ -420,6 +467,19 @@ pub fn missing_compare(split_value: &u16, cmp_value: u16, missing_right: bool) -
     }
 }
 
+/// The smallest f64 value greater than `v`.
+pub fn next_up(v: f64) -> f64 {
+    if v.is_nan() || v == f64::INFINITY {
+        v
+    } else if v == 0. {
+        f64::from_bits(1)
+    } else if v > 0. {
+        f64::from_bits(v.to_bits() + 1)
+    } else {
+        f64::from_bits(v.to_bits() - 1)
+    }
+}
+
 #[inline]
 pub fn precision_round(n: f64, precision: i32) -> f64 {
     let p = (10.0_f64).powi(precision);
@@ -456,6 +516,20 @@ mod tests {
         assert_eq!(p, vec![4.0, 4.0, 7.0, 10.0]);
     }
 
+    #[test]
+    fn test_percentiles_nan() {
+        // NaN values, and their weights, are left out.
+        let v = vec![10., 8., f64::NAN, 9., 1., 2., 3., 6., 7., 4., f64::NAN, 5.];
+        let w = vec![1., 1., 3., 1., 1., 1., 2., 1., 1., 5., 2., 1.];
+        let p = vec![0., 0.3, 0.5, 0.75, 1.0];
+        let p = percentiles(&v, &w, &p);
+        assert_eq!(p, vec![1.0, 4.0, 4.0, 7.0, 10.0]);
+        let v = vec![f64::NAN; 3];
+        assert!(percentiles(&v, &[1.; 3], &[0.5]).is_empty());
+        let v = vec![f32::NAN, 2., 1.];
+        assert_eq!(percentiles(&v, &[1.; 3], &[0.5, 1.]), vec![1., 2.]);
+    }
+
     #[test]
     fn test_map_bin_or_equal() {
         let v = vec![f64::MIN, 1., 4., 8., 9.];
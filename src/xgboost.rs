@@ -0,0 +1,595 @@
+use crate::errors::ForustError;
+use crate::gradientbooster::GradientBooster;
+use crate::node::Node;
+use crate::objective::ObjectiveType;
+use crate::tree::Tree;
+use crate::utils::next_up;
+use serde_json::{json, Value};
+use std::fs;
+
+/// Load an XGBoost model, saved as json with `save_model`, as a gradient booster.
+///
+/// * `path` - Path to the XGBoost json model.
+pub fn load_xgboost_json(path: &str) -> Result<GradientBooster, ForustError> {
+    let json_str = match fs::read_to_string(path) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(ForustError::Io(e)),
+    }?;
+    from_xgboost_json(&json_str)
+}
+
+/// Convert an XGBoost json model into a gradient booster. Only single target `gbtree`
+/// models with numeric splits and a `binary:logistic`, `binary:logitraw`,
+/// `reg:logistic`, or `reg:squarederror` objective are supported.
+///
+/// * `json_str` - The XGBoost model json, as created by `save_model`.
+pub fn from_xgboost_json(json_str: &str) -> Result<GradientBooster, ForustError> {
+    let model: Value = match serde_json::from_str(json_str) {
+        Ok(v) => Ok(v),
+        Err(e) => Err(ForustError::Serialization(e)),
+    }?;
+    let learner = get_field(&model, "learner")?;
+
+    let objective = get_field(get_field(learner, "objective")?, "name")?
+        .as_str()
+        .unwrap_or_default();
+    let (objective_type, probability_base_score) = match objective {
+        "binary:logistic" | "reg:logistic" => (ObjectiveType::LogLoss, true),
+        "binary:logitraw" => (ObjectiveType::LogLoss, false),
+        "reg:squarederror" | "reg:linear" => (ObjectiveType::SquaredLoss, false),
+        _ => {
+            return Err(ForustError::UnsupportedObjective {
+                objective: objective.to_string(),
+                reason: "only binary logistic, and squared error XGBoost models can be imported"
+                    .to_string(),
+            })
+        }
+    };
+
+    let model_param = get_field(learner, "learner_model_param")?;
+    let num_class = parse_number_string(get_field(model_param, "num_class")?)?;
+    if num_class > 1. {
+        return Err(ForustError::UnableToRead(
+            "multi-class XGBoost models are not supported".to_string(),
+        ));
+    }
+    let base_score = parse_number_string(get_field(model_param, "base_score")?)?;
+    // The base score of the logistic objectives is a probability.
+    let base_score = if probability_base_score {
+        (base_score / (1. - base_score)).ln()
+    } else {
+        base_score
+    };
+
+    let gradient_booster = get_field(learner, "gradient_booster")?;
+    let name = get_field(gradient_booster, "name")?
+        .as_str()
+        .unwrap_or_default();
+    if name != "gbtree" {
+        return Err(ForustError::UnableToRead(format!(
+            "unsupported XGBoost booster {}, only gbtree is supported",
+            name
+        )));
+    }
+    let trees = match get_field(get_field(gradient_booster, "model")?, "trees")?.as_array() {
+        Some(trees) => trees
+            .iter()
+            .map(xgboost_tree)
+            .collect::<Result<Vec<Tree>, ForustError>>(),
+        None => Err(ForustError::UnableToRead(
+            "XGBoost trees must be an array".to_string(),
+        )),
+    }?;
+
+    let mut booster = GradientBooster::default()
+        .set_objective_type(objective_type)
+        .set_iterations(trees.len())
+        .set_base_score(base_score)
+        .set_initialize_base_score(false);
+    booster.trees = trees;
+    let feature_names: Vec<String> = learner
+        .get("feature_names")
+        .and_then(|v| v.as_array())
+        .map(|names| {
+            names
+                .iter()
+                .filter_map(|n| n.as_str().map(|n| n.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !feature_names.is_empty() {
+        booster.feature_names = Some(feature_names);
+    }
+    Ok(booster)
+}
+
+/// Save a gradient booster as an XGBoost json model, that can be loaded with `load_model`.
+///
+/// * `booster` - The booster to export.
+/// * `path` - Path to save the XGBoost json model.
+pub fn save_xgboost_json(booster: &GradientBooster, path: &str) -> Result<(), ForustError> {
+    let model = to_xgboost_json(booster)?;
+    match fs::write(path, model) {
+        Err(e) => Err(ForustError::Io(e)),
+        Ok(_) => Ok(()),
+    }
+}
+
+/// Convert a gradient booster into an XGBoost json model. XGBoost stores split
+/// thresholds in single precision, so thresholds are rounded to the nearest f32,
+/// values within f32 precision below a threshold may be routed differently.
+/// Boosters trained with `create_missing_branch`, a missing value other
+/// than NaN, or an imputer, can't be represented in XGBoost.
+///
+/// * `booster` - The booster to export.
+pub fn to_xgboost_json(booster: &GradientBooster) -> Result<String, ForustError> {
+    if !booster.missing.is_nan() {
+        return Err(ForustError::UnableToWrite(
+            "only boosters with a missing value of NaN can be exported to XGBoost".to_string(),
+        ));
+    }
+    if booster.imputer.is_some() {
+        return Err(ForustError::UnableToWrite(
+            "boosters with an imputer can't be exported to XGBoost".to_string(),
+        ));
+    }
+    let trees = booster.get_prediction_trees();
+    if trees
+        .iter()
+        .any(|t| t.nodes.iter().any(|n| !n.is_leaf && n.has_missing_branch()))
+    {
+        return Err(ForustError::UnableToWrite(
+            "trees with a separate missing branch can't be exported to XGBoost".to_string(),
+        ));
+    }
+    let (objective, base_score) = match booster.objective_type {
+        ObjectiveType::LogLoss => ("binary:logistic", 1. / (1. + (-booster.base_score).exp())),
+        ObjectiveType::SquaredLoss => ("reg:squarederror", booster.base_score),
+        ObjectiveType::QuantileLoss => ("reg:quantileerror", booster.base_score),
+    };
+    let num_feature = trees
+        .iter()
+        .flat_map(|t| t.nodes.iter())
+        .filter(|n| !n.is_leaf)
+        .map(|n| n.split_feature + 1)
+        .max()
+        .unwrap_or(0);
+    let feature_names = booster.feature_names.clone().unwrap_or_default();
+    let num_feature = num_feature.max(feature_names.len());
+    let xgboost_trees: Vec<Value> = trees
+        .iter()
+        .enumerate()
+        .map(|(i, t)| xgboost_tree_json(t, i, num_feature))
+        .collect();
+
+    let model = json!({
+        "learner": {
+            "attributes": {},
+            "feature_names": feature_names,
+            "feature_types": vec!["float"; feature_names.len()],
+            "gradient_booster": {
+                "model": {
+                    "gbtree_model_param": {
+                        "num_parallel_tree": "1",
+                        "num_trees": trees.len().to_string(),
+                        "size_leaf_vector": "0"
+                    },
+                    "tree_info": vec![0; trees.len()],
+                    "trees": xgboost_trees
+                },
+                "name": "gbtree"
+            },
+            "learner_model_param": {
+                "base_score": format!("{:E}", base_score),
+                "boost_from_average": "1",
+                "num_class": "0",
+                "num_feature": num_feature.to_string(),
+                "num_target": "1"
+            },
+            "objective": {
+                "name": objective,
+                "reg_loss_param": {"scale_pos_weight": "1"},
+                "quantile_loss_param": {"quantile_alpha": format!("[{}]", booster.quantile)}
+            }
+        },
+        "version": [1, 7, 6]
+    });
+    match serde_json::to_string(&model) {
+        Ok(s) => Ok(s),
+        Err(e) => Err(ForustError::Serialization(e)),
+    }
+}
+
+fn xgboost_tree_json(tree: &Tree, id: usize, num_feature: usize) -> Value {
+    let n_nodes = tree.nodes.len();
+    // XGBoost uses the maximum int32 value, as the parent of the root.
+    let mut parents = vec![2147483647_i64; n_nodes];
+    for n in tree.nodes.iter().filter(|n| !n.is_leaf) {
+        parents[n.left_child] = n.num as i64;
+        parents[n.right_child] = n.num as i64;
+    }
+    let child = |n: &Node, c: usize| if n.is_leaf { -1 } else { c as i64 };
+    json!({
+        "base_weights": tree.nodes.iter().map(|n| n.weight_value).collect::<Vec<f32>>(),
+        "categories": [],
+        "categories_nodes": [],
+        "categories_segments": [],
+        "categories_sizes": [],
+        "default_left": tree
+            .nodes
+            .iter()
+            .map(|n| i32::from(!n.is_leaf && n.missing_node == n.left_child))
+            .collect::<Vec<i32>>(),
+        "id": id,
+        "left_children": tree.nodes.iter().map(|n| child(n, n.left_child)).collect::<Vec<i64>>(),
+        "loss_changes": tree
+            .nodes
+            .iter()
+            .map(|n| if n.is_leaf { 0. } else { n.split_gain })
+            .collect::<Vec<f32>>(),
+        "parents": parents,
+        "right_children": tree.nodes.iter().map(|n| child(n, n.right_child)).collect::<Vec<i64>>(),
+        "split_conditions": tree
+            .nodes
+            .iter()
+            .map(|n| {
+                if n.is_leaf {
+                    n.weight_value
+                } else {
+                    // Rounding to the nearest f32 ensures the threshold value
+                    // itself, is still routed to the right.
+                    n.split_value as f32
+                }
+            })
+            .collect::<Vec<f32>>(),
+        "split_indices": tree
+            .nodes
+            .iter()
+            .map(|n| if n.is_leaf { 0 } else { n.split_feature })
+            .collect::<Vec<usize>>(),
+        "split_type": vec![0; n_nodes],
+        "sum_hessian": tree.nodes.iter().map(|n| n.hessian_sum).collect::<Vec<f32>>(),
+        "tree_param": {
+            "num_deleted": "0",
+            "num_feature": num_feature.to_string(),
+            "num_nodes": n_nodes.to_string(),
+            "size_leaf_vector": "0"
+        }
+    })
+}
+
+fn xgboost_tree(tree: &Value) -> Result<Tree, ForustError> {
+    let left_children = get_i64_array(tree, "left_children")?;
+    let right_children = get_i64_array(tree, "right_children")?;
+    let split_indices = get_i64_array(tree, "split_indices")?;
+    let split_conditions = get_f64_array(tree, "split_conditions")?;
+    let sum_hessian = get_f64_array(tree, "sum_hessian")?;
+    let loss_changes = get_f64_array(tree, "loss_changes")?;
+    let default_left = get_bool_array(tree, "default_left")?;
+    if tree.get("split_type").is_some()
+        && get_i64_array(tree, "split_type")?.iter().any(|t| *t != 0)
+    {
+        return Err(ForustError::UnableToRead(
+            "categorical XGBoost splits are not supported".to_string(),
+        ));
+    }
+    let n_nodes = left_children.len();
+    if [
+        right_children.len(),
+        split_indices.len(),
+        split_conditions.len(),
+        sum_hessian.len(),
+        loss_changes.len(),
+        default_left.len(),
+    ]
+    .iter()
+    .any(|l| *l != n_nodes)
+        || n_nodes == 0
+    {
+        return Err(ForustError::UnableToRead(
+            "XGBoost tree arrays must all have the same, non zero, length".to_string(),
+        ));
+    }
+
+    let mut nodes: Vec<Node> = (0..n_nodes)
+        .map(|i| {
+            let is_leaf = left_children[i] == -1;
+            let (left_child, right_child) = if is_leaf {
+                (0, 0)
+            } else {
+                (left_children[i] as usize, right_children[i] as usize)
+            };
+            Node {
+                num: i,
+                // Leaves store their value in the split condition,
+                // parent weights are filled in below.
+                weight_value: if is_leaf {
+                    split_conditions[i] as f32
+                } else {
+                    0.
+                },
+                hessian_sum: sum_hessian[i] as f32,
+                depth: 0,
+                split_value: if is_leaf {
+                    0.
+                } else {
+                    f32_threshold_to_f64(split_conditions[i] as f32)
+                },
+                split_feature: if is_leaf {
+                    0
+                } else {
+                    split_indices[i] as usize
+                },
+                split_gain: loss_changes[i] as f32,
+                missing_node: if default_left[i] {
+                    left_child
+                } else {
+                    right_child
+                },
+                left_child,
+                right_child,
+                is_leaf,
+            }
+        })
+        .collect();
+    // Children are always stored after their parent.
+    if nodes.iter().any(|n| {
+        !n.is_leaf
+            && (n.left_child >= n_nodes
+                || n.right_child >= n_nodes
+                || n.left_child <= n.num
+                || n.right_child <= n.num)
+    }) {
+        return Err(ForustError::UnableToRead(
+            "XGBoost tree references an invalid node".to_string(),
+        ));
+    }
+    fill_parent_nodes(&mut nodes, 0, 0);
+    Ok(Tree {
+        nodes,
+        learning_rate: None,
+    })
+}
+
+/// Set the depth of every node, and the weight of every parent node as the cover
+/// weighted average of its children, returning the weight of the node.
+fn fill_parent_nodes(nodes: &mut [Node], idx: usize, depth: usize) -> f32 {
+    nodes[idx].depth = depth;
+    if nodes[idx].is_leaf {
+        return nodes[idx].weight_value;
+    }
+    let (left, right) = (nodes[idx].left_child, nodes[idx].right_child);
+    let left_weight = fill_parent_nodes(nodes, left, depth + 1);
+    let right_weight = fill_parent_nodes(nodes, right, depth + 1);
+    let (left_cover, right_cover) = (nodes[left].hessian_sum, nodes[right].hessian_sum);
+    let weight = if left_cover + right_cover > 0. {
+        (left_weight * left_cover + right_weight * right_cover) / (left_cover + right_cover)
+    } else {
+        (left_weight + right_weight) / 2.
+    };
+    nodes[idx].weight_value = weight;
+    weight
+}
+
+/// XGBoost compares single precision values, `f32(x) < threshold`. Find the
+/// f64 threshold, so that `x < threshold` routes every f64 value the same way.
+/// This is the boundary where values start rounding to the threshold,
+/// accounting for ties rounding to the value with an even mantissa.
+fn f32_threshold_to_f64(threshold: f32) -> f64 {
+    if !threshold.is_finite() {
+        return f64::from(threshold);
+    }
+    // Treat negative zero as zero.
+    let threshold = if threshold == 0. { 0. } else { threshold };
+    let previous = if threshold > 0. {
+        f32::from_bits(threshold.to_bits() - 1)
+    } else if threshold < 0. {
+        f32::from_bits(threshold.to_bits() + 1)
+    } else {
+        -f32::from_bits(1)
+    };
+    // This is exact, as both values are representable as f32.
+    let midpoint = (f64::from(threshold) + f64::from(previous)) / 2.;
+    if threshold.to_bits() & 1 == 0 {
+        midpoint
+    } else {
+        next_up(midpoint)
+    }
+}
+
+fn get_field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, ForustError> {
+    value
+        .get(key)
+        .ok_or_else(|| ForustError::UnableToRead(format!("XGBoost model is missing {}", key)))
+}
+
+// XGBoost stores numeric parameters as strings, newer versions wrap them in brackets.
+fn parse_number_string(value: &Value) -> Result<f64, ForustError> {
+    let s = value.as_str().unwrap_or_default();
+    s.trim_matches(|c| c == '[' || c == ']')
+        .parse::<f64>()
+        .map_err(|_| ForustError::UnableToRead(format!("unable to parse number {}", value)))
+}
+
+fn get_array<'a>(value: &'a Value, key: &str) -> Result<&'a Vec<Value>, ForustError> {
+    get_field(value, key)?
+        .as_array()
+        .ok_or_else(|| ForustError::UnableToRead(format!("XGBoost {} must be an array", key)))
+}
+
+fn get_f64_array(value: &Value, key: &str) -> Result<Vec<f64>, ForustError> {
+    get_array(value, key)?
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .ok_or_else(|| ForustError::UnableToRead(format!("invalid value in {}", key)))
+        })
+        .collect()
+}
+
+fn get_i64_array(value: &Value, key: &str) -> Result<Vec<i64>, ForustError> {
+    get_array(value, key)?
+        .iter()
+        .map(|v| {
+            v.as_i64()
+                .ok_or_else(|| ForustError::UnableToRead(format!("invalid value in {}", key)))
+        })
+        .collect()
+}
+
+// Older versions of XGBoost store booleans as integers.
+fn get_bool_array(value: &Value, key: &str) -> Result<Vec<bool>, ForustError> {
+    get_array(value, key)?
+        .iter()
+        .map(|v| match v {
+            Value::Bool(b) => Ok(*b),
+            Value::Number(n) => Ok(n.as_i64() != Some(0)),
+            _ => Err(ForustError::UnableToRead(format!(
+                "invalid value in {}",
+                key
+            ))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Matrix;
+    use crate::preprocessing::{ImputeStrategy, Imputer};
+    use std::fs;
+
+    // A model with two trees, as saved by XGBoost 1.7.
+    const XGBOOST_MODEL: &str = r#"{
+        "learner": {
+            "attributes": {},
+            "feature_names": [],
+            "feature_types": [],
+            "gradient_booster": {
+                "model": {
+                    "gbtree_model_param": {"num_parallel_tree": "1", "num_trees": "2", "size_leaf_vector": "0"},
+                    "tree_info": [0, 0],
+                    "trees": [
+                        {
+                            "base_weights": [0.1, -0.5, 0.5],
+                            "categories": [], "categories_nodes": [], "categories_segments": [], "categories_sizes": [],
+                            "default_left": [1, 0, 0],
+                            "id": 0,
+                            "left_children": [1, -1, -1],
+                            "loss_changes": [3.5, 0.0, 0.0],
+                            "parents": [2147483647, 0, 0],
+                            "right_children": [2, -1, -1],
+                            "split_conditions": [0.1, -0.15, 0.15],
+                            "split_indices": [0, 0, 0],
+                            "split_type": [0, 0, 0],
+                            "sum_hessian": [10.0, 4.0, 6.0],
+                            "tree_param": {"num_deleted": "0", "num_feature": "2", "num_nodes": "3", "size_leaf_vector": "0"}
+                        },
+                        {
+                            "base_weights": [0.0, -0.2, 0.2],
+                            "categories": [], "categories_nodes": [], "categories_segments": [], "categories_sizes": [],
+                            "default_left": [false, false, false],
+                            "id": 1,
+                            "left_children": [1, -1, -1],
+                            "loss_changes": [1.0, 0.0, 0.0],
+                            "parents": [2147483647, 0, 0],
+                            "right_children": [2, -1, -1],
+                            "split_conditions": [2.5, -0.05, 0.05],
+                            "split_indices": [1, 0, 0],
+                            "split_type": [0, 0, 0],
+                            "sum_hessian": [10.0, 5.0, 5.0],
+                            "tree_param": {"num_deleted": "0", "num_feature": "2", "num_nodes": "3", "size_leaf_vector": "0"}
+                        }
+                    ]
+                },
+                "name": "gbtree"
+            },
+            "learner_model_param": {"base_score": "5E-1", "num_class": "0", "num_feature": "2", "num_target": "1"},
+            "objective": {"name": "binary:logistic", "reg_loss_param": {"scale_pos_weight": "1"}}
+        },
+        "version": [1, 7, 6]
+    }"#;
+
+    #[test]
+    fn test_from_xgboost_json() {
+        let booster = from_xgboost_json(XGBOOST_MODEL).unwrap();
+        assert_eq!(booster.trees.len(), 2);
+        assert_eq!(booster.base_score, 0.);
+        // Column major data, the first column holds the value 0.1 in single,
+        // and double precision, which XGBoost would consider equal to the threshold.
+        let data_vec = vec![0.0, 0.1, 0.1f32 as f64, f64::NAN, 3.0, 1.0, f64::NAN, 3.0];
+        let data = Matrix::new(&data_vec, 4, 2);
+        let preds = booster.predict(&data, false);
+        let (l0, r0, l1, r1) = (
+            -0.15f32 as f64,
+            0.15f32 as f64,
+            -0.05f32 as f64,
+            0.05f32 as f64,
+        );
+        assert_eq!(preds, vec![l0 + r1, r0 + l1, r0 + r1, l0 + r1]);
+        // The parent weight is the cover weighted average of the leaves.
+        let root = &booster.trees[0].nodes[0];
+        assert!((root.weight_value - (-0.15 * 4. + 0.15 * 6.) / 10.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_xgboost_json_round_trip() {
+        let file = fs::read_to_string("resources/contiguous_with_missing.csv")
+            .expect("Something went wrong reading the file");
+        let data_vec: Vec<f64> = file
+            .lines()
+            .map(|x| x.parse::<f64>().unwrap_or(f64::NAN))
+            .collect();
+        let file = fs::read_to_string("resources/performance.csv")
+            .expect("Something went wrong reading the file");
+        let y: Vec<f64> = file.lines().map(|x| x.parse::<f64>().unwrap()).collect();
+
+        let data = Matrix::new(&data_vec, 891, 5);
+        let feature_names: Vec<String> = (0..5).map(|i| format!("f{}", i)).collect();
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_feature_names(Some(feature_names.clone()));
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        let preds = booster.predict(&data, false);
+
+        let xgboost_json = to_xgboost_json(&booster).unwrap();
+        let booster2 = from_xgboost_json(&xgboost_json).unwrap();
+        assert_eq!(booster2.feature_names, Some(feature_names));
+        let preds2 = booster2.predict(&data, false);
+        assert!(preds
+            .iter()
+            .zip(preds2)
+            .all(|(p1, p2)| (p1 - p2).abs() < 1e-9));
+
+        let mut booster = GradientBooster::default()
+            .set_iterations(10)
+            .set_max_depth(3)
+            .set_create_missing_branch(true);
+        booster.fit_unweighted(&data, &y, None).unwrap();
+        assert!(to_xgboost_json(&booster).is_err());
+
+        let imputer = Imputer::fit(&data, ImputeStrategy::Mean, f64::NAN).unwrap();
+        let booster = GradientBooster::default().set_imputer(Some(imputer));
+        assert!(to_xgboost_json(&booster).is_err());
+    }
+
+    #[test]
+    fn test_f32_threshold_to_f64() {
+        for t in [0.1f32, -0.1, 1.0, 2.5, 1e-30, -3.3e10, 0.0] {
+            let threshold = f32_threshold_to_f64(t);
+            // Values just below the new threshold are less than t as f32,
+            // and the threshold itself is not.
+            assert!(((threshold - threshold.abs() * 1e-15) as f32) < t);
+            assert!((threshold as f32) >= t);
+        }
+    }
+
+    #[test]
+    fn test_from_xgboost_json_unsupported() {
+        let model = XGBOOST_MODEL.replace("binary:logistic", "multi:softprob");
+        assert!(from_xgboost_json(&model).is_err());
+        let model = XGBOOST_MODEL.replace(r#""name": "gbtree""#, r#""name": "dart""#);
+        assert!(from_xgboost_json(&model).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+use forust_ml::data::RowMajorView;
+use forust_ml::gradientbooster::GradientBooster as CrateGradientBooster;
+use wasm_bindgen::prelude::*;
+
+fn to_js_error<T, E: std::fmt::Display>(value: Result<T, E>) -> Result<T, JsError> {
+    value.map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Check a row, or each row of a batch, has a value for every feature of the booster,
+/// a shorter row would trap the instance.
+fn check_features(booster: &CrateGradientBooster, cols: usize) -> Result<(), JsError> {
+    let n_features = booster.n_features();
+    if cols < n_features {
+        Err(JsError::new(&format!(
+            "The data has {} columns, but the model has {} features.",
+            cols, n_features
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// A booster trained, and saved in Rust or Python, loaded to score rows in the
+/// browser, or an edge worker. Only the prediction path is exposed, and it runs on
+/// a single thread, as there are no threads on `wasm32-unknown-unknown`.
+#[wasm_bindgen]
+pub struct GradientBooster {
+    booster: CrateGradientBooster,
+}
+
+#[wasm_bindgen]
+impl GradientBooster {
+    /// Load a booster from the json written by `save_booster`, or `json_dump`.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json_str: &str) -> Result<GradientBooster, JsError> {
+        let booster = to_js_error(CrateGradientBooster::from_json(json_str))?;
+        Ok(GradientBooster { booster })
+    }
+
+    /// Load a booster from the bytes written by `save_binary`.
+    #[wasm_bindgen(js_name = fromBinary)]
+    pub fn from_binary(bytes: &[u8]) -> Result<GradientBooster, JsError> {
+        let booster = to_js_error(CrateGradientBooster::from_binary(bytes))?;
+        Ok(GradientBooster { booster })
+    }
+
+    /// Predict on row major data, such as a `Float64Array` with the values of
+    /// each row, one row after the other.
+    pub fn predict(&self, data: &[f64], rows: usize, cols: usize) -> Result<Vec<f64>, JsError> {
+        check_features(&self.booster, cols)?;
+        let data = to_js_error(RowMajorView::new(data, rows, cols))?;
+        Ok(self.booster.predict(&data, false))
+    }
+
+    /// Predict on a single row, without allocating.
+    #[wasm_bindgen(js_name = predictRow)]
+    pub fn predict_row(&self, row: &[f64]) -> Result<f64, JsError> {
+        check_features(&self.booster, row.len())?;
+        Ok(self.booster.predict_row(row))
+    }
+
+    /// Set the iteration to predict up to, including the tree of that iteration, the
+    /// same as `set_prediction_iteration` of the crate. All trees are used by default.
+    #[wasm_bindgen(js_name = setPredictionIteration)]
+    pub fn set_prediction_iteration(&mut self, iteration: Option<usize>) -> Result<(), JsError> {
+        let n_trees = self.booster.trees.len();
+        if let Some(i) = iteration.filter(|i| *i >= n_trees) {
+            return Err(JsError::new(&format!(
+                "The iteration {} is past the last of the {} trees.",
+                i, n_trees
+            )));
+        }
+        self.booster.prediction_iteration = iteration.map(|i| i + 1);
+        Ok(())
+    }
+
+    /// Get the value associated with a key in the booster's metadata.
+    #[wasm_bindgen(js_name = getMetadata)]
+    pub fn get_metadata(&self, key: &str) -> Option<String> {
+        self.booster.get_metadata(&key.to_string())
+    }
+}